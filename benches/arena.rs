@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+
+use bumpalo::Bump;
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde::Serialize as _;
+use serde_buf::Serializer;
+use serde_derive::Serialize;
+
+#[derive(Serialize)]
+struct Record {
+    id: u64,
+    timestamp: String,
+    level: String,
+    message: String,
+    target: String,
+    module: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    tags: Vec<String>,
+    attributes: BTreeMap<String, String>,
+}
+
+fn record() -> Record {
+    Record {
+        id: 42,
+        timestamp: "2024-01-01T00:00:00.000Z".into(),
+        level: "INFO".into(),
+        message: "a request was handled successfully".into(),
+        target: "app::http::server".into(),
+        module: Some("app::http::server::handler".into()),
+        file: Some("src/http/server/handler.rs".into()),
+        line: Some(128),
+        tags: vec!["http".into(), "request".into(), "handled".into()],
+        attributes: (0..8)
+            .map(|i| (format!("attribute_{}", i), format!("value {}", i)))
+            .collect(),
+    }
+}
+
+fn buffer_record(c: &mut Criterion) {
+    let record = record();
+
+    c.bench_function("buffer_record_default", |b| {
+        b.iter(|| record.serialize(Serializer::new()).unwrap())
+    });
+
+    let mut arena = Bump::new();
+    c.bench_function("buffer_record_arena", |b| {
+        b.iter(|| {
+            arena.reset();
+            drop(
+                record
+                    .serialize(Serializer::new().with_arena(&arena))
+                    .unwrap(),
+            );
+        })
+    });
+}
+
+fn buffer_strings(c: &mut Criterion) {
+    let strings: Vec<String> = (0..1000).map(|i| format!("string {}", i)).collect();
+
+    c.bench_function("buffer_strings_default", |b| {
+        b.iter(|| strings.serialize(Serializer::new()).unwrap())
+    });
+
+    let mut arena = Bump::new();
+    c.bench_function("buffer_strings_arena", |b| {
+        b.iter(|| {
+            arena.reset();
+            drop(
+                strings
+                    .serialize(Serializer::new().with_arena(&arena))
+                    .unwrap(),
+            );
+        })
+    });
+}
+
+criterion_group!(benches, buffer_record, buffer_strings);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use serde::Serialize as _;
+use serde_buf::Serializer;
+
+fn buffer_large_vec(c: &mut Criterion) {
+    let v: Vec<u64> = (0..100_000).collect();
+
+    c.bench_function("buffer_100k_default_limit", |b| {
+        b.iter(|| v.serialize(Serializer::new()).unwrap())
+    });
+
+    c.bench_function("buffer_100k_no_limit", |b| {
+        b.iter(|| {
+            v.serialize(Serializer::new().with_capacity_limit(usize::MAX))
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, buffer_large_vec);
+criterion_main!(benches);
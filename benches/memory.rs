@@ -0,0 +1,99 @@
+/*!
+Measure the memory used by buffers.
+
+This isn't a timing benchmark, so it just prints the number of bytes held by each buffer.
+*/
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::Serialize as _;
+use serde_buf::Serializer;
+use serde_derive::Serialize;
+
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(Serialize)]
+struct Record {
+    level: String,
+    target: String,
+    message: String,
+    attributes: BTreeMap<String, String>,
+}
+
+fn measure<T>(name: &str, len: usize, f: impl FnOnce() -> T) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let buf = f();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+
+    println!(
+        "{} uses {} bytes ({} bytes per value)",
+        name,
+        after - before,
+        (after - before) / len
+    );
+
+    drop(buf);
+}
+
+fn main() {
+    let v: Vec<u8> = (0..1_000_000).map(|i| i as u8).collect();
+
+    measure("buffering 1,000,000 u8s", v.len(), || {
+        v.serialize(Serializer::new().with_capacity_limit(usize::MAX))
+            .unwrap()
+    });
+
+    let records: Vec<Record> = (0..10_000)
+        .map(|i| Record {
+            level: "info".into(),
+            target: "app::http::server".into(),
+            message: format!("handled request {}", i % 10),
+            attributes: BTreeMap::from([
+                ("method".into(), "GET".into()),
+                ("status".into(), "200".into()),
+            ]),
+        })
+        .collect();
+
+    measure("buffering 10,000 records", records.len(), || {
+        records
+            .serialize(Serializer::new().with_capacity_limit(usize::MAX))
+            .unwrap()
+    });
+
+    #[cfg(feature = "std")]
+    measure(
+        "buffering 10,000 records with string interning",
+        records.len(),
+        || {
+            records
+                .serialize(
+                    Serializer::new()
+                        .with_capacity_limit(usize::MAX)
+                        .with_string_interning(),
+                )
+                .unwrap()
+        },
+    );
+}
@@ -0,0 +1,63 @@
+/*!
+Measure the memory used by buffering a large byte-string.
+
+This isn't a timing benchmark, so it just prints the number of bytes allocated while buffering
+and cloning a 1MB blob.
+*/
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use bytes::Bytes;
+use serde_buf::{Owned, Serializer};
+
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let buf = f();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+
+    println!("{} allocates {} bytes", name, after - before);
+
+    buf
+}
+
+fn main() {
+    let blob = Bytes::from((0..1_000_000).map(|i| i as u8).collect::<Vec<u8>>());
+
+    let copied = measure("buffering a 1MB blob with `serialize_bytes`", || {
+        serde::Serializer::serialize_bytes(Serializer::new(), &blob).unwrap()
+    });
+
+    let cloned = measure("cloning a copied 1MB blob", || copied.clone());
+
+    drop((copied, cloned));
+
+    let shared = measure("buffering a 1MB blob with `Owned::shared_bytes`", || {
+        Owned::shared_bytes(blob.clone())
+    });
+
+    let cloned = measure("cloning a shared 1MB blob", || shared.clone());
+
+    drop((shared, cloned));
+}
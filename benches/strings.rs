@@ -0,0 +1,91 @@
+/*!
+Measure the allocations made while buffering records with many short strings.
+
+This isn't a timing benchmark, so it just prints the number of allocations and bytes held by
+each buffer. Run it with and without the `compact_str` feature to compare them.
+*/
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::Serialize as _;
+use serde_buf::Serializer;
+use serde_derive::Serialize;
+
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(Serialize)]
+struct Record {
+    level: String,
+    target: String,
+    message: String,
+    attributes: BTreeMap<String, String>,
+}
+
+fn measure<T>(name: &str, f: impl FnOnce() -> T) {
+    let (allocated, allocations) = (
+        ALLOCATED.load(Ordering::Relaxed),
+        ALLOCATIONS.load(Ordering::Relaxed),
+    );
+    let buf = f();
+
+    println!(
+        "{} makes {} allocations and uses {} bytes",
+        name,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED.load(Ordering::Relaxed) - allocated,
+    );
+
+    drop(buf);
+}
+
+fn main() {
+    println!(
+        "strings are {}",
+        if cfg!(feature = "compact_str") {
+            "compact"
+        } else {
+            "boxed"
+        }
+    );
+
+    let records: Vec<Record> = (0..10_000)
+        .map(|i| Record {
+            level: "info".into(),
+            target: "app::http::server".into(),
+            message: format!("handled request {}", i % 10),
+            attributes: BTreeMap::from([
+                ("method".into(), "GET".into()),
+                ("status".into(), "200".into()),
+            ]),
+        })
+        .collect();
+
+    measure("buffering 10,000 records", || {
+        records
+            .serialize(Serializer::new().with_capacity_limit(usize::MAX))
+            .unwrap()
+    });
+}
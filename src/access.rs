@@ -0,0 +1,1156 @@
+use core::{mem, ops::Index};
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{Error, ErrorKind, Kind, Name, Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Get the value of a boolean buffer.
+
+    This method returns `None` if the buffer doesn't contain a boolean.
+    */
+    pub fn as_bool(&self) -> Option<bool> {
+        self.0.as_bool()
+    }
+
+    /**
+    Get the value of a single character buffer.
+
+    This method returns `None` if the buffer doesn't contain a character.
+    */
+    pub fn as_char(&self) -> Option<char> {
+        self.0.as_char()
+    }
+
+    /**
+    Get the value of an unsigned integer buffer.
+
+    The buffer may contain an integer of any width or sign, so long as its value fits in a `u128`.
+    This method returns `None` if the buffer doesn't contain an integer or if it doesn't fit.
+    */
+    pub fn as_u128(&self) -> Option<u128> {
+        self.0.as_u128()
+    }
+
+    /**
+    Get the value of a signed integer buffer.
+
+    The buffer may contain an integer of any width or sign, so long as its value fits in an `i128`.
+    This method returns `None` if the buffer doesn't contain an integer or if it doesn't fit.
+    */
+    pub fn as_i128(&self) -> Option<i128> {
+        self.0.as_i128()
+    }
+
+    /**
+    Get the value of a binary floating point buffer.
+
+    The buffer may contain a float of any width, or an integer that can be represented exactly in an `f64`.
+    This method returns `None` if the buffer doesn't contain a number or if it can't be converted exactly.
+    */
+    pub fn as_f64(&self) -> Option<f64> {
+        self.0.as_f64()
+    }
+
+    /**
+    Get the value of a string buffer.
+
+    This method returns `None` if the buffer doesn't contain a string.
+    */
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_str()
+    }
+
+    /**
+    Get the value of a byte-string buffer.
+
+    This method returns `None` if the buffer doesn't contain a byte-string.
+    */
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.0.as_bytes()
+    }
+
+    /**
+    Get the value inside an option buffer.
+
+    Only a single layer of `Some` is looked inside, so `Some(Some(1))` gives `Some(1)`.
+    Use [`Owned::unwrap_options`] to remove every layer. This method returns `None` if the
+    buffer doesn't contain a `Some`.
+    */
+    pub fn as_some(&self) -> Option<Ref<'_>> {
+        self.0.as_some().map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get the number of elements in a collection buffer.
+
+    Sequences, tuples, maps, and structs with named or unnamed fields, including enum variants, are considered collections.
+    This method returns `None` if the buffer doesn't contain a collection.
+    */
+    pub fn len(&self) -> Option<usize> {
+        self.0.len()
+    }
+
+    /**
+    Whether a collection buffer contains no elements.
+
+    This method returns `None` if the buffer doesn't contain a collection.
+    */
+    pub fn is_empty(&self) -> Option<bool> {
+        self.0.len().map(|len| len == 0)
+    }
+
+    /**
+    Get a field of a struct or map buffer by name.
+
+    Structs, including enum variants, are searched by field name.
+    Maps are searched for a key that's a string equal to `field`.
+    This method returns `None` if the buffer doesn't contain a struct or map, or if there's no matching field.
+    */
+    pub fn get(&self, field: &str) -> Option<Ref<'_>> {
+        self.0.get(field).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get a field of a struct or map buffer by name, ignoring its ASCII case.
+
+    Fields are matched in the same way as [`Owned::get`], except the case of ASCII letters is ignored.
+    If more than one field matches then the first is returned.
+    */
+    pub fn get_ci(&self, field: &str) -> Option<Ref<'_>> {
+        self.0
+            .get_by(|k| k.eq_ignore_ascii_case(field))
+            .map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get a field of a struct or map buffer by any of its names.
+
+    Each name in `names` is tried in order, and the field matching the first one that's present is returned.
+    Fields are matched in the same way as [`Owned::get`].
+    */
+    pub fn get_alias(&self, names: &[&str]) -> Option<Ref<'_>> {
+        self.0.get_alias(names).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get the value of an entry in a map buffer by its key.
+
+    Unlike [`Owned::get`], the key can be any kind of value, like an integer or a tuple.
+    Keys are compared using the [`PartialEq`] implementation of buffers, so keys of different
+    types, like `1u8` and `1u16`, don't match.
+    This method returns `None` if the buffer doesn't contain a map, or if there's no matching key.
+    */
+    pub fn keyed_get(&self, key: &Ref) -> Option<Ref<'_>> {
+        self.0.keyed_get(&key.0).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get an element of a sequence or tuple buffer by its index.
+
+    Sequences, tuples, and structs with unnamed fields, including enum variants, can be indexed.
+    This method returns `None` if the buffer doesn't contain an indexable collection, or if `index` is out of range.
+    */
+    pub fn get_index(&self, index: usize) -> Option<Ref<'_>> {
+        self.0.get_index(index).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Iterate over the elements of a sequence or tuple buffer.
+
+    Sequences, tuples, and structs with unnamed fields, including enum variants, can be iterated.
+    Each element borrows from this buffer.
+    This method returns `None` if the buffer doesn't contain an indexable collection.
+    */
+    pub fn iter_seq(&self) -> Option<impl Iterator<Item = Ref<'_>> + '_> {
+        Some(self.0.as_seq()?.iter().map(|v| Ref(Value::Borrowed(v))))
+    }
+
+    /**
+    Iterate over the entries of a map buffer.
+
+    Each key and value borrows from this buffer.
+    This method returns `None` if the buffer doesn't contain a map.
+    */
+    pub fn iter_map(&self) -> Option<impl Iterator<Item = (Ref<'_>, Ref<'_>)> + '_> {
+        Some(
+            self.0
+                .as_map()?
+                .iter()
+                .map(|(k, v)| (Ref(Value::Borrowed(k)), Ref(Value::Borrowed(v)))),
+        )
+    }
+
+    /**
+    Iterate over the named fields of a struct buffer.
+
+    Structs with named fields, including enum variants, can be iterated.
+    Each value borrows from this buffer.
+    This method returns `None` if the buffer doesn't contain a struct with named fields.
+    */
+    pub fn iter_fields(&self) -> Option<impl Iterator<Item = (&str, Ref<'_>)> + '_> {
+        Some(
+            self.0
+                .as_fields()?
+                .iter()
+                .map(|(k, v)| (k.as_str(), Ref(Value::Borrowed(v)))),
+        )
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Get the value of a boolean buffer.
+
+    This method returns `None` if the buffer doesn't contain a boolean.
+    */
+    pub fn as_bool(&self) -> Option<bool> {
+        self.0.as_bool()
+    }
+
+    /**
+    Get the value of a single character buffer.
+
+    This method returns `None` if the buffer doesn't contain a character.
+    */
+    pub fn as_char(&self) -> Option<char> {
+        self.0.as_char()
+    }
+
+    /**
+    Get the value of an unsigned integer buffer.
+
+    The buffer may contain an integer of any width or sign, so long as its value fits in a `u128`.
+    This method returns `None` if the buffer doesn't contain an integer or if it doesn't fit.
+    */
+    pub fn as_u128(&self) -> Option<u128> {
+        self.0.as_u128()
+    }
+
+    /**
+    Get the value of a signed integer buffer.
+
+    The buffer may contain an integer of any width or sign, so long as its value fits in an `i128`.
+    This method returns `None` if the buffer doesn't contain an integer or if it doesn't fit.
+    */
+    pub fn as_i128(&self) -> Option<i128> {
+        self.0.as_i128()
+    }
+
+    /**
+    Get the value of a binary floating point buffer.
+
+    The buffer may contain a float of any width, or an integer that can be represented exactly in an `f64`.
+    This method returns `None` if the buffer doesn't contain a number or if it can't be converted exactly.
+    */
+    pub fn as_f64(&self) -> Option<f64> {
+        self.0.as_f64()
+    }
+
+    /**
+    Get the value of a string buffer.
+
+    The string may be either owned or borrowed by the buffer.
+    This method returns `None` if the buffer doesn't contain a string.
+    */
+    pub fn as_str(&self) -> Option<&str> {
+        self.0.as_str()
+    }
+
+    /**
+    Get the value of a byte-string buffer.
+
+    The byte-string may be either owned or borrowed by the buffer.
+    This method returns `None` if the buffer doesn't contain a byte-string.
+    */
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.0.as_bytes()
+    }
+
+    /**
+    Get the value inside an option buffer.
+
+    See [`Owned::as_some`] for details.
+    */
+    pub fn as_some(&self) -> Option<Ref<'_>> {
+        self.0.as_some().map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get the number of elements in a collection buffer.
+
+    Sequences, tuples, maps, and structs with named or unnamed fields, including enum variants, are considered collections.
+    This method returns `None` if the buffer doesn't contain a collection.
+    */
+    pub fn len(&self) -> Option<usize> {
+        self.0.len()
+    }
+
+    /**
+    Whether a collection buffer contains no elements.
+
+    This method returns `None` if the buffer doesn't contain a collection.
+    */
+    pub fn is_empty(&self) -> Option<bool> {
+        self.0.len().map(|len| len == 0)
+    }
+
+    /**
+    Get a field of a struct or map buffer by name.
+
+    Structs, including enum variants, are searched by field name.
+    Maps are searched for a key that's a string equal to `field`.
+    This method returns `None` if the buffer doesn't contain a struct or map, or if there's no matching field.
+    */
+    pub fn get(&self, field: &str) -> Option<Ref<'_>> {
+        self.0.get(field).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get a field of a struct or map buffer by name, ignoring its ASCII case.
+
+    See [`Owned::get_ci`] for details.
+    */
+    pub fn get_ci(&self, field: &str) -> Option<Ref<'_>> {
+        self.0
+            .get_by(|k| k.eq_ignore_ascii_case(field))
+            .map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get a field of a struct or map buffer by any of its names.
+
+    See [`Owned::get_alias`] for details.
+    */
+    pub fn get_alias(&self, names: &[&str]) -> Option<Ref<'_>> {
+        self.0.get_alias(names).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get the value of an entry in a map buffer by its key.
+
+    See [`Owned::keyed_get`] for details.
+    */
+    pub fn keyed_get(&self, key: &Ref) -> Option<Ref<'_>> {
+        self.0.keyed_get(&key.0).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get an element of a sequence or tuple buffer by its index.
+
+    Sequences, tuples, and structs with unnamed fields, including enum variants, can be indexed.
+    This method returns `None` if the buffer doesn't contain an indexable collection, or if `index` is out of range.
+    */
+    pub fn get_index(&self, index: usize) -> Option<Ref<'_>> {
+        self.0.get_index(index).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Iterate over the elements of a sequence or tuple buffer.
+
+    Sequences, tuples, and structs with unnamed fields, including enum variants, can be iterated.
+    Each element borrows from this buffer.
+    This method returns `None` if the buffer doesn't contain an indexable collection.
+    */
+    pub fn iter_seq(&self) -> Option<impl Iterator<Item = Ref<'_>> + '_> {
+        Some(self.0.as_seq()?.iter().map(|v| Ref(Value::Borrowed(v))))
+    }
+
+    /**
+    Iterate over the entries of a map buffer.
+
+    Each key and value borrows from this buffer.
+    This method returns `None` if the buffer doesn't contain a map.
+    */
+    pub fn iter_map(&self) -> Option<impl Iterator<Item = (Ref<'_>, Ref<'_>)> + '_> {
+        Some(
+            self.0
+                .as_map()?
+                .iter()
+                .map(|(k, v)| (Ref(Value::Borrowed(k)), Ref(Value::Borrowed(v)))),
+        )
+    }
+
+    /**
+    Iterate over the named fields of a struct buffer.
+
+    Structs with named fields, including enum variants, can be iterated.
+    Each value borrows from this buffer.
+    This method returns `None` if the buffer doesn't contain a struct with named fields.
+    */
+    pub fn iter_fields(&self) -> Option<impl Iterator<Item = (&str, Ref<'_>)> + '_> {
+        Some(
+            self.0
+                .as_fields()?
+                .iter()
+                .map(|(k, v)| (k.as_str(), Ref(Value::Borrowed(v)))),
+        )
+    }
+}
+
+impl Index<&str> for Owned {
+    type Output = Owned;
+
+    /**
+    Get a field of a struct or map buffer by name.
+
+    Fields are matched in the same way as [`Owned::get`].
+
+    # Panics
+
+    This method panics if the buffer doesn't contain a struct or map, or if there's no matching field.
+    Use [`Owned::get`] to get a field without panicking.
+    */
+    fn index(&self, field: &str) -> &Owned {
+        match self.0.get(field) {
+            Some(v) => Owned::from_value_ref(v),
+            None => missing_field(&self.0, field),
+        }
+    }
+}
+
+impl Index<usize> for Owned {
+    type Output = Owned;
+
+    /**
+    Get an element of a sequence or tuple buffer by its index.
+
+    Elements are indexed in the same way as [`Owned::get_index`].
+
+    # Panics
+
+    This method panics if the buffer doesn't contain an indexable collection, or if `index` is out of range.
+    Use [`Owned::get_index`] to get an element without panicking.
+    */
+    fn index(&self, index: usize) -> &Owned {
+        match self.0.get_index(index) {
+            Some(v) => Owned::from_value_ref(v),
+            None => missing_index(&self.0, index),
+        }
+    }
+}
+
+impl<'a> Index<&str> for Ref<'a> {
+    type Output = Ref<'a>;
+
+    /**
+    Get a field of a struct or map buffer by name.
+
+    This follows the same rules as indexing an [`Owned`] buffer by field name.
+    */
+    fn index(&self, field: &str) -> &Ref<'a> {
+        match self.0.get(field) {
+            Some(v) => Ref::from_value_ref(v),
+            None => missing_field(&self.0, field),
+        }
+    }
+}
+
+impl<'a> Index<usize> for Ref<'a> {
+    type Output = Ref<'a>;
+
+    /**
+    Get an element of a sequence or tuple buffer by its index.
+
+    This follows the same rules as indexing an [`Owned`] buffer by position.
+    */
+    fn index(&self, index: usize) -> &Ref<'a> {
+        match self.0.get_index(index) {
+            Some(v) => Ref::from_value_ref(v),
+            None => missing_index(&self.0, index),
+        }
+    }
+}
+
+impl Owned {
+    pub(crate) fn from_value_ref<'b>(value: &'b Value<'static>) -> &'b Owned {
+        // SAFETY: `Owned` is a `#[repr(transparent)]` wrapper around `Value<'static>`
+        unsafe { &*(value as *const Value<'static> as *const Owned) }
+    }
+}
+
+impl<'a> Ref<'a> {
+    pub(crate) fn from_value_ref<'b>(value: &'b Value<'a>) -> &'b Ref<'a> {
+        // SAFETY: `Ref` is a `#[repr(transparent)]` wrapper around `Value<'a>`
+        unsafe { &*(value as *const Value<'a> as *const Ref<'a>) }
+    }
+}
+
+#[cold]
+pub(crate) fn missing_field(value: &Value, field: &str) -> ! {
+    if value.as_fields().is_some() || value.as_map().is_some() {
+        panic!("no field named {:?} in the buffer", field)
+    } else {
+        panic!(
+            "cannot get field {:?} from a buffer containing {:?}",
+            field,
+            value.kind()
+        )
+    }
+}
+
+#[cold]
+pub(crate) fn missing_index(value: &Value, index: usize) -> ! {
+    match value.as_seq() {
+        Some(seq) => panic!(
+            "index {} is out of range for a buffer of length {}",
+            index,
+            seq.len()
+        ),
+        None => panic!(
+            "cannot get index {} from a buffer containing {:?}",
+            index,
+            value.kind()
+        ),
+    }
+}
+
+impl<'a> Value<'a> {
+    pub(crate) fn as_seq(&self) -> Option<&[Value<'a>]> {
+        match *self.resolve() {
+            Value::Seq(ref fields, _) | Value::Tuple(ref fields) => Some(fields),
+            Value::TupleStruct(ref v) => Some(&v.fields),
+            Value::TupleVariant(ref v) => Some(&v.fields),
+            _ => None,
+        }
+    }
+
+    fn as_some(&self) -> Option<&Value<'a>> {
+        match *self.resolve() {
+            Value::Some(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn as_map(&self) -> Option<&[(Value<'a>, Value<'a>)]> {
+        match *self.resolve() {
+            Value::Map(ref fields, _) => Some(fields),
+            _ => None,
+        }
+    }
+
+    fn as_fields(&self) -> Option<&[(Name<'a>, Value<'a>)]> {
+        match *self.resolve() {
+            Value::Struct(ref v) => Some(&v.fields),
+            Value::StructVariant(ref v) => Some(&v.fields),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_index(&self, index: usize) -> Option<&Value<'a>> {
+        self.as_seq()?.get(index)
+    }
+
+    pub(crate) fn get(&self, field: &str) -> Option<&Value<'a>> {
+        self.get_by(|k| k == field)
+    }
+
+    fn get_alias(&self, names: &[&str]) -> Option<&Value<'a>> {
+        names.iter().find_map(|name| self.get(name))
+    }
+
+    /**
+    Get the first field of a struct or map whose name satisfies `matches`.
+    */
+    fn get_by(&self, matches: impl Fn(&str) -> bool) -> Option<&Value<'a>> {
+        match *self.resolve() {
+            Value::Map(ref fields, _) => fields
+                .iter()
+                .find(|(k, _)| k.as_str().is_some_and(&matches))
+                .map(|(_, v)| v),
+            _ => self
+                .as_fields()?
+                .iter()
+                .find(|(k, _)| matches(k))
+                .map(|(_, v)| v),
+        }
+    }
+
+    fn keyed_get(&self, key: &Value) -> Option<&Value<'a>> {
+        self.as_map()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn len(&self) -> Option<usize> {
+        match *self.resolve() {
+            Value::Seq(ref fields, _) => Some(fields.len()),
+            Value::Tuple(ref fields) => Some(fields.len()),
+            Value::TupleStruct(ref v) => Some(v.fields.len()),
+            Value::TupleVariant(ref v) => Some(v.fields.len()),
+            Value::Map(ref fields, _) => Some(fields.len()),
+            Value::Struct(ref v) => Some(v.fields.len()),
+            Value::StructVariant(ref v) => Some(v.fields.len()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u128(&self) -> Option<u128> {
+        match *self.resolve() {
+            Value::U8(v) => Some(v.into()),
+            Value::U16(v) => Some(v.into()),
+            Value::U32(v) => Some(v.into()),
+            Value::U64(v) => Some(v.into()),
+            Value::U128(ref v) => Some(**v),
+            Value::I8(v) => v.try_into().ok(),
+            Value::I16(v) => v.try_into().ok(),
+            Value::I32(v) => v.try_into().ok(),
+            Value::I64(v) => v.try_into().ok(),
+            Value::I128(ref v) => (**v).try_into().ok(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_i128(&self) -> Option<i128> {
+        match *self.resolve() {
+            Value::U8(v) => Some(v.into()),
+            Value::U16(v) => Some(v.into()),
+            Value::U32(v) => Some(v.into()),
+            Value::U64(v) => Some(v.into()),
+            Value::U128(ref v) => (**v).try_into().ok(),
+            Value::I8(v) => Some(v.into()),
+            Value::I16(v) => Some(v.into()),
+            Value::I32(v) => Some(v.into()),
+            Value::I64(v) => Some(v.into()),
+            Value::I128(ref v) => Some(**v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        // `2^127`, the first float beyond the range of `i128`
+        const I128_END: f64 = 170141183460469231731687303715884105728.0;
+
+        match *self.resolve() {
+            Value::F32(v) => Some(v.into()),
+            Value::F64(v) => Some(v),
+            Value::U128(ref v) => {
+                let v = **v;
+                let f = v as f64;
+
+                // Floats at or beyond `2^128` saturate when converted back
+                if f < I128_END * 2.0 && f as u128 == v {
+                    Some(f)
+                } else {
+                    None
+                }
+            }
+            _ => {
+                let v = self.as_i128()?;
+                let f = v as f64;
+
+                if f < I128_END && f as i128 == v {
+                    Some(f)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match *self.resolve() {
+            Value::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn as_char(&self) -> Option<char> {
+        match *self.resolve() {
+            Value::Char(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match *self.resolve() {
+            Value::Str(ref v) => Some(v),
+            Value::SharedStr(ref v) => Some(v),
+            Value::BorrowedStr(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match *self.resolve() {
+            Value::Bytes(ref v) => Some(v),
+            Value::BorrowedBytes(v) => Some(v),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(ref v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/**
+Implement `TryFrom` a buffer for scalar types.
+
+These are the owning counterparts to the `as_*` methods, with errors that describe why the
+conversion failed.
+*/
+macro_rules! impl_try_from {
+    ($($buf:ty => [$($lt:lifetime)?],)*) => {
+        $(
+            impl<$($lt)?> TryFrom<$buf> for u64 {
+                type Error = Error;
+
+                fn try_from(buf: $buf) -> Result<Self, Self::Error> {
+                    buf.0.to_u64()
+                }
+            }
+
+            impl<$($lt)?> TryFrom<$buf> for i64 {
+                type Error = Error;
+
+                fn try_from(buf: $buf) -> Result<Self, Self::Error> {
+                    buf.0.to_i64()
+                }
+            }
+
+            impl<$($lt)?> TryFrom<$buf> for f64 {
+                type Error = Error;
+
+                fn try_from(buf: $buf) -> Result<Self, Self::Error> {
+                    buf.0.to_f64()
+                }
+            }
+
+            impl<$($lt)?> TryFrom<$buf> for bool {
+                type Error = Error;
+
+                fn try_from(buf: $buf) -> Result<Self, Self::Error> {
+                    buf.0.as_bool().ok_or_else(|| invalid_type("a boolean"))
+                }
+            }
+
+            impl<$($lt)?> TryFrom<$buf> for String {
+                type Error = Error;
+
+                fn try_from(buf: $buf) -> Result<Self, Self::Error> {
+                    buf.into_value().into_string()
+                }
+            }
+
+            impl<$($lt)?> TryFrom<$buf> for Vec<u8> {
+                type Error = Error;
+
+                fn try_from(buf: $buf) -> Result<Self, Self::Error> {
+                    buf.into_value().into_bytes()
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from!(
+    Owned => [],
+    Ref<'a> => ['a],
+);
+
+impl<'a> Value<'a> {
+    /**
+    Convert an integer of any width or sign into a `u64`.
+    */
+    fn to_u64(&self) -> Result<u64, Error> {
+        match self.as_u128() {
+            Some(v) => v.try_into().map_err(|_| out_of_range("u64")),
+            None if self.kind() == Kind::Int => Err(out_of_range("u64")),
+            None => Err(invalid_type("an integer")),
+        }
+    }
+
+    /**
+    Convert an integer of any width or sign into an `i64`.
+    */
+    fn to_i64(&self) -> Result<i64, Error> {
+        match self.as_i128() {
+            Some(v) => v.try_into().map_err(|_| out_of_range("i64")),
+            None if self.kind() == Kind::Int => Err(out_of_range("i64")),
+            None => Err(invalid_type("an integer")),
+        }
+    }
+
+    /**
+    Convert a float, or an integer that can be represented exactly, into an `f64`.
+    */
+    fn to_f64(&self) -> Result<f64, Error> {
+        match self.as_f64() {
+            Some(v) => Ok(v),
+            None if self.kind() == Kind::Int => Err(out_of_range("f64")),
+            None => Err(invalid_type("a number")),
+        }
+    }
+
+    /**
+    Convert a string into an owned `String`, reusing its allocation if it has one.
+    */
+    fn into_string(mut self) -> Result<String, Error> {
+        match self {
+            Value::Str(ref mut v) => Ok(mem::take(v).into()),
+            ref value => value
+                .as_str()
+                .map(Into::into)
+                .ok_or_else(|| invalid_type("a string")),
+        }
+    }
+
+    /**
+    Convert a byte-string into an owned `Vec<u8>`, reusing its allocation if it has one.
+    */
+    fn into_bytes(mut self) -> Result<Vec<u8>, Error> {
+        match self {
+            Value::Bytes(ref mut v) => Ok(mem::take(v).into_vec()),
+            ref value => value
+                .as_bytes()
+                .map(Into::into)
+                .ok_or_else(|| invalid_type("a byte-string")),
+        }
+    }
+}
+
+fn invalid_type(expected: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidType,
+        format_args!("the buffer isn't {}", expected),
+    )
+}
+
+fn out_of_range(ty: &str) -> Error {
+    Error::new(
+        ErrorKind::NumberOutOfRange,
+        format_args!("the buffer's value doesn't fit in {}", ty),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalars() {
+        assert_eq!(Some(true), Ref::bool(true).as_bool());
+        assert_eq!(Some('a'), Ref::char('a').as_char());
+        assert_eq!(Some("a string"), Ref::str("a string").as_str());
+        assert_eq!(Some("a string"), Ref::owned_str("a string").as_str());
+        assert_eq!(Some(b"bytes" as &[u8]), Ref::bytes(b"bytes").as_bytes());
+        assert_eq!(
+            Some(b"bytes" as &[u8]),
+            Ref::owned_bytes(b"bytes" as &[u8]).as_bytes()
+        );
+
+        assert_eq!(None, Ref::str("a string").as_bool());
+        assert_eq!(None, Ref::bool(true).as_str());
+        assert_eq!(None, Ref::str("a string").as_bytes());
+
+        let owned = Owned::buffer("a string").unwrap();
+        assert_eq!(Some("a string"), owned.as_str());
+        assert_eq!(Some("a string"), owned.as_ref().as_str());
+    }
+
+    #[test]
+    fn as_some() {
+        let owned = Owned::from(Ref::some(Ref::some(Ref::u8(1))));
+
+        assert_eq!(Some(Ref::some(Ref::u8(1))), owned.as_some());
+        assert_eq!(Some(Ref::u8(1)), Ref::some(Ref::u8(1)).as_some());
+
+        // Options borrowed from other buffers can be looked inside too
+        let inner = owned.as_some().unwrap();
+        assert_eq!(Some(Ref::u8(1)), inner.as_some());
+        assert_eq!(None, inner.as_some().unwrap().as_some());
+
+        assert_eq!(None, Ref::none().as_some());
+        assert_eq!(None, Ref::u8(1).as_some());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Some(5), Ref::u8(5).as_u128());
+        assert_eq!(Some(5), Ref::i64(5).as_u128());
+        assert_eq!(None, Ref::i8(-5).as_u128());
+        assert_eq!(Some(u128::MAX), Ref::u128(u128::MAX).as_u128());
+
+        assert_eq!(Some(-5), Ref::i8(-5).as_i128());
+        assert_eq!(Some(5), Ref::u32(5).as_i128());
+        assert_eq!(None, Ref::u128(u128::MAX).as_i128());
+
+        assert_eq!(Some(1.5), Ref::f32(1.5).as_f64());
+        assert_eq!(Some(1.5), Ref::f64(1.5).as_f64());
+        assert_eq!(Some(-5.0), Ref::i16(-5).as_f64());
+        assert_eq!(Some(2f64.powi(64)), Ref::u128(1 << 64).as_f64());
+        assert_eq!(None, Ref::u64(u64::MAX).as_f64());
+        assert_eq!(None, Ref::u128(u128::MAX).as_f64());
+        assert_eq!(None, Ref::i128(i128::MAX).as_f64());
+        assert_eq!(Some(-(2f64.powi(127))), Ref::i128(i128::MIN).as_f64());
+
+        assert_eq!(None, Ref::f64(1.5).as_u128());
+        assert_eq!(None, Ref::str("5").as_i128());
+    }
+
+    #[test]
+    fn len() {
+        assert_eq!(Some(2), Ref::seq([Ref::unit(), Ref::unit()]).len());
+        assert_eq!(Some(false), Ref::seq([Ref::unit()]).is_empty());
+        assert_eq!(Some(true), Ref::map([]).is_empty());
+        assert_eq!(
+            Some(1),
+            Ref::record_struct_variant("Enum", 0, "Variant", [("a", Ref::unit())]).len()
+        );
+
+        assert_eq!(None, Ref::str("a string").len());
+        assert_eq!(None, Ref::unit_struct("UnitStruct").is_empty());
+    }
+
+    #[test]
+    fn get() {
+        let buf = Ref::record_struct("Struct", [("a", Ref::u8(1)), ("b", Ref::str("b"))]);
+
+        assert_eq!(Some(1), buf.get("a").and_then(|a| a.as_u128()));
+        assert_eq!(Some("b"), buf.get("b").unwrap().as_str());
+        assert!(buf.get("c").is_none());
+
+        let buf = Ref::map([(Ref::u8(1), Ref::u8(1)), (Ref::owned_str("a"), Ref::u8(2))]);
+
+        assert_eq!(Some(2), buf.get("a").and_then(|a| a.as_u128()));
+        assert!(buf.get("1").is_none());
+
+        assert!(Ref::seq([Ref::str("a")]).get("a").is_none());
+    }
+
+    #[test]
+    fn get_index() {
+        let buf = Ref::tuple([Ref::str("a"), Ref::u8(1)]);
+
+        assert_eq!(Some("a"), buf.get_index(0).unwrap().as_str());
+        assert_eq!(Some(1), buf.get_index(1).unwrap().as_u128());
+        assert!(buf.get_index(2).is_none());
+
+        let buf = Ref::tuple_variant("Enum", 0, "Variant", [Ref::bool(true)]);
+
+        assert_eq!(Some(true), buf.get_index(0).unwrap().as_bool());
+
+        assert!(Ref::map([(Ref::u8(0), Ref::unit())]).get_index(0).is_none());
+    }
+
+    #[test]
+    fn iter() {
+        let buf = Owned::buffer([1, 2, 3]).unwrap();
+
+        assert_eq!(
+            alloc::vec![Some(1), Some(2), Some(3)],
+            buf.iter_seq()
+                .unwrap()
+                .map(|v| v.as_i128())
+                .collect::<alloc::vec::Vec<_>>()
+        );
+
+        let buf = Ref::map([(Ref::str("a"), Ref::u8(1))]);
+
+        let (k, v) = buf.iter_map().unwrap().next().unwrap();
+        assert_eq!(Some("a"), k.as_str());
+        assert_eq!(Some(1), v.as_u128());
+
+        let buf = Ref::record_struct("Struct", [("a", Ref::u8(1)), ("b", Ref::u8(2))]);
+
+        assert_eq!(
+            alloc::vec!["a", "b"],
+            buf.iter_fields()
+                .unwrap()
+                .map(|(k, _)| k)
+                .collect::<alloc::vec::Vec<_>>()
+        );
+
+        assert!(buf.iter_seq().is_none());
+        assert!(buf.iter_map().is_none());
+    }
+
+    #[test]
+    fn try_from_scalars() {
+        assert_eq!(1, u64::try_from(Owned::from(1i8)).unwrap());
+        assert_eq!(
+            u64::MAX,
+            u64::try_from(Owned::from(u64::MAX as u128)).unwrap()
+        );
+        assert_eq!(-1, i64::try_from(Owned::from(-1i128)).unwrap());
+        assert_eq!(1.0, f64::try_from(Owned::from(1u8)).unwrap());
+        assert_eq!(1.5, f64::try_from(Owned::from(1.5f32)).unwrap());
+        assert!(bool::try_from(Owned::from(true)).unwrap());
+        assert_eq!(
+            "a string",
+            String::try_from(Owned::from("a string")).unwrap()
+        );
+        assert_eq!(
+            b"bytes" as &[u8],
+            Vec::<u8>::try_from(Owned::from(Ref::owned_bytes(b"bytes" as &[u8]))).unwrap()
+        );
+
+        let owned = Owned::from("a string");
+        assert_eq!("a string", String::try_from(owned.as_ref()).unwrap());
+        assert_eq!(
+            b"bytes" as &[u8],
+            Vec::<u8>::try_from(Ref::bytes(b"bytes")).unwrap()
+        );
+    }
+
+    #[test]
+    fn try_from_scalars_err() {
+        for (kind, err) in [
+            (
+                ErrorKind::NumberOutOfRange,
+                u64::try_from(Owned::from(-1i8)).unwrap_err(),
+            ),
+            (
+                ErrorKind::NumberOutOfRange,
+                u64::try_from(Owned::from(u128::MAX)).unwrap_err(),
+            ),
+            (
+                ErrorKind::NumberOutOfRange,
+                i64::try_from(Owned::from(u64::MAX)).unwrap_err(),
+            ),
+            (
+                ErrorKind::NumberOutOfRange,
+                f64::try_from(Owned::from(u64::MAX - 1)).unwrap_err(),
+            ),
+            (
+                ErrorKind::InvalidType,
+                u64::try_from(Owned::from(1.0f64)).unwrap_err(),
+            ),
+            (
+                ErrorKind::InvalidType,
+                f64::try_from(Owned::from("1")).unwrap_err(),
+            ),
+            (
+                ErrorKind::InvalidType,
+                bool::try_from(Owned::from(1u8)).unwrap_err(),
+            ),
+            (
+                ErrorKind::InvalidType,
+                String::try_from(Ref::char('a')).unwrap_err(),
+            ),
+            (
+                ErrorKind::InvalidType,
+                Vec::<u8>::try_from(Ref::str("a")).unwrap_err(),
+            ),
+        ] {
+            assert_eq!(kind, err.kind(), "{}", err);
+        }
+    }
+
+    #[test]
+    fn index() {
+        let buf = Owned::from(Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::seq([Ref::u8(1), Ref::u8(2)])),
+                ("b", Ref::map([(Ref::str("c"), Ref::str("d"))])),
+            ],
+        ));
+
+        assert_eq!(2u64, buf["a"][1]);
+        assert_eq!("d", buf["b"]["c"]);
+
+        let borrowed = Ref::tuple([buf.as_ref()]);
+
+        assert_eq!(2u64, borrowed[0]["a"][1]);
+        assert_eq!("d", borrowed[0]["b"]["c"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no field named \"c\" in the buffer")]
+    fn index_missing_field() {
+        let buf = Owned::from(Ref::record_struct("Struct", [("a", Ref::unit())]));
+
+        let _ = &buf["c"];
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot get field \"a\" from a buffer containing Seq")]
+    fn index_field_of_seq() {
+        let buf = Owned::from(Ref::seq([Ref::unit()]));
+
+        let _ = &buf["a"];
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 is out of range for a buffer of length 1")]
+    fn index_missing_index() {
+        let buf = Owned::from(Ref::seq([Ref::unit()]));
+
+        let _ = &buf[1];
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot get index 0 from a buffer containing Int")]
+    fn index_of_int() {
+        let buf = Owned::from(1u8);
+
+        let _ = &buf[0];
+    }
+
+    #[test]
+    fn keyed_get() {
+        let buf = Owned::map([
+            (Owned::u8(1), Owned::str("a")),
+            (Owned::tuple([Owned::u8(1), Owned::u8(2)]), Owned::str("b")),
+            (Owned::str("c"), Owned::str("c")),
+        ]);
+
+        assert_eq!(Some(Ref::str("a")), buf.keyed_get(&Ref::u8(1)));
+        assert_eq!(
+            Some(Ref::str("b")),
+            buf.keyed_get(&Ref::tuple([Ref::u8(1), Ref::u8(2)]))
+        );
+        assert_eq!(Some(Ref::str("c")), buf.keyed_get(&Ref::str("c")));
+        assert_eq!(Some(Ref::str("a")), buf.as_ref().keyed_get(&Ref::u8(1)));
+
+        assert_eq!(None, buf.keyed_get(&Ref::u16(1)));
+        assert_eq!(None, buf.keyed_get(&Ref::u8(2)));
+        assert_eq!(None, Ref::seq([Ref::u8(1)]).keyed_get(&Ref::u8(1)));
+    }
+
+    #[test]
+    fn get_ci_alias() {
+        let buf = Owned::from(Ref::record_struct(
+            "Config",
+            [
+                ("Host", Ref::str("localhost")),
+                ("HOST", Ref::str("ignored")),
+                ("port_number", Ref::u16(8080)),
+            ],
+        ));
+
+        assert_eq!(Some(Ref::str("localhost")), buf.get_ci("host"));
+        assert_eq!(Some(Ref::str("localhost")), buf.get_ci("HoSt"));
+        assert_eq!(None, buf.get("host"));
+        assert_eq!(None, buf.get_ci("port"));
+
+        assert_eq!(
+            Some(Ref::u16(8080)),
+            buf.get_alias(&["port", "port_number", "Host"])
+        );
+        assert_eq!(
+            Some(Ref::str("ignored")),
+            buf.as_ref().get_alias(&["HOST", "Host"])
+        );
+        assert_eq!(None, buf.get_alias(&["host", "PORT_NUMBER"]));
+        assert_eq!(None, buf.get_alias(&[]));
+
+        let map = Ref::map([
+            (Ref::u8(1), Ref::unit()),
+            (Ref::str("Content-Type"), Ref::str("text/plain")),
+        ]);
+
+        assert_eq!(Some(Ref::str("text/plain")), map.get_ci("content-type"));
+        assert_eq!(
+            Some(Ref::str("text/plain")),
+            map.get_alias(&["mime", "Content-Type"])
+        );
+
+        let variant = Ref::record_struct_variant("Enum", 0, "A", [("Field", Ref::bool(true))]);
+
+        assert_eq!(Some(Ref::bool(true)), variant.get_ci("field"));
+        assert_eq!(None, Ref::seq([Ref::str("field")]).get_ci("field"));
+    }
+}
@@ -0,0 +1,244 @@
+/*!
+Generating arbitrary buffers with `arbitrary`, for fuzzing.
+*/
+
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{
+    LenHint, Name, NewtypeStruct, NewtypeVariant, Owned, Struct, StructVariant, TupleStruct,
+    TupleVariant, UnitStruct, UnitVariant, Value,
+};
+
+/**
+The maximum number of levels of nesting within a generated buffer.
+
+Values are generated recursively, so this also bounds the stack used to generate them.
+*/
+const MAX_DEPTH: usize = 8;
+
+/**
+The maximum number of elements in each generated sequence, map, tuple, and struct.
+*/
+const MAX_WIDTH: usize = 8;
+
+/**
+The names given to generated structs, enums, and enum variants.
+*/
+const TYPE_NAMES: &[&str] = &["A", "B", "C", "D"];
+
+/**
+The names given to the fields of generated structs and enum variants, in order.
+*/
+const FIELD_NAMES: &[&str] = &["a", "b", "c", "d", "e", "f", "g", "h"];
+
+impl<'a> Arbitrary<'a> for Owned {
+    /**
+    Generate an arbitrary buffer from unstructured input.
+
+    The maximum depth of the buffer is drawn from the input first, and is never more than 8
+    levels of nesting, so generating a buffer can't overflow the stack however the input is
+    crafted. Generated buffers cover every kind of value, including structs and enum variants
+    nested within each other, and maps with keys of any primitive type. Structs and enum variants
+    have `'static` names, so they serialize as structs.
+    */
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let depth = u.int_in_range(0..=MAX_DEPTH)?;
+
+        Ok(Owned(arbitrary_value(u, depth)?))
+    }
+}
+
+/**
+Generate a value with at most `depth` levels of nesting.
+*/
+fn arbitrary_value(u: &mut Unstructured, depth: usize) -> Result<Value<'static>> {
+    // Once the depth budget is spent, or the input is exhausted, only leaves are generated
+    if depth == 0 || u.is_empty() {
+        return arbitrary_leaf(u);
+    }
+
+    let depth = depth - 1;
+
+    Ok(match u.int_in_range(0..=10)? {
+        0 => Value::Some(Box::new(arbitrary_value(u, depth)?)),
+        1 => Value::NewtypeStruct(Box::new(NewtypeStruct {
+            name: arbitrary_type_name(u)?,
+            value: arbitrary_value(u, depth)?,
+        })),
+        2 => Value::Struct(Box::new(Struct {
+            name: arbitrary_type_name(u)?,
+            fields: arbitrary_named_fields(u, depth)?,
+        })),
+        3 => Value::TupleStruct(Box::new(TupleStruct {
+            name: arbitrary_type_name(u)?,
+            fields: arbitrary_fields(u, depth)?,
+        })),
+        4 => Value::Tuple(arbitrary_fields(u, depth)?),
+        5 => Value::Seq(arbitrary_fields(u, depth)?, arbitrary_len_hint(u)?),
+        6 => {
+            let len = u.int_in_range(0..=MAX_WIDTH)?;
+
+            let mut fields = Vec::with_capacity(len);
+            for _ in 0..len {
+                fields.push((arbitrary_leaf(u)?, arbitrary_value(u, depth)?));
+            }
+
+            Value::Map(fields.into_boxed_slice(), arbitrary_len_hint(u)?)
+        }
+        7 => {
+            let (name, variant_index, variant) = arbitrary_variant(u)?;
+
+            Value::NewtypeVariant(Box::new(NewtypeVariant {
+                name,
+                variant_index,
+                variant,
+                value: arbitrary_value(u, depth)?,
+            }))
+        }
+        8 => {
+            let (name, variant_index, variant) = arbitrary_variant(u)?;
+
+            Value::TupleVariant(Box::new(TupleVariant {
+                name,
+                variant_index,
+                variant,
+                fields: arbitrary_fields(u, depth)?,
+            }))
+        }
+        9 => {
+            let (name, variant_index, variant) = arbitrary_variant(u)?;
+
+            Value::StructVariant(Box::new(StructVariant {
+                name,
+                variant_index,
+                variant,
+                fields: arbitrary_named_fields(u, depth)?,
+            }))
+        }
+        _ => arbitrary_leaf(u)?,
+    })
+}
+
+/**
+Generate a value that doesn't contain other values.
+*/
+fn arbitrary_leaf(u: &mut Unstructured) -> Result<Value<'static>> {
+    Ok(match u.int_in_range(0..=20)? {
+        0 => Value::Unit,
+        1 => Value::U8(u.arbitrary()?),
+        2 => Value::U16(u.arbitrary()?),
+        3 => Value::U32(u.arbitrary()?),
+        4 => Value::U64(u.arbitrary()?),
+        5 => Value::U128(Box::new(u.arbitrary()?)),
+        6 => Value::I8(u.arbitrary()?),
+        7 => Value::I16(u.arbitrary()?),
+        8 => Value::I32(u.arbitrary()?),
+        9 => Value::I64(u.arbitrary()?),
+        10 => Value::I128(Box::new(u.arbitrary()?)),
+        11 => Value::F32(u.arbitrary()?),
+        12 => Value::F64(u.arbitrary()?),
+        13 => Value::Bool(u.arbitrary()?),
+        14 => Value::Char(u.arbitrary()?),
+        15 => Value::Str(u.arbitrary::<String>()?.into()),
+        16 => Value::SharedStr(Arc::from(u.arbitrary::<&str>()?)),
+        17 => Value::Bytes(u.arbitrary::<Vec<u8>>()?.into_boxed_slice()),
+        18 => Value::UnitStruct(Box::new(UnitStruct {
+            name: arbitrary_type_name(u)?,
+        })),
+        19 => {
+            let (name, variant_index, variant) = arbitrary_variant(u)?;
+
+            Value::UnitVariant(Box::new(UnitVariant {
+                name,
+                variant_index,
+                variant,
+            }))
+        }
+        _ => Value::None,
+    })
+}
+
+fn arbitrary_fields(u: &mut Unstructured, depth: usize) -> Result<Box<[Value<'static>]>> {
+    let len = u.int_in_range(0..=MAX_WIDTH)?;
+
+    (0..len).map(|_| arbitrary_value(u, depth)).collect()
+}
+
+fn arbitrary_named_fields(
+    u: &mut Unstructured,
+    depth: usize,
+) -> Result<Box<[(Name<'static>, Value<'static>)]>> {
+    let len = u.int_in_range(0..=FIELD_NAMES.len())?;
+
+    FIELD_NAMES[..len]
+        .iter()
+        .map(|name| Ok((Name::new(name), arbitrary_value(u, depth)?)))
+        .collect()
+}
+
+fn arbitrary_type_name(u: &mut Unstructured) -> Result<Name<'static>> {
+    Ok(Name::new(u.choose(TYPE_NAMES)?))
+}
+
+/**
+Generate the name of an enum, along with the index and name of one of its variants.
+
+Variants with the same index always have the same name.
+*/
+fn arbitrary_variant(u: &mut Unstructured) -> Result<(Name<'static>, u32, Name<'static>)> {
+    let name = arbitrary_type_name(u)?;
+    let index = u.choose_index(TYPE_NAMES.len())?;
+
+    Ok((name, index as u32, Name::new(TYPE_NAMES[index])))
+}
+
+fn arbitrary_len_hint(u: &mut Unstructured) -> Result<LenHint> {
+    Ok(if u.arbitrary()? {
+        LenHint::Known
+    } else {
+        LenHint::Unknown
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn arbitrary_owned() {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+
+        for len in [0, 1, 16, 256, 4096] {
+            for _ in 0..16 {
+                // A simple xorshift generator, so the inputs are the same on every run
+                let input = (0..len)
+                    .map(|_| {
+                        seed ^= seed << 13;
+                        seed ^= seed >> 7;
+                        seed ^= seed << 17;
+                        seed as u8
+                    })
+                    .collect::<Vec<_>>();
+
+                let buf = Owned::arbitrary(&mut Unstructured::new(&input)).unwrap();
+
+                assert!(buf.max_depth() <= MAX_DEPTH + 1, "{:?}", buf);
+                assert_eq!(buf, Owned::buffer(&buf).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn arbitrary_owned_deeply_nested() {
+        // However long the input is, buffers can't be nested deeper than the budget
+        let input = vec![0xff; 1 << 16];
+
+        let buf = Owned::arbitrary(&mut Unstructured::new(&input)).unwrap();
+
+        assert!(buf.max_depth() > 1 && buf.max_depth() <= MAX_DEPTH + 1);
+    }
+}
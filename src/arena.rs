@@ -0,0 +1,350 @@
+/*!
+Buffering values with strings and bytes allocated in a `bumpalo` arena.
+*/
+
+use alloc::boxed::Box;
+use bumpalo::Bump;
+use serde::Serialize;
+
+use crate::{
+    ser::{
+        Buffer, Config, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+        SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Error, NewtypeStruct, NewtypeVariant, Ref, Serializer, Value,
+};
+
+impl Serializer {
+    /**
+    Buffer values with their strings and bytes allocated in an arena.
+
+    The buffers produced by the returned serializer borrow from `arena`, so they're [`Ref`]s
+    instead of [`crate::Owned`]s. Buffering a value with many strings, like a wide struct or map,
+    makes a single allocation per chunk of the arena instead of one allocation per string.
+    Sequences, maps, and structs still allocate their fields on the heap.
+    */
+    pub fn with_arena(self, arena: &Bump) -> ArenaSerializer<'_> {
+        ArenaSerializer {
+            serializer: self,
+            arena,
+        }
+    }
+}
+
+/**
+A serializer that produces [`Ref`] buffers with strings and bytes allocated in a [`Bump`] arena.
+
+This serializer is created by [`Serializer::with_arena`].
+*/
+pub struct ArenaSerializer<'bump> {
+    serializer: Serializer,
+    arena: &'bump Bump,
+}
+
+impl<'bump> Buffer<'bump> for ArenaSerializer<'bump> {
+    fn child(&self) -> Result<Self, Error> {
+        Ok(ArenaSerializer {
+            serializer: self.serializer.child()?,
+            arena: self.arena,
+        })
+    }
+
+    fn charge(&self, bytes: usize) -> Result<(), Error> {
+        self.serializer.charge(bytes)
+    }
+
+    fn config(&self) -> &Config {
+        self.serializer.config()
+    }
+
+    fn into_value(buf: Ref<'bump>) -> Value<'bump> {
+        buf.into_value()
+    }
+
+    fn from_value(value: Value<'bump>) -> Ref<'bump> {
+        Ref(value)
+    }
+}
+
+macro_rules! serialize_primitives {
+    ($($method:ident($ty:ty) => $variant:ident,)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(Ref(Value::$variant(v)))
+            }
+        )*
+    };
+}
+
+impl<'bump> serde::Serializer for ArenaSerializer<'bump> {
+    type Ok = Ref<'bump>;
+    type Error = Error;
+    type SerializeSeq = SerializeSeq<'bump, Self>;
+    type SerializeTuple = SerializeTuple<'bump, Self>;
+    type SerializeTupleStruct = SerializeTupleStruct<'bump, Self>;
+    type SerializeTupleVariant = SerializeTupleVariant<'bump, Self>;
+    type SerializeMap = SerializeMap<'bump, Self>;
+    type SerializeStruct = SerializeStruct<'bump, Self>;
+    type SerializeStructVariant = SerializeStructVariant<'bump, Self>;
+
+    serialize_primitives! {
+        serialize_bool(bool) => Bool,
+        serialize_i8(i8) => I8,
+        serialize_i16(i16) => I16,
+        serialize_i32(i32) => I32,
+        serialize_i64(i64) => I64,
+        serialize_u8(u8) => U8,
+        serialize_u16(u16) => U16,
+        serialize_u32(u32) => U32,
+        serialize_u64(u64) => U64,
+        serialize_char(char) => Char,
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.config().check_float(v.into())?;
+
+        Ok(Ref(Value::F32(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        self.config().check_float(v)?;
+
+        Ok(Ref(Value::F64(v)))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        Ok(Ref(Value::I128(Box::new(v))))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        Ok(Ref(Value::U128(Box::new(v))))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.config().check_string_len(v.len())?;
+        self.charge(v.len())?;
+
+        Ok(Ref(Value::BorrowedStr(self.arena.alloc_str(v))))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.config().check_string_len(v.len())?;
+        self.charge(v.len())?;
+
+        Ok(Ref(Value::BorrowedBytes(self.arena.alloc_slice_copy(v))))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Ref(Value::None))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Ref(Value::Some(Box::new(
+            value.serialize(self.child()?)?.into_value(),
+        ))))
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Ref(Value::Unit))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Ref(self
+            .serializer
+            .serialize_unit_struct(name)?
+            .into_value()))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Ref(self
+            .serializer
+            .serialize_unit_variant(name, variant_index, variant)?
+            .into_value()))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Ref(Value::NewtypeStruct(Box::new(NewtypeStruct {
+            name: name.into(),
+            value: value.serialize(self.child()?)?.into_value(),
+        }))))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Ref(Value::NewtypeVariant(Box::new(NewtypeVariant {
+            name: name.into(),
+            variant_index,
+            variant: variant.into(),
+            value: value.serialize(self.child()?)?.into_value(),
+        }))))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeSeq::new(self, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(SerializeTuple::new(self, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(SerializeTupleStruct::new(self, name, len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeTupleVariant::new(
+            self,
+            name,
+            variant_index,
+            variant,
+            len,
+        ))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeMap::new(self, len))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(SerializeStruct::new(self, name, len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeStructVariant::new(
+            self,
+            name,
+            variant_index,
+            variant,
+            len,
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.config().is_human_readable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+    use serde::Serializer as _;
+    use serde_derive::Serialize;
+
+    use crate::Owned;
+
+    #[derive(Serialize)]
+    struct Record {
+        id: u64,
+        name: String,
+        tags: Vec<String>,
+        attributes: BTreeMap<String, String>,
+    }
+
+    #[test]
+    fn arena_buffer() {
+        let record = Record {
+            id: 1,
+            name: "a record".into(),
+            tags: vec!["a".into(), "b".into()],
+            attributes: BTreeMap::from([("key".into(), "value".into())]),
+        };
+
+        let arena = Bump::new();
+        let buf = record
+            .serialize(Serializer::new().with_arena(&arena))
+            .unwrap();
+
+        assert_eq!(Owned::buffer(&record).unwrap(), buf);
+        assert!(matches!(
+            buf.get("name").unwrap().0.resolve(),
+            Value::BorrowedStr(_)
+        ));
+
+        let buf = Serializer::new()
+            .with_arena(&arena)
+            .serialize_bytes(b"bytes")
+            .unwrap();
+
+        assert!(matches!(buf.0, Value::BorrowedBytes(b"bytes")));
+    }
+
+    #[test]
+    fn arena_max_depth() {
+        let arena = Bump::new();
+
+        assert!(Some(Some(1))
+            .serialize(Serializer::new().with_max_depth(1).with_arena(&arena))
+            .is_err());
+    }
+
+    #[test]
+    fn arena_max_string_len() {
+        let arena = Bump::new();
+
+        let err = ("short", "too long")
+            .serialize(Serializer::new().with_max_string_len(5).with_arena(&arena))
+            .unwrap_err();
+
+        assert_eq!(crate::ErrorKind::LengthExceeded, err.kind());
+    }
+
+    #[test]
+    fn arena_byte_budget() {
+        let arena = Bump::new();
+
+        assert!("budget"
+            .serialize(Serializer::new().with_byte_budget(6).with_arena(&arena))
+            .is_ok());
+
+        let err = "budget"
+            .serialize(Serializer::new().with_byte_budget(5).with_arena(&arena))
+            .unwrap_err();
+
+        assert_eq!(crate::ErrorKind::BudgetExceeded, err.kind());
+    }
+}
@@ -0,0 +1,306 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{LenHint, Name, Ref, Struct, Value};
+
+impl<'a> FromIterator<Ref<'a>> for Ref<'a> {
+    /**
+    Collect buffers into a sequence.
+    */
+    fn from_iter<T: IntoIterator<Item = Ref<'a>>>(iter: T) -> Self {
+        Ref::seq(iter)
+    }
+}
+
+impl<'a> FromIterator<(Ref<'a>, Ref<'a>)> for Ref<'a> {
+    /**
+    Collect pairs of buffers into a map.
+    */
+    fn from_iter<T: IntoIterator<Item = (Ref<'a>, Ref<'a>)>>(iter: T) -> Self {
+        Ref::map(iter)
+    }
+}
+
+/**
+A builder for a sequence that's assembled incrementally.
+
+Elements can be added one at a time with [`SeqBuilder::push`] or in bulk through its `Extend`
+implementation.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct SeqBuilder<'a>(Vec<Value<'a>>);
+
+impl<'a> SeqBuilder<'a> {
+    /**
+    Create a new, empty builder.
+    */
+    pub fn new() -> Self {
+        SeqBuilder(Vec::new())
+    }
+
+    /**
+    Create a new, empty builder with space for at least `capacity` elements.
+    */
+    pub fn with_capacity(capacity: usize) -> Self {
+        SeqBuilder(Vec::with_capacity(capacity))
+    }
+
+    /**
+    Add an element to the end of the sequence.
+    */
+    pub fn push(&mut self, v: impl Into<Ref<'a>>) -> &mut Self {
+        self.0.push(v.into().into_value());
+        self
+    }
+
+    /**
+    The number of elements added so far.
+    */
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /**
+    Whether any elements have been added.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /**
+    Complete the sequence.
+    */
+    pub fn build(self) -> Ref<'a> {
+        Ref(Value::Seq(self.0.into_boxed_slice(), LenHint::Known))
+    }
+}
+
+impl<'a> Extend<Ref<'a>> for SeqBuilder<'a> {
+    fn extend<T: IntoIterator<Item = Ref<'a>>>(&mut self, iter: T) {
+        self.0.extend(iter.into_iter().map(Ref::into_value))
+    }
+}
+
+/**
+A builder for a struct with named fields that's assembled incrementally.
+
+Fields are added one at a time with [`StructBuilder::field`], or conditionally with
+[`StructBuilder::maybe_field`]. Fields are kept in the order they're added.
+*/
+#[derive(Clone, Debug)]
+pub struct StructBuilder<'a> {
+    name: Name<'a>,
+    fields: Vec<(Name<'a>, Value<'a>)>,
+}
+
+impl<'a> StructBuilder<'a> {
+    /**
+    Create a new builder for a struct with no fields.
+    */
+    pub fn new(name: impl Into<Name<'a>>) -> Self {
+        StructBuilder {
+            name: name.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /**
+    Add a field to the struct.
+    */
+    pub fn field(&mut self, name: impl Into<Name<'a>>, v: impl Into<Ref<'a>>) -> &mut Self {
+        self.fields.push((name.into(), v.into().into_value()));
+        self
+    }
+
+    /**
+    Add a field to the struct if it has a value.
+
+    If `v` is `None` then the field is skipped entirely, like `#[serde(skip_serializing_if = "Option::is_none")]`.
+    Use [`StructBuilder::field`] with [`Ref::none`] to add a field with a `None` value instead.
+    */
+    pub fn maybe_field(
+        &mut self,
+        name: impl Into<Name<'a>>,
+        v: Option<impl Into<Ref<'a>>>,
+    ) -> &mut Self {
+        if let Some(v) = v {
+            self.field(name, v);
+        }
+
+        self
+    }
+
+    /**
+    The number of fields added so far.
+    */
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /**
+    Whether any fields have been added.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /**
+    Complete the struct.
+    */
+    pub fn build(self) -> Ref<'a> {
+        Ref(Value::Struct(Box::new(Struct {
+            name: self.name,
+            fields: self.fields.into_boxed_slice(),
+        })))
+    }
+}
+
+/**
+A builder for a map that's assembled incrementally.
+
+Entries can be added one at a time with [`MapBuilder::entry`] or in bulk through its `Extend`
+implementation. Entries are kept in the order they're added.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct MapBuilder<'a>(Vec<(Value<'a>, Value<'a>)>);
+
+impl<'a> MapBuilder<'a> {
+    /**
+    Create a new, empty builder.
+    */
+    pub fn new() -> Self {
+        MapBuilder(Vec::new())
+    }
+
+    /**
+    Create a new, empty builder with space for at least `capacity` entries.
+    */
+    pub fn with_capacity(capacity: usize) -> Self {
+        MapBuilder(Vec::with_capacity(capacity))
+    }
+
+    /**
+    Add an entry to the map.
+    */
+    pub fn entry(&mut self, k: impl Into<Ref<'a>>, v: impl Into<Ref<'a>>) -> &mut Self {
+        self.0.push((k.into().into_value(), v.into().into_value()));
+        self
+    }
+
+    /**
+    Add an entry to the map if it has a value.
+
+    If `v` is `None` then the entry is skipped entirely.
+    */
+    pub fn maybe_entry(
+        &mut self,
+        k: impl Into<Ref<'a>>,
+        v: Option<impl Into<Ref<'a>>>,
+    ) -> &mut Self {
+        if let Some(v) = v {
+            self.entry(k, v);
+        }
+
+        self
+    }
+
+    /**
+    The number of entries added so far.
+    */
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /**
+    Whether any entries have been added.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /**
+    Complete the map.
+    */
+    pub fn build(self) -> Ref<'a> {
+        Ref(Value::Map(self.0.into_boxed_slice(), LenHint::Known))
+    }
+}
+
+impl<'a> Extend<(Ref<'a>, Ref<'a>)> for MapBuilder<'a> {
+    fn extend<T: IntoIterator<Item = (Ref<'a>, Ref<'a>)>>(&mut self, iter: T) {
+        self.0.extend(
+            iter.into_iter()
+                .map(|(k, v)| (k.into_value(), v.into_value())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect() {
+        assert_eq!(
+            Ref::seq([Ref::u8(1), Ref::u8(2)]),
+            [Ref::u8(1), Ref::u8(2)].into_iter().collect::<Ref>()
+        );
+
+        assert_eq!(
+            Ref::map([(Ref::str("a"), Ref::u8(1))]),
+            [(Ref::str("a"), Ref::u8(1))].into_iter().collect::<Ref>()
+        );
+    }
+
+    #[test]
+    fn seq_builder() {
+        let mut builder = SeqBuilder::new();
+
+        builder.push(1u8).push("a");
+        builder.extend([Ref::bool(true), Ref::unit()]);
+
+        assert_eq!(4, builder.len());
+        assert_eq!(
+            Ref::seq([Ref::u8(1), Ref::str("a"), Ref::bool(true), Ref::unit()]),
+            builder.build()
+        );
+    }
+
+    #[test]
+    fn struct_builder() {
+        let name = alloc::string::String::from("b");
+
+        let mut builder = Ref::struct_builder("Struct");
+
+        builder
+            .field("a", 1u8)
+            .maybe_field(Name::borrowed(&name), Some("b"))
+            .maybe_field("c", None::<bool>);
+
+        assert_eq!(2, builder.len());
+        assert_eq!(
+            Ref::record_struct("Struct", [("a", Ref::u8(1)), ("b", Ref::str("b"))]),
+            builder.build()
+        );
+    }
+
+    #[test]
+    fn map_builder() {
+        let mut builder = Ref::map_builder();
+
+        builder
+            .entry("a", 1u8)
+            .maybe_entry("b", None::<u8>)
+            .maybe_entry("c", Some(3u8));
+        builder.extend([(Ref::str("d"), Ref::u8(4))]);
+
+        assert_eq!(3, builder.len());
+        assert_eq!(
+            Ref::map([
+                (Ref::str("a"), Ref::u8(1)),
+                (Ref::str("c"), Ref::u8(3)),
+                (Ref::str("d"), Ref::u8(4)),
+            ]),
+            builder.build()
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use alloc::boxed::Box;
+
+use crate::{LenHint, Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Convert a sequence of small integers into a byte-string.
+
+    Serde treats collections like `Vec<u8>` as sequences, but formats like CBOR and MessagePack
+    have a more compact representation for byte-strings. Each element of the sequence can be an
+    integer of any width or sign, so long as its value fits in a `u8`. If the buffer doesn't
+    contain a sequence of small integers then it's returned unchanged.
+    */
+    pub fn bytes_from_seq(self) -> Self {
+        match self.0.bytes_from_seq() {
+            Some(bytes) => Owned(Value::Bytes(bytes)),
+            None => self,
+        }
+    }
+
+    /**
+    Convert a byte-string into a sequence of `u8`s.
+
+    This is the inverse of [`Owned::bytes_from_seq`]. If the buffer doesn't contain a byte-string
+    then it's returned unchanged.
+    */
+    pub fn seq_from_bytes(self) -> Self {
+        match self.0.seq_from_bytes() {
+            Some(seq) => Owned(Value::Seq(seq, LenHint::Known)),
+            None => self,
+        }
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Convert a sequence of small integers into a byte-string.
+
+    Serde treats collections like `Vec<u8>` as sequences, but formats like CBOR and MessagePack
+    have a more compact representation for byte-strings. Each element of the sequence can be an
+    integer of any width or sign, so long as its value fits in a `u8`. If the buffer doesn't
+    contain a sequence of small integers then it's returned unchanged.
+    */
+    pub fn bytes_from_seq(self) -> Self {
+        match self.0.bytes_from_seq() {
+            Some(bytes) => Ref(Value::Bytes(bytes)),
+            None => self,
+        }
+    }
+
+    /**
+    Convert a byte-string into a sequence of `u8`s.
+
+    This is the inverse of [`Ref::bytes_from_seq`]. If the buffer doesn't contain a byte-string
+    then it's returned unchanged.
+    */
+    pub fn seq_from_bytes(self) -> Self {
+        match self.0.seq_from_bytes() {
+            Some(seq) => Ref(Value::Seq(seq, LenHint::Known)),
+            None => self,
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    fn bytes_from_seq(&self) -> Option<Box<[u8]>> {
+        match self.resolve() {
+            Value::Seq(fields, _) => fields
+                .iter()
+                .map(|v| u8::try_from(v.as_u128()?).ok())
+                .collect(),
+            _ => None,
+        }
+    }
+
+    fn seq_from_bytes<'b>(&self) -> Option<Box<[Value<'b>]>> {
+        match self.resolve() {
+            Value::Bytes(v) => Some(v.iter().copied().map(Value::U8).collect()),
+            Value::BorrowedBytes(v) => Some(v.iter().copied().map(Value::U8).collect()),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(v) => Some(v.iter().copied().map(Value::U8).collect()),
+            _ => None,
+        }
+    }
+
+    /**
+    Get the contents of a sequence that only contains `u8`s as a byte-string.
+    */
+    pub(crate) fn u8_seq_as_bytes(fields: &[Value]) -> Option<Box<[u8]>> {
+        if fields.is_empty() {
+            return None;
+        }
+
+        fields
+            .iter()
+            .map(|v| match v {
+                Value::U8(v) => Some(*v),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn bytes_from_seq() {
+        let owned = Owned::buffer(vec![1u8, 2, 3]).unwrap();
+
+        assert_eq!(None, owned.as_bytes());
+        assert_eq!(
+            Some(&[1, 2, 3][..]),
+            owned.as_ref().bytes_from_seq().as_bytes()
+        );
+
+        let owned = owned.bytes_from_seq();
+        assert_eq!(Some(&[1, 2, 3][..]), owned.as_bytes());
+
+        assert_eq!(
+            Owned::buffer(vec![1u8, 2, 3]).unwrap(),
+            owned.seq_from_bytes()
+        );
+
+        // Integers of any width are folded, so long as they fit in a byte
+        assert_eq!(
+            Some(&[1, 255][..]),
+            Ref::seq([Ref::u64(1), Ref::i32(255)])
+                .bytes_from_seq()
+                .as_bytes()
+        );
+
+        assert_eq!(
+            None,
+            Ref::seq([Ref::u64(1), Ref::i32(256)])
+                .bytes_from_seq()
+                .as_bytes()
+        );
+        assert_eq!(
+            None,
+            Ref::seq([Ref::u8(1), Ref::str("a")])
+                .bytes_from_seq()
+                .as_bytes()
+        );
+        assert_eq!(Ref::str("a"), Ref::str("a").bytes_from_seq());
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::vec;
+
+use crate::{Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Sort the entries of every map in this buffer by their keys.
+
+    Two buffers with the same entries in different orders will serialize identically once they've
+    been canonicalized. Keys are sorted using the [`Ord`] implementation of buffers, and entries
+    with equal keys keep their relative order, so canonicalizing a buffer more than once has no
+    further effect. The fields of structs keep the order they were buffered in.
+
+    Note that this changes the order of map entries, which is observable in formats that preserve it.
+    */
+    pub fn canonicalize(&mut self) {
+        self.0.canonicalize()
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Sort the entries of every map in this buffer by their keys.
+
+    See [`Owned::canonicalize`] for details. Values borrowed from other buffers are cloned
+    so their maps can be sorted.
+    */
+    pub fn canonicalize(&mut self) {
+        self.0.canonicalize()
+    }
+}
+
+impl<'a> Value<'a> {
+    fn canonicalize(&mut self) {
+        // Nested values are visited using a stack instead of recursion. Keys are canonicalized before
+        // their map is sorted, because the order of keys can depend on the order of their own entries
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            if let Value::Borrowed(v) = value {
+                *value = v.resolve().clone();
+            }
+
+            match value {
+                Value::Some(v) => stack.push(v),
+                Value::NewtypeStruct(v) => stack.push(&mut v.value),
+                Value::NewtypeVariant(v) => stack.push(&mut v.value),
+                Value::Seq(fields, _) | Value::Tuple(fields) => stack.extend(fields.iter_mut()),
+                Value::TupleStruct(v) => stack.extend(v.fields.iter_mut()),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter_mut()),
+                Value::Struct(v) => stack.extend(v.fields.iter_mut().map(|(_, v)| v)),
+                Value::StructVariant(v) => stack.extend(v.fields.iter_mut().map(|(_, v)| v)),
+                Value::Map(fields, _) => {
+                    for (k, _) in fields.iter_mut() {
+                        k.canonicalize();
+                    }
+
+                    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                    stack.extend(fields.iter_mut().map(|(_, v)| v));
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonicalize() {
+        let mut a = Ref::seq([Ref::map([
+            (
+                Ref::str("b"),
+                Ref::map([(Ref::u8(2), Ref::unit()), (Ref::u8(1), Ref::unit())]),
+            ),
+            (Ref::str("a"), Ref::unit()),
+        ])]);
+        let mut b = Ref::seq([Ref::map([
+            (Ref::str("a"), Ref::unit()),
+            (
+                Ref::str("b"),
+                Ref::map([(Ref::u8(1), Ref::unit()), (Ref::u8(2), Ref::unit())]),
+            ),
+        ])]);
+
+        assert_ne!(
+            serde_json::to_string(&a).unwrap(),
+            serde_json::to_string(&b).unwrap()
+        );
+
+        a.canonicalize();
+        b.canonicalize();
+
+        assert_eq!(
+            serde_json::to_string(&a).unwrap(),
+            serde_json::to_string(&b).unwrap()
+        );
+        assert_eq!(
+            r#"[{"a":null,"b":{"1":null,"2":null}}]"#,
+            serde_json::to_string(&a).unwrap()
+        );
+
+        let canonical = a.clone();
+        a.canonicalize();
+        assert_eq!(
+            serde_json::to_string(&canonical).unwrap(),
+            serde_json::to_string(&a).unwrap()
+        );
+    }
+
+    #[test]
+    fn canonicalize_keys() {
+        let key = |entries: [(u8, u8); 2]| Ref::map(entries.map(|(k, v)| (Ref::u8(k), Ref::u8(v))));
+
+        let mut a = Ref::map([
+            (key([(2, 0), (1, 1)]), Ref::str("a")),
+            (key([(1, 0), (2, 0)]), Ref::str("b")),
+        ]);
+        let mut b = Ref::map([
+            (key([(1, 0), (2, 0)]), Ref::str("b")),
+            (key([(1, 1), (2, 0)]), Ref::str("a")),
+        ]);
+
+        a.canonicalize();
+        b.canonicalize();
+
+        assert_eq!(
+            rmp_serde::to_vec(&a).unwrap(),
+            rmp_serde::to_vec(&b).unwrap()
+        );
+
+        let owned = a.into_owned();
+        let mut borrowed = owned.as_ref();
+        borrowed.canonicalize();
+
+        assert_eq!(
+            rmp_serde::to_vec(&b).unwrap(),
+            rmp_serde::to_vec(&borrowed).unwrap()
+        );
+    }
+}
@@ -0,0 +1,144 @@
+/*!
+Conversions between buffers and `ciborium`'s [`ciborium::value::Value`].
+*/
+
+use alloc::boxed::Box;
+use serde::ser::Error as _;
+
+use crate::{Error, LenHint, Owned, Ref, TupleVariant, Value};
+
+/**
+The name `ciborium` uses to serialize tagged values.
+*/
+const TAG_NAME: &str = "@@TAG@@";
+
+/**
+The variant `ciborium` uses to serialize tagged values.
+*/
+const TAG_VARIANT: &str = "@@TAGGED@@";
+
+impl From<ciborium::value::Value> for Owned {
+    /**
+    Convert a CBOR value into a buffer.
+
+    Maps are converted into maps with keys of any type, arrays into sequences, byte strings into
+    byte-strings, and `null` into `()`. Integers are converted into the narrowest integer type
+    that can hold them.
+
+    Tagged values, including big integers that don't fit in 64 bits, are converted into the same
+    tuple variant `ciborium` uses to serialize them: `@@TAG@@::@@TAGGED@@(tag, value)`.
+    This means tags are preserved when the buffer is serialized as CBOR, but will appear as an
+    enum variant in other formats.
+    */
+    fn from(value: ciborium::value::Value) -> Self {
+        Owned(from_cbor(value))
+    }
+}
+
+impl TryFrom<Owned> for ciborium::value::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a CBOR value.
+
+    Values are converted in the same way `ciborium` would serialize them.
+    */
+    fn try_from(value: Owned) -> Result<Self, Self::Error> {
+        ciborium::value::Value::serialized(&value).map_err(Error::custom)
+    }
+}
+
+impl<'a> TryFrom<Ref<'a>> for ciborium::value::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a CBOR value.
+
+    Values are converted in the same way `ciborium` would serialize them.
+    */
+    fn try_from(value: Ref<'a>) -> Result<Self, Self::Error> {
+        ciborium::value::Value::serialized(&value).map_err(Error::custom)
+    }
+}
+
+fn from_cbor(value: ciborium::value::Value) -> Value<'static> {
+    match value {
+        ciborium::value::Value::Integer(v) => {
+            let v = i128::from(v);
+
+            match u128::try_from(v) {
+                Ok(v) => Value::from_unsigned(v),
+                Err(_) => Value::from_signed(v),
+            }
+        }
+        ciborium::value::Value::Bytes(v) => Value::Bytes(v.into_boxed_slice()),
+        ciborium::value::Value::Float(v) => Value::F64(v),
+        ciborium::value::Value::Text(v) => Value::Str(v.into()),
+        ciborium::value::Value::Bool(v) => Value::Bool(v),
+        ciborium::value::Value::Null => Value::Unit,
+        ciborium::value::Value::Tag(tag, v) => Value::TupleVariant(Box::new(TupleVariant {
+            name: TAG_NAME.into(),
+            variant_index: 0,
+            variant: TAG_VARIANT.into(),
+            fields: [Value::U64(tag), from_cbor(*v)].into(),
+        })),
+        ciborium::value::Value::Array(v) => {
+            Value::Seq(v.into_iter().map(from_cbor).collect(), LenHint::Known)
+        }
+        ciborium::value::Value::Map(v) => Value::Map(
+            v.into_iter()
+                .map(|(k, v)| (from_cbor(k), from_cbor(v)))
+                .collect(),
+            LenHint::Known,
+        ),
+        // `ciborium::value::Value` is non-exhaustive
+        _ => Value::Unit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+    use ciborium::value::Value as Cbor;
+
+    fn to_cbor(v: impl serde::Serialize) -> Vec<u8> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(&v, &mut buf).unwrap();
+
+        buf
+    }
+
+    #[test]
+    fn cbor_roundtrip() {
+        let value = Cbor::Map(alloc::vec![
+            (Cbor::Integer(1.into()), Cbor::Bytes(b"bytes".to_vec())),
+            (
+                Cbor::Text("a".into()),
+                Cbor::Array(alloc::vec![
+                    Cbor::Integer((-1).into()),
+                    Cbor::Integer(u64::MAX.into()),
+                    Cbor::Float(1.5),
+                    Cbor::Null,
+                    Cbor::Bool(true),
+                ])
+            ),
+            (
+                Cbor::Text("b".into()),
+                Cbor::Tag(2, Cbor::Bytes(alloc::vec![1; 20]).into())
+            ),
+        ]);
+
+        let owned = Owned::from(value.clone());
+
+        assert_eq!(
+            Some(b"bytes" as &[u8]),
+            owned.iter_map().unwrap().next().unwrap().1.as_bytes()
+        );
+        assert_eq!(to_cbor(&value), to_cbor(&owned));
+
+        assert_eq!(value, Cbor::try_from(owned.as_ref()).unwrap());
+        assert_eq!(value, Cbor::try_from(owned).unwrap());
+    }
+}
@@ -0,0 +1,263 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use crate::{
+    Name, NewtypeStruct, NewtypeVariant, Struct, StructVariant, TupleStruct, TupleVariant, Value,
+};
+
+/**
+A step in cloning a value.
+*/
+enum Step<'v, 'a> {
+    /**
+    Clone the values nested within a value.
+    */
+    Visit(&'v Value<'a>),
+    /**
+    Build a clone of a value from the clones of its nested values.
+    */
+    Build(&'v Value<'a>),
+}
+
+impl<'a> Clone for Value<'a> {
+    /**
+    Clone a value without recursing through the values nested within it.
+
+    Nested values are cloned first onto a stack, and then popped off to build the clones of their
+    parents. This means cloning deeply nested buffers can't overflow the stack.
+    */
+    fn clone(&self) -> Self {
+        let mut cloned = Vec::new();
+        let mut steps = vec![Step::Visit(self)];
+
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Visit(value) => {
+                    let nested = match value {
+                        Value::Some(v) => {
+                            steps.push(Step::Build(value));
+                            steps.push(Step::Visit(v));
+                            true
+                        }
+                        Value::NewtypeStruct(v) => {
+                            steps.push(Step::Build(value));
+                            steps.push(Step::Visit(&v.value));
+                            true
+                        }
+                        Value::NewtypeVariant(v) => {
+                            steps.push(Step::Build(value));
+                            steps.push(Step::Visit(&v.value));
+                            true
+                        }
+                        Value::Seq(fields, _) | Value::Tuple(fields) => {
+                            steps.push(Step::Build(value));
+                            steps.extend(fields.iter().rev().map(Step::Visit));
+                            true
+                        }
+                        Value::TupleStruct(v) => {
+                            steps.push(Step::Build(value));
+                            steps.extend(v.fields.iter().rev().map(Step::Visit));
+                            true
+                        }
+                        Value::TupleVariant(v) => {
+                            steps.push(Step::Build(value));
+                            steps.extend(v.fields.iter().rev().map(Step::Visit));
+                            true
+                        }
+                        Value::Struct(v) => {
+                            steps.push(Step::Build(value));
+                            steps.extend(v.fields.iter().rev().map(|(_, v)| Step::Visit(v)));
+                            true
+                        }
+                        Value::StructVariant(v) => {
+                            steps.push(Step::Build(value));
+                            steps.extend(v.fields.iter().rev().map(|(_, v)| Step::Visit(v)));
+                            true
+                        }
+                        Value::Map(fields, _) => {
+                            steps.push(Step::Build(value));
+                            // Keys are visited before their values, so they're cloned first
+                            steps.extend(
+                                fields
+                                    .iter()
+                                    .rev()
+                                    .flat_map(|(k, v)| [Step::Visit(v), Step::Visit(k)]),
+                            );
+                            true
+                        }
+                        _ => false,
+                    };
+
+                    if !nested {
+                        cloned.push(value.clone_leaf());
+                    }
+                }
+                Step::Build(value) => {
+                    let value = value.clone_from_nested(&mut cloned);
+                    cloned.push(value);
+                }
+            }
+        }
+
+        cloned.pop().expect("missing cloned value")
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    Clone a value that doesn't own any other values.
+    */
+    fn clone_leaf(&self) -> Self {
+        match self {
+            Value::Unit => Value::Unit,
+            Value::U8(v) => Value::U8(*v),
+            Value::U16(v) => Value::U16(*v),
+            Value::U32(v) => Value::U32(*v),
+            Value::U64(v) => Value::U64(*v),
+            Value::U128(v) => Value::U128(v.clone()),
+            Value::I8(v) => Value::I8(*v),
+            Value::I16(v) => Value::I16(*v),
+            Value::I32(v) => Value::I32(*v),
+            Value::I64(v) => Value::I64(*v),
+            Value::I128(v) => Value::I128(v.clone()),
+            Value::F32(v) => Value::F32(*v),
+            Value::F64(v) => Value::F64(*v),
+            Value::Bool(v) => Value::Bool(*v),
+            Value::Char(v) => Value::Char(*v),
+            Value::Str(v) => Value::Str(v.clone()),
+            Value::SharedStr(v) => Value::SharedStr(v.clone()),
+            Value::BorrowedStr(v) => Value::BorrowedStr(v),
+            Value::Bytes(v) => Value::Bytes(v.clone()),
+            Value::BorrowedBytes(v) => Value::BorrowedBytes(v),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(v) => Value::SharedBytes(v.clone()),
+            #[cfg(feature = "json")]
+            Value::RawJson(v) => Value::RawJson(v.clone()),
+            Value::None => Value::None,
+            Value::UnitStruct(v) => Value::UnitStruct(v.clone()),
+            Value::UnitVariant(v) => Value::UnitVariant(v.clone()),
+            // Borrowed values are shared, not cloned
+            Value::Borrowed(v) => Value::Borrowed(v),
+            _ => unreachable!("nested values are built from their clones"),
+        }
+    }
+
+    /**
+    Clone a value that owns other values, taking the clones of those values from the end of `cloned`.
+    */
+    fn clone_from_nested(&self, cloned: &mut Vec<Value<'a>>) -> Self {
+        match self {
+            Value::Some(_) => Value::Some(Box::new(pop(cloned))),
+            Value::NewtypeStruct(v) => Value::NewtypeStruct(Box::new(NewtypeStruct {
+                name: v.name.clone(),
+                value: pop(cloned),
+            })),
+            Value::NewtypeVariant(v) => Value::NewtypeVariant(Box::new(NewtypeVariant {
+                name: v.name.clone(),
+                variant_index: v.variant_index,
+                variant: v.variant.clone(),
+                value: pop(cloned),
+            })),
+            Value::Seq(fields, len_hint) => Value::Seq(pop_n(cloned, fields.len()), *len_hint),
+            Value::Tuple(fields) => Value::Tuple(pop_n(cloned, fields.len())),
+            Value::TupleStruct(v) => Value::TupleStruct(Box::new(TupleStruct {
+                name: v.name.clone(),
+                fields: pop_n(cloned, v.fields.len()),
+            })),
+            Value::TupleVariant(v) => Value::TupleVariant(Box::new(TupleVariant {
+                name: v.name.clone(),
+                variant_index: v.variant_index,
+                variant: v.variant.clone(),
+                fields: pop_n(cloned, v.fields.len()),
+            })),
+            Value::Struct(v) => Value::Struct(Box::new(Struct {
+                name: v.name.clone(),
+                fields: pop_fields(cloned, &v.fields),
+            })),
+            Value::StructVariant(v) => Value::StructVariant(Box::new(StructVariant {
+                name: v.name.clone(),
+                variant_index: v.variant_index,
+                variant: v.variant.clone(),
+                fields: pop_fields(cloned, &v.fields),
+            })),
+            Value::Map(fields, len_hint) => {
+                let mut values = cloned.drain(cloned.len() - fields.len() * 2..);
+
+                let mut entries = Vec::with_capacity(fields.len());
+                while let (Some(k), Some(v)) = (values.next(), values.next()) {
+                    entries.push((k, v));
+                }
+
+                Value::Map(entries.into_boxed_slice(), *len_hint)
+            }
+            _ => unreachable!("leaf values are cloned directly"),
+        }
+    }
+}
+
+fn pop<'a>(cloned: &mut Vec<Value<'a>>) -> Value<'a> {
+    cloned.pop().expect("missing cloned value")
+}
+
+fn pop_n<'a>(cloned: &mut Vec<Value<'a>>, len: usize) -> Box<[Value<'a>]> {
+    cloned.drain(cloned.len() - len..).collect()
+}
+
+fn pop_fields<'a>(
+    cloned: &mut Vec<Value<'a>>,
+    fields: &[(Name<'a>, Value<'a>)],
+) -> Box<[(Name<'a>, Value<'a>)]> {
+    fields
+        .iter()
+        .map(|(k, _)| k.clone())
+        .zip(cloned.drain(cloned.len() - fields.len()..))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Owned, Ref};
+
+    #[test]
+    fn clone() {
+        let buf = Owned::buffer((
+            Some("a string"),
+            [1u8, 2, 3],
+            alloc::collections::BTreeMap::from([(1, "a"), (2, "b")]),
+        ))
+        .unwrap();
+
+        assert_eq!(buf, buf.clone());
+
+        let borrowed = Ref::record_struct(
+            "Struct",
+            [
+                ("a", buf.as_ref()),
+                ("b", Ref::tuple_variant("Enum", 1, "A", [Ref::unit()])),
+                ("c", Ref::map([(Ref::str("d"), Ref::seq([]))])),
+            ],
+        );
+
+        assert_eq!(borrowed, borrowed.clone());
+    }
+
+    #[test]
+    fn clone_deeply_nested() {
+        let mut deep = Ref::unit();
+        for i in 0..200_000 {
+            deep = match i % 6 {
+                0 => Ref::some(deep),
+                1 => Ref::newtype_struct("Newtype", deep),
+                2 => Ref::seq([Ref::u8(1), deep]),
+                3 => Ref::map([(Ref::str("a"), deep)]),
+                4 => Ref::record_struct_variant("Enum", 0, "A", [("a", deep)]),
+                _ => Ref::tuple_struct("Tuple", [deep, Ref::unit()]),
+            };
+        }
+
+        let cloned = deep.clone();
+
+        // Not `assert_eq!`, because formatting a deeply nested buffer recurses
+        assert!(deep == cloned);
+        assert_eq!(deep.max_depth(), cloned.max_depth());
+    }
+}
@@ -0,0 +1,887 @@
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use alloc::{vec, vec::Vec};
+
+use crate::{Name, Owned, Ref, Value};
+
+impl PartialEq for Owned {
+    fn eq(&self, other: &Owned) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<'a, 'b> PartialEq<Ref<'b>> for Ref<'a> {
+    fn eq(&self, other: &Ref<'b>) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<'a> PartialEq<Ref<'a>> for Owned {
+    fn eq(&self, other: &Ref<'a>) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<'a> PartialEq<Owned> for Ref<'a> {
+    fn eq(&self, other: &Owned) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Owned {}
+
+impl PartialOrd for Owned {
+    fn partial_cmp(&self, other: &Owned) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Owned {
+    fn cmp(&self, other: &Owned) -> Ordering {
+        cmp(&self.0, &other.0)
+    }
+}
+
+impl<'a, 'b> PartialOrd<Ref<'b>> for Ref<'a> {
+    fn partial_cmp(&self, other: &Ref<'b>) -> Option<Ordering> {
+        Some(cmp(&self.0, &other.0))
+    }
+}
+
+impl<'a> Ord for Ref<'a> {
+    fn cmp(&self, other: &Ref<'a>) -> Ordering {
+        cmp(&self.0, &other.0)
+    }
+}
+
+impl<'a> PartialOrd<Ref<'a>> for Owned {
+    fn partial_cmp(&self, other: &Ref<'a>) -> Option<Ordering> {
+        Some(cmp(&self.0, &other.0))
+    }
+}
+
+impl<'a> PartialOrd<Owned> for Ref<'a> {
+    fn partial_cmp(&self, other: &Owned) -> Option<Ordering> {
+        Some(cmp(&self.0, &other.0))
+    }
+}
+
+impl<'a> Eq for Ref<'a> {}
+
+impl Hash for Owned {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<'a> Hash for Ref<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl Owned {
+    /**
+    Compare this buffer with another, allowing their floats to differ by `epsilon`.
+
+    Buffers are compared structurally in the same way as with `==`, except for `f32` and `f64` values.
+    Floats are equal if the absolute difference between them is at most `epsilon`, or if it's at most
+    `epsilon` relative to the larger of their magnitudes. An `f32` can be approximately equal to an `f64`.
+    `NaN`s are equal to each other, and infinities are only equal to infinities with the same sign.
+    All other values, including map keys and the names of structs and fields, must be exactly equal.
+
+    This is useful for comparing buffers after round-tripping them through formats that lose
+    some float precision.
+    */
+    pub fn approx_eq(&self, other: &Owned, epsilon: f64) -> bool {
+        self.0.eq_with(&other.0, Some(epsilon))
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Compare this buffer with another, allowing their floats to differ by `epsilon`.
+
+    See [`Owned::approx_eq`] for details.
+    */
+    pub fn approx_eq(&self, other: &Ref, epsilon: f64) -> bool {
+        self.0.eq_with(&other.0, Some(epsilon))
+    }
+}
+
+/**
+Compare buffers with primitive values.
+
+Numbers are compared by value, so a buffer containing a `u8` is equal to the same `u64`, and a
+buffer containing an integer is equal to the same `f64` when it can be represented exactly.
+Buffers containing any other kind of value are never equal.
+*/
+macro_rules! impl_eq_primitive {
+    ($($ty:ty => |$buf:ident, $other:ident| $eq:expr,)*) => {
+        $(
+            impl PartialEq<$ty> for Owned {
+                fn eq(&self, other: &$ty) -> bool {
+                    let ($buf, $other) = (&self.0, other);
+                    $eq
+                }
+            }
+
+            impl<'a> PartialEq<$ty> for Ref<'a> {
+                fn eq(&self, other: &$ty) -> bool {
+                    let ($buf, $other) = (&self.0, other);
+                    $eq
+                }
+            }
+
+            impl PartialEq<Owned> for $ty {
+                fn eq(&self, other: &Owned) -> bool {
+                    other == self
+                }
+            }
+
+            impl<'a> PartialEq<Ref<'a>> for $ty {
+                fn eq(&self, other: &Ref<'a>) -> bool {
+                    other == self
+                }
+            }
+        )*
+    };
+}
+
+impl_eq_primitive!(
+    u64 => |buf, other| buf.as_u128() == Some(u128::from(*other)),
+    i64 => |buf, other| buf.as_i128() == Some(i128::from(*other)),
+    f64 => |buf, other| buf.as_f64().map(f64_bits) == Some(f64_bits(*other)),
+    bool => |buf, other| buf.as_bool() == Some(*other),
+    &str => |buf, other| buf.as_str() == Some(*other),
+);
+
+impl<'a, 'b> PartialEq<Value<'b>> for Value<'a> {
+    fn eq(&self, other: &Value<'b>) -> bool {
+        self.eq_with(other, None)
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    Compare two values without recursing through the values nested within them.
+
+    Pairs of nested values are pushed onto a stack and compared in turn, so comparing deeply
+    nested buffers can't overflow the stack. If `epsilon` is given then floats only need to be
+    approximately equal.
+    */
+    fn eq_with(&self, other: &Value, epsilon: Option<f64>) -> bool {
+        let mut stack = vec![(self, other)];
+
+        while let Some((a, b)) = stack.pop() {
+            let eq = match (a.resolve(), b.resolve()) {
+                (Value::Unit, Value::Unit) => true,
+                (a @ (Value::F32(_) | Value::F64(_)), b @ (Value::F32(_) | Value::F64(_)))
+                    if epsilon.is_some() =>
+                {
+                    match (a.as_f64(), b.as_f64(), epsilon) {
+                        (Some(a), Some(b), Some(epsilon)) => approx_eq(a, b, epsilon),
+                        _ => false,
+                    }
+                }
+                (Value::U8(a), Value::U8(b)) => a == b,
+                (Value::U16(a), Value::U16(b)) => a == b,
+                (Value::U32(a), Value::U32(b)) => a == b,
+                (Value::U64(a), Value::U64(b)) => a == b,
+                (Value::U128(a), Value::U128(b)) => **a == **b,
+                (Value::I8(a), Value::I8(b)) => a == b,
+                (Value::I16(a), Value::I16(b)) => a == b,
+                (Value::I32(a), Value::I32(b)) => a == b,
+                (Value::I64(a), Value::I64(b)) => a == b,
+                (Value::I128(a), Value::I128(b)) => **a == **b,
+                (Value::F32(a), Value::F32(b)) => f32_bits(*a) == f32_bits(*b),
+                (Value::F64(a), Value::F64(b)) => f64_bits(*a) == f64_bits(*b),
+                (Value::Bool(a), Value::Bool(b)) => a == b,
+                (Value::Char(a), Value::Char(b)) => a == b,
+                (
+                    a @ (Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_)),
+                    b @ (Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_)),
+                ) => a.as_str() == b.as_str(),
+                (
+                    a @ (Value::Bytes(_) | Value::BorrowedBytes(_)),
+                    b @ (Value::Bytes(_) | Value::BorrowedBytes(_)),
+                ) => a.as_bytes() == b.as_bytes(),
+                #[cfg(feature = "bytes")]
+                (a @ Value::SharedBytes(_), b) | (a, b @ Value::SharedBytes(_)) => {
+                    a.as_bytes() == b.as_bytes()
+                }
+                (Value::None, Value::None) => true,
+                (Value::Some(a), Value::Some(b)) => {
+                    stack.push((a, b));
+                    true
+                }
+                (Value::UnitStruct(a), Value::UnitStruct(b)) => a.name == b.name,
+                (Value::NewtypeStruct(a), Value::NewtypeStruct(b)) => {
+                    stack.push((&a.value, &b.value));
+                    a.name == b.name
+                }
+                (Value::Struct(a), Value::Struct(b)) => {
+                    a.name == b.name && fields_eq(&mut stack, &a.fields, &b.fields)
+                }
+                (Value::Tuple(a), Value::Tuple(b)) => seq_eq(&mut stack, a, b),
+                (Value::TupleStruct(a), Value::TupleStruct(b)) => {
+                    a.name == b.name && seq_eq(&mut stack, &a.fields, &b.fields)
+                }
+                (Value::UnitVariant(a), Value::UnitVariant(b)) => {
+                    a.name == b.name && a.variant_index == b.variant_index && a.variant == b.variant
+                }
+                (Value::NewtypeVariant(a), Value::NewtypeVariant(b)) => {
+                    stack.push((&a.value, &b.value));
+                    a.name == b.name && a.variant_index == b.variant_index && a.variant == b.variant
+                }
+                (Value::TupleVariant(a), Value::TupleVariant(b)) => {
+                    a.name == b.name
+                        && a.variant_index == b.variant_index
+                        && a.variant == b.variant
+                        && seq_eq(&mut stack, &a.fields, &b.fields)
+                }
+                (Value::StructVariant(a), Value::StructVariant(b)) => {
+                    a.name == b.name
+                        && a.variant_index == b.variant_index
+                        && a.variant == b.variant
+                        && fields_eq(&mut stack, &a.fields, &b.fields)
+                }
+                (Value::Seq(a, _), Value::Seq(b, _)) => seq_eq(&mut stack, a, b),
+                (Value::Map(a, _), Value::Map(b, _)) => map_eq(&mut stack, a, b),
+                #[cfg(feature = "json")]
+                (Value::RawJson(a), Value::RawJson(b)) => a == b,
+                _ => false,
+            };
+
+            if !eq {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl<'a> Eq for Value<'a> {}
+
+impl<'a> Hash for Value<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let value = self.resolve();
+
+        state.write_u8(value.tag());
+
+        match *value {
+            Value::Unit | Value::None => (),
+            Value::U8(v) => v.hash(state),
+            Value::U16(v) => v.hash(state),
+            Value::U32(v) => v.hash(state),
+            Value::U64(v) => v.hash(state),
+            Value::U128(ref v) => v.hash(state),
+            Value::I8(v) => v.hash(state),
+            Value::I16(v) => v.hash(state),
+            Value::I32(v) => v.hash(state),
+            Value::I64(v) => v.hash(state),
+            Value::I128(ref v) => v.hash(state),
+            Value::F32(v) => f32_bits(v).hash(state),
+            Value::F64(v) => f64_bits(v).hash(state),
+            Value::Bool(v) => v.hash(state),
+            Value::Char(v) => v.hash(state),
+            Value::Str(ref v) => (**v).hash(state),
+            Value::SharedStr(ref v) => (**v).hash(state),
+            Value::BorrowedStr(v) => v.hash(state),
+            Value::Bytes(ref v) => (**v).hash(state),
+            Value::BorrowedBytes(v) => v.hash(state),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(ref v) => v[..].hash(state),
+            #[cfg(feature = "json")]
+            Value::RawJson(ref v) => (**v).hash(state),
+            Value::Some(ref v) => v.hash(state),
+            Value::UnitStruct(ref v) => v.name.hash(state),
+            Value::NewtypeStruct(ref v) => {
+                v.name.hash(state);
+                v.value.hash(state);
+            }
+            Value::Struct(ref v) => {
+                v.name.hash(state);
+                v.fields.hash(state);
+            }
+            Value::Tuple(ref fields) => fields.hash(state),
+            Value::TupleStruct(ref v) => {
+                v.name.hash(state);
+                v.fields.hash(state);
+            }
+            Value::UnitVariant(ref v) => {
+                v.name.hash(state);
+                v.variant_index.hash(state);
+                v.variant.hash(state);
+            }
+            Value::NewtypeVariant(ref v) => {
+                v.name.hash(state);
+                v.variant_index.hash(state);
+                v.variant.hash(state);
+                v.value.hash(state);
+            }
+            Value::TupleVariant(ref v) => {
+                v.name.hash(state);
+                v.variant_index.hash(state);
+                v.variant.hash(state);
+                v.fields.hash(state);
+            }
+            Value::StructVariant(ref v) => {
+                v.name.hash(state);
+                v.variant_index.hash(state);
+                v.variant.hash(state);
+                v.fields.hash(state);
+            }
+            Value::Seq(ref fields, _) => fields.hash(state),
+            Value::Map(ref fields, _) => fields.hash(state),
+            Value::Borrowed(v) => v.hash(state),
+        }
+    }
+}
+
+impl<'a, 'b> PartialOrd<Value<'b>> for Value<'a> {
+    fn partial_cmp(&self, other: &Value<'b>) -> Option<Ordering> {
+        Some(cmp(self, other))
+    }
+}
+
+impl<'a> Ord for Value<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp(self, other)
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    A tag that identifies the variant of a value.
+
+    Owned and borrowed values share the same tag.
+    */
+    pub(crate) fn tag(&self) -> u8 {
+        match *self {
+            Value::Unit => 0,
+            Value::U8(_) => 1,
+            Value::U16(_) => 2,
+            Value::U32(_) => 3,
+            Value::U64(_) => 4,
+            Value::U128(_) => 5,
+            Value::I8(_) => 6,
+            Value::I16(_) => 7,
+            Value::I32(_) => 8,
+            Value::I64(_) => 9,
+            Value::I128(_) => 10,
+            Value::F32(_) => 11,
+            Value::F64(_) => 12,
+            Value::Bool(_) => 13,
+            Value::Char(_) => 14,
+            Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_) => 15,
+            Value::Bytes(_) | Value::BorrowedBytes(_) => 16,
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(_) => 16,
+            Value::None => 17,
+            Value::Some(_) => 18,
+            Value::UnitStruct(_) => 19,
+            Value::NewtypeStruct(_) => 20,
+            Value::Struct(_) => 21,
+            Value::Tuple(_) => 22,
+            Value::TupleStruct(_) => 23,
+            Value::UnitVariant(_) => 24,
+            Value::NewtypeVariant(_) => 25,
+            Value::TupleVariant(_) => 26,
+            Value::StructVariant(_) => 27,
+            Value::Seq(_, _) => 28,
+            Value::Map(_, _) => 29,
+            #[cfg(feature = "json")]
+            Value::RawJson(_) => 30,
+            Value::Borrowed(v) => v.tag(),
+        }
+    }
+
+    fn to_int(&self) -> Option<Int> {
+        match *self {
+            Value::U8(v) => Some(Int::Pos(v.into())),
+            Value::U16(v) => Some(Int::Pos(v.into())),
+            Value::U32(v) => Some(Int::Pos(v.into())),
+            Value::U64(v) => Some(Int::Pos(v.into())),
+            Value::U128(ref v) => Some(Int::Pos(**v)),
+            Value::I8(v) => Some(Int::from_i128(v.into())),
+            Value::I16(v) => Some(Int::from_i128(v.into())),
+            Value::I32(v) => Some(Int::from_i128(v.into())),
+            Value::I64(v) => Some(Int::from_i128(v.into())),
+            Value::I128(ref v) => Some(Int::from_i128(**v)),
+            _ => None,
+        }
+    }
+
+    fn to_float(&self) -> Option<f64> {
+        match *self {
+            Value::F32(v) => Some(v.into()),
+            Value::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn to_contents(&self) -> Contents<'_, 'a> {
+        match *self {
+            Value::Some(ref v) => Contents::Value(v),
+            Value::NewtypeStruct(ref v) => Contents::Value(&v.value),
+            Value::NewtypeVariant(ref v) => Contents::Value(&v.value),
+            Value::Seq(ref fields, _) | Value::Tuple(ref fields) => Contents::Seq(fields),
+            Value::TupleStruct(ref v) => Contents::Seq(&v.fields),
+            Value::TupleVariant(ref v) => Contents::Seq(&v.fields),
+            Value::Struct(ref v) => Contents::Fields(&v.fields),
+            Value::StructVariant(ref v) => Contents::Fields(&v.fields),
+            Value::Map(ref fields, _) => Contents::Map(fields),
+            _ => Contents::None,
+        }
+    }
+
+    fn struct_name(&self) -> Option<&str> {
+        match *self {
+            Value::UnitStruct(ref v) => Some(&v.name),
+            Value::NewtypeStruct(ref v) => Some(&v.name),
+            Value::TupleStruct(ref v) => Some(&v.name),
+            Value::Struct(ref v) => Some(&v.name),
+            _ => None,
+        }
+    }
+
+    fn variant(&self) -> Option<(&str, u32, &str)> {
+        match *self {
+            Value::UnitVariant(ref v) => Some((&v.name, v.variant_index, &v.variant)),
+            Value::NewtypeVariant(ref v) => Some((&v.name, v.variant_index, &v.variant)),
+            Value::TupleVariant(ref v) => Some((&v.name, v.variant_index, &v.variant)),
+            Value::StructVariant(ref v) => Some((&v.name, v.variant_index, &v.variant)),
+            _ => None,
+        }
+    }
+}
+
+/**
+An integer of any width and sign.
+
+Negative integers always sort before positive ones.
+*/
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Int {
+    Neg(i128),
+    Pos(u128),
+}
+
+impl Int {
+    fn from_i128(v: i128) -> Self {
+        match u128::try_from(v) {
+            Ok(v) => Int::Pos(v),
+            Err(_) => Int::Neg(v),
+        }
+    }
+}
+
+enum Contents<'v, 'a> {
+    None,
+    Value(&'v Value<'a>),
+    Seq(&'v [Value<'a>]),
+    Fields(&'v [(Name<'a>, Value<'a>)]),
+    Map(&'v [(Value<'a>, Value<'a>)]),
+}
+
+/**
+Compare two values.
+
+Values are first ordered by their [`crate::Kind`], and then by their contents.
+Numbers of different widths are compared by value, with narrower types sorting first when their values are equal.
+Floats use a total order, consistent with their equality.
+*/
+fn cmp(a: &Value, b: &Value) -> Ordering {
+    let (a, b) = (a.resolve(), b.resolve());
+
+    a.kind()
+        .cmp(&b.kind())
+        .then_with(|| match (a, b) {
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Char(a), Value::Char(b)) => a.cmp(b),
+            (
+                a @ (Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_)),
+                b @ (Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_)),
+            ) => a.as_str().cmp(&b.as_str()),
+            (
+                a @ (Value::Bytes(_) | Value::BorrowedBytes(_)),
+                b @ (Value::Bytes(_) | Value::BorrowedBytes(_)),
+            ) => a.as_bytes().cmp(&b.as_bytes()),
+            #[cfg(feature = "bytes")]
+            (a @ Value::SharedBytes(_), b) | (a, b @ Value::SharedBytes(_)) => {
+                a.as_bytes().cmp(&b.as_bytes())
+            }
+            #[cfg(feature = "json")]
+            (Value::RawJson(a), Value::RawJson(b)) => a.cmp(b),
+            (
+                Value::UnitVariant(_)
+                | Value::NewtypeVariant(_)
+                | Value::TupleVariant(_)
+                | Value::StructVariant(_),
+                Value::UnitVariant(_)
+                | Value::NewtypeVariant(_)
+                | Value::TupleVariant(_)
+                | Value::StructVariant(_),
+            ) => a.variant().cmp(&b.variant()),
+            (
+                Value::UnitStruct(_)
+                | Value::NewtypeStruct(_)
+                | Value::TupleStruct(_)
+                | Value::Struct(_),
+                Value::UnitStruct(_)
+                | Value::NewtypeStruct(_)
+                | Value::TupleStruct(_)
+                | Value::Struct(_),
+            ) => a.struct_name().cmp(&b.struct_name()),
+            _ => match (a.to_int(), b.to_int()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => match (a.to_float(), b.to_float()) {
+                    (Some(a), Some(b)) => {
+                        f64::from_bits(f64_bits(a)).total_cmp(&f64::from_bits(f64_bits(b)))
+                    }
+                    _ => Ordering::Equal,
+                },
+            },
+        })
+        .then_with(|| a.tag().cmp(&b.tag()))
+        .then_with(|| match (a.to_contents(), b.to_contents()) {
+            (Contents::Value(a), Contents::Value(b)) => cmp(a, b),
+            (Contents::Seq(a), Contents::Seq(b)) => cmp_seq(a, b),
+            (Contents::Fields(a), Contents::Fields(b)) => {
+                for ((ak, av), (bk, bv)) in a.iter().zip(b.iter()) {
+                    match ak.cmp(bk).then_with(|| cmp(av, bv)) {
+                        Ordering::Equal => continue,
+                        ord => return ord,
+                    }
+                }
+
+                a.len().cmp(&b.len())
+            }
+            (Contents::Map(a), Contents::Map(b)) => {
+                for ((ak, av), (bk, bv)) in a.iter().zip(b.iter()) {
+                    match cmp(ak, bk).then_with(|| cmp(av, bv)) {
+                        Ordering::Equal => continue,
+                        ord => return ord,
+                    }
+                }
+
+                a.len().cmp(&b.len())
+            }
+            _ => Ordering::Equal,
+        })
+}
+
+fn cmp_seq(a: &[Value], b: &[Value]) -> Ordering {
+    for (a, b) in a.iter().zip(b.iter()) {
+        match cmp(a, b) {
+            Ordering::Equal => continue,
+            ord => return ord,
+        }
+    }
+
+    a.len().cmp(&b.len())
+}
+
+/**
+Get the bits of a float for equality and hashing.
+
+All `NaN`s are considered equal to each other, and `-0.0` is considered equal to `0.0`.
+*/
+fn f32_bits(v: f32) -> u32 {
+    if v.is_nan() {
+        f32::NAN.to_bits()
+    } else if v == 0.0 {
+        0
+    } else {
+        v.to_bits()
+    }
+}
+
+/**
+Get the bits of a float for equality and hashing.
+
+All `NaN`s are considered equal to each other, and `-0.0` is considered equal to `0.0`.
+*/
+fn f64_bits(v: f64) -> u64 {
+    if v.is_nan() {
+        f64::NAN.to_bits()
+    } else if v == 0.0 {
+        0
+    } else {
+        v.to_bits()
+    }
+}
+
+/**
+Whether two floats are within `epsilon` of each other, either absolutely or relative to their magnitudes.
+*/
+fn approx_eq(a: f64, b: f64, epsilon: f64) -> bool {
+    if a == b || (a.is_nan() && b.is_nan()) {
+        return true;
+    }
+
+    if !a.is_finite() || !b.is_finite() {
+        return false;
+    }
+
+    let abs = |v: f64| if v < 0.0 { -v } else { v };
+
+    let diff = abs(a - b);
+    diff <= epsilon || diff <= epsilon * abs(a).max(abs(b))
+}
+
+/**
+Compare the lengths of two sequences, pushing their values onto `stack` to compare if they're equal.
+*/
+fn seq_eq<'v, 'a, 'b>(
+    stack: &mut Vec<(&'v Value<'a>, &'v Value<'b>)>,
+    a: &'v [Value<'a>],
+    b: &'v [Value<'b>],
+) -> bool {
+    if a.len() == b.len() {
+        stack.extend(a.iter().zip(b.iter()));
+        true
+    } else {
+        false
+    }
+}
+
+/**
+Compare the lengths of two maps, pushing their keys and values onto `stack` to compare if they're equal.
+*/
+fn map_eq<'v, 'a, 'b>(
+    stack: &mut Vec<(&'v Value<'a>, &'v Value<'b>)>,
+    a: &'v [(Value<'a>, Value<'a>)],
+    b: &'v [(Value<'b>, Value<'b>)],
+) -> bool {
+    if a.len() == b.len() {
+        stack.extend(
+            a.iter()
+                .zip(b.iter())
+                .flat_map(|((ak, av), (bk, bv))| [(ak, bk), (av, bv)]),
+        );
+        true
+    } else {
+        false
+    }
+}
+
+/**
+Compare the names of two sets of fields, pushing their values onto `stack` to compare if they're equal.
+*/
+fn fields_eq<'v, 'a, 'b>(
+    stack: &mut Vec<(&'v Value<'a>, &'v Value<'b>)>,
+    a: &'v [(Name<'a>, Value<'a>)],
+    b: &'v [(Name<'b>, Value<'b>)],
+) -> bool {
+    if a.len() == b.len() && a.iter().zip(b.iter()).all(|((ak, _), (bk, _))| ak == bk) {
+        stack.extend(a.iter().zip(b.iter()).map(|((_, av), (_, bv))| (av, bv)));
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+
+    #[test]
+    fn eq() {
+        assert_eq!(Ref::str("a"), Ref::owned_str("a"));
+        assert_eq!(Ref::bytes(b"a"), Ref::owned_bytes(b"a" as &[u8]));
+        assert_ne!(Ref::str("a"), Ref::bytes(b"a"));
+        assert_ne!(Ref::u8(1), Ref::u16(1));
+
+        let owned = Owned::buffer(("a", [1, 2])).unwrap();
+        let buf = Ref::tuple([Ref::str("a"), Ref::tuple([Ref::i32(1), Ref::i32(2)])]);
+
+        assert_eq!(owned, buf);
+        assert_eq!(buf, owned);
+        assert_eq!(owned, owned.as_ref());
+        assert_eq!(owned, owned.clone());
+
+        assert_ne!(
+            Ref::record_struct("A", [("a", Ref::unit())]),
+            Ref::record_struct("B", [("a", Ref::unit())])
+        );
+        assert_ne!(
+            Ref::record_struct("A", [("a", Ref::unit())]),
+            Ref::record_struct("A", [("b", Ref::unit())])
+        );
+    }
+
+    #[test]
+    fn eq_deeply_nested() {
+        let deep = |leaf| {
+            let mut deep = leaf;
+            for i in 0..200_000 {
+                deep = match i % 4 {
+                    0 => Ref::some(deep),
+                    1 => Ref::newtype_variant("Enum", 0, "A", deep),
+                    2 => Ref::seq([Ref::u8(1), deep]),
+                    _ => Ref::map([(Ref::str("a"), deep)]),
+                };
+            }
+            deep
+        };
+
+        // Not `assert_eq!`, because formatting a deeply nested buffer recurses
+        assert!(deep(Ref::unit()) == deep(Ref::unit()));
+        assert!(deep(Ref::unit()) != deep(Ref::none()));
+    }
+
+    #[test]
+    fn approx_eq() {
+        let a = Owned::from(Ref::record_struct(
+            "Point",
+            [
+                ("x", Ref::f64(0.1 + 0.2)),
+                ("y", Ref::seq([Ref::f32(1.0e6), Ref::f64(f64::NAN)])),
+                ("label", Ref::str("a")),
+            ],
+        ));
+        let b = Owned::from(Ref::record_struct(
+            "Point",
+            [
+                ("x", Ref::f64(0.3)),
+                ("y", Ref::seq([Ref::f64(1.0e6 + 0.5), Ref::f32(f32::NAN)])),
+                ("label", Ref::str("a")),
+            ],
+        ));
+
+        assert!(a != b);
+        assert!(a.approx_eq(&b, 1e-6));
+        assert!(a.as_ref().approx_eq(&b.as_ref(), 1e-6));
+        assert!(!a.approx_eq(&b, 1e-9));
+
+        // Floats only match other floats, and everything else must be equal
+        assert!(!Ref::f64(1.0).approx_eq(&Ref::u8(1), 1.0));
+        assert!(!Ref::str("a").approx_eq(&Ref::str("b"), 1.0));
+        assert!(!Ref::record_struct("A", [("x", Ref::f64(1.0))])
+            .approx_eq(&Ref::record_struct("A", [("y", Ref::f64(1.0))]), 1.0));
+
+        assert!(Ref::f64(f64::INFINITY).approx_eq(&Ref::f32(f32::INFINITY), 0.1));
+        assert!(!Ref::f64(f64::INFINITY).approx_eq(&Ref::f64(f64::NEG_INFINITY), 0.1));
+        assert!(!Ref::f64(f64::INFINITY).approx_eq(&Ref::f64(f64::MAX), 0.1));
+        assert!(Ref::f64(-0.0).approx_eq(&Ref::f64(1e-12), 1e-9));
+    }
+
+    #[test]
+    fn eq_primitive() {
+        assert_eq!(Ref::u8(5), 5u64);
+        assert_eq!(Owned::u128(5), 5u64);
+        assert_eq!(Ref::i16(-5), -5i64);
+        assert_eq!(Ref::u32(5), 5i64);
+        assert_eq!(Ref::i8(5), 5u64);
+        assert_ne!(Ref::i8(-5), 5u64);
+        assert_ne!(Owned::u128(u128::MAX), u64::MAX);
+        assert_ne!(Owned::i128(i128::MIN), i64::MIN);
+
+        assert_eq!(Ref::f32(1.5), 1.5f64);
+        assert_eq!(Ref::u8(1), 1.0f64);
+        assert_eq!(Ref::f64(f64::NAN), f64::NAN);
+        assert_ne!(Ref::u64(u64::MAX), u64::MAX as f64);
+
+        assert_eq!(Ref::bool(true), true);
+        assert_eq!(Ref::str("a"), "a");
+        assert_eq!(Owned::from(String::from("a")), "a");
+        assert_eq!("a", Ref::str("a"));
+        assert_eq!(5u64, Owned::u8(5));
+
+        // Values of other kinds are never equal to primitives
+        assert_ne!(Ref::str("5"), 5u64);
+        assert_ne!(Ref::u8(1), true);
+        assert_ne!(Ref::bool(true), 1u64);
+        assert_ne!(Ref::some(Ref::u8(5)), 5u64);
+        assert_ne!(Ref::bytes(b"a"), "a");
+        assert_ne!(Ref::char('a'), "a");
+    }
+
+    #[test]
+    fn hash() {
+        use core::hash::BuildHasher;
+        use std::collections::hash_map::RandomState;
+
+        let state = RandomState::new();
+
+        let owned = Owned::buffer(("a", [1, 2])).unwrap();
+        let buf = Ref::tuple([Ref::str("a"), Ref::tuple([Ref::i32(1), Ref::i32(2)])]);
+
+        assert_eq!(state.hash_one(&owned), state.hash_one(&buf));
+        assert_eq!(state.hash_one(&owned), state.hash_one(owned.as_ref()));
+
+        assert_eq!(Ref::f64(f64::NAN), Ref::f64(-f64::NAN));
+        assert_eq!(
+            state.hash_one(Ref::f64(f64::NAN)),
+            state.hash_one(Ref::f64(-f64::NAN))
+        );
+
+        assert_eq!(Ref::f32(0.0), Ref::f32(-0.0));
+        assert_eq!(
+            state.hash_one(Ref::f32(0.0)),
+            state.hash_one(Ref::f32(-0.0))
+        );
+    }
+
+    #[test]
+    fn ord() {
+        use alloc::vec::Vec;
+
+        let mut bufs = alloc::vec![
+            Ref::str("b"),
+            Ref::u64(2),
+            Ref::i8(-1),
+            Ref::owned_str("a"),
+            Ref::unit(),
+            Ref::u8(2),
+            Ref::f64(f64::NAN),
+            Ref::f32(1.5),
+            Ref::seq([Ref::u8(1), Ref::u8(2)]),
+            Ref::seq([Ref::u8(1)]),
+        ];
+
+        bufs.sort();
+
+        assert_eq!(
+            alloc::vec![
+                Ref::unit(),
+                Ref::i8(-1),
+                Ref::u8(2),
+                Ref::u64(2),
+                Ref::f32(1.5),
+                Ref::f64(f64::NAN),
+                Ref::owned_str("a"),
+                Ref::str("b"),
+                Ref::seq([Ref::u8(1)]),
+                Ref::seq([Ref::u8(1), Ref::u8(2)]),
+            ],
+            bufs
+        );
+
+        let owned = Owned::buffer(("a", [1, 2])).unwrap();
+        assert_eq!(Some(Ordering::Equal), owned.partial_cmp(&owned.as_ref()));
+
+        let bufs: Vec<Ref> = alloc::vec![
+            Ref::f64(0.0),
+            Ref::f64(-0.0),
+            Ref::f64(f64::NAN),
+            Ref::f64(-f64::NAN),
+            Ref::str("a"),
+            Ref::owned_str("a"),
+        ];
+
+        for a in &bufs {
+            for b in &bufs {
+                assert_eq!(a == b, a.cmp(b) == Ordering::Equal, "{a:?} {b:?}");
+            }
+        }
+    }
+}
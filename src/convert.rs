@@ -0,0 +1,560 @@
+use core::mem;
+
+use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+
+use crate::{LenHint, Name, Owned, Ref, Struct, Value};
+
+impl Owned {
+    /**
+    Convert a struct into a map with string keys.
+
+    Each field becomes an entry with the field's name as its key, in the same order as the struct.
+    The struct's name is discarded. This method returns the original buffer if it doesn't contain a struct.
+    */
+    pub fn into_map(self) -> Result<Self, Self> {
+        self.into_value().into_map().map(Owned).map_err(Owned)
+    }
+
+    /**
+    Convert a map with string keys into a struct with the given name and fields.
+
+    Each entry becomes a field with the entry's key as its name, in the same order as the map.
+    Keys are matched against `fields` in the same way [`serde::Deserializer::deserialize_struct`]
+    uses them, so the struct's fields get their `'static` names. If every key is in `fields` then the
+    struct serializes the same as a Rust struct would, making this the inverse of [`Owned::into_map`].
+    Keys that aren't in `fields` keep their own names, so the struct serializes as a map instead.
+    See [`Name`] for details.
+
+    This method returns the original buffer, rather than discarding it, if it doesn't contain a map,
+    or if any of its keys aren't strings.
+    */
+    pub fn into_struct(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+    ) -> Result<Self, Self> {
+        self.into_value()
+            .into_struct(Name::new(name), fields)
+            .map(Owned)
+            .map_err(Owned)
+    }
+
+    /**
+    Take the elements of a sequence buffer.
+
+    Sequences, tuples, and structs with unnamed fields, including enum variants, can be taken apart.
+    This is the owning counterpart to [`Owned::iter_seq`]: the elements are moved out of the buffer
+    rather than cloned. This method returns the original buffer if it doesn't contain an indexable collection.
+    */
+    pub fn into_seq(self) -> Result<Vec<Owned>, Owned> {
+        self.into_value()
+            .into_seq()
+            .map(|fields| fields.into_iter().map(Owned).collect())
+            .map_err(Owned)
+    }
+
+    /**
+    Take the entries of a map buffer.
+
+    This is the owning counterpart to [`Owned::iter_map`]: the keys and values are moved out of the
+    buffer rather than cloned. Use [`Owned::into_map`] instead to convert a struct into a map buffer.
+    This method returns the original buffer if it doesn't contain a map.
+    */
+    pub fn into_entries(self) -> Result<Vec<(Owned, Owned)>, Owned> {
+        self.into_value()
+            .into_entries()
+            .map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (Owned(k), Owned(v)))
+                    .collect()
+            })
+            .map_err(Owned)
+    }
+
+    /**
+    Take the named fields of a struct buffer.
+
+    Structs with named fields, including enum variants, can be taken apart.
+    This is the owning counterpart to [`Owned::iter_fields`]: the names and values are moved out of the
+    buffer rather than cloned. This method returns the original buffer if it doesn't contain a struct
+    with named fields.
+    */
+    pub fn into_fields(self) -> Result<Vec<(String, Owned)>, Owned> {
+        self.into_value()
+            .into_fields()
+            .map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (k.into_cow().into_owned(), Owned(v)))
+                    .collect()
+            })
+            .map_err(Owned)
+    }
+
+    /**
+    Remove any layers of `Some` around the value in this buffer.
+
+    A buffer like `Some(Some(1))` becomes `1`. Buffers that don't contain a `Some`, including
+    `None`, are returned unchanged. Use [`Owned::as_some`] to only look inside a single layer.
+    Options nested within other values, like the fields of a struct, aren't changed.
+    */
+    pub fn unwrap_options(self) -> Self {
+        Owned(self.into_value().unwrap_options(false))
+    }
+
+    /**
+    Remove any layers of `Some` around the value in this buffer, replacing a `None` with a unit.
+
+    This is like [`Owned::unwrap_options`], except a `None`, including one wrapped in layers of `Some`
+    like `Some(None)`, becomes `()`. That can be used to treat absent and null values the same way.
+    */
+    pub fn unwrap_options_or_unit(self) -> Self {
+        Owned(self.into_value().unwrap_options(true))
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Convert a struct into a map with string keys.
+
+    See [`Owned::into_map`] for details.
+    */
+    pub fn into_map(self) -> Result<Self, Self> {
+        self.into_value().into_map().map(Ref).map_err(Ref)
+    }
+
+    /**
+    Convert a map with string keys into a struct with the given name.
+
+    See [`Owned::into_struct`] for details. Keys are borrowed from maps that are themselves borrowed.
+    */
+    pub fn into_struct(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+    ) -> Result<Self, Self> {
+        self.into_value()
+            .into_struct(Name::new(name), fields)
+            .map(Ref)
+            .map_err(Ref)
+    }
+
+    /**
+    Take the elements of a sequence buffer.
+
+    See [`Owned::into_seq`] for details. Elements of collections borrowed from other buffers are
+    borrowed rather than cloned.
+    */
+    pub fn into_seq(self) -> Result<Vec<Ref<'a>>, Ref<'a>> {
+        self.into_value()
+            .into_seq()
+            .map(|fields| fields.into_iter().map(Ref).collect())
+            .map_err(Ref)
+    }
+
+    /**
+    Take the entries of a map buffer.
+
+    See [`Owned::into_entries`] for details. Entries of maps borrowed from other buffers are
+    borrowed rather than cloned.
+    */
+    pub fn into_entries(self) -> Result<Vec<(Ref<'a>, Ref<'a>)>, Ref<'a>> {
+        self.into_value()
+            .into_entries()
+            .map(|fields| fields.into_iter().map(|(k, v)| (Ref(k), Ref(v))).collect())
+            .map_err(Ref)
+    }
+
+    /**
+    Take the named fields of a struct buffer.
+
+    See [`Owned::into_fields`] for details. Fields of structs borrowed from other buffers are
+    borrowed rather than cloned, including their names.
+    */
+    pub fn into_fields(self) -> Result<Vec<(Cow<'a, str>, Ref<'a>)>, Ref<'a>> {
+        self.into_value()
+            .into_fields()
+            .map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (k.into_cow(), Ref(v)))
+                    .collect()
+            })
+            .map_err(Ref)
+    }
+
+    /**
+    Remove any layers of `Some` around the value in this buffer.
+
+    See [`Owned::unwrap_options`] for details. Values inside options borrowed from other buffers
+    are borrowed rather than cloned.
+    */
+    pub fn unwrap_options(self) -> Self {
+        Ref(self.into_value().unwrap_options(false))
+    }
+
+    /**
+    Remove any layers of `Some` around the value in this buffer, replacing a `None` with a unit.
+
+    See [`Owned::unwrap_options_or_unit`] for details.
+    */
+    pub fn unwrap_options_or_unit(self) -> Self {
+        Ref(self.into_value().unwrap_options(true))
+    }
+}
+
+impl<'a> Value<'a> {
+    fn into_map(mut self) -> Result<Value<'a>, Value<'a>> {
+        match self {
+            Value::Struct(ref mut v) => Ok(Value::Map(
+                mem::take(&mut v.fields)
+                    .into_vec()
+                    .into_iter()
+                    .map(|(k, v)| (k.into_value(), v))
+                    .collect(),
+                LenHint::Known,
+            )),
+            Value::Borrowed(v) => match v.resolve() {
+                Value::Struct(v) => Ok(Value::Map(
+                    v.fields
+                        .iter()
+                        .map(|(k, v)| (k.by_ref().into_value(), Value::Borrowed(v)))
+                        .collect(),
+                    LenHint::Known,
+                )),
+                _ => Err(self),
+            },
+            value => Err(value),
+        }
+    }
+
+    fn into_struct(
+        mut self,
+        name: Name<'a>,
+        names: &'static [&'static str],
+    ) -> Result<Value<'a>, Value<'a>> {
+        // Keys are checked before the map is taken apart so it can be returned unchanged
+        let fields = match self {
+            Value::Map(ref mut fields, _) if fields.iter().all(|(k, _)| Name::is_name(k)) => {
+                mem::take(fields)
+                    .into_vec()
+                    .into_iter()
+                    .map(|field| into_field(field, names))
+                    .collect()
+            }
+            Value::Borrowed(v) => match v.resolve() {
+                Value::Map(fields, _)
+                    if fields
+                        .iter()
+                        .all(|(k, _)| Name::is_name(&Value::Borrowed(k))) =>
+                {
+                    fields
+                        .iter()
+                        .map(|(k, v)| into_field((Value::Borrowed(k), Value::Borrowed(v)), names))
+                        .collect()
+                }
+                _ => return Err(self),
+            },
+            value => return Err(value),
+        };
+
+        Ok(Value::Struct(Box::new(Struct { name, fields })))
+    }
+}
+
+impl<'a> Value<'a> {
+    fn into_seq(mut self) -> Result<Vec<Value<'a>>, Value<'a>> {
+        match self {
+            Value::Seq(ref mut fields, _) | Value::Tuple(ref mut fields) => {
+                Ok(mem::take(fields).into_vec())
+            }
+            Value::TupleStruct(ref mut v) => Ok(mem::take(&mut v.fields).into_vec()),
+            Value::TupleVariant(ref mut v) => Ok(mem::take(&mut v.fields).into_vec()),
+            Value::Borrowed(v) => match v.as_seq() {
+                Some(fields) => Ok(fields.iter().map(Value::Borrowed).collect()),
+                None => Err(self),
+            },
+            value => Err(value),
+        }
+    }
+
+    fn into_entries(mut self) -> Result<Vec<(Value<'a>, Value<'a>)>, Value<'a>> {
+        match self {
+            Value::Map(ref mut fields, _) => Ok(mem::take(fields).into_vec()),
+            Value::Borrowed(v) => match v.resolve() {
+                Value::Map(fields, _) => Ok(fields
+                    .iter()
+                    .map(|(k, v)| (Value::Borrowed(k), Value::Borrowed(v)))
+                    .collect()),
+                _ => Err(self),
+            },
+            value => Err(value),
+        }
+    }
+
+    fn into_fields(mut self) -> Result<Vec<(Name<'a>, Value<'a>)>, Value<'a>> {
+        match self {
+            Value::Struct(ref mut v) => Ok(mem::take(&mut v.fields).into_vec()),
+            Value::StructVariant(ref mut v) => Ok(mem::take(&mut v.fields).into_vec()),
+            Value::Borrowed(v) => match v.resolve() {
+                Value::Struct(v) => Ok(borrow_fields(&v.fields)),
+                Value::StructVariant(v) => Ok(borrow_fields(&v.fields)),
+                _ => Err(self),
+            },
+            value => Err(value),
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    fn unwrap_options(self, none_as_unit: bool) -> Value<'a> {
+        let mut value = self;
+
+        loop {
+            value = match value {
+                Value::Some(ref mut v) => mem::replace(&mut **v, Value::Unit),
+                Value::None if none_as_unit => return Value::Unit,
+                Value::Borrowed(v) => match v.resolve() {
+                    Value::Some(v) => Value::Borrowed(v),
+                    Value::None if none_as_unit => return Value::Unit,
+                    _ => return value,
+                },
+                value => return value,
+            };
+        }
+    }
+}
+
+/**
+Convert a map entry into a struct field, using the `'static` name from `names` that matches its key.
+*/
+fn into_field<'a>(
+    (k, v): (Value<'a>, Value<'a>),
+    names: &'static [&'static str],
+) -> (Name<'a>, Value<'a>) {
+    match Name::from_value(k) {
+        Ok(k) => match names.iter().find(|name| **name == k.as_str()) {
+            Some(name) => (Name::new(name), v),
+            None => (k, v),
+        },
+        Err(_) => unreachable!("keys are checked before they're converted"),
+    }
+}
+
+fn borrow_fields<'a>(fields: &'a [(Name<'a>, Value<'a>)]) -> Vec<(Name<'a>, Value<'a>)> {
+    fields
+        .iter()
+        .map(|(k, v)| (k.by_ref(), Value::Borrowed(v)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+
+    #[test]
+    fn into_map_into_struct() {
+        let buf = Owned::from(Ref::record_struct(
+            "Struct",
+            [("b", Ref::u8(1)), ("a", Ref::seq([Ref::bool(true)]))],
+        ));
+
+        let map = buf.clone().into_map().unwrap();
+        assert_eq!(
+            Owned::from(Ref::map([
+                (Ref::str("b"), Ref::u8(1)),
+                (Ref::str("a"), Ref::seq([Ref::bool(true)])),
+            ])),
+            map
+        );
+
+        assert_eq!(Ok(buf), map.into_struct("Struct", &["a", "b"]));
+
+        // Mismatched kinds return the original buffer
+        assert_eq!(Err(Owned::from(1u8)), Owned::from(1u8).into_map());
+        assert_eq!(
+            Err(Owned::from(1u8)),
+            Owned::from(1u8).into_struct("Struct", &["a", "b"])
+        );
+
+        let map = Owned::from(Ref::map([
+            (Ref::str("a"), Ref::unit()),
+            (Ref::u8(1), Ref::unit()),
+        ]));
+        assert_eq!(
+            Err(map.clone()),
+            map.clone().into_struct("Struct", &["a", "b"])
+        );
+    }
+
+    #[test]
+    fn into_map_into_struct_serialize() {
+        use serde_derive::Serialize;
+        use serde_test::{assert_ser_tokens, Token};
+
+        #[derive(Serialize)]
+        struct Record {
+            a: u8,
+            b: bool,
+        }
+
+        let map = Owned::buffer(Record { a: 1, b: true })
+            .unwrap()
+            .into_map()
+            .unwrap();
+
+        // Fields matched against the given names serialize as a struct
+        assert_ser_tokens(
+            &map.clone().into_struct("Record", &["a", "b"]).unwrap(),
+            &[
+                Token::Struct {
+                    name: "Record",
+                    len: 2,
+                },
+                Token::Str("a"),
+                Token::U8(1),
+                Token::Str("b"),
+                Token::Bool(true),
+                Token::StructEnd,
+            ],
+        );
+
+        // Fields that aren't in the given names serialize as a map
+        assert_ser_tokens(
+            &map.into_struct("Record", &["a"]).unwrap(),
+            &[
+                Token::Map { len: Some(2) },
+                Token::Str("a"),
+                Token::U8(1),
+                Token::Str("b"),
+                Token::Bool(true),
+                Token::MapEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn into_map_into_struct_borrowed() {
+        let owned = Owned::from(Ref::record_struct("Struct", [("a", Ref::owned_str("b"))]));
+
+        let map = owned.as_ref().into_map().unwrap();
+        assert_eq!(Ref::map([(Ref::str("a"), Ref::str("b"))]), map);
+
+        let owned = map.into_owned();
+        let fields = owned.as_ref().into_struct("Struct", &["a", "b"]).unwrap();
+        assert_eq!(Ref::record_struct("Struct", [("a", Ref::str("b"))]), fields);
+
+        // Keys borrowed through more than one layer are still strings
+        let key = Owned::from("a");
+        let key = key.as_ref();
+        let map = Ref::map([(key.get_path("").unwrap(), Ref::unit())]);
+        assert_eq!(
+            Ok(Ref::record_struct("Struct", [("a", Ref::unit())])),
+            map.into_struct("Struct", &["a", "b"])
+        );
+
+        let map = Owned::from(Ref::map([(Ref::u8(1), Ref::unit())]));
+        assert_eq!(
+            Err(map.as_ref()),
+            map.as_ref().into_struct("Struct", &["a", "b"])
+        );
+        assert_eq!(Err(map.as_ref()), map.as_ref().into_map());
+    }
+
+    #[test]
+    fn into_seq_entries_fields() {
+        let seq = Owned::from(Ref::tuple_struct("Tuple", [Ref::u8(1), Ref::str("a")]));
+        assert_eq!(
+            vec![Owned::from(1u8), Owned::from("a")],
+            seq.into_seq().unwrap()
+        );
+
+        let map = Owned::from(Ref::map([(Ref::u8(1), Ref::unit())]));
+        assert_eq!(
+            vec![(Owned::from(1u8), Owned::unit())],
+            map.into_entries().unwrap()
+        );
+
+        let fields = Owned::from(Ref::record_struct_variant(
+            "Enum",
+            0,
+            "A",
+            [("a", Ref::bool(true)), ("b", Ref::none())],
+        ));
+        assert_eq!(
+            vec![
+                (String::from("a"), Owned::from(true)),
+                (String::from("b"), Owned::none())
+            ],
+            fields.into_fields().unwrap()
+        );
+
+        // Mismatched kinds return the original buffer
+        let map = Owned::from(Ref::map([]));
+        assert_eq!(Err(map.clone()), map.clone().into_seq());
+        assert_eq!(Err(map.clone()), map.clone().into_fields());
+        assert_eq!(
+            Err(Owned::from(Ref::seq([]))),
+            Owned::from(Ref::seq([])).into_entries()
+        );
+        assert_eq!(Err(Owned::from("a")), Owned::from("a").into_seq());
+    }
+
+    #[test]
+    fn into_seq_entries_fields_borrowed() {
+        let owned = Owned::from(Ref::seq([
+            Ref::owned_str("a"),
+            Ref::map([(Ref::owned_str("b"), Ref::u8(1))]),
+            Ref::record_struct("Struct", [("c", Ref::owned_str("d"))]),
+        ]));
+
+        let seq = owned.as_ref().into_seq().unwrap();
+        assert_eq!(3, seq.len());
+        assert!(matches!(seq[0].0, Value::Borrowed(Value::Str(_))));
+
+        let mut seq = seq.into_iter();
+        let _ = seq.next();
+
+        let entries = seq.next().unwrap().into_entries().unwrap();
+        assert_eq!(vec![(Ref::str("b"), Ref::u8(1))], entries);
+        assert!(matches!(entries[0].0 .0, Value::Borrowed(_)));
+
+        let fields = seq.next().unwrap().into_fields().unwrap();
+        assert_eq!(vec![(Cow::Borrowed("c"), Ref::str("d"))], fields);
+        assert!(matches!(fields[0].0, Cow::Borrowed(_)));
+
+        assert_eq!(Err(Ref::u8(1)), Ref::u8(1).into_fields());
+        assert!(owned.as_ref().into_entries().is_err());
+    }
+
+    #[test]
+    fn unwrap_options() {
+        let nested = Owned::from(Ref::some(Ref::some(Ref::some(Ref::seq([Ref::some(
+            Ref::u8(1),
+        )])))));
+
+        // Only the outer layers are removed
+        assert_eq!(
+            Ref::seq([Ref::some(Ref::u8(1))]),
+            nested.clone().unwrap_options()
+        );
+        assert_eq!(Owned::from(1u8), Owned::from(1u8).unwrap_options());
+
+        assert_eq!(Ref::none(), Ref::some(Ref::none()).unwrap_options());
+        assert_eq!(Ref::unit(), Ref::some(Ref::none()).unwrap_options_or_unit());
+        assert_eq!(Ref::unit(), Ref::none().unwrap_options_or_unit());
+        assert_eq!(Ref::u8(1), Ref::some(Ref::u8(1)).unwrap_options_or_unit());
+
+        // Borrowed options are unwrapped without cloning their values
+        let unwrapped = nested.as_ref().unwrap_options();
+        assert_eq!(Ref::seq([Ref::some(Ref::u8(1))]), unwrapped);
+        assert!(matches!(unwrapped.0, Value::Borrowed(Value::Seq(..))));
+
+        let none = Owned::from(Ref::some(Ref::none()));
+        assert_eq!(Ref::none(), none.as_ref().unwrap_options());
+        assert_eq!(Ref::unit(), none.as_ref().unwrap_options_or_unit());
+    }
+}
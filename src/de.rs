@@ -1,110 +1,442 @@
-use core::{fmt, marker::PhantomData};
+use core::{fmt, mem, slice};
 
-use alloc::{boxed::Box, string::ToString, vec};
-use serde::de::{self, Error as _, IntoDeserializer, Unexpected, Visitor};
+use alloc::{boxed::Box, vec, vec::Vec};
+use serde::de::{
+    self,
+    value::{
+        BorrowedStrDeserializer, MapAccessDeserializer, SeqAccessDeserializer, SeqDeserializer,
+        StrDeserializer, StringDeserializer,
+    },
+    Error as _, Expected, IntoDeserializer, Unexpected, Visitor,
+};
 
-use crate::{Error, Owned, Ref, Value};
+use crate::{Error, ErrorKind, Name, Owned, Ref, Struct, Value};
+
+/**
+A unit value to deserialize borrowed unit variants from.
+
+`Value` implements `Drop`, so a `&Value::Unit` temporary isn't promoted to a `'static` reference.
+*/
+static UNIT: Value<'static> = Value::Unit;
 
 impl de::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
         T: fmt::Display,
     {
-        Error(msg.to_string())
+        Error::new(ErrorKind::Message, msg)
+    }
+
+    fn invalid_type(unexp: Unexpected, exp: &dyn Expected) -> Self {
+        Error::new(
+            ErrorKind::InvalidType,
+            format_args!("invalid type: {}, expected {}", unexp, exp),
+        )
+    }
+
+    fn invalid_value(unexp: Unexpected, exp: &dyn Expected) -> Self {
+        Error::new(
+            ErrorKind::InvalidValue,
+            format_args!("invalid value: {}, expected {}", unexp, exp),
+        )
+    }
+}
+
+/**
+Options that change how values are offered to visitors.
+
+These are shared by all the deserializers created while deserializing a single buffer.
+*/
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    coerce_numbers: bool,
+    coerce_bytes: bool,
+    human_readable: bool,
+    max_len: Option<usize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            coerce_numbers: false,
+            coerce_bytes: false,
+            human_readable: true,
+            max_len: None,
+        }
+    }
+}
+
+impl Config {
+    /**
+    Check that a collection with `len` elements can be offered to a visitor.
+    */
+    fn check_len(&self, len: usize) -> Result<(), Error> {
+        match self.max_len {
+            Some(max_len) if len > max_len => Err(Error::new(
+                ErrorKind::LengthExceeded,
+                format_args!(
+                    "collection of length {} exceeds the maximum of {}",
+                    len, max_len
+                ),
+            )),
+            _ => Ok(()),
+        }
     }
 }
 
+/**
+Implement the `deserialize_*` methods for byte-strings and sequences.
+
+If byte coercion is enabled then sequences of `u8`s are offered to visitors that expect byte-strings,
+and byte-strings are offered to visitors that expect sequences. Anything else is forwarded to
+`deserialize_any`.
+*/
+macro_rules! deserialize_bytes {
+    () => {
+        fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            if self.config.coerce_bytes {
+                if let Some(v) = self.value.coerce_bytes(self.config) {
+                    return visitor.visit_byte_buf(v?);
+                }
+            }
+
+            self.deserialize_any(visitor)
+        }
+
+        fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            self.deserialize_bytes(visitor)
+        }
+
+        fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            if self.config.coerce_bytes {
+                if let Some(v) = self.value.as_bytes() {
+                    self.config.check_len(v.len())?;
+
+                    let mut seq = SeqDeserializer::new(v.iter().copied());
+                    let value = visitor.visit_seq(&mut seq)?;
+                    seq.end()?;
+
+                    return Ok(value);
+                }
+            }
+
+            self.deserialize_any(visitor)
+        }
+    };
+}
+
+/**
+Implement the numeric `deserialize_*` methods.
+
+If numeric coercion is enabled then numbers are converted into the requested type, as long as
+the conversion is lossless. Anything else is forwarded to `deserialize_any`.
+*/
+macro_rules! deserialize_numbers {
+    ($($method:ident => $visit:ident($coerce:ident $(::<$ty:ty>)?, $expected:literal),)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                if self.config.coerce_numbers {
+                    if let Some(v) = self.value.$coerce$(::<$ty>)?($expected) {
+                        return visitor.$visit(v?);
+                    }
+                }
+
+                self.deserialize_any(visitor)
+            }
+        )*
+    };
+}
+
 /**
 A deserializer that produces values from buffers.
 
 This is the result of calling `into_deserializer` on [`Owned`] or [`Ref`].
+Deserializers can be cloned to attempt deserializing the same buffer more than once.
+Types like `#[serde(untagged)]` enums that need to try several shapes are supported without
+cloning, because `serde` buffers whatever [`serde::Deserializer::deserialize_any`] produces.
+
+Strings and characters are interchangeable without any extra options. A `char` can be deserialized
+from a string buffer containing exactly one character, like the ones produced by JSON, and a `String`
+can be deserialized from a `char` buffer. Strings with any other number of characters fail to
+deserialize as a `char`.
 */
-pub struct Deserializer<'de>(Value<'de>);
+#[derive(Clone)]
+pub struct Deserializer<'de> {
+    value: Value<'de>,
+    config: Config,
+}
+
+impl<'de> Deserializer<'de> {
+    fn new(value: Value<'de>, config: Config) -> Self {
+        Deserializer { value, config }
+    }
+
+    /**
+    Whether to convert numbers into the type requested by the value being deserialized.
+
+    By default, numbers are always offered to visitors at the same width they were buffered with.
+    When this is enabled, integers and floats are converted into the requested width or
+    signedness, as long as the conversion is lossless. Integers can be converted into floats
+    that represent them exactly, but floats are never converted into integers. Values that are
+    out of range for the requested type produce an error.
+    */
+    pub fn coerce_numbers(mut self, coerce: bool) -> Self {
+        self.config.coerce_numbers = coerce;
+        self
+    }
+
+    /**
+    Whether to convert between byte-strings and sequences of bytes.
+
+    Some formats, like JSON, represent byte-strings as sequences of numbers, while others have a
+    dedicated byte-string type. By default, values are offered to visitors in whichever form they
+    were buffered in. When this is enabled, a sequence whose elements are all `u8`s can be deserialized
+    by a value that expects a byte-string, like `serde_bytes::ByteBuf`, and a byte-string can be
+    deserialized by a value that expects a sequence, like `Vec<u8>`. If numeric coercion is also enabled
+    then any integer in the range of a `u8` is accepted as an element. Sequences with any other elements
+    produce an error. Sequences are converted into owned byte-strings, so values that borrow byte-strings
+    can't be deserialized from them.
+    */
+    pub fn coerce_bytes(mut self, coerce: bool) -> Self {
+        self.config.coerce_bytes = coerce;
+        self
+    }
+
+    /**
+    Set whether values should expect to deserialize from a human-readable form.
+
+    Some values, like timestamps and IP addresses, check [`serde::Deserializer::is_human_readable`]
+    to decide between a readable form, like a string, and a compact one, like an integer or bytes.
+    By default, the deserializer reports that it's human-readable, like `serde` itself does.
+    Buffers don't record which form their values were buffered in, so this should match the setting
+    of the [`crate::Serializer::with_human_readable`] that produced the buffer. The setting applies
+    to all values nested within the buffer too.
+    */
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.config.human_readable = human_readable;
+        self
+    }
+
+    /**
+    Set the maximum number of elements in any collection offered to a visitor.
+
+    The limit applies to sequences, tuples, maps, and the fields of structs and enum variants
+    nested anywhere within the buffer. Collections that are longer than the limit produce an
+    error before any of their elements are deserialized, so a buffer from an untrusted source
+    can't cause the value being deserialized to allocate more than it expects. By default,
+    collections of any length are deserialized.
+    */
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.config.max_len = Some(max_len);
+        self
+    }
+}
 
 impl<'de> de::Deserializer<'de> for Deserializer<'de> {
     type Error = Error;
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.0 {
+        let config = self.config;
+
+        // Values implement `Drop`, so their contents are taken out of them rather than moved
+        match self.value {
             Value::U8(v) => visitor.visit_u8(v),
             Value::U16(v) => visitor.visit_u16(v),
             Value::U32(v) => visitor.visit_u32(v),
             Value::U64(v) => visitor.visit_u64(v),
-            Value::U128(v) => visitor.visit_u128(v),
+            Value::U128(ref v) => visitor.visit_u128(**v),
             Value::I8(v) => visitor.visit_i8(v),
             Value::I16(v) => visitor.visit_i16(v),
             Value::I32(v) => visitor.visit_i32(v),
             Value::I64(v) => visitor.visit_i64(v),
-            Value::I128(v) => visitor.visit_i128(v),
+            Value::I128(ref v) => visitor.visit_i128(**v),
             Value::F32(v) => visitor.visit_f32(v),
             Value::F64(v) => visitor.visit_f64(v),
             Value::Bool(v) => visitor.visit_bool(v),
             Value::Char(v) => visitor.visit_char(v),
-            Value::Str(v) => visitor.visit_string(v.into()),
+            Value::Str(ref mut v) => visitor.visit_string(mem::take(v).into()),
+            Value::SharedStr(ref v) => visitor.visit_str(v),
             Value::BorrowedStr(v) => visitor.visit_borrowed_str(v),
-            Value::Bytes(v) => visitor.visit_byte_buf(v.into_vec()),
+            Value::Bytes(ref mut v) => visitor.visit_byte_buf(mem::take(v).into_vec()),
             Value::BorrowedBytes(v) => visitor.visit_borrowed_bytes(v),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(ref v) => visitor.visit_bytes(v),
+            #[cfg(feature = "json")]
+            Value::RawJson(ref v) => {
+                Deserializer::new(Value::parse_raw_json(v)?, config).deserialize_any(visitor)
+            }
             Value::None => visitor.visit_none(),
-            Value::Some(v) => visitor.visit_some((*v).into_deserializer()),
+            Value::Some(ref mut v) => visitor.visit_some(Deserializer::new(v.take(), config)),
             Value::Unit => visitor.visit_unit(),
-            Value::UnitStruct { name: _ } => visitor.visit_unit(),
-            Value::NewtypeStruct { name: _, value } => {
-                visitor.visit_newtype_struct(Deserializer(*value))
+            Value::UnitStruct(_) => visitor.visit_unit(),
+            Value::NewtypeStruct(ref mut v) => {
+                visitor.visit_newtype_struct(Deserializer::new(v.value.take(), config))
+            }
+            Value::Struct(ref mut v) => {
+                visitor.visit_map(Fields::new(mem::take(&mut v.fields), config)?)
+            }
+            Value::TupleStruct(ref mut v) => {
+                visitor.visit_seq(Seq::new(mem::take(&mut v.fields), config)?)
             }
-            Value::Struct { fields, name: _ } => visitor.visit_map(Map::new_str_key(fields)),
-            Value::TupleStruct { fields, name: _ } => visitor.visit_seq(Seq::new(fields)),
-            Value::Tuple(v) => visitor.visit_seq(Seq::new(v)),
-            Value::UnitVariant {
-                name: _,
-                variant_index,
-                variant,
-            } => visitor.visit_enum(Enum {
-                variant_index,
-                variant,
+            Value::Tuple(ref mut v) => visitor.visit_seq(Seq::new(mem::take(v), config)?),
+            Value::UnitVariant(ref mut v) => visitor.visit_enum(Enum {
+                variant_index: v.variant_index,
+                variant: v.variant.take(),
                 value: Variant::Value(Value::Unit),
+                config,
             }),
-            Value::NewtypeVariant {
-                name: _,
-                variant_index,
-                variant,
-                value,
-            } => visitor.visit_enum(Enum {
-                variant_index,
-                variant,
-                value: Variant::Value(*value),
+            Value::NewtypeVariant(ref mut v) => visitor.visit_enum(Enum {
+                variant_index: v.variant_index,
+                variant: v.variant.take(),
+                value: Variant::Value(v.value.take()),
+                config,
             }),
-            Value::TupleVariant {
-                name: _,
-                variant_index,
-                variant,
-                fields,
-            } => visitor.visit_enum(Enum {
-                variant_index,
-                variant,
-                value: Variant::Tuple(fields),
+            Value::TupleVariant(ref mut v) => visitor.visit_enum(Enum {
+                variant_index: v.variant_index,
+                variant: v.variant.take(),
+                value: Variant::Tuple(mem::take(&mut v.fields)),
+                config,
             }),
-            Value::StructVariant {
-                name: _,
-                variant_index,
-                variant,
-                fields,
-            } => visitor.visit_enum(Enum {
-                variant_index,
-                variant,
-                value: Variant::Struct(fields),
+            Value::StructVariant(ref mut v) => visitor.visit_enum(Enum {
+                variant_index: v.variant_index,
+                variant: v.variant.take(),
+                value: Variant::Struct(mem::take(&mut v.fields)),
+                config,
             }),
-            Value::Seq(v) => visitor.visit_seq(Seq::new(v)),
-            Value::Map(v) => visitor.visit_map(Map::new(v)),
+            Value::Seq(ref mut v, _) => visitor.visit_seq(Seq::new(mem::take(v), config)?),
+            Value::Map(ref mut v, _) => visitor.visit_map(Map::new(mem::take(v), config)?),
+            Value::Borrowed(v) => RefDeserializer::new(v, config).deserialize_any(visitor),
+        }
+    }
+
+    deserialize_numbers! {
+        deserialize_u8 => visit_u8(coerce_int::<u8>, "u8"),
+        deserialize_u16 => visit_u16(coerce_int::<u16>, "u16"),
+        deserialize_u32 => visit_u32(coerce_int::<u32>, "u32"),
+        deserialize_u64 => visit_u64(coerce_int::<u64>, "u64"),
+        deserialize_u128 => visit_u128(coerce_int::<u128>, "u128"),
+        deserialize_i8 => visit_i8(coerce_int::<i8>, "i8"),
+        deserialize_i16 => visit_i16(coerce_int::<i16>, "i16"),
+        deserialize_i32 => visit_i32(coerce_int::<i32>, "i32"),
+        deserialize_i64 => visit_i64(coerce_int::<i64>, "i64"),
+        deserialize_i128 => visit_i128(coerce_int::<i128>, "i128"),
+        deserialize_f32 => visit_f32(coerce_f32, "f32"),
+        deserialize_f64 => visit_f64(coerce_f64, "f64"),
+    }
+
+    /**
+    Deserialize a unit struct.
+
+    Unit structs are offered to visitors as units, whatever their buffered name is.
+    */
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::UnitStruct(_) | Value::Unit => visitor.visit_unit(),
+            Value::Borrowed(v) => {
+                RefDeserializer::new(v, self.config).deserialize_unit_struct(name, visitor)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /**
+    Deserialize a newtype struct.
+
+    Buffered newtype structs offer their inner value to the visitor. Any other value is offered as
+    the inner value itself, like a newtype struct that was buffered from a format that doesn't
+    preserve them.
+    */
+    fn deserialize_newtype_struct<V>(
+        mut self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::NewtypeStruct(ref mut v) => {
+                visitor.visit_newtype_struct(Deserializer::new(v.value.take(), self.config))
+            }
+            #[cfg(feature = "json")]
+            Value::RawJson(ref mut v) if name == crate::json::RAW_VALUE_TOKEN => {
+                crate::json::deserialize_raw(StringDeserializer::new(mem::take(v).into()), visitor)
+            }
+            Value::Borrowed(v) => {
+                RefDeserializer::new(v, self.config).deserialize_newtype_struct(name, visitor)
+            }
+            _ => visitor.visit_newtype_struct(self),
         }
     }
 
+    /**
+    Deserialize an enum.
+
+    Buffered enum variants are offered to the visitor directly. Enums can also be deserialized
+    from their externally tagged form, which is how self-describing formats like JSON represent
+    them: a map with a single string key is a variant with the key as its name and the value
+    as its contents, and a string is a unit variant.
+    */
+    fn deserialize_enum<V>(
+        mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Map(ref mut fields, _) if is_externally_tagged(fields) => visitor.visit_enum(
+                MapAccessDeserializer::new(Map::new(mem::take(fields), self.config)?),
+            ),
+            Value::Str(ref mut v) => {
+                visitor.visit_enum(StringDeserializer::new(mem::take(v).into()))
+            }
+            Value::SharedStr(ref v) => visitor.visit_enum(StrDeserializer::new(v)),
+            Value::BorrowedStr(v) => visitor.visit_enum(BorrowedStrDeserializer::new(v)),
+            Value::Borrowed(v) => {
+                RefDeserializer::new(v, self.config).deserialize_enum(name, variants, visitor)
+            }
+            #[cfg(feature = "json")]
+            Value::RawJson(ref v) => Deserializer::new(Value::parse_raw_json(v)?, self.config)
+                .deserialize_enum(name, variants, visitor),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.config.human_readable
+    }
+
+    deserialize_bytes!();
+
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        bool char str string
+        option unit tuple
+        tuple_struct map struct identifier ignored_any
     }
 }
 
@@ -112,7 +444,7 @@ impl<'de> IntoDeserializer<'de, Error> for Owned {
     type Deserializer = Deserializer<'de>;
 
     fn into_deserializer(self) -> Self::Deserializer {
-        self.0.into_deserializer()
+        self.into_value().into_deserializer()
     }
 }
 
@@ -120,7 +452,7 @@ impl<'de> IntoDeserializer<'de, Error> for Ref<'de> {
     type Deserializer = Deserializer<'de>;
 
     fn into_deserializer(self) -> Self::Deserializer {
-        self.0.into_deserializer()
+        self.into_value().into_deserializer()
     }
 }
 
@@ -128,15 +460,23 @@ impl<'de> IntoDeserializer<'de, Error> for Value<'de> {
     type Deserializer = Deserializer<'de>;
 
     fn into_deserializer(self) -> Self::Deserializer {
-        Deserializer(self)
+        Deserializer::new(self, Config::default())
     }
 }
 
-struct Seq<'de>(vec::IntoIter<Value<'de>>);
+struct Seq<'de> {
+    remaining: vec::IntoIter<Value<'de>>,
+    config: Config,
+}
 
 impl<'de> Seq<'de> {
-    fn new(fields: Box<[Value<'de>]>) -> Self {
-        Seq(fields.into_vec().into_iter())
+    fn new(fields: Box<[Value<'de>]>, config: Config) -> Result<Self, Error> {
+        config.check_len(fields.len())?;
+
+        Ok(Seq {
+            remaining: fields.into_vec().into_iter(),
+            config,
+        })
     }
 }
 
@@ -147,36 +487,87 @@ impl<'de> de::SeqAccess<'de> for Seq<'de> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        self.0
+        self.remaining
             .next()
-            .map(|field| seed.deserialize(Deserializer(field)))
+            .map(|field| seed.deserialize(Deserializer::new(field, self.config)))
             .transpose()
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
 }
 
-struct Map<'de, K: IntoDeserializer<'de, E>, E: de::Error> {
-    remaining: vec::IntoIter<(K, Value<'de>)>,
+struct Map<'de> {
+    remaining: vec::IntoIter<(Value<'de>, Value<'de>)>,
     value: Option<Value<'de>>,
-    _m: PhantomData<E>,
+    config: Config,
+}
+
+impl<'de> Map<'de> {
+    fn new(fields: Box<[(Value<'de>, Value<'de>)]>, config: Config) -> Result<Self, Error> {
+        config.check_len(fields.len())?;
+
+        Ok(Map {
+            remaining: fields.into_vec().into_iter(),
+            value: None,
+            config,
+        })
+    }
 }
 
-impl<'de> Map<'de, &'de str, de::value::Error> {
-    fn new_str_key(fields: Box<[(&'de str, Value<'de>)]>) -> Self {
-        Map::new(fields)
+impl<'de> de::MapAccess<'de> for Map<'de> {
+    type Error = Error;
+
+    fn next_key_seed<D>(&mut self, seed: D) -> Result<Option<D::Value>, Self::Error>
+    where
+        D: de::DeserializeSeed<'de>,
+    {
+        if let Some((k, v)) = self.remaining.next() {
+            self.value = Some(v);
+
+            Ok(Some(seed.deserialize(Deserializer::new(k, self.config))?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<D>(&mut self, seed: D) -> Result<D::Value, Self::Error>
+    where
+        D: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(Deserializer::new(
+            self.value
+                .take()
+                .ok_or_else(|| Error::new(ErrorKind::MissingMapValue, "missing map value"))?,
+            self.config,
+        ))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
     }
 }
 
-impl<'de, K: IntoDeserializer<'de, E>, E: de::Error> Map<'de, K, E> {
-    fn new(fields: Box<[(K, Value<'de>)]>) -> Self {
-        Map {
+struct Fields<'de> {
+    remaining: vec::IntoIter<(Name<'de>, Value<'de>)>,
+    value: Option<Value<'de>>,
+    config: Config,
+}
+
+impl<'de> Fields<'de> {
+    fn new(fields: Box<[(Name<'de>, Value<'de>)]>, config: Config) -> Result<Self, Error> {
+        config.check_len(fields.len())?;
+
+        Ok(Fields {
             remaining: fields.into_vec().into_iter(),
             value: None,
-            _m: PhantomData,
-        }
+            config,
+        })
     }
 }
 
-impl<'de, K: IntoDeserializer<'de, E>, E: de::Error> de::MapAccess<'de> for Map<'de, K, E> {
+impl<'de> de::MapAccess<'de> for Fields<'de> {
     type Error = Error;
 
     fn next_key_seed<D>(&mut self, seed: D) -> Result<Option<D::Value>, Self::Error>
@@ -186,10 +577,7 @@ impl<'de, K: IntoDeserializer<'de, E>, E: de::Error> de::MapAccess<'de> for Map<
         if let Some((k, v)) = self.remaining.next() {
             self.value = Some(v);
 
-            Ok(Some(
-                seed.deserialize(k.into_deserializer())
-                    .map_err(Error::custom)?,
-            ))
+            Ok(Some(seed.deserialize(NameDeserializer(k))?))
         } else {
             Ok(None)
         }
@@ -199,24 +587,108 @@ impl<'de, K: IntoDeserializer<'de, E>, E: de::Error> de::MapAccess<'de> for Map<
     where
         D: de::DeserializeSeed<'de>,
     {
-        seed.deserialize(Deserializer(
+        seed.deserialize(Deserializer::new(
             self.value
                 .take()
-                .ok_or_else(|| Error::custom("missing map value"))?,
+                .ok_or_else(|| Error::new(ErrorKind::MissingMapValue, "missing map value"))?,
+            self.config,
         ))
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
+}
+
+/**
+A deserializer for the name of a struct field.
+*/
+struct NameDeserializer<'de>(Name<'de>);
+
+impl<'de> de::Deserializer<'de> for NameDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.0.visit(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/**
+A deserializer for the identifier of an enum variant.
+
+The variant is offered as its name to visitors expecting strings or identifiers, and as its index
+to visitors expecting integers.
+*/
+struct VariantDeserializer<'de> {
+    variant_index: u32,
+    variant: Name<'de>,
+}
+
+impl<'de> de::Deserializer<'de> for VariantDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.variant.visit(visitor)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u32(self.variant_index)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u32(self.variant_index)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u32(self.variant_index)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u32(self.variant_index)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
 }
 
 struct Enum<'de> {
     variant_index: u32,
-    variant: &'static str,
+    variant: Name<'de>,
     value: Variant<'de>,
+    config: Config,
 }
 
 enum Variant<'de> {
     Value(Value<'de>),
     Tuple(Box<[Value<'de>]>),
-    Struct(Box<[(&'static str, Value<'de>)]>),
+    Struct(Box<[(Name<'de>, Value<'de>)]>),
 }
 
 impl<'de> de::EnumAccess<'de> for Enum<'de> {
@@ -229,7 +701,10 @@ impl<'de> de::EnumAccess<'de> for Enum<'de> {
         V: de::DeserializeSeed<'de>,
     {
         Ok((
-            seed.deserialize(Deserializer(Value::U32(self.variant_index)))?,
+            seed.deserialize(VariantDeserializer {
+                variant_index: self.variant_index,
+                variant: self.variant.clone(),
+            })?,
             self,
         ))
     }
@@ -263,13 +738,13 @@ impl<'de> de::VariantAccess<'de> for Enum<'de> {
         let value = match self.value {
             Variant::Value(v) => v,
             Variant::Tuple(v) => Value::Tuple(v),
-            Variant::Struct(v) => Value::Struct {
+            Variant::Struct(v) => Value::Struct(Box::new(Struct {
                 name: self.variant,
                 fields: v,
-            },
+            })),
         };
 
-        seed.deserialize(Deserializer(value))
+        seed.deserialize(Deserializer::new(value, self.config))
     }
 
     fn tuple_variant<V>(self, _: usize, visitor: V) -> Result<V::Value, Self::Error>
@@ -277,7 +752,7 @@ impl<'de> de::VariantAccess<'de> for Enum<'de> {
         V: Visitor<'de>,
     {
         match self.value {
-            Variant::Tuple(v) => visitor.visit_seq(Seq::new(v)),
+            Variant::Tuple(v) => visitor.visit_seq(Seq::new(v, self.config)?),
             Variant::Value(Value::Unit) => Err(Error::invalid_type(
                 Unexpected::UnitVariant,
                 &"tuple variant",
@@ -302,7 +777,7 @@ impl<'de> de::VariantAccess<'de> for Enum<'de> {
         V: Visitor<'de>,
     {
         match self.value {
-            Variant::Struct(v) => visitor.visit_map(Map::new_str_key(v)),
+            Variant::Struct(v) => visitor.visit_map(Fields::new(v, self.config)?),
             Variant::Value(Value::Unit) => Err(Error::invalid_type(
                 Unexpected::UnitVariant,
                 &"struct variant",
@@ -318,3 +793,1409 @@ impl<'de> de::VariantAccess<'de> for Enum<'de> {
         }
     }
 }
+
+/**
+A deserializer that produces values from borrowed buffers.
+
+This is the result of calling `into_deserializer` on a [`&Owned`](Owned) or [`&Ref`](Ref).
+Unlike [`Deserializer`], it doesn't consume the buffer, so the same buffer can be deserialized
+any number of times. Any strings or byte-strings in the buffer, including owned ones, can be
+borrowed for `'de`. Copying a `RefDeserializer` is cheap, since it only holds a reference to its buffer.
+*/
+#[derive(Clone, Copy)]
+pub struct RefDeserializer<'de> {
+    value: &'de Value<'de>,
+    config: Config,
+}
+
+impl<'de> RefDeserializer<'de> {
+    fn new(value: &'de Value<'de>, config: Config) -> Self {
+        RefDeserializer { value, config }
+    }
+
+    /**
+    Whether to convert numbers into the type requested by the value being deserialized.
+
+    See [`Deserializer::coerce_numbers`] for details.
+    */
+    pub fn coerce_numbers(mut self, coerce: bool) -> Self {
+        self.config.coerce_numbers = coerce;
+        self
+    }
+
+    /**
+    Whether to convert between byte-strings and sequences of bytes.
+
+    See [`Deserializer::coerce_bytes`] for details.
+    */
+    pub fn coerce_bytes(mut self, coerce: bool) -> Self {
+        self.config.coerce_bytes = coerce;
+        self
+    }
+
+    /**
+    Set whether values should expect to deserialize from a human-readable form.
+
+    See [`Deserializer::human_readable`] for details.
+    */
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.config.human_readable = human_readable;
+        self
+    }
+
+    /**
+    Set the maximum number of elements in any collection offered to a visitor.
+
+    See [`Deserializer::max_len`] for details.
+    */
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.config.max_len = Some(max_len);
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for RefDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let config = self.config;
+
+        match self.value {
+            Value::U8(v) => visitor.visit_u8(*v),
+            Value::U16(v) => visitor.visit_u16(*v),
+            Value::U32(v) => visitor.visit_u32(*v),
+            Value::U64(v) => visitor.visit_u64(*v),
+            Value::U128(v) => visitor.visit_u128(**v),
+            Value::I8(v) => visitor.visit_i8(*v),
+            Value::I16(v) => visitor.visit_i16(*v),
+            Value::I32(v) => visitor.visit_i32(*v),
+            Value::I64(v) => visitor.visit_i64(*v),
+            Value::I128(v) => visitor.visit_i128(**v),
+            Value::F32(v) => visitor.visit_f32(*v),
+            Value::F64(v) => visitor.visit_f64(*v),
+            Value::Bool(v) => visitor.visit_bool(*v),
+            Value::Char(v) => visitor.visit_char(*v),
+            Value::Str(v) => visitor.visit_borrowed_str(v),
+            Value::SharedStr(v) => visitor.visit_borrowed_str(v),
+            Value::BorrowedStr(v) => visitor.visit_borrowed_str(v),
+            Value::Bytes(v) => visitor.visit_borrowed_bytes(v),
+            Value::BorrowedBytes(v) => visitor.visit_borrowed_bytes(v),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(v) => visitor.visit_borrowed_bytes(v),
+            #[cfg(feature = "json")]
+            Value::RawJson(v) => {
+                Deserializer::new(Value::parse_raw_json(v)?, config).deserialize_any(visitor)
+            }
+            Value::None => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(RefDeserializer::new(v, config)),
+            Value::Unit => visitor.visit_unit(),
+            Value::UnitStruct(_) => visitor.visit_unit(),
+            Value::NewtypeStruct(v) => {
+                visitor.visit_newtype_struct(RefDeserializer::new(&v.value, config))
+            }
+            Value::Struct(v) => visitor.visit_map(RefFields::new(&v.fields, config)?),
+            Value::TupleStruct(v) => visitor.visit_seq(RefSeq::new(&v.fields, config)?),
+            Value::Tuple(v) => visitor.visit_seq(RefSeq::new(v, config)?),
+            Value::UnitVariant(v) => visitor.visit_enum(RefEnum {
+                variant_index: v.variant_index,
+                variant: v.variant.by_ref(),
+                value: RefVariant::Unit,
+                config,
+            }),
+            Value::NewtypeVariant(v) => visitor.visit_enum(RefEnum {
+                variant_index: v.variant_index,
+                variant: v.variant.by_ref(),
+                value: RefVariant::Value(&v.value),
+                config,
+            }),
+            Value::TupleVariant(v) => visitor.visit_enum(RefEnum {
+                variant_index: v.variant_index,
+                variant: v.variant.by_ref(),
+                value: RefVariant::Tuple(&v.fields),
+                config,
+            }),
+            Value::StructVariant(v) => visitor.visit_enum(RefEnum {
+                variant_index: v.variant_index,
+                variant: v.variant.by_ref(),
+                value: RefVariant::Struct(&v.fields),
+                config,
+            }),
+            Value::Seq(v, _) => visitor.visit_seq(RefSeq::new(v, config)?),
+            Value::Map(v, _) => visitor.visit_map(RefMap::new(v, config)?),
+            Value::Borrowed(v) => RefDeserializer::new(v, config).deserialize_any(visitor),
+        }
+    }
+
+    deserialize_numbers! {
+        deserialize_u8 => visit_u8(coerce_int::<u8>, "u8"),
+        deserialize_u16 => visit_u16(coerce_int::<u16>, "u16"),
+        deserialize_u32 => visit_u32(coerce_int::<u32>, "u32"),
+        deserialize_u64 => visit_u64(coerce_int::<u64>, "u64"),
+        deserialize_u128 => visit_u128(coerce_int::<u128>, "u128"),
+        deserialize_i8 => visit_i8(coerce_int::<i8>, "i8"),
+        deserialize_i16 => visit_i16(coerce_int::<i16>, "i16"),
+        deserialize_i32 => visit_i32(coerce_int::<i32>, "i32"),
+        deserialize_i64 => visit_i64(coerce_int::<i64>, "i64"),
+        deserialize_i128 => visit_i128(coerce_int::<i128>, "i128"),
+        deserialize_f32 => visit_f32(coerce_f32, "f32"),
+        deserialize_f64 => visit_f64(coerce_f64, "f64"),
+    }
+
+    /**
+    Deserialize a unit struct.
+
+    See [`Deserializer`] for details.
+    */
+    fn deserialize_unit_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value.resolve() {
+            Value::UnitStruct(_) | Value::Unit => visitor.visit_unit(),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /**
+    Deserialize a newtype struct.
+
+    See [`Deserializer`] for details.
+    */
+    #[cfg_attr(not(feature = "json"), allow(unused_variables))]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value.resolve() {
+            Value::NewtypeStruct(v) => {
+                visitor.visit_newtype_struct(RefDeserializer::new(&v.value, self.config))
+            }
+            #[cfg(feature = "json")]
+            Value::RawJson(v) if name == crate::json::RAW_VALUE_TOKEN => {
+                crate::json::deserialize_raw(BorrowedStrDeserializer::new(v), visitor)
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    /**
+    Deserialize an enum.
+
+    See [`Deserializer`] for details.
+    */
+    #[cfg_attr(not(feature = "json"), allow(unused_variables))]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value.resolve() {
+            Value::Map(fields, _) if is_externally_tagged(fields) => visitor.visit_enum(
+                MapAccessDeserializer::new(RefMap::new(fields, self.config)?),
+            ),
+            #[cfg(feature = "json")]
+            Value::RawJson(v) => Deserializer::new(Value::parse_raw_json(v)?, self.config)
+                .deserialize_enum(name, variants, visitor),
+            value => match value.as_str() {
+                Some(v) => visitor.visit_enum(BorrowedStrDeserializer::new(v)),
+                None => self.deserialize_any(visitor),
+            },
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.config.human_readable
+    }
+
+    deserialize_bytes!();
+
+    serde::forward_to_deserialize_any! {
+        bool char str string
+        option unit tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/**
+Whether the entries of a map look like an externally tagged enum variant.
+*/
+fn is_externally_tagged(fields: &[(Value, Value)]) -> bool {
+    matches!(fields, [(k, _)] if k.as_str().is_some())
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de Owned {
+    type Deserializer = RefDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        RefDeserializer::new(&self.0, Config::default())
+    }
+}
+
+impl<'de, 'a: 'de> IntoDeserializer<'de, Error> for &'de Ref<'a> {
+    type Deserializer = RefDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        RefDeserializer::new(&self.0, Config::default())
+    }
+}
+
+struct RefSeq<'de> {
+    remaining: slice::Iter<'de, Value<'de>>,
+    config: Config,
+}
+
+impl<'de> RefSeq<'de> {
+    fn new(fields: &'de [Value<'de>], config: Config) -> Result<Self, Error> {
+        config.check_len(fields.len())?;
+
+        Ok(RefSeq {
+            remaining: fields.iter(),
+            config,
+        })
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for RefSeq<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        self.remaining
+            .next()
+            .map(|field| seed.deserialize(RefDeserializer::new(field, self.config)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
+}
+
+struct RefMap<'de> {
+    remaining: slice::Iter<'de, (Value<'de>, Value<'de>)>,
+    value: Option<&'de Value<'de>>,
+    config: Config,
+}
+
+impl<'de> RefMap<'de> {
+    fn new(fields: &'de [(Value<'de>, Value<'de>)], config: Config) -> Result<Self, Error> {
+        config.check_len(fields.len())?;
+
+        Ok(RefMap {
+            remaining: fields.iter(),
+            value: None,
+            config,
+        })
+    }
+}
+
+impl<'de> de::MapAccess<'de> for RefMap<'de> {
+    type Error = Error;
+
+    fn next_key_seed<D>(&mut self, seed: D) -> Result<Option<D::Value>, Self::Error>
+    where
+        D: de::DeserializeSeed<'de>,
+    {
+        if let Some((k, v)) = self.remaining.next() {
+            self.value = Some(v);
+
+            Ok(Some(
+                seed.deserialize(RefDeserializer::new(k, self.config))?,
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<D>(&mut self, seed: D) -> Result<D::Value, Self::Error>
+    where
+        D: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(RefDeserializer::new(
+            self.value
+                .take()
+                .ok_or_else(|| Error::new(ErrorKind::MissingMapValue, "missing map value"))?,
+            self.config,
+        ))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
+}
+
+struct RefFields<'de> {
+    remaining: slice::Iter<'de, (Name<'de>, Value<'de>)>,
+    value: Option<&'de Value<'de>>,
+    config: Config,
+}
+
+impl<'de> RefFields<'de> {
+    fn new(fields: &'de [(Name<'de>, Value<'de>)], config: Config) -> Result<Self, Error> {
+        config.check_len(fields.len())?;
+
+        Ok(RefFields {
+            remaining: fields.iter(),
+            value: None,
+            config,
+        })
+    }
+}
+
+impl<'de> de::MapAccess<'de> for RefFields<'de> {
+    type Error = Error;
+
+    fn next_key_seed<D>(&mut self, seed: D) -> Result<Option<D::Value>, Self::Error>
+    where
+        D: de::DeserializeSeed<'de>,
+    {
+        if let Some((k, v)) = self.remaining.next() {
+            self.value = Some(v);
+
+            Ok(Some(seed.deserialize(NameDeserializer(k.by_ref()))?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn next_value_seed<D>(&mut self, seed: D) -> Result<D::Value, Self::Error>
+    where
+        D: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(RefDeserializer::new(
+            self.value
+                .take()
+                .ok_or_else(|| Error::new(ErrorKind::MissingMapValue, "missing map value"))?,
+            self.config,
+        ))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.len())
+    }
+}
+
+struct RefEnum<'de> {
+    variant_index: u32,
+    variant: Name<'de>,
+    value: RefVariant<'de>,
+    config: Config,
+}
+
+enum RefVariant<'de> {
+    Unit,
+    Value(&'de Value<'de>),
+    Tuple(&'de [Value<'de>]),
+    Struct(&'de [(Name<'de>, Value<'de>)]),
+}
+
+impl<'de> de::EnumAccess<'de> for RefEnum<'de> {
+    type Error = Error;
+
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        Ok((
+            seed.deserialize(VariantDeserializer {
+                variant_index: self.variant_index,
+                variant: self.variant.clone(),
+            })?,
+            self,
+        ))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for RefEnum<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            RefVariant::Unit => Ok(()),
+            RefVariant::Value(_) => Err(Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+            RefVariant::Tuple(_) => Err(Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+            RefVariant::Struct(_) => Err(Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.value {
+            RefVariant::Unit => seed.deserialize(RefDeserializer::new(&UNIT, self.config)),
+            RefVariant::Value(v) => seed.deserialize(RefDeserializer::new(v, self.config)),
+            RefVariant::Tuple(v) => {
+                seed.deserialize(SeqAccessDeserializer::new(RefSeq::new(v, self.config)?))
+            }
+            RefVariant::Struct(v) => {
+                seed.deserialize(MapAccessDeserializer::new(RefFields::new(v, self.config)?))
+            }
+        }
+    }
+
+    fn tuple_variant<V>(self, _: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            RefVariant::Tuple(v) => visitor.visit_seq(RefSeq::new(v, self.config)?),
+            RefVariant::Unit => Err(Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+            RefVariant::Value(_) => Err(Error::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"tuple variant",
+            )),
+            RefVariant::Struct(_) => Err(Error::invalid_type(
+                Unexpected::StructVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            RefVariant::Struct(v) => visitor.visit_map(RefFields::new(v, self.config)?),
+            RefVariant::Unit => Err(Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+            RefVariant::Value(_) => Err(Error::invalid_type(
+                Unexpected::NewtypeVariant,
+                &"struct variant",
+            )),
+            RefVariant::Tuple(_) => Err(Error::invalid_type(
+                Unexpected::TupleVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    Convert a number into the integer type `T`.
+
+    This method returns `None` if the value isn't an integer, and an error if it's out of
+    range for `T`.
+    */
+    fn coerce_int<T: TryFrom<u128> + TryFrom<i128>>(
+        &self,
+        expected: &'static str,
+    ) -> Option<Result<T, Error>> {
+        let value = self.resolve();
+
+        let coerced = if let Some(v) = value.as_u128() {
+            T::try_from(v).ok()
+        } else {
+            T::try_from(value.as_i128()?).ok()
+        };
+
+        Some(coerced.ok_or_else(|| Error::invalid_value(value.unexpected(), &expected)))
+    }
+
+    /**
+    Convert a sequence of `u8`s into a byte-string.
+
+    This method returns `None` if the value isn't a sequence, and an error if any of its elements
+    aren't `u8`s.
+    */
+    fn coerce_bytes(&self, config: Config) -> Option<Result<Vec<u8>, Error>> {
+        let Value::Seq(fields, _) = self.resolve() else {
+            return None;
+        };
+
+        if let Err(err) = config.check_len(fields.len()) {
+            return Some(Err(err));
+        }
+
+        Some(
+            fields
+                .iter()
+                .map(|v| match *v.resolve() {
+                    Value::U8(b) => Ok(b),
+                    _ => match v.coerce_int::<u8>("a byte") {
+                        Some(b) if config.coerce_numbers => b,
+                        _ => Err(Error::invalid_type(v.unexpected(), &"a byte")),
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    /**
+    Convert a number into an `f32`.
+
+    This method returns `None` if the value isn't a number, and an error if it can't be
+    represented exactly.
+    */
+    fn coerce_f32(&self, expected: &'static str) -> Option<Result<f32, Error>> {
+        let value = self.resolve();
+
+        let coerced = match *value {
+            Value::F32(v) => Some(v),
+            // NaNs can't be compared, but are still preserved when narrowed
+            Value::F64(v) if v.is_nan() => Some(v as f32),
+            _ if value.is_int() || value.is_float() => value.as_f64().and_then(|v| {
+                let f = v as f32;
+
+                if f64::from(f) == v {
+                    Some(f)
+                } else {
+                    None
+                }
+            }),
+            _ => return None,
+        };
+
+        Some(coerced.ok_or_else(|| Error::invalid_value(value.unexpected(), &expected)))
+    }
+
+    /**
+    Convert a number into an `f64`.
+
+    This method returns `None` if the value isn't a number, and an error if it can't be
+    represented exactly.
+    */
+    fn coerce_f64(&self, expected: &'static str) -> Option<Result<f64, Error>> {
+        let value = self.resolve();
+
+        if !value.is_int() && !value.is_float() {
+            return None;
+        }
+
+        Some(
+            value
+                .as_f64()
+                .ok_or_else(|| Error::invalid_value(value.unexpected(), &expected)),
+        )
+    }
+
+    fn is_int(&self) -> bool {
+        self.as_u128().is_some() || self.as_i128().is_some()
+    }
+
+    fn is_float(&self) -> bool {
+        matches!(self.resolve(), Value::F32(_) | Value::F64(_))
+    }
+
+    fn unexpected(&self) -> Unexpected<'_> {
+        match *self.resolve() {
+            Value::U8(v) => Unexpected::Unsigned(v.into()),
+            Value::U16(v) => Unexpected::Unsigned(v.into()),
+            Value::U32(v) => Unexpected::Unsigned(v.into()),
+            Value::U64(v) => Unexpected::Unsigned(v),
+            Value::I8(v) => Unexpected::Signed(v.into()),
+            Value::I16(v) => Unexpected::Signed(v.into()),
+            Value::I32(v) => Unexpected::Signed(v.into()),
+            Value::I64(v) => Unexpected::Signed(v),
+            Value::U128(_) | Value::I128(_) => Unexpected::Other("128-bit integer"),
+            Value::F32(v) => Unexpected::Float(v.into()),
+            Value::F64(v) => Unexpected::Float(v),
+            _ => Unexpected::Other("non-numeric value"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{string::String, vec::Vec};
+    use serde::Deserialize as _;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Struct<'a> {
+        a: &'a str,
+        #[serde(borrow)]
+        b: Vec<Enum<'a>>,
+        c: Option<&'a str>,
+        d: (u8, Newtype),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Enum<'a> {
+        Unit,
+        Newtype(&'a str),
+        Tuple(i32, i32),
+        Struct { a: i32 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Newtype(i64);
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Partial {
+        a: String,
+    }
+
+    #[test]
+    fn ref_deserializer() {
+        let value = Struct {
+            a: "a string",
+            b: alloc::vec![
+                Enum::Unit,
+                Enum::Newtype("a string"),
+                Enum::Tuple(1, 2),
+                Enum::Struct { a: 1 },
+            ],
+            c: Some("a string"),
+            d: (1, Newtype(-1)),
+        };
+
+        let owned = Owned::buffer(&value).unwrap();
+
+        // The same buffer can be deserialized multiple times, borrowing from it each time
+        let first = Struct::deserialize((&owned).into_deserializer()).unwrap();
+        let second = Partial::deserialize((&owned).into_deserializer()).unwrap();
+
+        assert_eq!(value, first);
+        assert_eq!("a string", second.a);
+
+        let as_ref = owned.as_ref();
+        assert_eq!(
+            value,
+            Struct::deserialize((&as_ref).into_deserializer()).unwrap()
+        );
+
+        // Deserializing an owned buffer that borrows from another goes through `RefDeserializer`
+        assert_eq!(
+            value,
+            Struct::deserialize(owned.as_ref().into_deserializer()).unwrap()
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum ByName {
+        A,
+        B,
+    }
+
+    impl<'de> de::Deserialize<'de> for ByName {
+        fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct EnumVisitor;
+
+            impl<'de> de::Visitor<'de> for EnumVisitor {
+                type Value = ByName;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("an enum")
+                }
+
+                fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<ByName, A::Error> {
+                    use serde::de::VariantAccess as _;
+
+                    let (variant, access) = data.variant::<String>()?;
+                    access.unit_variant()?;
+
+                    match &*variant {
+                        "A" => Ok(ByName::A),
+                        "B" => Ok(ByName::B),
+                        _ => Err(A::Error::unknown_variant(&variant, &["A", "B"])),
+                    }
+                }
+            }
+
+            deserializer.deserialize_enum("ByName", &["A", "B"], EnumVisitor)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct ByIndex(u64);
+
+    impl<'de> de::Deserialize<'de> for ByIndex {
+        fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct EnumVisitor;
+
+            impl<'de> de::Visitor<'de> for EnumVisitor {
+                type Value = ByIndex;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("an enum")
+                }
+
+                fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<ByIndex, A::Error> {
+                    use serde::de::VariantAccess as _;
+
+                    let (variant, access) = data.variant::<u64>()?;
+                    access.unit_variant()?;
+
+                    Ok(ByIndex(variant))
+                }
+            }
+
+            deserializer.deserialize_enum("ByIndex", &["A", "B"], EnumVisitor)
+        }
+    }
+
+    #[test]
+    fn variant_by_name_or_index() {
+        let owned = Ref::unit_variant("ByName", 1, "B").into_owned();
+
+        assert_eq!(
+            ByName::B,
+            ByName::deserialize((&owned).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            ByName::B,
+            ByName::deserialize(owned.clone().into_deserializer()).unwrap()
+        );
+
+        assert_eq!(
+            ByIndex(1),
+            ByIndex::deserialize((&owned).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            ByIndex(1),
+            ByIndex::deserialize(owned.into_deserializer()).unwrap()
+        );
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Numbers {
+        a: f64,
+        b: u32,
+        c: i8,
+        d: f32,
+    }
+
+    #[test]
+    fn coerce_numbers() {
+        let owned = Ref::record_struct(
+            "Numbers",
+            [
+                ("a", Ref::f32(1.5)),
+                ("b", Ref::i64(42)),
+                ("c", Ref::u128(1)),
+                ("d", Ref::u64(1 << 24)),
+            ],
+        )
+        .into_owned();
+
+        let expected = Numbers {
+            a: 1.5,
+            b: 42,
+            c: 1,
+            d: 16777216.0,
+        };
+
+        assert!(Numbers::deserialize((&owned).into_deserializer()).is_err());
+        assert_eq!(
+            expected,
+            Numbers::deserialize((&owned).into_deserializer().coerce_numbers(true)).unwrap()
+        );
+        assert_eq!(
+            expected,
+            Numbers::deserialize(owned.into_deserializer().coerce_numbers(true)).unwrap()
+        );
+    }
+
+    #[test]
+    fn coerce_numbers_out_of_range() {
+        fn de<'de, T: de::Deserialize<'de>>(v: &'de Owned) -> Result<T, Error> {
+            T::deserialize(v.into_deserializer().coerce_numbers(true))
+        }
+
+        let err = de::<u32>(&Owned::from(-1i64)).unwrap_err();
+        assert!(std::format!("{:?}", err).contains("invalid value: integer `-1`, expected u32"));
+
+        assert!(de::<u8>(&Owned::from(256u16)).is_err());
+        assert!(de::<i64>(&Owned::from(u128::MAX)).is_err());
+        assert!(de::<u32>(&Owned::from(1.0f64)).is_err());
+        assert!(de::<f32>(&Owned::from(0.1f64)).is_err());
+        assert!(de::<f64>(&Owned::from(u64::MAX)).is_err());
+
+        assert!(de::<f32>(&Owned::from(f64::NAN)).unwrap().is_nan());
+        assert_eq!(-1, de::<i128>(&Owned::from(-1i8)).unwrap());
+        assert_eq!("a string", de::<String>(&Owned::from("a string")).unwrap());
+    }
+
+    #[test]
+    fn ref_deserializer_bytes() {
+        let owned = Ref::owned_bytes(*b"bytes").into_owned();
+
+        assert_eq!(
+            b"bytes",
+            <&[u8]>::deserialize((&owned).into_deserializer()).unwrap()
+        );
+    }
+
+    #[test]
+    fn ref_deserializer_invalid_type() {
+        let owned = Owned::buffer(Enum::Tuple(1, 2)).unwrap();
+
+        assert!(<&str>::deserialize((&owned).into_deserializer()).is_err());
+        assert!(Enum::deserialize((&Owned::buffer(1).unwrap()).into_deserializer()).is_err());
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Untagged {
+        Point { x: i32, y: i32 },
+        Pair(i32, String),
+        Newtype(Newtype),
+        Name(String),
+        Unit,
+    }
+
+    #[test]
+    fn untagged_enum() {
+        for value in [
+            Untagged::Point { x: 1, y: 2 },
+            Untagged::Pair(1, "a".into()),
+            Untagged::Newtype(Newtype(-1)),
+            Untagged::Name("a".into()),
+            Untagged::Unit,
+        ] {
+            let owned = Owned::buffer(&value).unwrap();
+
+            assert_eq!(
+                value,
+                Untagged::deserialize((&owned).into_deserializer()).unwrap()
+            );
+            assert_eq!(
+                value,
+                Untagged::deserialize(owned.clone().into_deserializer()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn clone_deserializer() {
+        let owned = Owned::buffer(&Struct {
+            a: "a string",
+            b: Vec::new(),
+            c: None,
+            d: (1, Newtype(-1)),
+        })
+        .unwrap();
+
+        let de = owned.clone().into_deserializer();
+        assert!(Newtype::deserialize(de.clone()).is_err());
+        assert_eq!("a string", Partial::deserialize(de).unwrap().a);
+
+        let de = (&owned).into_deserializer();
+        assert!(Newtype::deserialize(de).is_err());
+        assert_eq!("a string", Partial::deserialize(de).unwrap().a);
+    }
+
+    /**
+    A value that's a string in human-readable formats and an integer in compact ones.
+    */
+    #[derive(Debug, PartialEq)]
+    struct Readable(u32);
+
+    impl serde::Serialize for Readable {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.collect_str(&self.0)
+            } else {
+                serializer.serialize_u32(self.0)
+            }
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for Readable {
+        fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map(Readable)
+                    .map_err(D::Error::custom)
+            } else {
+                u32::deserialize(deserializer).map(Readable)
+            }
+        }
+    }
+
+    #[test]
+    fn human_readable() {
+        let value = alloc::vec![Some(Readable(42))];
+
+        let readable = Owned::buffer(&value).unwrap();
+        assert_eq!(Ref::seq([Ref::some(Ref::str("42"))]), readable);
+        assert_eq!(
+            value,
+            Vec::<Option<Readable>>::deserialize(readable.into_deserializer()).unwrap()
+        );
+
+        let compact = serde::Serialize::serialize(
+            &value,
+            crate::Serializer::new().with_human_readable(false),
+        )
+        .unwrap();
+        assert_eq!(Ref::seq([Ref::some(Ref::u32(42))]), compact);
+        assert_eq!(
+            value,
+            Vec::<Option<Readable>>::deserialize(
+                (&compact).into_deserializer().human_readable(false)
+            )
+            .unwrap()
+        );
+        assert!(Vec::<Option<Readable>>::deserialize(compact.clone().into_deserializer()).is_err());
+        assert_eq!(
+            value,
+            Vec::<Option<Readable>>::deserialize(compact.into_deserializer().human_readable(false))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn max_len() {
+        use alloc::collections::BTreeMap;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        enum Enum {
+            Tuple(u8, u8, u8),
+            Struct { a: u8, b: u8, c: u8 },
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Data {
+            a: Vec<u8>,
+            b: BTreeMap<u8, u8>,
+        }
+
+        let short = Owned::buffer(Data {
+            a: vec![1, 2],
+            b: [(1, 1)].into_iter().collect(),
+        })
+        .unwrap();
+
+        assert!(Data::deserialize(short.clone().into_deserializer().max_len(2)).is_ok());
+        assert!(Data::deserialize((&short).into_deserializer().max_len(2)).is_ok());
+
+        // Every kind of collection is limited, wherever it's nested
+        for long in [
+            Owned::buffer(vec![1u8, 2, 3]).unwrap(),
+            Owned::buffer((1u8, 2u8, 3u8)).unwrap(),
+            Owned::buffer(
+                [(1u8, 1u8), (2, 2), (3, 3)]
+                    .into_iter()
+                    .collect::<BTreeMap<_, _>>(),
+            )
+            .unwrap(),
+            Owned::buffer(Data {
+                a: vec![1, 2, 3],
+                b: BTreeMap::new(),
+            })
+            .unwrap(),
+            Owned::buffer(Enum::Tuple(1, 2, 3)).unwrap(),
+            Owned::buffer(Enum::Struct { a: 1, b: 2, c: 3 }).unwrap(),
+            Owned::buffer(Some(vec![vec![1u8, 2, 3]])).unwrap(),
+        ] {
+            let err =
+                serde::de::IgnoredAny::deserialize(long.clone().into_deserializer().max_len(2))
+                    .unwrap_err();
+            assert_eq!(ErrorKind::LengthExceeded, err.kind(), "{:?}", long);
+
+            let err = serde::de::IgnoredAny::deserialize((&long).into_deserializer().max_len(2))
+                .unwrap_err();
+            assert_eq!(ErrorKind::LengthExceeded, err.kind(), "{:?}", long);
+
+            assert!(serde::de::IgnoredAny::deserialize(long.into_deserializer()).is_ok());
+        }
+
+        let err = Enum::deserialize(
+            Owned::buffer(Enum::Struct { a: 1, b: 2, c: 3 })
+                .unwrap()
+                .into_deserializer()
+                .max_len(2),
+        )
+        .unwrap_err();
+        assert_eq!(ErrorKind::LengthExceeded, err.kind());
+    }
+
+    #[test]
+    fn non_string_map_keys() {
+        use alloc::collections::BTreeMap;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Key {
+            a: u8,
+            b: Option<String>,
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        enum EnumKey {
+            A,
+            B(i16),
+        }
+
+        fn round_trip<T>(v: T)
+        where
+            T: serde::Serialize + for<'de> serde::Deserialize<'de> + PartialEq + fmt::Debug,
+        {
+            let owned = Owned::buffer(&v).unwrap();
+
+            assert_eq!(v, T::deserialize((&owned).into_deserializer()).unwrap());
+            assert_eq!(v, T::deserialize(owned.into_deserializer()).unwrap());
+        }
+
+        let ints: BTreeMap<u32, String> = [(1, String::from("a")), (300, String::from("b"))]
+            .into_iter()
+            .collect();
+        let buf = Owned::buffer(&ints).unwrap();
+        assert_eq!(Some(Ref::str("b")), buf.keyed_get(&Ref::u32(300)));
+        round_trip(ints);
+
+        round_trip::<BTreeMap<i8, bool>>([(-1, true), (1, false)].into_iter().collect());
+        round_trip::<BTreeMap<(u8, char), u8>>(
+            [((1, 'a'), 1), ((2, 'b'), 2)].into_iter().collect(),
+        );
+        round_trip::<BTreeMap<EnumKey, u8>>(
+            [(EnumKey::A, 1), (EnumKey::B(-1), 2)].into_iter().collect(),
+        );
+
+        let structs: BTreeMap<Key, u8> = [
+            (Key { a: 1, b: None }, 1),
+            (
+                Key {
+                    a: 2,
+                    b: Some(String::from("b")),
+                },
+                2,
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let buf = Owned::buffer(&structs).unwrap();
+        assert_eq!(
+            Some(Ref::u8(1)),
+            buf.keyed_get(&Ref::record_struct(
+                "Key",
+                [("a", Ref::u8(1)), ("b", Ref::none())]
+            ))
+        );
+        round_trip(structs);
+
+        // Maps built directly can have non-string keys too
+        let buf = Ref::map([(Ref::u64(1), Ref::str("a")), (Ref::u64(2), Ref::str("b"))]);
+        assert_eq!(
+            [(1u64, String::from("a")), (2, String::from("b"))]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+            BTreeMap::<u64, String>::deserialize((&buf).into_deserializer()).unwrap()
+        );
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Marker;
+
+    #[test]
+    fn unit_struct() {
+        let owned = Owned::buffer(Marker).unwrap();
+        assert_eq!(Ref::unit_struct("Marker"), owned.as_ref());
+
+        assert_eq!(
+            Marker,
+            Marker::deserialize((&owned).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Marker,
+            Marker::deserialize(owned.clone().into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Marker,
+            Marker::deserialize(
+                Ref::seq([owned.as_ref()])
+                    .get_index(0)
+                    .unwrap()
+                    .into_deserializer()
+            )
+            .unwrap()
+        );
+
+        assert!(Marker::deserialize(Ref::u8(1).into_deserializer()).is_err());
+    }
+
+    #[test]
+    fn newtype_struct() {
+        let owned = Owned::buffer(Newtype(-1)).unwrap();
+        assert_eq!(Ref::newtype_struct("Newtype", Ref::i64(-1)), owned.as_ref());
+
+        assert_eq!(
+            Newtype(-1),
+            Newtype::deserialize((&owned).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Newtype(-1),
+            Newtype::deserialize(owned.into_deserializer()).unwrap()
+        );
+
+        // Newtypes can also be deserialized from their inner value
+        assert_eq!(
+            Newtype(-1),
+            Newtype::deserialize(Ref::i64(-1).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Newtype(-1),
+            Newtype::deserialize((&Owned::from(-1i64)).into_deserializer()).unwrap()
+        );
+    }
+
+    #[test]
+    fn struct_name_mismatch() {
+        // Like other self-describing formats, buffered struct names aren't checked
+        let unit = Owned::unit_struct("Other");
+
+        assert_eq!(
+            Marker,
+            Marker::deserialize((&unit).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Marker,
+            Marker::deserialize(unit.into_deserializer()).unwrap()
+        );
+
+        let newtype = Owned::newtype_struct("Other", Owned::i64(-1));
+
+        assert_eq!(
+            Newtype(-1),
+            Newtype::deserialize((&newtype).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Newtype(-1),
+            Newtype::deserialize(newtype.into_deserializer()).unwrap()
+        );
+    }
+
+    #[test]
+    fn externally_tagged_enum() {
+        for (json, expected) in [
+            (r#""Unit""#, Enum::Unit),
+            (r#"{"Newtype":"a string"}"#, Enum::Newtype("a string")),
+            (r#"{"Tuple":[1,2]}"#, Enum::Tuple(1, 2)),
+            (r#"{"Struct":{"a":1}}"#, Enum::Struct { a: 1 }),
+        ] {
+            let owned =
+                Owned::buffer_from_deserializer(&mut serde_json::Deserializer::from_str(json))
+                    .unwrap();
+
+            assert_eq!(
+                expected,
+                Enum::deserialize((&owned).into_deserializer().coerce_numbers(true)).unwrap(),
+                "{}",
+                json
+            );
+        }
+
+        let owned = Owned::from(Ref::map([(
+            Ref::owned_str("Tuple"),
+            Ref::seq([Ref::i32(1), Ref::i32(2)]),
+        )]));
+        assert_eq!(
+            Enum::Tuple(1, 2),
+            Enum::deserialize(owned.clone().into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Enum::Tuple(1, 2),
+            Enum::deserialize(owned.as_ref().into_deserializer()).unwrap()
+        );
+
+        assert!(Enum::deserialize(
+            Ref::map([
+                (Ref::str("Unit"), Ref::unit()),
+                (Ref::str("Tuple"), Ref::unit()),
+            ])
+            .into_deserializer()
+        )
+        .is_err());
+        assert!(Enum::deserialize(Ref::str("Missing").into_deserializer()).is_err());
+    }
+
+    #[test]
+    fn flatten() {
+        use alloc::collections::BTreeMap;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Outer {
+            a: u8,
+            #[serde(flatten)]
+            inner: Inner,
+            #[serde(flatten)]
+            extra: BTreeMap<String, u8>,
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Inner {
+            b: String,
+            c: Option<(u8, u8)>,
+        }
+
+        let v = Outer {
+            a: 1,
+            inner: Inner {
+                b: String::from("b"),
+                c: Some((2, 3)),
+            },
+            extra: [(String::from("d"), 4)].into_iter().collect(),
+        };
+
+        let owned = Owned::buffer(&v).unwrap();
+
+        assert_eq!(v, Outer::deserialize((&owned).into_deserializer()).unwrap());
+        assert_eq!(v, Outer::deserialize(owned.into_deserializer()).unwrap());
+
+        // Buffers with flattened fields can also be built by hand
+        let mut built = Owned::from(Ref::record_struct("Outer", [("a", Ref::u8(1))]));
+        Owned::buffer(&v.inner)
+            .unwrap()
+            .flatten_into(&mut built)
+            .unwrap();
+        Owned::buffer(&v.extra)
+            .unwrap()
+            .flatten_into(&mut built)
+            .unwrap();
+
+        assert_eq!(
+            v,
+            Outer::deserialize(built.as_ref().into_deserializer()).unwrap()
+        );
+        assert_eq!(v, Outer::deserialize(built.into_deserializer()).unwrap());
+    }
+
+    #[test]
+    fn char_str() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Chars {
+            a: char,
+            b: String,
+        }
+
+        let owned = Owned::from(Ref::record_struct(
+            "Chars",
+            [("a", Ref::owned_str("é")), ("b", Ref::char('c'))],
+        ));
+
+        let expected = Chars {
+            a: 'é',
+            b: String::from("c"),
+        };
+
+        assert_eq!(
+            expected,
+            Chars::deserialize((&owned).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            expected,
+            Chars::deserialize(owned.into_deserializer()).unwrap()
+        );
+
+        for s in ["", "ab", "e\u{301}"] {
+            let err = char::deserialize(Ref::str(s).into_deserializer()).unwrap_err();
+
+            assert_eq!(ErrorKind::InvalidValue, err.kind(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn coerce_bytes() {
+        use serde_bytes::ByteBuf;
+
+        let seq = Owned::from(Ref::seq([Ref::u8(1), Ref::u8(2), Ref::u8(3)]));
+        let bytes = Owned::from(Ref::bytes(&[1, 2, 3]));
+
+        assert!(ByteBuf::deserialize(Ref::seq([Ref::str("a")]).into_deserializer()).is_err());
+        assert!(Vec::<u8>::deserialize((&bytes).into_deserializer()).is_err());
+
+        assert_eq!(
+            ByteBuf::from([1, 2, 3]),
+            ByteBuf::deserialize((&seq).into_deserializer().coerce_bytes(true)).unwrap()
+        );
+        assert_eq!(
+            ByteBuf::from([1, 2, 3]),
+            ByteBuf::deserialize(seq.clone().into_deserializer().coerce_bytes(true)).unwrap()
+        );
+        assert_eq!(
+            vec![1, 2, 3],
+            Vec::<u8>::deserialize((&bytes).into_deserializer().coerce_bytes(true)).unwrap()
+        );
+        assert_eq!(
+            vec![1, 2, 3],
+            Vec::<u8>::deserialize(bytes.clone().into_deserializer().coerce_bytes(true)).unwrap()
+        );
+
+        // Values that are already in the expected form are unaffected
+        assert_eq!(
+            vec![1, 2, 3],
+            Vec::<u8>::deserialize((&seq).into_deserializer().coerce_bytes(true)).unwrap()
+        );
+        assert_eq!(
+            ByteBuf::from([1, 2, 3]),
+            ByteBuf::deserialize(bytes.into_deserializer().coerce_bytes(true)).unwrap()
+        );
+
+        // Sequences of other integers are only bytes when numbers are coerced too
+        let wide = Owned::from(Ref::seq([Ref::u64(1), Ref::i32(255)]));
+
+        let err = ByteBuf::deserialize((&wide).into_deserializer().coerce_bytes(true)).unwrap_err();
+        assert_eq!(ErrorKind::InvalidType, err.kind());
+        assert_eq!(
+            ByteBuf::from([1, 255]),
+            ByteBuf::deserialize(
+                (&wide)
+                    .into_deserializer()
+                    .coerce_bytes(true)
+                    .coerce_numbers(true)
+            )
+            .unwrap()
+        );
+
+        for invalid in [
+            Ref::seq([Ref::u8(1), Ref::u16(256)]),
+            Ref::seq([Ref::i8(-1)]),
+            Ref::seq([Ref::str("a")]),
+        ] {
+            assert!(ByteBuf::deserialize(
+                invalid
+                    .into_deserializer()
+                    .coerce_bytes(true)
+                    .coerce_numbers(true)
+            )
+            .is_err());
+        }
+
+        let err = ByteBuf::deserialize(seq.into_deserializer().coerce_bytes(true).max_len(2))
+            .unwrap_err();
+        assert_eq!(ErrorKind::LengthExceeded, err.kind());
+    }
+}
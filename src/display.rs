@@ -0,0 +1,232 @@
+use core::fmt::{self, Write as _};
+
+use crate::{Name, Owned, Ref, Value};
+
+impl fmt::Display for Owned {
+    /**
+    Format the buffer in a readable, JSON-like notation.
+
+    Struct and enum names are included, strings and characters are escaped, and bytes are written
+    as hex. The alternate flag (`{:#}`) writes each nested value on its own indented line.
+    The output is meant for people to read, not as a format to parse.
+    */
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_value(&self.0, f, 0)
+    }
+}
+
+impl<'a> fmt::Display for Ref<'a> {
+    /**
+    Format the buffer in a readable, JSON-like notation.
+
+    This uses the same notation as [`Owned`].
+    */
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_value(&self.0, f, 0)
+    }
+}
+
+pub(crate) fn fmt_value(value: &Value, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+    match value {
+        Value::Unit => f.write_str("()"),
+        Value::U8(v) => fmt::Display::fmt(v, f),
+        Value::U16(v) => fmt::Display::fmt(v, f),
+        Value::U32(v) => fmt::Display::fmt(v, f),
+        Value::U64(v) => fmt::Display::fmt(v, f),
+        Value::U128(v) => fmt::Display::fmt(v, f),
+        Value::I8(v) => fmt::Display::fmt(v, f),
+        Value::I16(v) => fmt::Display::fmt(v, f),
+        Value::I32(v) => fmt::Display::fmt(v, f),
+        Value::I64(v) => fmt::Display::fmt(v, f),
+        Value::I128(v) => fmt::Display::fmt(v, f),
+        // `Debug` always includes a decimal point, so floats can be told apart from integers
+        Value::F32(v) => fmt::Debug::fmt(v, f),
+        Value::F64(v) => fmt::Debug::fmt(v, f),
+        Value::Bool(v) => fmt::Display::fmt(v, f),
+        Value::Char(v) => write!(f, "'{}'", v.escape_debug()),
+        Value::Str(v) => fmt_str(v, f),
+        Value::SharedStr(v) => fmt_str(v, f),
+        Value::BorrowedStr(v) => fmt_str(v, f),
+        Value::Bytes(v) => fmt_bytes(v, f),
+        Value::BorrowedBytes(v) => fmt_bytes(v, f),
+        #[cfg(feature = "bytes")]
+        Value::SharedBytes(v) => fmt_bytes(v, f),
+        #[cfg(feature = "json")]
+        Value::RawJson(v) => f.write_str(v),
+        Value::None => f.write_str("None"),
+        Value::Some(v) => {
+            f.write_str("Some")?;
+            fmt_fields(f, depth, "(", ")", [&**v], fmt_value)
+        }
+        Value::UnitStruct(v) => f.write_str(&v.name),
+        Value::NewtypeStruct(v) => {
+            f.write_str(&v.name)?;
+            fmt_fields(f, depth, "(", ")", [&v.value], fmt_value)
+        }
+        Value::Struct(v) => {
+            write!(f, "{} ", v.name)?;
+            fmt_fields(f, depth, "{", "}", v.fields.iter(), fmt_field)
+        }
+        Value::Tuple(fields) => fmt_fields(f, depth, "(", ")", fields.iter(), fmt_value),
+        Value::TupleStruct(v) => {
+            f.write_str(&v.name)?;
+            fmt_fields(f, depth, "(", ")", v.fields.iter(), fmt_value)
+        }
+        Value::UnitVariant(v) => write!(f, "{}::{}", v.name, v.variant),
+        Value::NewtypeVariant(v) => {
+            write!(f, "{}::{}", v.name, v.variant)?;
+            fmt_fields(f, depth, "(", ")", [&v.value], fmt_value)
+        }
+        Value::TupleVariant(v) => {
+            write!(f, "{}::{}", v.name, v.variant)?;
+            fmt_fields(f, depth, "(", ")", v.fields.iter(), fmt_value)
+        }
+        Value::StructVariant(v) => {
+            write!(f, "{}::{} ", v.name, v.variant)?;
+            fmt_fields(f, depth, "{", "}", v.fields.iter(), fmt_field)
+        }
+        Value::Seq(fields, _) => fmt_fields(f, depth, "[", "]", fields.iter(), fmt_value),
+        Value::Map(fields, _) => fmt_fields(f, depth, "{", "}", fields.iter(), fmt_entry),
+        Value::Borrowed(v) => fmt_value(v, f, depth),
+    }
+}
+
+fn fmt_str(v: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "\"{}\"", v.escape_debug())
+}
+
+fn fmt_bytes(v: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("0x")?;
+
+    for b in v {
+        write!(f, "{:02x}", b)?;
+    }
+
+    Ok(())
+}
+
+fn fmt_field((k, v): &(Name, Value), f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+    write!(f, "{}: ", k)?;
+    fmt_value(v, f, depth)
+}
+
+fn fmt_entry((k, v): &(Value, Value), f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+    fmt_value(k, f, depth)?;
+    f.write_str(": ")?;
+    fmt_value(v, f, depth)
+}
+
+/**
+Write a delimited list of fields.
+
+In alternate mode each field is written on its own line, indented one level deeper than `depth`.
+*/
+fn fmt_fields<T>(
+    f: &mut fmt::Formatter,
+    depth: usize,
+    open: &str,
+    close: &str,
+    fields: impl IntoIterator<Item = T>,
+    mut fmt_field: impl FnMut(T, &mut fmt::Formatter, usize) -> fmt::Result,
+) -> fmt::Result {
+    f.write_str(open)?;
+
+    let mut empty = true;
+    for field in fields {
+        if f.alternate() {
+            f.write_char('\n')?;
+            fmt_indent(f, depth + 1)?;
+        } else if !empty {
+            f.write_str(", ")?;
+        }
+
+        fmt_field(field, f, depth + 1)?;
+
+        if f.alternate() {
+            f.write_char(',')?;
+        }
+
+        empty = false;
+    }
+
+    if f.alternate() && !empty {
+        f.write_char('\n')?;
+        fmt_indent(f, depth)?;
+    }
+
+    f.write_str(close)
+}
+
+fn fmt_indent(f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str("  ")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{collections::BTreeMap, format, string::String, vec, vec::Vec};
+    use serde_derive::Serialize;
+
+    #[derive(Serialize)]
+    enum Enum {
+        Unit,
+        Newtype(i32),
+        Struct { a: Option<f64> },
+    }
+
+    #[derive(Serialize)]
+    struct Data {
+        a: String,
+        b: Vec<Enum>,
+        c: BTreeMap<char, (u8, ())>,
+        d: Vec<u8>,
+    }
+
+    #[test]
+    fn display() {
+        let owned = Owned::buffer(Data {
+            a: "a \"string\"\n".into(),
+            b: vec![Enum::Unit, Enum::Newtype(-1), Enum::Struct { a: Some(1.0) }],
+            c: BTreeMap::from([('a', (1, ()))]),
+            d: vec![],
+        })
+        .unwrap();
+
+        assert_eq!(
+            r#"Data {a: "a \"string\"\n", b: [Enum::Unit, Enum::Newtype(-1), Enum::Struct {a: Some(1.0)}], c: {'a': (1, ())}, d: []}"#,
+            format!("{}", owned)
+        );
+
+        assert_eq!(
+            r#"Data {
+  a: "a \"string\"\n",
+  b: [
+    Enum::Unit,
+    Enum::Newtype(
+      -1,
+    ),
+    Enum::Struct {
+      a: Some(
+        1.0,
+      ),
+    },
+  ],
+  c: {
+    'a': (
+      1,
+      (),
+    ),
+  },
+  d: [],
+}"#,
+            format!("{:#}", owned.as_ref())
+        );
+
+        assert_eq!("0x00ff10", format!("{}", Ref::bytes(&[0, 255, 16])));
+    }
+}
@@ -0,0 +1,202 @@
+use core::mem;
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{Name, Owned, Ref, Value};
+
+impl<'a> Drop for Value<'a> {
+    /**
+    Drop a value without recursing through the values nested within it.
+
+    This applies to every value, not just the ones held by a buffer, so values owned by
+    deserializers or moved out of buffers can be dropped however deeply they're nested.
+    */
+    fn drop(&mut self) {
+        // Values with no nested collections are dropped directly, which can only recurse one level
+        if self.has_nested_children() {
+            mem::replace(self, Value::Unit).dismantle();
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    Take this value, leaving a `()` in its place.
+
+    Values can't be destructured because they implement `Drop`, so the values nested within
+    them are taken out instead.
+    */
+    pub(crate) fn take(&mut self) -> Self {
+        mem::replace(self, Value::Unit)
+    }
+}
+
+impl Owned {
+    /**
+    Take the value out of this buffer.
+    */
+    pub(crate) fn into_value(self) -> Value<'static> {
+        self.0
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Take the value out of this buffer.
+    */
+    pub(crate) fn into_value(self) -> Value<'a> {
+        self.0
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    Drop a value without recursing through the values nested within it.
+
+    Nested values are moved out of their parents onto a stack, so each value is dropped once it
+    has no nested values of its own. This means dropping deeply nested buffers can't overflow
+    the stack.
+    */
+    fn dismantle(self) {
+        let mut stack = Vec::new();
+        let mut next = Some(self);
+
+        while let Some(mut value) = next.take().or_else(|| stack.pop()) {
+            match value {
+                Value::Some(ref mut v) => {
+                    next = Some(mem::replace(&mut **v, Value::Unit));
+                }
+                Value::NewtypeStruct(ref mut v) => {
+                    next = Some(mem::replace(&mut v.value, Value::Unit));
+                }
+                Value::NewtypeVariant(ref mut v) => {
+                    next = Some(mem::replace(&mut v.value, Value::Unit));
+                }
+                Value::Seq(ref mut fields, _) | Value::Tuple(ref mut fields) => {
+                    stack.extend(nested(mem::take(fields)));
+                }
+                Value::TupleStruct(ref mut v) => {
+                    stack.extend(nested(mem::take(&mut v.fields)));
+                }
+                Value::TupleVariant(ref mut v) => {
+                    stack.extend(nested(mem::take(&mut v.fields)));
+                }
+                Value::Struct(ref mut v) => {
+                    stack.extend(nested_fields(mem::take(&mut v.fields)));
+                }
+                Value::StructVariant(ref mut v) => {
+                    stack.extend(nested_fields(mem::take(&mut v.fields)));
+                }
+                Value::Map(ref mut fields, _) => {
+                    for (k, v) in mem::take(fields).into_vec() {
+                        stack.extend([k, v].into_iter().filter(Value::is_nested));
+                    }
+                }
+                _ => (),
+            }
+
+            // `value` no longer contains any nested collections, so dropping it won't recurse
+        }
+    }
+
+    /**
+    Whether any of the values this value owns themselves own other values.
+    */
+    fn has_nested_children(&self) -> bool {
+        match self {
+            Value::Some(v) => v.is_nested(),
+            Value::NewtypeStruct(v) => v.value.is_nested(),
+            Value::NewtypeVariant(v) => v.value.is_nested(),
+            Value::Seq(fields, _) | Value::Tuple(fields) => fields.iter().any(Value::is_nested),
+            Value::TupleStruct(v) => v.fields.iter().any(Value::is_nested),
+            Value::TupleVariant(v) => v.fields.iter().any(Value::is_nested),
+            Value::Struct(v) => v.fields.iter().any(|(_, v)| v.is_nested()),
+            Value::StructVariant(v) => v.fields.iter().any(|(_, v)| v.is_nested()),
+            Value::Map(fields, _) => fields.iter().any(|(k, v)| k.is_nested() || v.is_nested()),
+            _ => false,
+        }
+    }
+
+    /**
+    Whether this value owns any other values.
+    */
+    fn is_nested(&self) -> bool {
+        match self {
+            Value::Some(_) | Value::NewtypeStruct(_) | Value::NewtypeVariant(_) => true,
+            Value::Seq(fields, _) | Value::Tuple(fields) => !fields.is_empty(),
+            Value::TupleStruct(v) => !v.fields.is_empty(),
+            Value::TupleVariant(v) => !v.fields.is_empty(),
+            Value::Struct(v) => !v.fields.is_empty(),
+            Value::StructVariant(v) => !v.fields.is_empty(),
+            Value::Map(fields, _) => !fields.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+fn nested<'a>(fields: Box<[Value<'a>]>) -> impl Iterator<Item = Value<'a>> {
+    fields.into_vec().into_iter().filter(Value::is_nested)
+}
+
+fn nested_fields<'a>(fields: Box<[(Name<'a>, Value<'a>)]>) -> impl Iterator<Item = Value<'a>> {
+    fields
+        .into_vec()
+        .into_iter()
+        .map(|(_, v)| v)
+        .filter(Value::is_nested)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_deeply_nested() {
+        let mut deep = Ref::unit();
+        for i in 0..200_000 {
+            deep = match i % 4 {
+                0 => Ref::some(deep),
+                1 => Ref::newtype_struct("Newtype", deep),
+                2 => Ref::seq([Ref::u8(1), deep]),
+                _ => Ref::map([(Ref::str("a"), deep)]),
+            };
+        }
+
+        drop(deep);
+    }
+
+    fn deep_some(depth: usize) -> Owned {
+        let mut deep = Owned::unit();
+        for _ in 0..depth {
+            deep = Owned::some(deep);
+        }
+
+        deep
+    }
+
+    #[test]
+    fn drop_deeply_nested_deserializer() {
+        use serde::de::IntoDeserializer;
+
+        drop(deep_some(1_000_000).into_deserializer());
+    }
+
+    #[test]
+    fn drop_deeply_nested_partially_deserialized() {
+        use serde::de::{Deserialize, Deserializer, IntoDeserializer};
+
+        // Drops the deserializer for the rest of the value without visiting it
+        struct Skip;
+
+        impl<'de> Deserialize<'de> for Skip {
+            fn deserialize<D: Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+                Ok(Skip)
+            }
+        }
+
+        let skipped =
+            Option::<Skip>::deserialize(deep_some(1_000_000).into_deserializer()).unwrap();
+
+        assert!(skipped.is_some());
+    }
+}
@@ -0,0 +1,743 @@
+use core::{mem, ops::IndexMut};
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    access::{missing_field, missing_index},
+    Error, ErrorKind, Name, Owned, Ref, Value,
+};
+
+impl Owned {
+    /**
+    Get a mutable reference to a field of a struct or map buffer by name.
+
+    Fields are matched in the same way as [`Owned::get`].
+    This method returns `None` if the buffer doesn't contain a struct or map, or if there's no matching field.
+    */
+    pub fn get_mut(&mut self, field: &str) -> Option<&mut Owned> {
+        self.0.get_mut(field).map(Owned::from_value_mut)
+    }
+
+    /**
+    Get a mutable reference to an element of a sequence or tuple buffer by its index.
+
+    Elements are indexed in the same way as [`Owned::get_index`].
+    This method returns `None` if the buffer doesn't contain an indexable collection, or if `index` is out of range.
+    */
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Owned> {
+        self.0.get_index_mut(index).map(Owned::from_value_mut)
+    }
+
+    /**
+    Set the value of a field in a struct or map buffer, returning its previous value.
+
+    If the buffer doesn't have a matching field then a new one is added after the existing ones.
+    Maps use `field` as a string key.
+    This method returns an error if the buffer doesn't contain a struct or map.
+    */
+    pub fn set_field(
+        &mut self,
+        field: impl Into<Name<'static>>,
+        value: impl Into<Owned>,
+    ) -> Result<Option<Owned>, Error> {
+        Ok(self
+            .0
+            .set_field(field.into(), value.into().into_value())?
+            .map(Owned))
+    }
+
+    /**
+    Remove a field from a struct or map buffer, returning its value.
+
+    Fields are matched in the same way as [`Owned::get`], and the order of remaining fields is preserved.
+    This method returns `None` if the buffer doesn't contain a struct or map, or if there's no matching field.
+    */
+    pub fn remove_field(&mut self, field: &str) -> Option<Owned> {
+        self.0.remove_field(field).map(Owned)
+    }
+
+    /**
+    Add an element to the end of a sequence buffer.
+
+    Sequences are stored without any spare capacity, so each push reallocates the sequence and
+    copies its elements. Use [`Owned::extend`] to add many elements at once.
+    This method returns an error if the buffer doesn't contain a sequence.
+    */
+    pub fn push(&mut self, value: impl Into<Owned>) -> Result<(), Error> {
+        self.0.push(value.into().into_value())
+    }
+
+    /**
+    Add elements to the end of a sequence buffer.
+
+    The sequence is only reallocated once, however many elements are added.
+    This method returns an error if the buffer doesn't contain a sequence.
+    */
+    pub fn extend<T: Into<Owned>>(
+        &mut self,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<(), Error> {
+        self.0
+            .extend(values.into_iter().map(|v| v.into().into_value()))
+    }
+
+    /**
+    Remove the last element of a sequence buffer, returning it.
+
+    This method returns `None` if the buffer doesn't contain a sequence, or if the sequence is empty.
+    */
+    pub fn pop(&mut self) -> Option<Owned> {
+        self.0.pop().map(Owned)
+    }
+
+    /**
+    Fold the fields of a struct or map buffer into another struct or map buffer.
+
+    This is what `#[serde(flatten)]` does to the fields of a nested struct when it's serialized.
+    The fields of `self` are added after the existing fields of `other`, in order.
+    If `self` is a map then its keys must be strings. A unit or `None` buffer has no fields to add.
+
+    This method returns an error if either buffer isn't a struct or map, or if `other` already
+    has a field with the same name as one in `self`. If an error is returned then `other` is unchanged.
+    */
+    pub fn flatten_into(self, other: &mut Owned) -> Result<(), Error> {
+        self.into_value().flatten_into(&mut other.0)
+    }
+
+    pub(crate) fn from_value_mut<'b>(value: &'b mut Value<'static>) -> &'b mut Owned {
+        // SAFETY: `Owned` is a `#[repr(transparent)]` wrapper around `Value<'static>`
+        unsafe { &mut *(value as *mut Value<'static> as *mut Owned) }
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Get a mutable reference to a field of a struct or map buffer by name.
+
+    See [`Owned::get_mut`] for details. Values borrowed from other buffers are cloned
+    before they're returned so they can be changed.
+    */
+    pub fn get_mut(&mut self, field: &str) -> Option<&mut Ref<'a>> {
+        self.0.get_mut(field).map(Ref::from_value_mut)
+    }
+
+    /**
+    Get a mutable reference to an element of a sequence or tuple buffer by its index.
+
+    See [`Owned::get_index_mut`] for details. Values borrowed from other buffers are cloned
+    before they're returned so they can be changed.
+    */
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Ref<'a>> {
+        self.0.get_index_mut(index).map(Ref::from_value_mut)
+    }
+
+    /**
+    Set the value of a field in a struct or map buffer, returning its previous value.
+
+    See [`Owned::set_field`] for details.
+    */
+    pub fn set_field(
+        &mut self,
+        field: impl Into<Name<'a>>,
+        value: impl Into<Ref<'a>>,
+    ) -> Result<Option<Ref<'a>>, Error> {
+        Ok(self
+            .0
+            .set_field(field.into(), value.into().into_value())?
+            .map(Ref))
+    }
+
+    /**
+    Remove a field from a struct or map buffer, returning its value.
+
+    See [`Owned::remove_field`] for details.
+    */
+    pub fn remove_field(&mut self, field: &str) -> Option<Ref<'a>> {
+        self.0.remove_field(field).map(Ref)
+    }
+
+    /**
+    Add an element to the end of a sequence buffer.
+
+    See [`Owned::push`] for details.
+    */
+    pub fn push(&mut self, value: impl Into<Ref<'a>>) -> Result<(), Error> {
+        self.0.push(value.into().into_value())
+    }
+
+    /**
+    Add elements to the end of a sequence buffer.
+
+    See [`Owned::extend`] for details.
+    */
+    pub fn extend<T: Into<Ref<'a>>>(
+        &mut self,
+        values: impl IntoIterator<Item = T>,
+    ) -> Result<(), Error> {
+        self.0
+            .extend(values.into_iter().map(|v| v.into().into_value()))
+    }
+
+    /**
+    Remove the last element of a sequence buffer, returning it.
+
+    See [`Owned::pop`] for details.
+    */
+    pub fn pop(&mut self) -> Option<Ref<'a>> {
+        self.0.pop().map(Ref)
+    }
+
+    /**
+    Fold the fields of a struct or map buffer into another struct or map buffer.
+
+    See [`Owned::flatten_into`] for details.
+    */
+    pub fn flatten_into(self, other: &mut Ref<'a>) -> Result<(), Error> {
+        self.into_value().flatten_into(&mut other.0)
+    }
+
+    pub(crate) fn from_value_mut<'b>(value: &'b mut Value<'a>) -> &'b mut Ref<'a> {
+        // SAFETY: `Ref` is a `#[repr(transparent)]` wrapper around `Value`
+        unsafe { &mut *(value as *mut Value<'a> as *mut Ref<'a>) }
+    }
+}
+
+impl IndexMut<&str> for Owned {
+    /**
+    Get a mutable reference to a field of a struct or map buffer by name.
+
+    Fields are matched in the same way as [`Owned::get`].
+
+    # Panics
+
+    This method panics if the buffer doesn't contain a struct or map, or if there's no matching field.
+    Use [`Owned::get_mut`] to get a field without panicking, or [`Owned::set_field`] to add one.
+    */
+    fn index_mut(&mut self, field: &str) -> &mut Owned {
+        if self.0.get(field).is_none() {
+            missing_field(&self.0, field);
+        }
+
+        Owned::from_value_mut(self.0.get_mut(field).expect("missing field"))
+    }
+}
+
+impl IndexMut<usize> for Owned {
+    /**
+    Get a mutable reference to an element of a sequence or tuple buffer by its index.
+
+    Elements are indexed in the same way as [`Owned::get_index`].
+
+    # Panics
+
+    This method panics if the buffer doesn't contain an indexable collection, or if `index` is out of range.
+    Use [`Owned::get_index_mut`] to get an element without panicking.
+    */
+    fn index_mut(&mut self, index: usize) -> &mut Owned {
+        if self.0.get_index(index).is_none() {
+            missing_index(&self.0, index);
+        }
+
+        Owned::from_value_mut(self.0.get_index_mut(index).expect("missing element"))
+    }
+}
+
+impl<'a> IndexMut<&str> for Ref<'a> {
+    /**
+    Get a mutable reference to a field of a struct or map buffer by name.
+
+    This follows the same rules as indexing an [`Owned`] buffer by field name. Values borrowed from
+    other buffers are cloned before they're returned so they can be changed.
+    */
+    fn index_mut(&mut self, field: &str) -> &mut Ref<'a> {
+        if self.0.get(field).is_none() {
+            missing_field(&self.0, field);
+        }
+
+        Ref::from_value_mut(self.0.get_mut(field).expect("missing field"))
+    }
+}
+
+impl<'a> IndexMut<usize> for Ref<'a> {
+    /**
+    Get a mutable reference to an element of a sequence or tuple buffer by its index.
+
+    This follows the same rules as indexing an [`Owned`] buffer by position. Values borrowed from
+    other buffers are cloned before they're returned so they can be changed.
+    */
+    fn index_mut(&mut self, index: usize) -> &mut Ref<'a> {
+        if self.0.get_index(index).is_none() {
+            missing_index(&self.0, index);
+        }
+
+        Ref::from_value_mut(self.0.get_index_mut(index).expect("missing element"))
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    Get this value so it can be changed.
+
+    If this value is borrowed then it's replaced with a clone of what it points to.
+    */
+    fn make_mut(&mut self) -> &mut Value<'a> {
+        if let Value::Borrowed(v) = self {
+            *self = v.resolve().clone();
+        }
+
+        self
+    }
+
+    pub(crate) fn get_mut(&mut self, field: &str) -> Option<&mut Value<'a>> {
+        // Only clone a borrowed value if there's a field to change
+        if let Value::Borrowed(v) = self {
+            v.get(field)?;
+        }
+
+        match self.make_mut() {
+            Value::Map(fields, _) => fields
+                .iter_mut()
+                .find(|(k, _)| k.as_str() == Some(field))
+                .map(|(_, v)| v),
+            Value::Struct(v) => v
+                .fields
+                .iter_mut()
+                .find(|(k, _)| *k == field)
+                .map(|(_, v)| v),
+            Value::StructVariant(v) => v
+                .fields
+                .iter_mut()
+                .find(|(k, _)| *k == field)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_index_mut(&mut self, index: usize) -> Option<&mut Value<'a>> {
+        if let Value::Borrowed(v) = self {
+            v.get_index(index)?;
+        }
+
+        match self.make_mut() {
+            Value::Seq(fields, _) | Value::Tuple(fields) => fields.get_mut(index),
+            Value::TupleStruct(v) => v.fields.get_mut(index),
+            Value::TupleVariant(v) => v.fields.get_mut(index),
+            _ => None,
+        }
+    }
+
+    fn set_field(&mut self, field: Name<'a>, value: Value<'a>) -> Result<Option<Value<'a>>, Error> {
+        if let Some(existing) = self.get_mut(&field) {
+            return Ok(Some(mem::replace(existing, value)));
+        }
+
+        match self.make_mut() {
+            Value::Map(fields, _) => {
+                push_boxed(fields, (field.into_value(), value));
+            }
+            Value::Struct(v) => {
+                push_boxed(&mut v.fields, (field, value));
+            }
+            Value::StructVariant(v) => {
+                push_boxed(&mut v.fields, (field, value));
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidType,
+                    "the buffer isn't a struct or map",
+                ))
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn remove_field(&mut self, field: &str) -> Option<Value<'a>> {
+        if let Value::Borrowed(v) = self {
+            v.get(field)?;
+        }
+
+        match self.make_mut() {
+            Value::Map(fields, _) => {
+                let i = fields.iter().position(|(k, _)| k.as_str() == Some(field))?;
+
+                Some(remove_boxed(fields, i).1)
+            }
+            Value::Struct(v) => {
+                let i = v.fields.iter().position(|(k, _)| *k == field)?;
+
+                Some(remove_boxed(&mut v.fields, i).1)
+            }
+            Value::StructVariant(v) => {
+                let i = v.fields.iter().position(|(k, _)| *k == field)?;
+
+                Some(remove_boxed(&mut v.fields, i).1)
+            }
+            _ => None,
+        }
+    }
+
+    fn flatten_into(mut self, target: &mut Value<'a>) -> Result<(), Error> {
+        let fields = match self {
+            Value::Borrowed(v) => return v.resolve().clone().flatten_into(target),
+            Value::Struct(ref mut v) => mem::take(&mut v.fields).into_vec(),
+            Value::Map(ref mut fields, _) => mem::take(fields)
+                .into_vec()
+                .into_iter()
+                .map(|(k, v)| {
+                    let k = Name::from_value(k).map_err(|_| {
+                        Error::new(
+                            ErrorKind::UnsupportedMapKey,
+                            "only maps with string keys can be flattened",
+                        )
+                    })?;
+
+                    Ok((k, v))
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+            Value::Unit | Value::None => Vec::new(),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidType,
+                    "the buffer isn't a struct or map",
+                ))
+            }
+        };
+
+        let target = target.make_mut();
+
+        if !matches!(target, Value::Map(..) | Value::Struct(_)) {
+            return Err(Error::new(
+                ErrorKind::InvalidType,
+                "the buffer to flatten into isn't a struct or map",
+            ));
+        }
+
+        // Check every field before adding any so the target is unchanged on failure
+        if let Some((k, _)) = fields.iter().find(|(k, _)| target.get(k).is_some()) {
+            return Err(Error::new(
+                ErrorKind::DuplicateKey,
+                format_args!("duplicate key {}", k),
+            ));
+        }
+
+        match target {
+            Value::Map(entries, _) => extend_boxed(
+                entries,
+                fields.into_iter().map(|(k, v)| (k.into_value(), v)),
+            ),
+            Value::Struct(v) => extend_boxed(&mut v.fields, fields),
+            _ => unreachable!("only maps and structs are flattened into"),
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, value: Value<'a>) -> Result<(), Error> {
+        self.extend([value])
+    }
+
+    fn extend(&mut self, values: impl IntoIterator<Item = Value<'a>>) -> Result<(), Error> {
+        if !matches!(self.resolve(), Value::Seq(..)) {
+            return Err(Error::new(
+                ErrorKind::InvalidType,
+                "the buffer isn't a sequence",
+            ));
+        }
+
+        match self.make_mut() {
+            Value::Seq(fields, _) => {
+                extend_boxed(fields, values);
+
+                Ok(())
+            }
+            _ => unreachable!("only sequences are extended"),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Value<'a>> {
+        if !matches!(self.resolve(), Value::Seq(fields, _) if !fields.is_empty()) {
+            return None;
+        }
+
+        match self.make_mut() {
+            Value::Seq(fields, _) if !fields.is_empty() => {
+                Some(remove_boxed(fields, fields.len() - 1))
+            }
+            _ => None,
+        }
+    }
+}
+
+/**
+Add an element to the end of a boxed slice.
+
+Buffers store their collections as boxed slices, so this reallocates them.
+*/
+fn push_boxed<T>(slice: &mut Box<[T]>, value: T) {
+    let mut vec = Vec::from(mem::take(slice));
+    vec.push(value);
+
+    *slice = vec.into_boxed_slice();
+}
+
+/**
+Add elements to the end of a boxed slice.
+*/
+fn extend_boxed<T>(slice: &mut Box<[T]>, values: impl IntoIterator<Item = T>) {
+    let mut vec = Vec::from(mem::take(slice));
+    vec.extend(values);
+
+    *slice = vec.into_boxed_slice();
+}
+
+/**
+Remove an element from a boxed slice, preserving the order of the remaining elements.
+*/
+fn remove_boxed<T>(slice: &mut Box<[T]>, index: usize) -> T {
+    let mut vec = Vec::from(mem::take(slice));
+    let value = vec.remove(index);
+
+    *slice = vec.into_boxed_slice();
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_mut() {
+        let mut buf = Owned::from(Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::u8(1)),
+                ("b", Ref::map([(Ref::str("c"), Ref::seq([Ref::u8(2)]))])),
+            ],
+        ));
+
+        *buf.get_mut("a").unwrap() = Owned::from(true);
+        *buf.get_mut("b")
+            .unwrap()
+            .get_mut("c")
+            .unwrap()
+            .get_index_mut(0)
+            .unwrap() = Owned::from("d");
+
+        assert!(buf.get_mut("z").is_none());
+        assert!(buf.get_index_mut(0).is_none());
+
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "Struct",
+                [
+                    ("a", Ref::bool(true)),
+                    ("b", Ref::map([(Ref::str("c"), Ref::seq([Ref::str("d")]))])),
+                ],
+            )),
+            buf
+        );
+    }
+
+    #[test]
+    fn get_mut_borrowed() {
+        let owned = Owned::from(Ref::map([(Ref::str("a"), Ref::u8(1))]));
+
+        let mut buf = owned.as_ref();
+        *buf.get_mut("a").unwrap() = Ref::u8(2);
+
+        assert_eq!(Ref::map([(Ref::str("a"), Ref::u8(2))]), buf);
+        assert_eq!(Some(1), owned.get("a").and_then(|v| v.as_u128()));
+
+        // Borrowed values aren't cloned unless there's something to change
+        let mut buf = owned.as_ref();
+
+        assert!(buf.get_mut("z").is_none());
+        assert!(buf.get_index_mut(0).is_none());
+        assert!(buf.remove_field("z").is_none());
+        assert!(buf.pop().is_none());
+        assert!(buf.push(Ref::u8(1)).is_err());
+        assert!(matches!(buf.0, Value::Borrowed(_)));
+
+        buf.set_field("b", Ref::u8(2)).unwrap();
+        assert_eq!(
+            Ref::map([(Ref::str("a"), Ref::u8(1)), (Ref::str("b"), Ref::u8(2))]),
+            buf
+        );
+    }
+
+    #[test]
+    fn set_remove_field() {
+        let mut buf = Owned::from(Ref::record_struct("Struct", [("a", Ref::u8(1))]));
+
+        assert_eq!(
+            Some(Owned::from(1u8)),
+            buf.set_field("a", Owned::from(2u8)).unwrap()
+        );
+        assert_eq!(None, buf.set_field("b", Owned::from(3u8)).unwrap());
+        assert_eq!(Some(Owned::from(2u8)), buf.remove_field("a"));
+        assert_eq!(None, buf.remove_field("a"));
+
+        assert_eq!(
+            Owned::from(Ref::record_struct("Struct", [("b", Ref::u8(3))])),
+            buf
+        );
+
+        let mut buf = Ref::map([(Ref::str("a"), Ref::u8(1))]);
+
+        buf.set_field("b", Ref::u8(2)).unwrap();
+        assert_eq!(Some(Ref::u8(1)), buf.remove_field("a"));
+
+        assert_eq!(Ref::map([(Ref::str("b"), Ref::u8(2))]), buf);
+
+        assert_eq!(
+            ErrorKind::InvalidType,
+            Ref::u8(1).set_field("a", Ref::u8(1)).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut buf = Owned::from(Ref::seq([Ref::u8(1)]));
+
+        buf.push(2u8).unwrap();
+        assert_eq!(Owned::from(Ref::seq([Ref::u8(1), Ref::u8(2)])), buf);
+
+        assert_eq!(Some(Owned::from(2u8)), buf.pop());
+        assert_eq!(Some(Owned::from(1u8)), buf.pop());
+        assert_eq!(None, buf.pop());
+
+        assert_eq!(
+            ErrorKind::InvalidType,
+            Owned::from(1u8).push(1u8).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn extend() {
+        let mut buf = Owned::from(Ref::seq([Ref::u8(1)]));
+
+        buf.extend([2u8, 3]).unwrap();
+        assert_eq!(
+            Owned::from(Ref::seq([Ref::u8(1), Ref::u8(2), Ref::u8(3)])),
+            buf
+        );
+
+        let mut borrowed = buf.as_ref();
+
+        borrowed.extend([Ref::u8(4)]).unwrap();
+        assert_eq!(Some(4), borrowed.len());
+        assert_eq!(Some(3), buf.len());
+
+        assert_eq!(
+            ErrorKind::InvalidType,
+            Owned::from(1u8).extend([1u8]).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn index_mut() {
+        let mut buf = Owned::from(Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::seq([Ref::u8(1), Ref::u8(2)])),
+                ("b", Ref::map([(Ref::str("c"), Ref::unit())])),
+            ],
+        ));
+
+        buf["a"][1] = Owned::from(3u8);
+        buf["b"]["c"] = Owned::from("d");
+
+        assert_eq!(3u64, buf["a"][1]);
+        assert_eq!("d", buf["b"]["c"]);
+
+        let mut borrowed = Ref::seq([buf.as_ref()]);
+        borrowed[0]["a"][0] = Ref::u8(4);
+
+        assert_eq!(4u64, borrowed[0]["a"][0]);
+        assert_eq!(1u64, buf["a"][0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no field named \"c\" in the buffer")]
+    fn index_mut_missing_field() {
+        let mut buf = Owned::from(Ref::record_struct("Struct", [("a", Ref::unit())]));
+
+        buf["c"] = Owned::unit();
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 is out of range for a buffer of length 1")]
+    fn index_mut_missing_index() {
+        let mut buf = Owned::from(Ref::seq([Ref::unit()]));
+
+        buf[1] = Owned::unit();
+    }
+
+    #[test]
+    fn flatten_into() {
+        let mut buf = Owned::from(Ref::record_struct("Outer", [("a", Ref::u8(1))]));
+
+        Owned::from(Ref::record_struct(
+            "Inner",
+            [("b", Ref::u8(2)), ("c", Ref::u8(3))],
+        ))
+        .flatten_into(&mut buf)
+        .unwrap();
+        Owned::from(Ref::map([(Ref::str("d"), Ref::u8(4))]))
+            .flatten_into(&mut buf)
+            .unwrap();
+        Owned::none().flatten_into(&mut buf).unwrap();
+
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "Outer",
+                [
+                    ("a", Ref::u8(1)),
+                    ("b", Ref::u8(2)),
+                    ("c", Ref::u8(3)),
+                    ("d", Ref::u8(4)),
+                ],
+            )),
+            buf
+        );
+
+        let mut map = Ref::map([]);
+        buf.as_ref().flatten_into(&mut map).unwrap();
+
+        assert_eq!(Some(4), map.len());
+        assert_eq!(2u64, map["b"]);
+    }
+
+    #[test]
+    fn flatten_into_invalid() {
+        let mut buf = Owned::from(Ref::record_struct("Outer", [("a", Ref::u8(1))]));
+
+        let err = Owned::from(Ref::map([
+            (Ref::str("b"), Ref::u8(2)),
+            (Ref::str("a"), Ref::u8(3)),
+        ]))
+        .flatten_into(&mut buf)
+        .unwrap_err();
+        assert_eq!(ErrorKind::DuplicateKey, err.kind());
+
+        let err = Owned::from(Ref::map([(Ref::u8(1), Ref::u8(2))]))
+            .flatten_into(&mut buf)
+            .unwrap_err();
+        assert_eq!(ErrorKind::UnsupportedMapKey, err.kind());
+
+        let err = Owned::from(1u8).flatten_into(&mut buf).unwrap_err();
+        assert_eq!(ErrorKind::InvalidType, err.kind());
+
+        let err = Owned::from(Ref::record_struct("Inner", [("b", Ref::u8(2))]))
+            .flatten_into(&mut Owned::from(Ref::seq([])))
+            .unwrap_err();
+        assert_eq!(ErrorKind::InvalidType, err.kind());
+
+        assert_eq!(
+            Owned::from(Ref::record_struct("Outer", [("a", Ref::u8(1))])),
+            buf
+        );
+    }
+}
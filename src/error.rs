@@ -0,0 +1,275 @@
+use core::fmt;
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
+
+/**
+An error encountered while buffering a value.
+*/
+pub struct Error(Box<Inner>);
+
+/**
+The details of an [`Error`].
+
+These are boxed so results that may contain an error stay small when they're returned through
+each level of a deeply nested value.
+*/
+struct Inner {
+    kind: ErrorKind,
+    message: String,
+    path: Option<String>,
+    #[cfg(feature = "std")]
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+/**
+The reason an [`Error`] was produced.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /**
+    A custom error, like one produced by a [`serde::Serialize`] or [`serde::de::Visitor`] implementation.
+    */
+    Message,
+    /**
+    A map value was buffered without a key.
+    */
+    MissingMapKey,
+    /**
+    A map key was buffered without a value.
+    */
+    MissingMapValue,
+    /**
+    A value was nested deeper than the serializer allows.
+    */
+    DepthExceeded,
+    /**
+    A collection, string, or byte-string was longer than allowed.
+    */
+    LengthExceeded,
+    /**
+    A number couldn't be represented in the target format.
+    */
+    NumberOutOfRange,
+    /**
+    A map key couldn't be represented in the target format.
+    */
+    UnsupportedMapKey,
+    /**
+    A value was accessed or deserialized as a different type than the one it holds.
+    */
+    InvalidType,
+    /**
+    A value was deserialized as the right type, but with an unexpected value.
+    */
+    InvalidValue,
+    /**
+    A map or struct contained the same key more than once.
+    */
+    DuplicateKey,
+    /**
+    A float was `NaN` or infinite.
+    */
+    NonFiniteFloat,
+    /**
+    A value needed more memory than the serializer allows.
+    */
+    BudgetExceeded,
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, message: impl fmt::Display) -> Self {
+        Error(Box::new(Inner {
+            kind,
+            message: message.to_string(),
+            path: None,
+            #[cfg(feature = "std")]
+            source: None,
+        }))
+    }
+
+    /**
+    Create an error that was caused by some other error.
+
+    The error's message is taken from `source`, which is returned by [`std::error::Error::source`].
+    This can be used to propagate errors from formats or other libraries through a buffer.
+    */
+    #[cfg(feature = "std")]
+    pub fn from_source(source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        let source = source.into();
+
+        Error(Box::new(Inner {
+            kind: ErrorKind::Message,
+            message: source.to_string(),
+            path: None,
+            source: Some(source),
+        }))
+    }
+
+    /**
+    Get the reason this error was produced.
+    */
+    pub fn kind(&self) -> ErrorKind {
+        self.0.kind
+    }
+
+    /**
+    Get the message describing this error.
+    */
+    pub fn message(&self) -> &str {
+        &self.0.message
+    }
+
+    /**
+    Get the path to the value that produced this error, as a JSON Pointer like `/users/3/email`.
+
+    Paths are only recorded by serializers using [`crate::Serializer::with_error_paths`]. This method
+    returns `None` if the error wasn't produced by a value nested in the one being buffered.
+    */
+    pub fn path(&self) -> Option<&str> {
+        self.0.path.as_deref()
+    }
+
+    /**
+    Record that this error was produced by a value nested at `segment`.
+
+    Errors are produced by the most deeply nested value first, so segments are added to the start
+    of the path as the error is returned through the values containing it.
+    */
+    pub(crate) fn at(mut self, segment: impl fmt::Display) -> Self {
+        let mut path = String::from("/");
+
+        for c in segment.to_string().chars() {
+            match c {
+                '~' => path.push_str("~0"),
+                '/' => path.push_str("~1"),
+                c => path.push(c),
+            }
+        }
+
+        if let Some(ref nested) = self.0.path {
+            path.push_str(nested);
+        }
+
+        self.0.path = Some(path);
+        self
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Error");
+
+        debug
+            .field("kind", &self.0.kind)
+            .field("message", &self.0.message)
+            .field("path", &self.0.path);
+
+        #[cfg(feature = "std")]
+        debug.field("source", &self.0.source);
+
+        debug.finish()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.message.is_empty() {
+            write!(f, "error buffering a value")?;
+        } else {
+            f.write_str(&self.0.message)?;
+        }
+
+        if let Some(ref path) = self.0.path {
+            write!(f, " at {}", path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl serde::ser::StdError for Error {
+    #[cfg(feature = "std")]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0
+            .source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{collections::BTreeMap, string::ToString, vec};
+    use serde::{
+        de::{Deserialize as _, IntoDeserializer as _},
+        ser::{Error as _, SerializeMap as _},
+        Serialize as _, Serializer as _,
+    };
+
+    use crate::{Owned, Serializer};
+
+    #[test]
+    fn error_display() {
+        assert!(Error::custom("boom").to_string().contains("boom"));
+        assert_eq!("error buffering a value", Error::custom("").to_string());
+    }
+
+    #[test]
+    fn error_kind() {
+        let err = Error::custom("boom");
+        assert_eq!(ErrorKind::Message, err.kind());
+        assert_eq!("boom", err.message());
+
+        let err = vec![vec![1]]
+            .serialize(Serializer::new().with_max_depth(1))
+            .unwrap_err();
+        assert_eq!(ErrorKind::DepthExceeded, err.kind());
+
+        let err = bool::deserialize(
+            Owned::buffer(BTreeMap::from([(1, 1)]))
+                .unwrap()
+                .into_deserializer(),
+        )
+        .unwrap_err();
+        assert_eq!(ErrorKind::InvalidType, err.kind());
+
+        let mut map = Serializer::new().serialize_map(None).unwrap();
+        map.serialize_key(&1).unwrap();
+        assert_eq!(ErrorKind::MissingMapValue, map.end().unwrap_err().kind());
+    }
+
+    #[test]
+    fn error_path() {
+        let err = Error::custom("").at("email").at(3).at("users");
+
+        assert_eq!(Some("/users/3/email"), err.path());
+        assert_eq!("error buffering a value at /users/3/email", err.to_string());
+
+        let err = Error::custom("boom").at("a/b~c");
+
+        assert_eq!(Some("/a~1b~0c"), err.path());
+        assert_eq!("boom at /a~1b~0c", err.to_string());
+        assert_eq!("boom", err.message());
+
+        assert!(Error::custom("boom").path().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn error_source() {
+        use std::{error::Error as _, io};
+
+        let err = Error::from_source(io::Error::other("boom"));
+
+        assert_eq!("boom", err.to_string());
+        assert!(err.source().unwrap().downcast_ref::<io::Error>().is_some());
+
+        assert!(Error::custom("boom").source().is_none());
+    }
+}
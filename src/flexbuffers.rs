@@ -0,0 +1,497 @@
+/*!
+Conversions between buffers and `flexbuffers`' [`flexbuffers::Reader`] and [`flexbuffers::Builder`].
+*/
+
+use flexbuffers::{Blob, Builder, FlexBufferType, MapBuilder, Pushable, VectorBuilder};
+
+use crate::{Error, ErrorKind, LenHint, Owned, Ref, Value};
+
+impl<B: flexbuffers::Buffer> TryFrom<flexbuffers::Reader<B>> for Owned {
+    type Error = Error;
+
+    /**
+    Convert a FlexBuffer into a buffer, copying its strings and blobs.
+
+    Values are converted into the closest equivalent in a buffer:
+
+    - Nulls are converted into `()`.
+    - Integers are converted into `i64`s, unsigned integers into `u64`s, and floats into `f64`s.
+      FlexBuffers store all the scalars in a vector or map with the same width, so the width a
+      value is stored with isn't meaningful and isn't kept. Indirect integers and floats are
+      converted in the same way as inline ones.
+    - Keys and strings are converted into strings, and blobs into byte-strings.
+    - Maps are converted into maps with string keys, in the order of their keys.
+    - Vectors are converted into sequences. Typed vectors, including fixed-length ones like
+      `VectorInt3`, and vectors of booleans, keys, and strings, are converted in the same way as
+      untyped vectors, so their elements are converted like any other value.
+
+    This method fails if the FlexBuffer is invalid, like if one of its offsets is out of bounds
+    or one of its strings isn't valid UTF-8.
+    */
+    fn try_from(reader: flexbuffers::Reader<B>) -> Result<Self, Self::Error> {
+        from_flexbuffers(&reader, &|v| Value::Str((&*v).into()), &|v| {
+            Value::Bytes((&*v).into())
+        })
+        .map(Owned)
+    }
+}
+
+impl<'a> TryFrom<flexbuffers::Reader<&'a [u8]>> for Ref<'a> {
+    type Error = Error;
+
+    /**
+    Convert a FlexBuffer into a buffer, borrowing its strings and blobs.
+
+    Values are converted in the same way as [`Owned`], except strings, keys, and blobs borrow from
+    the FlexBuffer instead of being copied.
+    */
+    fn try_from(reader: flexbuffers::Reader<&'a [u8]>) -> Result<Self, Self::Error> {
+        from_flexbuffers(&reader, &Value::BorrowedStr, &Value::BorrowedBytes).map(Ref)
+    }
+}
+
+impl Owned {
+    /**
+    Build a FlexBuffer from this buffer using `builder`.
+
+    The builder is reset, so the buffer becomes the root of a new FlexBuffer that can be read from
+    [`flexbuffers::Builder::view`]. Values are converted into the closest equivalent in a FlexBuffer,
+    using the same representation as `flexbuffers`' own serializer for most values:
+
+    - `()`, `None`, and unit structs are converted into nulls.
+    - `Some` and newtype structs are converted into the value they contain.
+    - Integers and floats are converted into integers and floats with the same width.
+      128-bit integers are converted if they fit in 64 bits.
+    - Strings are converted into strings, and byte-strings into blobs. Characters are converted into
+      strings, instead of the lossy `u8` that `flexbuffers`' own serializer uses.
+    - Sequences, tuples, and tuple structs are converted into vectors.
+    - Maps with string keys and structs are converted into maps.
+    - Enum variants are converted using an external tag: unit variants become a string with the
+      variant's name, and other variants become a map with a single entry from the variant's name
+      to its value.
+
+    This method fails if the buffer contains a map with keys that aren't strings, or a 128-bit
+    integer that doesn't fit in 64 bits. If it fails then the builder is reset.
+    */
+    pub fn build_flexbuffer(&self, builder: &mut Builder) -> Result<(), Error> {
+        build_flexbuffer(&self.0, builder)
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Build a FlexBuffer from this buffer using `builder`.
+
+    See [`Owned::build_flexbuffer`] for details.
+    */
+    pub fn build_flexbuffer(&self, builder: &mut Builder) -> Result<(), Error> {
+        build_flexbuffer(&self.0, builder)
+    }
+}
+
+fn from_flexbuffers<'a, B: flexbuffers::Buffer>(
+    reader: &flexbuffers::Reader<B>,
+    string: &impl Fn(B::BufferString) -> Value<'a>,
+    blob: &impl Fn(B) -> Value<'a>,
+) -> Result<Value<'a>, Error> {
+    let ty = reader.flexbuffer_type();
+
+    Ok(match ty {
+        FlexBufferType::Null => Value::Unit,
+        FlexBufferType::Bool => Value::Bool(reader.get_bool().map_err(Error::from_source)?),
+        FlexBufferType::Int => Value::I64(reader.get_i64().map_err(Error::from_source)?),
+        FlexBufferType::UInt => Value::U64(reader.get_u64().map_err(Error::from_source)?),
+        FlexBufferType::Float => Value::F64(reader.get_f64().map_err(Error::from_source)?),
+        FlexBufferType::Key => string(reader.get_key().map_err(Error::from_source)?),
+        FlexBufferType::String => string(reader.get_str().map_err(Error::from_source)?),
+        FlexBufferType::Blob => blob(reader.get_blob().map_err(Error::from_source)?.0),
+        FlexBufferType::Map => {
+            let map = reader.get_map().map_err(Error::from_source)?;
+            let keys = map.keys_vector();
+
+            let mut fields = alloc::vec::Vec::with_capacity(map.len());
+            for i in 0..map.len() {
+                let key = keys.index(i).map_err(Error::from_source)?;
+                let value = map.index(i).map_err(Error::from_source)?;
+
+                fields.push((
+                    string(key.get_key().map_err(Error::from_source)?),
+                    from_flexbuffers(&value, string, blob)?,
+                ));
+            }
+
+            Value::Map(fields.into_boxed_slice(), LenHint::Known)
+        }
+        ty if ty.is_vector() => {
+            let vector = reader.get_vector().map_err(Error::from_source)?;
+
+            let mut fields = alloc::vec::Vec::with_capacity(vector.len());
+            for i in 0..vector.len() {
+                let value = vector.index(i).map_err(Error::from_source)?;
+
+                fields.push(from_flexbuffers(&value, string, blob)?);
+            }
+
+            Value::Seq(fields.into_boxed_slice(), LenHint::Known)
+        }
+        // Indirect values are dereferenced by the reader, so only direct types are seen here
+        ty => {
+            return Err(Error::new(
+                ErrorKind::InvalidType,
+                format_args!("the FlexBuffer type {:?} can't be buffered", ty),
+            ))
+        }
+    })
+}
+
+fn build_flexbuffer(value: &Value, builder: &mut Builder) -> Result<(), Error> {
+    let built = push(&mut Root(builder), value);
+
+    if built.is_err() {
+        builder.reset();
+    }
+
+    built
+}
+
+/**
+A place in a FlexBuffer that a value can be pushed to.
+*/
+trait Push {
+    fn push<P: Pushable>(&mut self, p: P);
+
+    fn start_vector(&mut self) -> VectorBuilder<'_>;
+
+    fn start_map(&mut self) -> MapBuilder<'_>;
+}
+
+/**
+The root of a FlexBuffer.
+*/
+struct Root<'b>(&'b mut Builder);
+
+/**
+The value of an entry in a FlexBuffer map.
+*/
+struct Entry<'m, 'b, 'k> {
+    map: &'m mut MapBuilder<'b>,
+    key: &'k str,
+}
+
+impl<'b> Push for Root<'b> {
+    fn push<P: Pushable>(&mut self, p: P) {
+        self.0.build_singleton(p)
+    }
+
+    fn start_vector(&mut self) -> VectorBuilder<'_> {
+        self.0.start_vector()
+    }
+
+    fn start_map(&mut self) -> MapBuilder<'_> {
+        self.0.start_map()
+    }
+}
+
+impl<'b> Push for VectorBuilder<'b> {
+    fn push<P: Pushable>(&mut self, p: P) {
+        VectorBuilder::push(self, p)
+    }
+
+    fn start_vector(&mut self) -> VectorBuilder<'_> {
+        VectorBuilder::start_vector(self)
+    }
+
+    fn start_map(&mut self) -> MapBuilder<'_> {
+        VectorBuilder::start_map(self)
+    }
+}
+
+impl<'m, 'b, 'k> Push for Entry<'m, 'b, 'k> {
+    fn push<P: Pushable>(&mut self, p: P) {
+        self.map.push(self.key, p)
+    }
+
+    fn start_vector(&mut self) -> VectorBuilder<'_> {
+        self.map.start_vector(self.key)
+    }
+
+    fn start_map(&mut self) -> MapBuilder<'_> {
+        self.map.start_map(self.key)
+    }
+}
+
+fn push(to: &mut impl Push, value: &Value) -> Result<(), Error> {
+    if let Some(v) = value.as_str() {
+        to.push(v);
+        return Ok(());
+    }
+
+    if let Some(v) = value.as_bytes() {
+        to.push(Blob(v));
+        return Ok(());
+    }
+
+    match value.resolve() {
+        Value::Unit | Value::None | Value::UnitStruct(_) => to.push(()),
+        Value::U8(v) => to.push(*v),
+        Value::U16(v) => to.push(*v),
+        Value::U32(v) => to.push(*v),
+        Value::U64(v) => to.push(*v),
+        Value::U128(v) => to.push(u64::try_from(**v).map_err(|_| out_of_range())?),
+        Value::I8(v) => to.push(*v),
+        Value::I16(v) => to.push(*v),
+        Value::I32(v) => to.push(*v),
+        Value::I64(v) => to.push(*v),
+        Value::I128(v) => to.push(i64::try_from(**v).map_err(|_| out_of_range())?),
+        Value::F32(v) => to.push(*v),
+        Value::F64(v) => to.push(*v),
+        Value::Bool(v) => to.push(*v),
+        Value::Char(v) => to.push(&*v.encode_utf8(&mut [0; 4])),
+        Value::Some(v) => push(to, v)?,
+        Value::NewtypeStruct(v) => push(to, &v.value)?,
+        Value::Seq(fields, _) | Value::Tuple(fields) => push_seq(to, fields)?,
+        Value::TupleStruct(v) => push_seq(to, &v.fields)?,
+        Value::Struct(v) => push_fields(to, v.fields.iter().map(|(k, v)| (&**k, v)))?,
+        Value::Map(fields, _) => {
+            let mut map = to.start_map();
+
+            for (k, v) in fields.iter() {
+                let key = k.as_str().ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::UnsupportedMapKey,
+                        "FlexBuffer maps can only have string keys",
+                    )
+                })?;
+
+                push(&mut Entry { map: &mut map, key }, v)?;
+            }
+        }
+        Value::UnitVariant(v) => to.push(&*v.variant),
+        Value::NewtypeVariant(v) => push_variant(to, &v.variant, |entry| push(entry, &v.value))?,
+        Value::TupleVariant(v) => push_variant(to, &v.variant, |entry| push_seq(entry, &v.fields))?,
+        Value::StructVariant(v) => push_variant(to, &v.variant, |entry| {
+            push_fields(entry, v.fields.iter().map(|(k, v)| (&**k, v)))
+        })?,
+        #[cfg(feature = "json")]
+        Value::RawJson(v) => push(to, &Value::parse_raw_json(v)?)?,
+        // Strings, bytes, and borrowed values are handled above
+        Value::Str(_)
+        | Value::SharedStr(_)
+        | Value::BorrowedStr(_)
+        | Value::Bytes(_)
+        | Value::BorrowedBytes(_)
+        | Value::Borrowed(_) => unreachable!(),
+        #[cfg(feature = "bytes")]
+        Value::SharedBytes(_) => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn push_seq(to: &mut impl Push, fields: &[Value]) -> Result<(), Error> {
+    let mut vector = to.start_vector();
+
+    for v in fields {
+        push(&mut vector, v)?;
+    }
+
+    Ok(())
+}
+
+fn push_fields<'v, 'a: 'v>(
+    to: &mut impl Push,
+    fields: impl Iterator<Item = (&'v str, &'v Value<'a>)>,
+) -> Result<(), Error> {
+    let mut map = to.start_map();
+
+    for (key, v) in fields {
+        push(&mut Entry { map: &mut map, key }, v)?;
+    }
+
+    Ok(())
+}
+
+fn push_variant(
+    to: &mut impl Push,
+    variant: &str,
+    value: impl FnOnce(&mut Entry) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut map = to.start_map();
+
+    value(&mut Entry {
+        map: &mut map,
+        key: variant,
+    })
+}
+
+fn out_of_range() -> Error {
+    Error::new(
+        ErrorKind::NumberOutOfRange,
+        "FlexBuffers can only store integers that fit in 64 bits",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{string::String, vec, vec::Vec};
+    use flexbuffers::Reader;
+    use serde_derive::Serialize;
+
+    #[test]
+    fn flexbuffers_roundtrip() {
+        let mut builder = Builder::default();
+        {
+            let mut map = builder.start_map();
+            map.push("a", 1u8);
+            map.push("b", -300i16);
+            map.push("c", 1.5f32);
+            map.push("d", "a string");
+            map.push("e", Blob(&b"bytes"[..]));
+            map.push("f", ());
+            map.push("g", flexbuffers::IndirectUInt(u64::MAX));
+
+            let mut vector = map.start_vector("h");
+            vector.push(true);
+            vector.push("b");
+            vector.end_vector();
+
+            // A typed vector of ints
+            map.push("i", &[1i32, 2, 3][..]);
+            map.end_map();
+        }
+
+        let expected = Ref::map([
+            (Ref::str("a"), Ref::u64(1)),
+            (Ref::str("b"), Ref::i64(-300)),
+            (Ref::str("c"), Ref::f64(1.5)),
+            (Ref::str("d"), Ref::str("a string")),
+            (Ref::str("e"), Ref::bytes(b"bytes")),
+            (Ref::str("f"), Ref::unit()),
+            (Ref::str("g"), Ref::u64(u64::MAX)),
+            (Ref::str("h"), Ref::seq([Ref::bool(true), Ref::str("b")])),
+            (
+                Ref::str("i"),
+                Ref::seq([Ref::i64(1), Ref::i64(2), Ref::i64(3)]),
+            ),
+        ]);
+
+        let reader = Reader::get_root(builder.view()).unwrap();
+
+        let owned = Owned::try_from(reader.clone()).unwrap();
+        assert_eq!(expected, owned);
+
+        let borrowed = Ref::try_from(reader).unwrap();
+        assert_eq!(expected, borrowed);
+        assert!(matches!(
+            borrowed.get("d").unwrap().0,
+            Value::Borrowed(Value::BorrowedStr(_))
+        ));
+
+        let mut rebuilt = Builder::default();
+        owned.build_flexbuffer(&mut rebuilt).unwrap();
+
+        assert_eq!(
+            owned,
+            Owned::try_from(Reader::get_root(rebuilt.view()).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn flexbuffers_build() {
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(u8),
+            Tuple(u8, u8),
+            Struct { a: u8 },
+        }
+
+        #[derive(Serialize)]
+        struct Tuple(u8, String);
+
+        #[derive(Serialize)]
+        struct Record {
+            id: u64,
+            name: Option<String>,
+            tuple: Tuple,
+            variants: Vec<Enum>,
+        }
+
+        let owned = Owned::buffer(Record {
+            id: 1,
+            name: Some(String::from("a")),
+            tuple: Tuple(2, String::from("b")),
+            variants: vec![
+                Enum::Unit,
+                Enum::Newtype(1),
+                Enum::Tuple(1, 2),
+                Enum::Struct { a: 1 },
+            ],
+        })
+        .unwrap();
+
+        let mut builder = Builder::default();
+        owned.as_ref().build_flexbuffer(&mut builder).unwrap();
+
+        let built = Owned::try_from(Reader::get_root(builder.view()).unwrap()).unwrap();
+
+        assert_eq!(
+            Ref::map([
+                (Ref::str("id"), Ref::u64(1)),
+                (Ref::str("name"), Ref::str("a")),
+                (Ref::str("tuple"), Ref::seq([Ref::u64(2), Ref::str("b")])),
+                (
+                    Ref::str("variants"),
+                    Ref::seq([
+                        Ref::str("Unit"),
+                        Ref::map([(Ref::str("Newtype"), Ref::u64(1))]),
+                        Ref::map([(Ref::str("Tuple"), Ref::seq([Ref::u64(1), Ref::u64(2)]))]),
+                        Ref::map([(Ref::str("Struct"), Ref::map([(Ref::str("a"), Ref::u64(1))]))]),
+                    ])
+                ),
+            ]),
+            built
+        );
+
+        let mut builder = Builder::default();
+        Ref::char('é').build_flexbuffer(&mut builder).unwrap();
+        assert_eq!(
+            Some("é"),
+            Owned::try_from(Reader::get_root(builder.view()).unwrap())
+                .unwrap()
+                .as_str()
+        );
+    }
+
+    #[test]
+    fn flexbuffers_build_invalid() {
+        let mut builder = Builder::default();
+
+        let err = Ref::map([(Ref::u8(1), Ref::unit())])
+            .build_flexbuffer(&mut builder)
+            .unwrap_err();
+        assert_eq!(ErrorKind::UnsupportedMapKey, err.kind());
+        assert!(builder.view().is_empty());
+
+        let err = Ref::seq([Ref::u128(u128::MAX)])
+            .build_flexbuffer(&mut builder)
+            .unwrap_err();
+        assert_eq!(ErrorKind::NumberOutOfRange, err.kind());
+
+        Ref::i128(-1).build_flexbuffer(&mut builder).unwrap();
+        assert_eq!(
+            Ref::i64(-1),
+            Owned::try_from(Reader::get_root(builder.view()).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn flexbuffers_invalid() {
+        // A string root whose length points outside the buffer
+        let reader = Reader::get_root(&[0xff, 0x61, 0x01, 0x14, 0x01][..]).unwrap();
+
+        assert!(Owned::try_from(reader).is_err());
+    }
+}
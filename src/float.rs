@@ -0,0 +1,86 @@
+use alloc::vec;
+
+use crate::{Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Whether this buffer contains a float that's `NaN` or infinite.
+
+    Every value nested in the buffer is checked, including the keys of maps.
+    */
+    pub fn contains_non_finite_float(&self) -> bool {
+        self.0.contains_non_finite_float()
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Whether this buffer contains a float that's `NaN` or infinite.
+
+    Every value nested in the buffer is checked, including the keys of maps.
+    */
+    pub fn contains_non_finite_float(&self) -> bool {
+        self.0.contains_non_finite_float()
+    }
+}
+
+impl<'a> Value<'a> {
+    fn contains_non_finite_float(&self) -> bool {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value.resolve() {
+                Value::F32(v) if !v.is_finite() => return true,
+                Value::F64(v) if !v.is_finite() => return true,
+                Value::Some(v) => stack.push(v),
+                Value::NewtypeStruct(v) => stack.push(&v.value),
+                Value::NewtypeVariant(v) => stack.push(&v.value),
+                Value::Seq(fields, _) | Value::Tuple(fields) => stack.extend(fields.iter()),
+                Value::TupleStruct(v) => stack.extend(v.fields.iter()),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter()),
+                Value::Struct(v) => stack.extend(v.fields.iter().map(|(_, v)| v)),
+                Value::StructVariant(v) => stack.extend(v.fields.iter().map(|(_, v)| v)),
+                Value::Map(fields, _) => stack.extend(fields.iter().flat_map(|(k, v)| [k, v])),
+                _ => (),
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::Serialize as _;
+
+    use crate::{ErrorKind, Serializer};
+
+    #[test]
+    fn contains_non_finite_float() {
+        assert!(!Ref::seq([Ref::f64(1.0), Ref::f32(-0.0)]).contains_non_finite_float());
+        assert!(Ref::seq([Ref::f64(1.0), Ref::f32(f32::NAN)]).contains_non_finite_float());
+        assert!(Ref::map([(Ref::f64(f64::INFINITY), Ref::unit())]).contains_non_finite_float());
+
+        let owned = Owned::buffer(Some((1, f64::NEG_INFINITY))).unwrap();
+        assert!(owned.contains_non_finite_float());
+        assert!(owned.as_ref().contains_non_finite_float());
+    }
+
+    #[test]
+    fn finite_floats() {
+        assert!(Owned::buffer(f64::NAN).is_ok());
+
+        assert_eq!(
+            ErrorKind::NonFiniteFloat,
+            [1.0, f32::INFINITY]
+                .serialize(Serializer::new().reject_non_finite_floats())
+                .unwrap_err()
+                .kind()
+        );
+        assert!(1.0f64
+            .serialize(Serializer::new().reject_non_finite_floats())
+            .is_ok());
+    }
+}
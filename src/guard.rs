@@ -0,0 +1,233 @@
+use core::{fmt, ops::Deref};
+
+use serde::{Serialize, Serializer};
+
+use crate::{Owned, Ref};
+
+/**
+A buffer that's known to contain a string.
+
+The string may be owned, shared, or borrowed by the buffer. Unlike [`Ref::as_str`], which can fail,
+this type implements [`AsRef<str>`] and [`Deref<Target = str>`](Deref), so it can be passed straight
+to functions that accept strings. It's created with [`Ref::into_str_buf`] or [`Owned::into_str_buf`].
+*/
+#[derive(Clone)]
+pub struct StrBuf<'a>(Ref<'a>);
+
+/**
+A buffer that's known to contain a byte-string.
+
+The byte-string may be owned, shared, or borrowed by the buffer. Unlike [`Ref::as_bytes`], which can fail,
+this type implements [`AsRef<[u8]>`](AsRef) and [`Deref<Target = [u8]>`](Deref), so it can be passed straight
+to functions that accept byte slices. It's created with [`Ref::into_bytes_buf`] or [`Owned::into_bytes_buf`].
+*/
+#[derive(Clone)]
+pub struct BytesBuf<'a>(Ref<'a>);
+
+impl Owned {
+    /**
+    Convert this buffer into one that's known to contain a string.
+
+    This method returns the original buffer if it doesn't contain a string.
+    */
+    pub fn into_str_buf(self) -> Result<StrBuf<'static>, Owned> {
+        Ref::from(self).into_str_buf().map_err(Ref::into_owned)
+    }
+
+    /**
+    Convert this buffer into one that's known to contain a byte-string.
+
+    This method returns the original buffer if it doesn't contain a byte-string.
+    */
+    pub fn into_bytes_buf(self) -> Result<BytesBuf<'static>, Owned> {
+        Ref::from(self).into_bytes_buf().map_err(Ref::into_owned)
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Convert this buffer into one that's known to contain a string.
+
+    This method returns the original buffer if it doesn't contain a string.
+    */
+    pub fn into_str_buf(self) -> Result<StrBuf<'a>, Ref<'a>> {
+        if self.as_str().is_some() {
+            Ok(StrBuf(self))
+        } else {
+            Err(self)
+        }
+    }
+
+    /**
+    Convert this buffer into one that's known to contain a byte-string.
+
+    This method returns the original buffer if it doesn't contain a byte-string.
+    */
+    pub fn into_bytes_buf(self) -> Result<BytesBuf<'a>, Ref<'a>> {
+        if self.as_bytes().is_some() {
+            Ok(BytesBuf(self))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<'a> StrBuf<'a> {
+    /**
+    Get the string in this buffer.
+    */
+    pub fn as_str(&self) -> &str {
+        self.0.as_str().expect("the buffer contains a string")
+    }
+
+    /**
+    Convert this back into a buffer.
+    */
+    pub fn into_inner(self) -> Ref<'a> {
+        self.0
+    }
+}
+
+impl<'a> BytesBuf<'a> {
+    /**
+    Get the byte-string in this buffer.
+    */
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0
+            .as_bytes()
+            .expect("the buffer contains a byte-string")
+    }
+
+    /**
+    Convert this back into a buffer.
+    */
+    pub fn into_inner(self) -> Ref<'a> {
+        self.0
+    }
+}
+
+impl<'a> Deref for StrBuf<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a> AsRef<str> for StrBuf<'a> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a> Deref for BytesBuf<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<'a> AsRef<[u8]> for BytesBuf<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<'a> From<StrBuf<'a>> for Ref<'a> {
+    fn from(buf: StrBuf<'a>) -> Self {
+        buf.0
+    }
+}
+
+impl<'a> From<BytesBuf<'a>> for Ref<'a> {
+    fn from(buf: BytesBuf<'a>) -> Self {
+        buf.0
+    }
+}
+
+impl<'a> fmt::Debug for StrBuf<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a> fmt::Display for StrBuf<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a> fmt::Debug for BytesBuf<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_bytes(), f)
+    }
+}
+
+impl<'a> Serialize for StrBuf<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'a> Serialize for BytesBuf<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::{String, ToString};
+
+    #[test]
+    fn str_buf() {
+        fn shout(s: impl AsRef<str>) -> String {
+            s.as_ref().to_uppercase()
+        }
+
+        let owned = Owned::from(String::from("owned"));
+        let borrowed = Ref::str("borrowed");
+
+        assert_eq!("OWNED", shout(owned.clone().into_str_buf().unwrap()));
+        assert_eq!("BORROWED", shout(borrowed.clone().into_str_buf().unwrap()));
+        assert_eq!("OWNED", shout(owned.as_ref().into_str_buf().unwrap()));
+
+        let buf = borrowed.into_str_buf().unwrap();
+
+        assert_eq!(8, buf.len());
+        assert_eq!("borrowed", buf.to_string());
+        assert_eq!("\"borrowed\"", alloc::format!("{:?}", buf));
+        assert_eq!(Ref::str("borrowed"), buf.into_inner());
+
+        assert_eq!(Ref::u8(1), Ref::u8(1).into_str_buf().unwrap_err());
+        assert_eq!(
+            Owned::from(Ref::bytes(b"bytes")),
+            Owned::from(Ref::bytes(b"bytes"))
+                .into_str_buf()
+                .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn bytes_buf() {
+        fn checksum(b: impl AsRef<[u8]>) -> u32 {
+            b.as_ref().iter().map(|b| u32::from(*b)).sum()
+        }
+
+        let owned = Owned::from(Ref::bytes(&[1, 2, 3]));
+
+        assert_eq!(6, checksum(owned.clone().into_bytes_buf().unwrap()));
+        assert_eq!(6, checksum(owned.as_ref().into_bytes_buf().unwrap()));
+        assert_eq!(1, checksum(Ref::bytes(&[1]).into_bytes_buf().unwrap()));
+
+        let buf = owned.into_bytes_buf().unwrap();
+
+        assert_eq!(Some(&3), buf.last());
+        assert_eq!(Ref::bytes(&[1, 2, 3]), Ref::from(buf));
+
+        assert!(Ref::str("not bytes").into_bytes_buf().is_err());
+    }
+}
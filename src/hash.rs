@@ -0,0 +1,254 @@
+use core::hash::Hasher;
+
+use alloc::{vec, vec::Vec};
+
+use crate::{Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Feed a stable fingerprint of this buffer's contents into `hasher`.
+
+    Unlike the [`core::hash::Hash`] implementation, the bytes written to `hasher` don't depend on
+    the platform or the order that map entries were buffered in, so the resulting hash can be
+    stored and compared across process restarts, as long as `hasher` itself is stable. Each value
+    writes its kind, followed by its contents, with numbers written as little-endian bytes and
+    strings, names, and collections prefixed by their length.
+
+    Map entries are sorted by their keys for hashing purposes, in the same way as [`Owned::canonicalize`],
+    without changing the buffer itself. Keys that are themselves maps are compared as they were buffered,
+    so canonicalize buffers with complex keys before hashing them. The fields of structs are hashed
+    in the order they were buffered in.
+
+    Two buffers that serialize identically will produce the same hash. Strings produce the same hash
+    whether they're owned, shared, or borrowed.
+    */
+    pub fn content_hash<H: Hasher>(&self, hasher: &mut H) {
+        self.0.content_hash(hasher)
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Feed a stable fingerprint of this buffer's contents into `hasher`.
+
+    See [`Owned::content_hash`] for details.
+    */
+    pub fn content_hash<H: Hasher>(&self, hasher: &mut H) {
+        self.0.content_hash(hasher)
+    }
+}
+
+enum Step<'v, 'a> {
+    Value(&'v Value<'a>),
+    Name(&'v str),
+}
+
+impl<'a> Value<'a> {
+    fn content_hash<H: Hasher>(&self, hasher: &mut H) {
+        // Steps are pushed in reverse, so they're popped in the order they're hashed
+        let mut stack = vec![Step::Value(self)];
+
+        while let Some(step) = stack.pop() {
+            let value = match step {
+                Step::Value(value) => value.resolve(),
+                Step::Name(name) => {
+                    write_str(hasher, name);
+                    continue;
+                }
+            };
+
+            hasher.write_u8(value.tag());
+
+            match value {
+                Value::Unit | Value::None => (),
+                Value::U8(v) => hasher.write(&v.to_le_bytes()),
+                Value::U16(v) => hasher.write(&v.to_le_bytes()),
+                Value::U32(v) => hasher.write(&v.to_le_bytes()),
+                Value::U64(v) => hasher.write(&v.to_le_bytes()),
+                Value::U128(v) => hasher.write(&v.to_le_bytes()),
+                Value::I8(v) => hasher.write(&v.to_le_bytes()),
+                Value::I16(v) => hasher.write(&v.to_le_bytes()),
+                Value::I32(v) => hasher.write(&v.to_le_bytes()),
+                Value::I64(v) => hasher.write(&v.to_le_bytes()),
+                Value::I128(v) => hasher.write(&v.to_le_bytes()),
+                Value::F32(v) => hasher.write(&v.to_bits().to_le_bytes()),
+                Value::F64(v) => hasher.write(&v.to_bits().to_le_bytes()),
+                Value::Bool(v) => hasher.write_u8(*v as u8),
+                Value::Char(v) => hasher.write(&u32::from(*v).to_le_bytes()),
+                Value::Str(v) => write_str(hasher, v),
+                Value::SharedStr(v) => write_str(hasher, v),
+                Value::BorrowedStr(v) => write_str(hasher, v),
+                Value::Bytes(v) => write_bytes(hasher, v),
+                Value::BorrowedBytes(v) => write_bytes(hasher, v),
+                #[cfg(feature = "bytes")]
+                Value::SharedBytes(v) => write_bytes(hasher, v),
+                #[cfg(feature = "json")]
+                Value::RawJson(v) => write_str(hasher, v),
+                Value::Some(v) => stack.push(Step::Value(v)),
+                Value::UnitStruct(v) => write_str(hasher, &v.name),
+                Value::NewtypeStruct(v) => {
+                    write_str(hasher, &v.name);
+                    stack.push(Step::Value(&v.value));
+                }
+                Value::Struct(v) => {
+                    write_str(hasher, &v.name);
+                    write_len(hasher, v.fields.len());
+
+                    for (k, v) in v.fields.iter().rev() {
+                        stack.push(Step::Value(v));
+                        stack.push(Step::Name(k));
+                    }
+                }
+                Value::Tuple(fields) | Value::Seq(fields, _) => {
+                    write_len(hasher, fields.len());
+                    stack.extend(fields.iter().rev().map(Step::Value));
+                }
+                Value::TupleStruct(v) => {
+                    write_str(hasher, &v.name);
+                    write_len(hasher, v.fields.len());
+                    stack.extend(v.fields.iter().rev().map(Step::Value));
+                }
+                Value::UnitVariant(v) => {
+                    write_variant(hasher, &v.name, v.variant_index, &v.variant);
+                }
+                Value::NewtypeVariant(v) => {
+                    write_variant(hasher, &v.name, v.variant_index, &v.variant);
+                    stack.push(Step::Value(&v.value));
+                }
+                Value::TupleVariant(v) => {
+                    write_variant(hasher, &v.name, v.variant_index, &v.variant);
+                    write_len(hasher, v.fields.len());
+                    stack.extend(v.fields.iter().rev().map(Step::Value));
+                }
+                Value::StructVariant(v) => {
+                    write_variant(hasher, &v.name, v.variant_index, &v.variant);
+                    write_len(hasher, v.fields.len());
+
+                    for (k, v) in v.fields.iter().rev() {
+                        stack.push(Step::Value(v));
+                        stack.push(Step::Name(k));
+                    }
+                }
+                Value::Map(fields, _) => {
+                    write_len(hasher, fields.len());
+
+                    let mut sorted = fields.iter().collect::<Vec<_>>();
+                    sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                    for (k, v) in sorted.into_iter().rev() {
+                        stack.push(Step::Value(v));
+                        stack.push(Step::Value(k));
+                    }
+                }
+                Value::Borrowed(_) => unreachable!("borrowed values are resolved"),
+            }
+        }
+    }
+}
+
+fn write_len(hasher: &mut impl Hasher, len: usize) {
+    // Lengths are always written as 64-bit integers, so they're the same on every platform
+    hasher.write(&(len as u64).to_le_bytes());
+}
+
+fn write_bytes(hasher: &mut impl Hasher, bytes: &[u8]) {
+    write_len(hasher, bytes.len());
+    hasher.write(bytes);
+}
+
+fn write_str(hasher: &mut impl Hasher, s: &str) {
+    write_bytes(hasher, s.as_bytes());
+}
+
+fn write_variant(hasher: &mut impl Hasher, name: &str, variant_index: u32, variant: &str) {
+    write_str(hasher, name);
+    hasher.write(&variant_index.to_le_bytes());
+    write_str(hasher, variant);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    A hasher that keeps the bytes written to it, so tests can inspect them.
+    */
+    #[derive(Default)]
+    struct Bytes(Vec<u8>);
+
+    impl Hasher for Bytes {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    fn content_hash(buf: &Ref) -> Vec<u8> {
+        let mut hasher = Bytes::default();
+        buf.content_hash(&mut hasher);
+        hasher.0
+    }
+
+    #[test]
+    fn content_hash_map_order() {
+        let a = Ref::record_struct(
+            "Struct",
+            [(
+                "a",
+                Ref::map([(Ref::str("x"), Ref::u8(1)), (Ref::str("y"), Ref::u8(2))]),
+            )],
+        );
+        let b = Ref::record_struct(
+            "Struct",
+            [(
+                "a",
+                Ref::map([(Ref::str("y"), Ref::u8(2)), (Ref::str("x"), Ref::u8(1))]),
+            )],
+        );
+
+        assert_eq!(content_hash(&a), content_hash(&b));
+
+        let owned = Owned::from(Ref::map([(Ref::owned_str("x"), Ref::u8(1))]));
+        assert_eq!(
+            content_hash(&owned.as_ref()),
+            content_hash(&Ref::map([(Ref::str("x"), Ref::u8(1))]))
+        );
+    }
+
+    #[test]
+    fn content_hash_distinct() {
+        let values = [
+            Ref::unit(),
+            Ref::none(),
+            Ref::u8(1),
+            Ref::u16(1),
+            Ref::str("1"),
+            Ref::bytes(b"1"),
+            Ref::seq([Ref::str("a"), Ref::str("b")]),
+            Ref::seq([Ref::str("ab")]),
+            Ref::tuple([Ref::str("a"), Ref::str("b")]),
+            Ref::unit_struct("A"),
+            Ref::unit_variant("A", 0, "B"),
+            Ref::unit_variant("A", 1, "B"),
+            Ref::record_struct("A", [("b", Ref::unit())]),
+            Ref::record_struct("A", [("c", Ref::unit())]),
+        ];
+
+        for (i, a) in values.iter().enumerate() {
+            for b in &values[i + 1..] {
+                assert_ne!(content_hash(a), content_hash(b), "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn content_hash_stable() {
+        assert_eq!(
+            vec![28, 1, 0, 0, 0, 0, 0, 0, 0, 15, 1, 0, 0, 0, 0, 0, 0, 0, b'a'],
+            content_hash(&Ref::seq([Ref::str("a")]))
+        );
+    }
+}
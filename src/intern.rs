@@ -0,0 +1,182 @@
+/*!
+Sharing the storage of equal strings within and across buffers.
+*/
+
+use core::fmt;
+
+use alloc::{collections::BTreeSet, sync::Arc};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/**
+A set of strings that can be shared by the buffers produced by many serializers.
+
+Each distinct string buffered through the interner is only allocated once, as an `Arc<str>`, and every
+buffer it appears in shares that allocation. Pass an interner to [`crate::Serializer::with_interner`]
+to share strings across every value buffered with it, like the records in a log pipeline that repeat
+the same map keys and values. Use [`crate::Serializer::with_string_interning`] instead to only share
+strings within a single buffer.
+
+Interned strings are kept until the interner and every buffer using them are dropped, so an interner
+that sees many distinct strings will keep growing. Use [`StringInterner::hits`] and
+[`StringInterner::misses`] to check whether interning is paying off, and [`StringInterner::clear`]
+to release the strings it holds.
+
+Cloning an interner gives another handle to the same set of strings. Interners can be shared
+between threads, so the serializers on many threads can use the same one. Strings are interned
+while holding a mutex, so this type needs the `std` feature.
+*/
+#[derive(Clone, Default)]
+pub struct StringInterner(Arc<Mutex<Interner>>);
+
+/**
+A set of strings that have already been buffered.
+*/
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+    strings: BTreeSet<Arc<str>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl StringInterner {
+    /**
+    Create an empty interner.
+    */
+    pub fn new() -> Self {
+        StringInterner::default()
+    }
+
+    /**
+    Get the number of distinct strings in the interner.
+    */
+    pub fn len(&self) -> usize {
+        self.lock().strings.len()
+    }
+
+    /**
+    Whether the interner doesn't contain any strings.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.lock().strings.is_empty()
+    }
+
+    /**
+    Get the number of strings that were shared with an existing allocation.
+    */
+    pub fn hits(&self) -> u64 {
+        self.lock().hits
+    }
+
+    /**
+    Get the number of strings that needed a new allocation.
+    */
+    pub fn misses(&self) -> u64 {
+        self.lock().misses
+    }
+
+    /**
+    Remove all strings from the interner and reset its hits and misses.
+
+    Buffers that already share strings from the interner keep them.
+    */
+    pub fn clear(&self) {
+        *self.lock() = Interner::default();
+    }
+
+    pub(crate) fn intern(&self, v: &str) -> Arc<str> {
+        self.lock().intern(v)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Interner> {
+        // Interning can't leave the strings in an inconsistent state, so a poisoned lock is still usable
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for StringInterner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.lock(), f)
+    }
+}
+
+impl Interner {
+    /**
+    Get a shared string equal to `v`, allocating it if it hasn't been seen before.
+    */
+    pub(crate) fn intern(&mut self, v: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(v) {
+            self.hits += 1;
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = v.into();
+        self.strings.insert(interned.clone());
+        self.misses += 1;
+
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let mut interner = Interner::default();
+
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+
+        assert!(Arc::ptr_eq(&a, &interner.intern("a")));
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert_eq!(2, interner.strings.len());
+        assert_eq!((1, 2), (interner.hits, interner.misses));
+    }
+
+    #[test]
+    fn string_interner() {
+        let interner = StringInterner::new();
+        let shared = interner.clone();
+
+        let a = interner.intern("a");
+
+        assert!(Arc::ptr_eq(&a, &shared.intern("a")));
+        assert_eq!(1, shared.len());
+        assert_eq!((1, 1), (interner.hits(), interner.misses()));
+
+        interner.clear();
+
+        assert!(shared.is_empty());
+        assert_eq!((0, 0), (shared.hits(), shared.misses()));
+        assert!(!Arc::ptr_eq(&a, &shared.intern("a")));
+        assert_eq!("a", &*a);
+    }
+
+    #[test]
+    fn string_interner_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<StringInterner>();
+
+        let interner = StringInterner::new();
+
+        let handles = (0..4)
+            .map(|_| {
+                let interner = interner.clone();
+
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        interner.intern("a");
+                    }
+                })
+            })
+            .collect::<alloc::vec::Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(1, interner.len());
+        assert_eq!((399, 1), (interner.hits(), interner.misses()));
+    }
+}
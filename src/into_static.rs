@@ -0,0 +1,431 @@
+use core::mem;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use crate::{
+    LenHint, Name, NewtypeStruct, NewtypeVariant, Struct, StructVariant, TupleStruct, TupleVariant,
+    UnitStruct, UnitVariant, Value,
+};
+
+/**
+A step in converting a value into one that doesn't borrow any data.
+*/
+enum Step<'v, 'a> {
+    /**
+    Convert an owned value, moving the data it owns into the result.
+    */
+    Owned(Value<'a>),
+    /**
+    Convert a borrowed value, copying the data it contains into the result.
+    */
+    Borrowed(&'v Value<'a>),
+    /**
+    Build a converted value from the conversions of its nested values.
+    */
+    Build(Shell),
+}
+
+/**
+The parts of a value that owns other values, without those values.
+*/
+enum Shell {
+    Some,
+    NewtypeStruct(Name<'static>),
+    NewtypeVariant(Name<'static>, u32, Name<'static>),
+    Seq(usize, LenHint),
+    Tuple(usize),
+    TupleStruct(Name<'static>, usize),
+    TupleVariant(Name<'static>, u32, Name<'static>, usize),
+    Struct(Name<'static>, Vec<Name<'static>>),
+    StructVariant(Name<'static>, u32, Name<'static>, Vec<Name<'static>>),
+    Map(usize, LenHint),
+}
+
+impl<'a> Value<'a> {
+    /**
+    Convert this value into one that doesn't borrow any data.
+
+    Strings, byte-strings, and names that are already owned are moved into the result. Borrowed
+    ones are copied. Nested values are converted first onto a stack, and then popped off to build
+    their parents, so converting deeply nested buffers can't overflow the stack.
+    */
+    pub(crate) fn into_static(self) -> Value<'static> {
+        let mut converted = Vec::new();
+        let mut steps = vec![Step::Owned(self)];
+
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Owned(mut value) => match value {
+                    Value::Some(ref mut v) => {
+                        let nested = mem::replace(&mut **v, Value::Unit);
+                        visit_owned(&mut steps, Shell::Some, [nested]);
+                    }
+                    Value::NewtypeStruct(ref mut v) => {
+                        let nested = mem::replace(&mut v.value, Value::Unit);
+                        let shell = Shell::NewtypeStruct(take_name(&mut v.name));
+                        visit_owned(&mut steps, shell, [nested]);
+                    }
+                    Value::NewtypeVariant(ref mut v) => {
+                        let nested = mem::replace(&mut v.value, Value::Unit);
+                        let shell = Shell::NewtypeVariant(
+                            take_name(&mut v.name),
+                            v.variant_index,
+                            take_name(&mut v.variant),
+                        );
+                        visit_owned(&mut steps, shell, [nested]);
+                    }
+                    Value::Seq(ref mut fields, len_hint) => {
+                        let fields = mem::take(fields).into_vec();
+                        visit_owned(&mut steps, Shell::Seq(fields.len(), len_hint), fields);
+                    }
+                    Value::Tuple(ref mut fields) => {
+                        let fields = mem::take(fields).into_vec();
+                        visit_owned(&mut steps, Shell::Tuple(fields.len()), fields);
+                    }
+                    Value::TupleStruct(ref mut v) => {
+                        let fields = mem::take(&mut v.fields).into_vec();
+                        let shell = Shell::TupleStruct(take_name(&mut v.name), fields.len());
+                        visit_owned(&mut steps, shell, fields);
+                    }
+                    Value::TupleVariant(ref mut v) => {
+                        let fields = mem::take(&mut v.fields).into_vec();
+                        let shell = Shell::TupleVariant(
+                            take_name(&mut v.name),
+                            v.variant_index,
+                            take_name(&mut v.variant),
+                            fields.len(),
+                        );
+                        visit_owned(&mut steps, shell, fields);
+                    }
+                    Value::Struct(ref mut v) => {
+                        let (names, fields) = take_fields(&mut v.fields);
+                        let shell = Shell::Struct(take_name(&mut v.name), names);
+                        visit_owned(&mut steps, shell, fields);
+                    }
+                    Value::StructVariant(ref mut v) => {
+                        let (names, fields) = take_fields(&mut v.fields);
+                        let shell = Shell::StructVariant(
+                            take_name(&mut v.name),
+                            v.variant_index,
+                            take_name(&mut v.variant),
+                            names,
+                        );
+                        visit_owned(&mut steps, shell, fields);
+                    }
+                    Value::Map(ref mut fields, len_hint) => {
+                        let fields = mem::take(fields).into_vec();
+                        let shell = Shell::Map(fields.len(), len_hint);
+                        visit_owned(
+                            &mut steps,
+                            shell,
+                            fields.into_iter().flat_map(|(k, v)| [k, v]),
+                        );
+                    }
+                    Value::Borrowed(v) => steps.push(Step::Borrowed(v)),
+                    _ => converted.push(value.into_static_leaf()),
+                },
+                Step::Borrowed(value) => match value {
+                    Value::Some(v) => visit_borrowed(&mut steps, Shell::Some, [&**v]),
+                    Value::NewtypeStruct(v) => {
+                        let shell = Shell::NewtypeStruct(v.name.to_static());
+                        visit_borrowed(&mut steps, shell, [&v.value]);
+                    }
+                    Value::NewtypeVariant(v) => {
+                        let shell = Shell::NewtypeVariant(
+                            v.name.to_static(),
+                            v.variant_index,
+                            v.variant.to_static(),
+                        );
+                        visit_borrowed(&mut steps, shell, [&v.value]);
+                    }
+                    Value::Seq(fields, len_hint) => {
+                        let shell = Shell::Seq(fields.len(), *len_hint);
+                        visit_borrowed(&mut steps, shell, fields.iter());
+                    }
+                    Value::Tuple(fields) => {
+                        visit_borrowed(&mut steps, Shell::Tuple(fields.len()), fields.iter());
+                    }
+                    Value::TupleStruct(v) => {
+                        let shell = Shell::TupleStruct(v.name.to_static(), v.fields.len());
+                        visit_borrowed(&mut steps, shell, v.fields.iter());
+                    }
+                    Value::TupleVariant(v) => {
+                        let shell = Shell::TupleVariant(
+                            v.name.to_static(),
+                            v.variant_index,
+                            v.variant.to_static(),
+                            v.fields.len(),
+                        );
+                        visit_borrowed(&mut steps, shell, v.fields.iter());
+                    }
+                    Value::Struct(v) => {
+                        let shell = Shell::Struct(v.name.to_static(), copy_names(&v.fields));
+                        visit_borrowed(&mut steps, shell, v.fields.iter().map(|(_, v)| v));
+                    }
+                    Value::StructVariant(v) => {
+                        let shell = Shell::StructVariant(
+                            v.name.to_static(),
+                            v.variant_index,
+                            v.variant.to_static(),
+                            copy_names(&v.fields),
+                        );
+                        visit_borrowed(&mut steps, shell, v.fields.iter().map(|(_, v)| v));
+                    }
+                    Value::Map(fields, len_hint) => {
+                        let shell = Shell::Map(fields.len(), *len_hint);
+                        visit_borrowed(&mut steps, shell, fields.iter().flat_map(|(k, v)| [k, v]));
+                    }
+                    Value::Borrowed(v) => steps.push(Step::Borrowed(v)),
+                    _ => converted.push(value.to_static_leaf()),
+                },
+                Step::Build(shell) => {
+                    let value = shell.build(&mut converted);
+                    converted.push(value);
+                }
+            }
+        }
+
+        converted.pop().expect("missing converted value")
+    }
+}
+
+/**
+Schedule `nested` to be converted before the value described by `shell` is built from them.
+*/
+fn visit_owned<'v, 'a>(
+    steps: &mut Vec<Step<'v, 'a>>,
+    shell: Shell,
+    nested: impl IntoIterator<Item = Value<'a>>,
+) {
+    steps.push(Step::Build(shell));
+
+    // Steps are popped in reverse, so nested values are converted in order
+    let start = steps.len();
+    steps.extend(nested.into_iter().map(Step::Owned));
+    steps[start..].reverse();
+}
+
+/**
+Schedule `nested` to be copied before the value described by `shell` is built from them.
+*/
+fn visit_borrowed<'v, 'a>(
+    steps: &mut Vec<Step<'v, 'a>>,
+    shell: Shell,
+    nested: impl IntoIterator<Item = &'v Value<'a>>,
+) {
+    steps.push(Step::Build(shell));
+
+    let start = steps.len();
+    steps.extend(nested.into_iter().map(Step::Borrowed));
+    steps[start..].reverse();
+}
+
+fn take_name(name: &mut Name) -> Name<'static> {
+    mem::replace(name, Name::new("")).into_static()
+}
+
+fn take_fields<'a>(
+    fields: &mut Box<[(Name<'a>, Value<'a>)]>,
+) -> (Vec<Name<'static>>, Vec<Value<'a>>) {
+    mem::take(fields)
+        .into_vec()
+        .into_iter()
+        .map(|(k, v)| (k.into_static(), v))
+        .unzip()
+}
+
+fn copy_names(fields: &[(Name, Value)]) -> Vec<Name<'static>> {
+    fields.iter().map(|(k, _)| k.to_static()).collect()
+}
+
+impl Shell {
+    /**
+    Build a value from the conversions of its nested values, taken from the end of `converted`.
+    */
+    fn build(self, converted: &mut Vec<Value<'static>>) -> Value<'static> {
+        match self {
+            Shell::Some => Value::Some(Box::new(pop(converted))),
+            Shell::NewtypeStruct(name) => Value::NewtypeStruct(Box::new(NewtypeStruct {
+                name,
+                value: pop(converted),
+            })),
+            Shell::NewtypeVariant(name, variant_index, variant) => {
+                Value::NewtypeVariant(Box::new(NewtypeVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    value: pop(converted),
+                }))
+            }
+            Shell::Seq(len, len_hint) => Value::Seq(pop_n(converted, len), len_hint),
+            Shell::Tuple(len) => Value::Tuple(pop_n(converted, len)),
+            Shell::TupleStruct(name, len) => Value::TupleStruct(Box::new(TupleStruct {
+                name,
+                fields: pop_n(converted, len),
+            })),
+            Shell::TupleVariant(name, variant_index, variant, len) => {
+                Value::TupleVariant(Box::new(TupleVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    fields: pop_n(converted, len),
+                }))
+            }
+            Shell::Struct(name, names) => Value::Struct(Box::new(Struct {
+                name,
+                fields: pop_fields(converted, names),
+            })),
+            Shell::StructVariant(name, variant_index, variant, names) => {
+                Value::StructVariant(Box::new(StructVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    fields: pop_fields(converted, names),
+                }))
+            }
+            Shell::Map(len, len_hint) => {
+                let mut values = converted.drain(converted.len() - len * 2..);
+
+                let mut entries = Vec::with_capacity(len);
+                while let (Some(k), Some(v)) = (values.next(), values.next()) {
+                    entries.push((k, v));
+                }
+
+                Value::Map(entries.into_boxed_slice(), len_hint)
+            }
+        }
+    }
+}
+
+fn pop(converted: &mut Vec<Value<'static>>) -> Value<'static> {
+    converted.pop().expect("missing converted value")
+}
+
+fn pop_n(converted: &mut Vec<Value<'static>>, len: usize) -> Box<[Value<'static>]> {
+    converted.drain(converted.len() - len..).collect()
+}
+
+fn pop_fields(
+    converted: &mut Vec<Value<'static>>,
+    names: Vec<Name<'static>>,
+) -> Box<[(Name<'static>, Value<'static>)]> {
+    let len = names.len();
+
+    names
+        .into_iter()
+        .zip(converted.drain(converted.len() - len..))
+        .collect()
+}
+
+impl<'a> Value<'a> {
+    /**
+    Convert a value that doesn't own any other values.
+    */
+    fn into_static_leaf(mut self) -> Value<'static> {
+        match self {
+            Value::Str(ref mut v) => Value::Str(mem::take(v)),
+            Value::Bytes(ref mut v) => Value::Bytes(mem::take(v)),
+            #[cfg(feature = "json")]
+            Value::RawJson(ref mut v) => Value::RawJson(mem::take(v)),
+            Value::UnitStruct(ref mut v) => Value::UnitStruct(Box::new(UnitStruct {
+                name: take_name(&mut v.name),
+            })),
+            Value::UnitVariant(ref mut v) => Value::UnitVariant(Box::new(UnitVariant {
+                name: take_name(&mut v.name),
+                variant_index: v.variant_index,
+                variant: take_name(&mut v.variant),
+            })),
+            ref value => value.to_static_leaf(),
+        }
+    }
+
+    /**
+    Copy a value that doesn't own any other values.
+    */
+    fn to_static_leaf(&self) -> Value<'static> {
+        match *self {
+            Value::Unit => Value::Unit,
+            Value::U8(v) => Value::U8(v),
+            Value::U16(v) => Value::U16(v),
+            Value::U32(v) => Value::U32(v),
+            Value::U64(v) => Value::U64(v),
+            Value::U128(ref v) => Value::U128(v.clone()),
+            Value::I8(v) => Value::I8(v),
+            Value::I16(v) => Value::I16(v),
+            Value::I32(v) => Value::I32(v),
+            Value::I64(v) => Value::I64(v),
+            Value::I128(ref v) => Value::I128(v.clone()),
+            Value::F32(v) => Value::F32(v),
+            Value::F64(v) => Value::F64(v),
+            Value::Bool(v) => Value::Bool(v),
+            Value::Char(v) => Value::Char(v),
+            Value::Str(ref v) => Value::Str(v.clone()),
+            Value::SharedStr(ref v) => Value::SharedStr(v.clone()),
+            Value::BorrowedStr(v) => Value::Str(v.into()),
+            Value::Bytes(ref v) => Value::Bytes(v.clone()),
+            Value::BorrowedBytes(v) => Value::Bytes(v.into()),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(ref v) => Value::SharedBytes(v.clone()),
+            #[cfg(feature = "json")]
+            Value::RawJson(ref v) => Value::RawJson(v.clone()),
+            Value::None => Value::None,
+            Value::UnitStruct(ref v) => Value::UnitStruct(Box::new(UnitStruct {
+                name: v.name.to_static(),
+            })),
+            Value::UnitVariant(ref v) => Value::UnitVariant(Box::new(UnitVariant {
+                name: v.name.to_static(),
+                variant_index: v.variant_index,
+                variant: v.variant.to_static(),
+            })),
+            _ => unreachable!("nested values are built from their conversions"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Owned, Ref};
+
+    #[test]
+    fn into_static() {
+        let owned = Owned::from(Ref::map([(Ref::str("b"), Ref::owned_str("c"))]));
+
+        let borrowed = Ref::record_struct_variant(
+            "Enum",
+            1,
+            "A",
+            [
+                ("a", owned.as_ref()),
+                ("b", Ref::seq([Ref::str("d"), Ref::some(Ref::bytes(b"e"))])),
+                ("c", Ref::tuple_struct("Tuple", [Ref::u128(1), Ref::unit()])),
+            ],
+        );
+
+        assert_eq!(borrowed, borrowed.clone().into_owned());
+        assert_eq!(owned.as_ref(), owned.as_ref().into_owned());
+    }
+
+    #[test]
+    fn into_static_deeply_nested() {
+        let mut deep = Ref::unit();
+        for i in 0..200_000 {
+            deep = match i % 6 {
+                0 => Ref::some(deep),
+                1 => Ref::newtype_struct("Newtype", deep),
+                2 => Ref::seq([Ref::str("a"), deep]),
+                3 => Ref::map([(Ref::str("a"), deep)]),
+                4 => Ref::record_struct_variant("Enum", 0, "A", [("a", deep)]),
+                _ => Ref::tuple_struct("Tuple", [deep, Ref::unit()]),
+            };
+        }
+
+        let owned = Owned::from(deep.clone());
+
+        // Borrowing the owned buffer means the conversion copies it
+        let copied = owned.as_ref().into_owned();
+
+        // Not `assert_eq!`, because formatting a deeply nested buffer recurses
+        assert!(deep == owned.as_ref());
+        assert!(owned == copied);
+    }
+}
@@ -0,0 +1,992 @@
+/*!
+Conversions between buffers and `serde_json`'s [`serde_json::Value`].
+*/
+
+use core::{iter, mem};
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use serde::{
+    de::{self, value::MapDeserializer, IntoDeserializer},
+    ser::{Error as _, SerializeStruct as _},
+    Serialize as _,
+};
+
+use crate::{Error, ErrorKind, LenHint, Name, Owned, Ref, Value};
+
+impl From<serde_json::Value> for Owned {
+    /**
+    Convert a JSON value into a buffer.
+
+    Objects are converted into maps with string keys, arrays into sequences, and `null` into `()`.
+    Integers are converted into the narrowest integer type that can hold them, and all other numbers
+    into `f64`s. The resulting buffer serializes to the same JSON document as `value`.
+    */
+    fn from(value: serde_json::Value) -> Self {
+        Owned(from_json(value))
+    }
+}
+
+impl TryFrom<Owned> for serde_json::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a JSON value.
+
+    Values are converted in the same way `serde_json` would serialize them:
+
+    - Structs and maps are converted into objects.
+    - Sequences, tuples, and structs with unnamed fields are converted into arrays.
+    - `()`, unit structs, and `Option::None` are converted into `null`.
+    - Enum variants are externally tagged, so `A::B(T)` is converted into `{"B": T}`.
+    - Byte-strings are converted into arrays of numbers.
+    - Non-finite floats are converted into `null`.
+
+    This method will fail if a map contains keys that can't be converted into strings,
+    or if a 128bit integer doesn't fit in 64 bits.
+    */
+    fn try_from(value: Owned) -> Result<Self, Self::Error> {
+        into_json(value.into_value())
+    }
+}
+
+impl<'a> TryFrom<Ref<'a>> for serde_json::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a JSON value.
+
+    This conversion follows the same rules as the one from [`Owned`].
+    */
+    fn try_from(value: Ref<'a>) -> Result<Self, Self::Error> {
+        into_json(value.into_value())
+    }
+}
+
+impl PartialEq<serde_json::Value> for Owned {
+    /**
+    Compare a buffer with a JSON value.
+
+    The buffer is compared as if it had been converted into a JSON value first, so structs and
+    maps are compared with objects, byte-strings with arrays of numbers, and enum variants with
+    their externally tagged representation. Map keys are converted into strings in the same way.
+
+    Numbers are compared by value, but integers and floats are never equal to each other, just as
+    they aren't in `serde_json`. A buffer containing a `u8` is equal to the JSON number `1`, but
+    not `1.0`. Floats are widened to `f64` before they're compared, and non-finite floats are
+    equal to `null`. Buffers that can't be converted into JSON, like ones containing 128-bit
+    integers that don't fit in 64 bits, are never equal to any JSON value.
+    */
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        json_eq(&self.0, other)
+    }
+}
+
+impl<'a> PartialEq<serde_json::Value> for Ref<'a> {
+    /**
+    Compare a buffer with a JSON value.
+
+    This comparison follows the same rules as the one for [`Owned`].
+    */
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        json_eq(&self.0, other)
+    }
+}
+
+impl PartialEq<Owned> for serde_json::Value {
+    fn eq(&self, other: &Owned) -> bool {
+        other == self
+    }
+}
+
+impl<'a> PartialEq<Ref<'a>> for serde_json::Value {
+    fn eq(&self, other: &Ref<'a>) -> bool {
+        other == self
+    }
+}
+
+impl Owned {
+    /**
+    Estimate the number of bytes this buffer would take when serialized as compact JSON.
+
+    See [`Ref::serialized_len_hint`] for details on how the estimate is calculated.
+    */
+    pub fn serialized_len_hint(&self) -> usize {
+        self.0.json_len_hint()
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Estimate the number of bytes this buffer would take when serialized as compact JSON.
+
+    The estimate follows the same rules as the conversion into [`serde_json::Value`], without
+    serializing anything. Integers, punctuation, and field names are counted exactly. Strings are
+    counted without any escaping they might need, and floats are counted at the longest length
+    they could be. The estimate only grows as values are added to the buffer, so it's suitable for
+    deciding when a batch of buffers is large enough to send.
+    */
+    pub fn serialized_len_hint(&self) -> usize {
+        self.0.json_len_hint()
+    }
+}
+
+impl<'a> Value<'a> {
+    fn json_len_hint(&self) -> usize {
+        let mut len = 0;
+        let mut stack = vec![(self, false)];
+
+        while let Some((value, is_key)) = stack.pop() {
+            let value = value.resolve();
+
+            // Map keys that aren't strings are quoted
+            if is_key
+                && !matches!(
+                    value,
+                    Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_)
+                )
+            {
+                len += 2;
+            }
+
+            len += match value {
+                Value::Unit | Value::None | Value::UnitStruct(_) => 4,
+                Value::Bool(true) => 4,
+                Value::Bool(false) => 5,
+                Value::U8(v) => unsigned_len((*v).into()),
+                Value::U16(v) => unsigned_len((*v).into()),
+                Value::U32(v) => unsigned_len((*v).into()),
+                Value::U64(v) => unsigned_len((*v).into()),
+                Value::U128(v) => unsigned_len(**v),
+                Value::I8(v) => signed_len((*v).into()),
+                Value::I16(v) => signed_len((*v).into()),
+                Value::I32(v) => signed_len((*v).into()),
+                Value::I64(v) => signed_len((*v).into()),
+                Value::I128(v) => signed_len(**v),
+                // Non-finite floats are converted into `null`
+                Value::F32(v) if !v.is_finite() => 4,
+                Value::F64(v) if !v.is_finite() => 4,
+                // The longest representations are like `-1.1754944e-38` and `-2.2250738585072014e-308`
+                Value::F32(_) => 14,
+                Value::F64(_) => 23,
+                Value::Char(v) => v.len_utf8() + 2,
+                Value::Str(v) => v.len() + 2,
+                Value::SharedStr(v) => v.len() + 2,
+                Value::BorrowedStr(v) => v.len() + 2,
+                Value::Bytes(v) => bytes_len(v),
+                Value::BorrowedBytes(v) => bytes_len(v),
+                #[cfg(feature = "bytes")]
+                Value::SharedBytes(v) => bytes_len(v),
+                Value::RawJson(v) => v.len(),
+                Value::Some(v) => {
+                    stack.push((v, false));
+                    0
+                }
+                Value::NewtypeStruct(v) => {
+                    stack.push((&v.value, false));
+                    0
+                }
+                Value::Struct(v) => {
+                    stack.extend(v.fields.iter().map(|(_, v)| (v, false)));
+                    fields_len(&v.fields)
+                }
+                Value::Tuple(fields) | Value::Seq(fields, _) => {
+                    stack.extend(fields.iter().map(|v| (v, false)));
+                    seq_len(fields.len())
+                }
+                Value::TupleStruct(v) => {
+                    stack.extend(v.fields.iter().map(|v| (v, false)));
+                    seq_len(v.fields.len())
+                }
+                Value::UnitVariant(v) => v.variant.len() + 2,
+                Value::NewtypeVariant(v) => {
+                    stack.push((&v.value, false));
+                    variant_len(&v.variant)
+                }
+                Value::TupleVariant(v) => {
+                    stack.extend(v.fields.iter().map(|v| (v, false)));
+                    variant_len(&v.variant) + seq_len(v.fields.len())
+                }
+                Value::StructVariant(v) => {
+                    stack.extend(v.fields.iter().map(|(_, v)| (v, false)));
+                    variant_len(&v.variant) + fields_len(&v.fields)
+                }
+                Value::Map(fields, _) => {
+                    stack.extend(fields.iter().flat_map(|(k, v)| [(k, true), (v, false)]));
+                    // `{}`, plus a `:` for each entry and a `,` between them
+                    2 + (fields.len() * 2).saturating_sub(1)
+                }
+                Value::Borrowed(_) => unreachable!("borrowed values are resolved"),
+            };
+        }
+
+        len
+    }
+}
+
+fn unsigned_len(mut v: u128) -> usize {
+    let mut len = 1;
+
+    while v >= 10 {
+        v /= 10;
+        len += 1;
+    }
+
+    len
+}
+
+fn signed_len(v: i128) -> usize {
+    unsigned_len(v.unsigned_abs()) + usize::from(v < 0)
+}
+
+fn bytes_len(v: &[u8]) -> usize {
+    seq_len(v.len()) + v.iter().map(|b| unsigned_len((*b).into())).sum::<usize>()
+}
+
+fn seq_len(len: usize) -> usize {
+    // `[]`, plus a `,` between each field
+    2 + len.saturating_sub(1)
+}
+
+fn fields_len(fields: &[(Name, Value)]) -> usize {
+    // `{}`, plus a `"":` for each field and a `,` between them
+    2 + fields.len().saturating_sub(1) + fields.iter().map(|(k, _)| k.len() + 3).sum::<usize>()
+}
+
+fn variant_len(variant: &str) -> usize {
+    // `{"":}`
+    variant.len() + 5
+}
+
+/**
+A step in converting a value into a [`serde_json::Value`].
+*/
+enum Step<'a> {
+    /**
+    Convert a value, scheduling any values nested within it.
+    */
+    Convert(Value<'a>),
+    /**
+    Build a JSON value from the conversions of its nested values.
+    */
+    Build(Shell),
+}
+
+/**
+The parts of a JSON value that contains other values, without those values.
+*/
+enum Shell {
+    Array(usize),
+    Object(Vec<String>),
+    Variant(String),
+}
+
+/**
+Convert a value into a [`serde_json::Value`].
+
+Nested values are converted first onto a stack, and then popped off to build their parents, so
+converting deeply nested buffers can't overflow the stack.
+*/
+fn into_json(value: Value) -> Result<serde_json::Value, Error> {
+    let mut converted = Vec::new();
+    let mut steps = vec![Step::Convert(value)];
+
+    while let Some(step) = steps.pop() {
+        let mut value = match step {
+            Step::Convert(value) => value,
+            Step::Build(shell) => {
+                let value = shell.build(&mut converted);
+                converted.push(value);
+
+                continue;
+            }
+        };
+
+        converted.push(match value {
+            Value::Unit | Value::None | Value::UnitStruct(_) => serde_json::Value::Null,
+            Value::U8(v) => v.into(),
+            Value::U16(v) => v.into(),
+            Value::U32(v) => v.into(),
+            Value::U64(v) => v.into(),
+            Value::U128(ref v) => u64::try_from(**v)
+                .map_err(|_| Error::new(ErrorKind::NumberOutOfRange, "number out of range"))?
+                .into(),
+            Value::I8(v) => v.into(),
+            Value::I16(v) => v.into(),
+            Value::I32(v) => v.into(),
+            Value::I64(v) => v.into(),
+            Value::I128(ref v) => i64::try_from(**v)
+                .map_err(|_| Error::new(ErrorKind::NumberOutOfRange, "number out of range"))?
+                .into(),
+            Value::F32(v) => v.into(),
+            Value::F64(v) => v.into(),
+            Value::Bool(v) => v.into(),
+            Value::Char(v) => v.to_string().into(),
+            Value::Str(ref mut v) => String::from(mem::take(v)).into(),
+            Value::SharedStr(ref v) => (**v).into(),
+            Value::BorrowedStr(v) => v.into(),
+            Value::Bytes(ref v) => v.iter().copied().collect(),
+            Value::BorrowedBytes(v) => v.iter().copied().collect(),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(ref v) => v.iter().copied().collect(),
+            Value::RawJson(ref v) => parse_raw_json(v)?,
+            Value::UnitVariant(ref v) => v.variant.as_str().into(),
+            Value::Some(ref mut v) => {
+                steps.push(Step::Convert(v.take()));
+                continue;
+            }
+            Value::NewtypeStruct(ref mut v) => {
+                steps.push(Step::Convert(v.value.take()));
+                continue;
+            }
+            Value::Struct(ref mut v) => {
+                visit_fields(&mut steps, mem::take(&mut v.fields));
+                continue;
+            }
+            Value::Tuple(ref mut fields) | Value::Seq(ref mut fields, _) => {
+                visit_seq(&mut steps, mem::take(fields));
+                continue;
+            }
+            Value::TupleStruct(ref mut v) => {
+                visit_seq(&mut steps, mem::take(&mut v.fields));
+                continue;
+            }
+            Value::NewtypeVariant(ref mut v) => {
+                steps.push(Step::Build(Shell::Variant(v.variant.as_str().into())));
+                steps.push(Step::Convert(v.value.take()));
+                continue;
+            }
+            Value::TupleVariant(ref mut v) => {
+                steps.push(Step::Build(Shell::Variant(v.variant.as_str().into())));
+                visit_seq(&mut steps, mem::take(&mut v.fields));
+                continue;
+            }
+            Value::StructVariant(ref mut v) => {
+                steps.push(Step::Build(Shell::Variant(v.variant.as_str().into())));
+                visit_fields(&mut steps, mem::take(&mut v.fields));
+                continue;
+            }
+            Value::Map(ref mut fields, _) => {
+                let (keys, values): (Vec<_>, Vec<_>) =
+                    mem::take(fields).into_vec().into_iter().unzip();
+                let keys = keys
+                    .into_iter()
+                    .map(into_json_key)
+                    .collect::<Result<_, _>>()?;
+
+                visit(&mut steps, Shell::Object(keys), values);
+                continue;
+            }
+            Value::Borrowed(v) => {
+                steps.push(Step::Convert(v.clone()));
+                continue;
+            }
+        });
+    }
+
+    Ok(converted.pop().expect("missing converted value"))
+}
+
+/**
+Schedule `nested` to be converted before the JSON value described by `shell` is built from them.
+*/
+fn visit<'a>(steps: &mut Vec<Step<'a>>, shell: Shell, nested: Vec<Value<'a>>) {
+    steps.push(Step::Build(shell));
+
+    // Steps are popped in reverse, so nested values are converted in order
+    steps.extend(nested.into_iter().rev().map(Step::Convert));
+}
+
+fn visit_seq<'a>(steps: &mut Vec<Step<'a>>, fields: Box<[Value<'a>]>) {
+    visit(steps, Shell::Array(fields.len()), fields.into_vec());
+}
+
+fn visit_fields<'a>(steps: &mut Vec<Step<'a>>, fields: Box<[(Name<'a>, Value<'a>)]>) {
+    let (names, values) = fields
+        .into_vec()
+        .into_iter()
+        .map(|(k, v)| (String::from(k.as_str()), v))
+        .unzip();
+
+    visit(steps, Shell::Object(names), values);
+}
+
+impl Shell {
+    /**
+    Build a JSON value from the conversions of its nested values, taken from the end of `converted`.
+    */
+    fn build(self, converted: &mut Vec<serde_json::Value>) -> serde_json::Value {
+        match self {
+            Shell::Array(len) => {
+                serde_json::Value::Array(converted.split_off(converted.len() - len))
+            }
+            Shell::Object(keys) => {
+                let values = converted.split_off(converted.len() - keys.len());
+
+                serde_json::Value::Object(keys.into_iter().zip(values).collect())
+            }
+            Shell::Variant(variant) => {
+                let value = converted.pop().expect("missing converted value");
+
+                let mut map = serde_json::Map::new();
+                map.insert(variant, value);
+
+                serde_json::Value::Object(map)
+            }
+        }
+    }
+}
+
+fn into_json_key(mut key: Value) -> Result<String, Error> {
+    match key {
+        Value::Str(ref mut v) => Ok(mem::take(v).into()),
+        Value::SharedStr(ref v) => Ok((**v).into()),
+        Value::BorrowedStr(v) => Ok(v.into()),
+        Value::Char(v) => Ok(v.to_string()),
+        Value::U8(v) => Ok(v.to_string()),
+        Value::U16(v) => Ok(v.to_string()),
+        Value::U32(v) => Ok(v.to_string()),
+        Value::U64(v) => Ok(v.to_string()),
+        Value::U128(ref v) => Ok(v.to_string()),
+        Value::I8(v) => Ok(v.to_string()),
+        Value::I16(v) => Ok(v.to_string()),
+        Value::I32(v) => Ok(v.to_string()),
+        Value::I64(v) => Ok(v.to_string()),
+        Value::I128(ref v) => Ok(v.to_string()),
+        Value::UnitVariant(ref v) => Ok(v.variant.as_str().into()),
+        Value::NewtypeStruct(ref mut v) => into_json_key(v.value.take()),
+        Value::RawJson(ref v) => into_json_key(Value::parse_raw_json(v)?),
+        Value::Borrowed(v) => into_json_key(v.clone()),
+        _ => Err(Error::new(
+            ErrorKind::UnsupportedMapKey,
+            "key must be a string",
+        )),
+    }
+}
+
+fn json_eq(value: &Value, json: &serde_json::Value) -> bool {
+    use serde_json::Value as Json;
+
+    match (value.resolve(), json) {
+        (Value::RawJson(v), json) => parse_raw_json(v).is_ok_and(|v| v == *json),
+        (Value::Some(v), json) => json_eq(v, json),
+        (Value::NewtypeStruct(v), json) => json_eq(&v.value, json),
+        (Value::Unit | Value::None | Value::UnitStruct(_), Json::Null) => true,
+        (Value::F32(v), Json::Null) => !v.is_finite(),
+        (Value::F64(v), Json::Null) => !v.is_finite(),
+        (Value::F32(v), Json::Number(n)) => n.is_f64() && n.as_f64() == Some((*v).into()),
+        (Value::F64(v), Json::Number(n)) => n.is_f64() && n.as_f64() == Some(*v),
+        (v, Json::Number(n)) => {
+            if let Some(n) = n.as_u64() {
+                v.as_u128() == Some(n.into())
+            } else if let Some(n) = n.as_i64() {
+                v.as_i128() == Some(n.into())
+            } else {
+                false
+            }
+        }
+        (Value::Bool(v), Json::Bool(b)) => v == b,
+        (Value::Char(v), Json::String(s)) => {
+            let mut buf = [0; 4];
+            v.encode_utf8(&mut buf) == s
+        }
+        (Value::UnitVariant(v), Json::String(s)) => v.variant.as_str() == s,
+        (v, Json::String(s)) => v.as_str() == Some(s.as_str()),
+        (Value::Tuple(fields) | Value::Seq(fields, _), Json::Array(a)) => json_seq_eq(fields, a),
+        (Value::TupleStruct(v), Json::Array(a)) => json_seq_eq(&v.fields, a),
+        (v, Json::Array(a)) => match v.as_bytes() {
+            Some(bytes) => {
+                bytes.len() == a.len()
+                    && bytes
+                        .iter()
+                        .zip(a)
+                        .all(|(b, n)| n.as_u64() == Some((*b).into()))
+            }
+            None => false,
+        },
+        (Value::Struct(v), Json::Object(o)) => json_fields_eq(&v.fields, o),
+        (Value::NewtypeVariant(v), Json::Object(o)) => {
+            json_variant(&v.variant, o).is_some_and(|json| json_eq(&v.value, json))
+        }
+        (Value::TupleVariant(v), Json::Object(o)) => {
+            json_variant(&v.variant, o).is_some_and(|json| match json {
+                Json::Array(a) => json_seq_eq(&v.fields, a),
+                _ => false,
+            })
+        }
+        (Value::StructVariant(v), Json::Object(o)) => {
+            json_variant(&v.variant, o).is_some_and(|json| match json {
+                Json::Object(o) => json_fields_eq(&v.fields, o),
+                _ => false,
+            })
+        }
+        (Value::Map(fields, _), Json::Object(o)) => {
+            fields.len() == o.len()
+                && fields.iter().all(|(k, v)| {
+                    into_json_key(k.clone())
+                        .ok()
+                        .and_then(|k| o.get(&k))
+                        .is_some_and(|json| json_eq(v, json))
+                })
+        }
+        _ => false,
+    }
+}
+
+fn json_seq_eq(fields: &[Value], json: &[serde_json::Value]) -> bool {
+    fields.len() == json.len() && fields.iter().zip(json).all(|(v, json)| json_eq(v, json))
+}
+
+fn json_fields_eq(
+    fields: &[(Name, Value)],
+    json: &serde_json::Map<String, serde_json::Value>,
+) -> bool {
+    fields.len() == json.len()
+        && fields
+            .iter()
+            .all(|(k, v)| json.get(k.as_str()).is_some_and(|json| json_eq(v, json)))
+}
+
+/**
+Get the value of an externally tagged enum variant.
+*/
+fn json_variant<'j>(
+    variant: &str,
+    json: &'j serde_json::Map<String, serde_json::Value>,
+) -> Option<&'j serde_json::Value> {
+    if json.len() == 1 {
+        json.get(variant)
+    } else {
+        None
+    }
+}
+
+fn from_json(value: serde_json::Value) -> Value<'static> {
+    match value {
+        serde_json::Value::Null => Value::Unit,
+        serde_json::Value::Bool(v) => Value::Bool(v),
+        serde_json::Value::Number(v) => from_json_number(v),
+        serde_json::Value::String(v) => Value::Str(v.into()),
+        serde_json::Value::Array(v) => {
+            Value::Seq(v.into_iter().map(from_json).collect(), LenHint::Known)
+        }
+        serde_json::Value::Object(v) => Value::Map(
+            v.into_iter()
+                .map(|(k, v)| (Value::Str(k.into()), from_json(v)))
+                .collect(),
+            LenHint::Known,
+        ),
+    }
+}
+
+fn from_json_number(v: serde_json::Number) -> Value<'static> {
+    if let Some(v) = v.as_u64() {
+        Value::from_unsigned(v.into())
+    } else if let Some(v) = v.as_i64() {
+        Value::from_signed(v.into())
+    } else if let Some(f) = v.as_f64() {
+        Value::F64(f)
+    } else {
+        // Only possible with `serde_json`'s `arbitrary_precision` feature
+        Value::Str(v.to_string().into())
+    }
+}
+
+/**
+The name and field `serde_json` uses to serialize its `RawValue`.
+*/
+pub(crate) const RAW_VALUE_TOKEN: &str = "$serde_json::private::RawValue";
+
+impl<'a> Value<'a> {
+    /**
+    Get a raw JSON value from a struct, if it's how `serde_json` serializes a `RawValue`.
+    */
+    pub(crate) fn from_raw_json_struct(
+        name: &str,
+        fields: &[(Name<'a>, Value<'a>)],
+    ) -> Option<Value<'a>> {
+        match fields {
+            [(field, raw)] if name == RAW_VALUE_TOKEN && field.as_str() == RAW_VALUE_TOKEN => {
+                Some(Value::RawJson(raw.as_str()?.into()))
+            }
+            _ => None,
+        }
+    }
+
+    /**
+    Parse a raw JSON value into a regular value.
+
+    The value is parsed in the same way as a [`serde_json::Value`] converted into a buffer.
+    */
+    pub(crate) fn parse_raw_json(raw: &str) -> Result<Value<'static>, Error> {
+        parse_raw_json(raw).map(from_json)
+    }
+}
+
+fn parse_raw_json(raw: &str) -> Result<serde_json::Value, Error> {
+    serde_json::from_str(raw).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidValue,
+            format_args!("invalid raw JSON: {}", e),
+        )
+    })
+}
+
+/**
+Serialize a raw JSON value.
+
+The raw JSON is serialized in the same way as `serde_json`'s `RawValue`, so `serde_json` writes it
+as-is, without parsing and reformatting it. Other serializers see a struct with a single string
+field holding the raw JSON, just like they would for a `RawValue`.
+*/
+pub(crate) fn serialize_raw<S: serde::Serializer>(
+    raw: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut serializer = serializer.serialize_struct(RAW_VALUE_TOKEN, 1)?;
+    serializer.serialize_field(RAW_VALUE_TOKEN, raw)?;
+    serializer.end()
+}
+
+/**
+Serialize a raw JSON value as the key of a map entry.
+
+Map keys can't be written as raw JSON, so the raw JSON is parsed and its value is serialized instead.
+*/
+pub(crate) fn serialize_raw_key<S: serde::Serializer>(
+    raw: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    Value::parse_raw_json(raw)
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+/**
+Offer a raw JSON value to a visitor that's deserializing `serde_json`'s `RawValue`.
+*/
+pub(crate) fn deserialize_raw<'de, V: de::Visitor<'de>>(
+    raw: impl IntoDeserializer<'de, Error>,
+    visitor: V,
+) -> Result<V::Value, Error> {
+    visitor.visit_map(MapDeserializer::new(iter::once((RAW_VALUE_TOKEN, raw))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Kind, Ref};
+
+    #[test]
+    fn from_json_roundtrip() {
+        let json = r#"{"a":[1,-1,300,-300,70000,4294967296,-4294967296,1.5,0.1],"b":null,"c":{"d":true,"e":"a string"}}"#;
+
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let owned = Owned::from(value);
+
+        assert_eq!(json, serde_json::to_string(&owned).unwrap());
+
+        assert_eq!(Some(Ref::u8(1)), owned.get("a").unwrap().get_index(0));
+        assert_eq!(Some(Ref::i8(-1)), owned.get("a").unwrap().get_index(1));
+        assert_eq!(Some(Ref::u16(300)), owned.get("a").unwrap().get_index(2));
+        assert_eq!(Some(Ref::unit()), owned.get("b"));
+    }
+
+    #[test]
+    fn into_json_deeply_nested() {
+        let mut deep = Owned::from(1u8);
+        for i in 0..200_000 {
+            deep = match i % 4 {
+                0 => Owned::some(deep),
+                1 => Owned::seq([deep]),
+                2 => Owned::map([(Owned::from("a"), deep)]),
+                _ => Owned::newtype_variant("Enum", 0, "B", deep),
+            };
+        }
+
+        let mut json = vec![serde_json::Value::try_from(deep).unwrap()];
+
+        // `serde_json::Value` drops recursively, so take the converted value apart while checking it
+        let mut depth = 0;
+        while let Some(value) = json.pop() {
+            match value {
+                serde_json::Value::Array(mut values) => json.extend(values.pop()),
+                serde_json::Value::Object(mut values) => {
+                    json.extend(values.remove("a").or_else(|| values.remove("B")))
+                }
+                value => assert_eq!(serde_json::json!(1), value),
+            }
+
+            depth += 1;
+        }
+
+        // `Some` doesn't add a level of nesting
+        assert_eq!(150_001, depth);
+    }
+
+    #[test]
+    fn into_json() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(i32),
+            Tuple(i32, i32),
+            Struct { a: i32 },
+        }
+
+        #[derive(Serialize)]
+        struct Data<'a> {
+            a: Option<&'a str>,
+            #[serde(with = "serde_bytes_as_bytes")]
+            b: &'a [u8],
+            c: [Enum; 4],
+            d: alloc::collections::BTreeMap<u32, char>,
+            e: f64,
+        }
+
+        mod serde_bytes_as_bytes {
+            pub fn serialize<S: serde::Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_bytes(v)
+            }
+        }
+
+        let data = Data {
+            a: Some("a string"),
+            b: b"bytes",
+            c: [
+                Enum::Unit,
+                Enum::Newtype(1),
+                Enum::Tuple(1, 2),
+                Enum::Struct { a: 1 },
+            ],
+            d: [(1, 'a'), (2, 'b')].into_iter().collect(),
+            e: f64::NAN,
+        };
+
+        let owned = Owned::buffer(&data).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&data).unwrap(),
+            serde_json::Value::try_from(owned.as_ref()).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&data).unwrap(),
+            serde_json::Value::try_from(owned).unwrap()
+        );
+
+        assert!(serde_json::Value::try_from(Ref::map([(Ref::seq([]), Ref::unit())])).is_err());
+        assert!(serde_json::Value::try_from(Ref::u128(u128::MAX)).is_err());
+    }
+
+    #[test]
+    fn eq_json() {
+        use serde_derive::Serialize;
+        use serde_json::json;
+
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(i32),
+            Tuple(i32, i32),
+            Struct { a: i32 },
+        }
+
+        #[derive(Serialize)]
+        struct Data<'a> {
+            a: Option<&'a str>,
+            #[serde(with = "serde_bytes_as_bytes")]
+            b: &'a [u8],
+            c: [Enum; 4],
+            d: alloc::collections::BTreeMap<u32, char>,
+            e: (f32, f64, i64, u64),
+        }
+
+        mod serde_bytes_as_bytes {
+            pub fn serialize<S: serde::Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_bytes(v)
+            }
+        }
+
+        let data = Data {
+            a: Some("a string"),
+            b: b"bytes",
+            c: [
+                Enum::Unit,
+                Enum::Newtype(1),
+                Enum::Tuple(1, 2),
+                Enum::Struct { a: 1 },
+            ],
+            d: [(1, 'a'), (2, 'b')].into_iter().collect(),
+            e: (1.5, f64::NAN, i64::MIN, u64::MAX),
+        };
+
+        let owned = Owned::buffer(&data).unwrap();
+        let json = serde_json::to_value(&data).unwrap();
+
+        assert_eq!(owned, json);
+        assert_eq!(owned.as_ref(), json);
+        assert_eq!(json, owned);
+        assert_eq!(Owned::from(json.clone()), json);
+
+        // Integers and floats are never equal
+        assert_eq!(Ref::u8(1), json!(1));
+        assert_eq!(Ref::i64(-1), json!(-1));
+        assert_eq!(Ref::f32(1.0), json!(1.0));
+        assert_eq!(Ref::some(Ref::newtype_struct("A", Ref::u8(1))), json!(1));
+        assert_ne!(Ref::u8(1), json!(1.0));
+        assert_ne!(Ref::f64(1.0), json!(1));
+
+        assert_ne!(Ref::u128(u128::MAX), json!(u64::MAX));
+        assert_ne!(Ref::bytes(b"a"), json!("a"));
+        assert_ne!(Ref::str("a"), json!(["a"]));
+        assert_ne!(Ref::seq([Ref::u8(1)]), json!([1, 2]));
+        assert_ne!(
+            Ref::record_struct("A", [("a", Ref::u8(1))]),
+            json!({"b": 1})
+        );
+        assert_ne!(
+            Ref::newtype_variant("A", 0, "B", Ref::u8(1)),
+            json!({"B": 1, "C": 1})
+        );
+        assert_ne!(Ref::map([(Ref::seq([]), Ref::unit())]), json!({"[]": null}));
+    }
+
+    #[test]
+    fn serialized_len_hint() {
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(i32),
+            Tuple(i32, i32),
+            Struct { a: i32 },
+        }
+
+        #[derive(Serialize)]
+        struct Data<'a> {
+            a: Option<&'a str>,
+            b: Vec<u8>,
+            c: [Enum; 4],
+            d: alloc::collections::BTreeMap<u32, char>,
+            e: (i64, u128, bool, ()),
+        }
+
+        let data = Data {
+            a: Some("a string"),
+            b: vec![0, 10, 255],
+            c: [
+                Enum::Unit,
+                Enum::Newtype(-1),
+                Enum::Tuple(1, 2),
+                Enum::Struct { a: 1 },
+            ],
+            d: [(1, 'a'), (20, 'b')].into_iter().collect(),
+            e: (i64::MIN, u128::MAX, false, ()),
+        };
+
+        let owned = Owned::buffer(&data).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&data).unwrap().len(),
+            owned.serialized_len_hint()
+        );
+        assert_eq!(
+            owned.serialized_len_hint(),
+            owned.as_ref().serialized_len_hint()
+        );
+
+        for v in [0.1, -1.0e-300, f64::MAX, f64::NAN] {
+            assert!(serde_json::to_string(&v).unwrap().len() <= Ref::f64(v).serialized_len_hint());
+        }
+        assert!(
+            serde_json::to_string(&f32::MIN_POSITIVE).unwrap().len()
+                <= Ref::f32(f32::MIN_POSITIVE).serialized_len_hint()
+        );
+    }
+
+    #[test]
+    fn raw_json() {
+        use serde::Deserialize as _;
+        use serde_derive::{Deserialize, Serialize};
+        use serde_json::value::RawValue;
+
+        #[derive(Serialize, Deserialize)]
+        struct Data {
+            id: u64,
+            raw: alloc::boxed::Box<RawValue>,
+        }
+
+        let json = r#"{"id":1,"raw":{ "b" : [1, 2.50 ,"c"],"a":null }}"#;
+
+        let data: Data = serde_json::from_str(json).unwrap();
+        let owned = Owned::buffer(&data).unwrap();
+
+        // Raw JSON is written as-is
+        assert_eq!(json, serde_json::to_string(&owned).unwrap());
+        assert_eq!(json, serde_json::to_string(&owned.as_ref()).unwrap());
+        assert_eq!(json.len(), owned.serialized_len_hint());
+
+        // Raw JSON is kept when it's buffered again
+        assert_eq!(owned, Owned::buffer(&owned).unwrap());
+        assert_eq!(Kind::RawJson, owned.get("raw").unwrap().kind());
+        assert_eq!(None, owned.get("raw").unwrap().len());
+        assert_eq!(None, owned.get("raw").unwrap().get("a"));
+        assert_eq!(
+            r#"{ "b" : [1, 2.50 ,"c"],"a":null }"#,
+            owned.get("raw").unwrap().to_string()
+        );
+        assert_ne!(
+            Owned::from(serde_json::from_str::<serde_json::Value>(data.raw.get()).unwrap()),
+            owned.get("raw").unwrap()
+        );
+
+        // Other formats are given the raw JSON the same way as a `RawValue`
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            rmp_serde::to_vec(&data.raw).unwrap(),
+            rmp_serde::to_vec(&owned.get("raw").unwrap()).unwrap()
+        );
+        assert_eq!(parsed, serde_json::Value::try_from(owned.as_ref()).unwrap());
+        assert_eq!(owned, parsed);
+
+        // Raw JSON can be deserialized as raw JSON or as any other value
+        let de = Data::deserialize(owned.as_ref().into_deserializer()).unwrap();
+        assert_eq!(data.raw.get(), de.raw.get());
+
+        let de = Data::deserialize(owned.clone().into_deserializer()).unwrap();
+        assert_eq!(data.raw.get(), de.raw.get());
+
+        let raw = owned.get("raw").unwrap();
+        let de = <&RawValue>::deserialize(raw.clone().into_deserializer()).unwrap();
+        assert_eq!(data.raw.get(), de.get());
+
+        let de = alloc::collections::BTreeMap::<String, serde_json::Value>::deserialize(
+            raw.into_deserializer(),
+        )
+        .unwrap();
+        assert_eq!(Some(&serde_json::Value::Null), de.get("a"));
+    }
+
+    #[test]
+    fn raw_json_map_key() {
+        use serde_json::value::RawValue;
+
+        let raw = RawValue::from_string(r#""a""#.into()).unwrap();
+
+        let owned = Owned::map([(Owned::buffer(&raw).unwrap(), Owned::i32(1))]);
+
+        assert_eq!(r#"{"a":1}"#, serde_json::to_string(&owned).unwrap());
+        assert_eq!(
+            serde_json::json!({ "a": 1 }),
+            serde_json::Value::try_from(owned).unwrap()
+        );
+    }
+}
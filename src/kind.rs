@@ -0,0 +1,145 @@
+use crate::{Owned, Ref, Value};
+
+/**
+The coarse shape of a buffered value.
+
+Buffers retain more detail about their values than this enum captures, such as the width of integers
+or the names of structs. Those details are still used when the buffer is serialized.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Kind {
+    /**
+    A `()` value.
+    */
+    Unit,
+    /**
+    A boolean value.
+    */
+    Bool,
+    /**
+    A signed or unsigned integer value of any width.
+    */
+    Int,
+    /**
+    A binary floating point value of any width.
+    */
+    Float,
+    /**
+    A single character value.
+    */
+    Char,
+    /**
+    An owned or borrowed string value.
+    */
+    Str,
+    /**
+    An owned or borrowed byte-string value.
+    */
+    Bytes,
+    /**
+    Raw JSON that's buffered as-is, without being parsed.
+
+    Raw JSON doesn't have any fields or elements of its own. It can be deserialized into any value
+    its JSON represents.
+    */
+    #[cfg(feature = "json")]
+    RawJson,
+    /**
+    An `Option::None` or `Option::Some` value.
+    */
+    Option,
+    /**
+    A sequence or tuple.
+    */
+    Seq,
+    /**
+    A map.
+    */
+    Map,
+    /**
+    A unit, newtype, tuple, or record struct.
+    */
+    Struct,
+    /**
+    A unit, newtype, tuple, or record enum variant.
+    */
+    Enum,
+}
+
+impl Owned {
+    /**
+    Get the kind of value in this buffer.
+    */
+    pub fn kind(&self) -> Kind {
+        self.0.kind()
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Get the kind of value in this buffer.
+    */
+    pub fn kind(&self) -> Kind {
+        self.0.kind()
+    }
+}
+
+impl<'a> Value<'a> {
+    pub(crate) fn kind(&self) -> Kind {
+        match *self {
+            Value::Unit => Kind::Unit,
+            Value::Bool(_) => Kind::Bool,
+            Value::U8(_)
+            | Value::U16(_)
+            | Value::U32(_)
+            | Value::U64(_)
+            | Value::U128(_)
+            | Value::I8(_)
+            | Value::I16(_)
+            | Value::I32(_)
+            | Value::I64(_)
+            | Value::I128(_) => Kind::Int,
+            Value::F32(_) | Value::F64(_) => Kind::Float,
+            Value::Char(_) => Kind::Char,
+            Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_) => Kind::Str,
+            Value::Bytes(_) | Value::BorrowedBytes(_) => Kind::Bytes,
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(_) => Kind::Bytes,
+            #[cfg(feature = "json")]
+            Value::RawJson(_) => Kind::RawJson,
+            Value::None | Value::Some(_) => Kind::Option,
+            Value::Seq(_, _) | Value::Tuple(_) => Kind::Seq,
+            Value::Map(_, _) => Kind::Map,
+            Value::UnitStruct(_)
+            | Value::NewtypeStruct(_)
+            | Value::TupleStruct(_)
+            | Value::Struct(_) => Kind::Struct,
+            Value::UnitVariant(_)
+            | Value::NewtypeVariant(_)
+            | Value::TupleVariant(_)
+            | Value::StructVariant(_) => Kind::Enum,
+            Value::Borrowed(v) => v.kind(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind() {
+        assert_eq!(Kind::Int, Ref::u8(1).kind());
+        assert_eq!(Kind::Int, Ref::i128(1).kind());
+        assert_eq!(Kind::Str, Ref::str("a").kind());
+        assert_eq!(Kind::Option, Ref::some(Ref::unit()).kind());
+        assert_eq!(Kind::Seq, Ref::tuple([]).kind());
+        assert_eq!(Kind::Struct, Ref::tuple_struct("Struct", []).kind());
+        assert_eq!(Kind::Enum, Ref::unit_variant("Enum", 0, "Variant").kind());
+
+        let owned = Owned::buffer(1.0f32).unwrap();
+        assert_eq!(Kind::Float, owned.kind());
+        assert_eq!(Kind::Float, owned.as_ref().kind());
+    }
+}
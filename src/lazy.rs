@@ -0,0 +1,86 @@
+use core::{cell::Cell, fmt};
+
+use serde::{
+    ser::{Error as _, SerializeSeq},
+    Serialize, Serializer,
+};
+
+/**
+A sequence that serializes the items of an iterator as they're produced.
+
+This is a streaming counterpart to buffering: items are passed straight to the serializer without
+ever being collected in memory. The length of the sequence is reported as unknown to the serializer,
+even if the iterator knows its length, so formats that need a length upfront may not support it.
+
+Iterators can only be consumed once, so a `LazySeq` can only be serialized once.
+Serializing it again produces an error.
+*/
+pub struct LazySeq<I>(Cell<Option<I>>);
+
+impl<I: Iterator> LazySeq<I> {
+    /**
+    Create a sequence that will serialize the items of `iter`.
+    */
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        LazySeq(Cell::new(Some(iter.into_iter())))
+    }
+}
+
+impl<I> fmt::Debug for LazySeq<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LazySeq").finish_non_exhaustive()
+    }
+}
+
+impl<I: Iterator> Serialize for LazySeq<I>
+where
+    I::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let iter = self
+            .0
+            .take()
+            .ok_or_else(|| S::Error::custom("the sequence has already been serialized"))?;
+
+        let mut seq = serializer.serialize_seq(None)?;
+
+        for item in iter {
+            seq.serialize_element(&item)?;
+        }
+
+        seq.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Owned, Ref};
+    use serde_test::Token;
+
+    #[test]
+    fn lazy_seq() {
+        let seq = LazySeq::new((1..=3).map(|i| Ref::u8(i * 2)));
+
+        assert_eq!("[2,4,6]", serde_json::to_string(&seq).unwrap());
+        assert!(serde_json::to_string(&seq).is_err());
+
+        let buf = Owned::buffer(LazySeq::new(["a", "b"])).unwrap();
+        assert_eq!(Ref::seq([Ref::str("a"), Ref::str("b")]), buf.as_ref());
+
+        // The length is unknown, even though the iterator knows it
+        serde_test::assert_ser_tokens(
+            &buf,
+            &[
+                Token::Seq { len: None },
+                Token::Str("a"),
+                Token::Str("b"),
+                Token::SeqEnd,
+            ],
+        );
+    }
+}
@@ -59,7 +59,6 @@ Once you've got an [`Owned`] or [`Ref`] buffer, you can then later use their own
 implementations to encode in some format:
 
 ```
-# use serde::Serialize;
 # use serde_derive::Serialize;
 # use serde_buf::Owned;
 # fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -97,7 +96,7 @@ implementations:
 #         ("content", Ref::str(data.content)),
 #     ])
 # }
-# use serde::{Deserialize, de::IntoDeserializer};
+# use serde::{Deserialize as _, de::IntoDeserializer};
 # use serde_derive::Deserialize;
 # use serde_buf::Ref;
 #[derive(Deserialize, Debug, PartialEq)]
@@ -120,6 +119,33 @@ assert_eq!(data, deserialized);
 # }
 ```
 
+References to buffers can also be deserialized without consuming them. Any strings in the buffer,
+including owned ones, can then be borrowed by the deserialized value:
+
+```
+# use serde::{Deserialize as _, de::IntoDeserializer};
+# use serde_derive::{Serialize, Deserialize};
+# use serde_buf::Owned;
+# fn main() -> Result<(), serde_buf::Error> {
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct MyData<'a> {
+    id: u64,
+    content: &'a str,
+}
+
+let buffer = Owned::buffer(&MyData {
+    id: 42,
+    content: "Some content",
+})?;
+
+let first = MyData::deserialize((&buffer).into_deserializer())?;
+let second = MyData::deserialize((&buffer).into_deserializer())?;
+
+assert_eq!(first, second);
+# Ok(())
+# }
+```
+
 # Deserializing directly to a buffer
 
 The [`Ref`] and [`Owned`] types don't implement [`serde::Deserialize`] and can't be deserialized directly.
@@ -145,6 +171,41 @@ let buffer = Owned::buffer(&serde_json::from_str::<MyData>(&json)?)?;
 # Ok(())
 # }
 ```
+
+For self-describing formats like JSON, you can also buffer whatever value a deserializer contains
+using [`Owned::buffer_from_deserializer`], but enums will be buffered in their externally tagged form,
+as maps or strings. These can still be deserialized back into enums:
+
+```
+# use serde_buf::Owned;
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let mut de = serde_json::Deserializer::from_str(r#"{"Full":{"id":42,"content":"Some content"}}"#);
+
+let buffer = Owned::buffer_from_deserializer(&mut de)?;
+# Ok(())
+# }
+```
+
+# Cargo features
+
+- `std`: Implement `std::error::Error` for [`Error`], with support for capturing an underlying source error.
+  Also enables sharing the storage of equal strings with `StringInterner` and `Serializer::with_string_interning`.
+- `json`: Conversions between buffers and `serde_json::Value`.
+- `cbor`: Conversions between buffers and `ciborium::value::Value`.
+- `rmpv`: Conversions between buffers and `rmpv::Value`.
+- `serde-value`: Conversions between buffers and `serde_value::Value`.
+- `sval`: Streaming buffers as `sval::Value`s and buffering `sval::Value`s.
+- `bumpalo`: Buffering values with their strings and bytes allocated in a `bumpalo::Bump` arena.
+- `valuable`: Inspecting buffers as `valuable::Valuable`s.
+- `bytes`: Buffering byte-strings held in a `bytes::Bytes` without copying them.
+- `rc`: Sharing owned buffers between many consumers with `SharedOwned`.
+- `compact_str`: Storing short owned strings inline instead of allocating them.
+- `proptest`: Generating arbitrary buffers with `proptest`, configured by `ArbitraryOwned`.
+- `arbitrary`: Generating arbitrary buffers from unstructured input with `arbitrary`, for fuzzing.
+- `serde_test`: Converting buffers to and from `serde_test::Token` streams.
+- `flexbuffers`: Conversions between buffers and `flexbuffers::Reader`s and `flexbuffers::Builder`s.
+- `toml`: Conversions between buffers and `toml::Value`.
+- `yaml`: Conversions between buffers and `serde_yaml::Value`.
 */
 
 #![deny(missing_docs)]
@@ -152,40 +213,170 @@ let buffer = Owned::buffer(&serde_json::from_str::<MyData>(&json)?)?;
 
 extern crate alloc;
 
-use core::{borrow::Borrow, fmt};
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+use core::borrow::Borrow;
 
-use alloc::{boxed::Box, string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use serde::Serialize;
 
+use self::owned_str::OwnedStr;
+
+mod access;
+mod build;
+mod bytes;
+mod canonical;
+mod clone;
+mod cmp;
+mod convert;
 mod de;
+mod display;
+mod drop;
+mod edit;
+mod error;
+mod float;
+mod guard;
+mod hash;
+#[cfg(feature = "std")]
+mod intern;
+mod into_static;
+mod kind;
+mod lazy;
+mod merge;
+mod name;
+mod owned_str;
+mod pointer;
+mod prune;
+mod rename;
+mod select;
 mod ser;
+mod size;
+mod transform;
+mod unique;
+mod visit;
+mod walk;
 
-pub use self::{de::Deserializer, ser::Serializer};
+#[cfg(feature = "json")]
+mod json;
 
-/**
-An error encountered while buffering a value.
-*/
-#[derive(Debug)]
-pub struct Error(String);
+#[cfg(feature = "cbor")]
+mod cbor;
 
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "error buffering a value")
-    }
-}
+#[cfg(feature = "rmpv")]
+mod msgpack;
+
+#[cfg(feature = "serde-value")]
+mod serde_value;
+
+#[cfg(feature = "sval")]
+mod sval;
+
+#[cfg(feature = "bumpalo")]
+mod arena;
+
+#[cfg(feature = "valuable")]
+mod valuable;
+
+#[cfg(feature = "bytes")]
+mod shared_bytes;
+
+#[cfg(feature = "rc")]
+mod shared;
+
+#[cfg(feature = "proptest")]
+mod proptest;
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+
+#[cfg(feature = "serde_test")]
+mod tokens;
+
+#[cfg(feature = "flexbuffers")]
+mod flexbuffers;
 
-impl serde::ser::StdError for Error {}
+#[cfg(feature = "toml")]
+mod toml;
+
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use self::{
+    build::{MapBuilder, SeqBuilder, StructBuilder},
+    de::{Deserializer, RefDeserializer},
+    error::{Error, ErrorKind},
+    guard::{BytesBuf, StrBuf},
+    kind::Kind,
+    lazy::LazySeq,
+    name::Name,
+    prune::PruneNulls,
+    rename::Case,
+    ser::Serializer,
+    visit::transcode,
+    walk::BufVisitor,
+};
+
+#[cfg(feature = "bumpalo")]
+pub use self::arena::ArenaSerializer;
+
+#[cfg(feature = "rc")]
+pub use self::shared::SharedOwned;
+
+#[cfg(feature = "std")]
+pub use self::intern::StringInterner;
+
+#[cfg(feature = "proptest")]
+pub use self::proptest::ArbitraryOwned;
 
 /**
 A fully owned value.
+
+# Equality, ordering, and hashing
+
+Buffers are compared structurally, so an owned string is equal to a borrowed one with the same contents.
+Integers and floats are only equal to other numbers of the same width.
+Floats are compared by value, except that all `NaN`s are considered equal to each other so that buffers
+can implement [`Eq`]. Hashing is consistent with equality: all `NaN`s hash the same, and `-0.0` hashes
+the same as `0.0`.
+
+Buffers are ordered first by their [`Kind`], and then by their contents. Numbers are ordered by value
+across widths, with narrower types sorting first when their values are equal. Floats are totally ordered,
+with `NaN` sorting after all other values.
+
+# Borrowing
+
+`Owned` implements [`Borrow<Ref<'static>>`](Borrow), so maps and sets keyed by owned buffers can be
+queried with a `&Ref<'static>`. Both buffers compare and hash the same way, as `Borrow` requires.
+There's no `ToOwned` impl bridging the other way, because `Ref` already gets one from its `Clone`
+impl. That means a `Cow<'_, Ref<'static>>` owns a `Ref<'static>`, which can be converted into an
+`Owned` with [`Ref::into_owned`].
 */
 #[derive(Clone, Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[repr(transparent)]
 pub struct Owned(Value<'static>);
 
+impl Default for Owned {
+    /**
+    Create a buffer for a `()` value.
+    */
+    fn default() -> Self {
+        Owned::unit()
+    }
+}
+
+impl<'a> Default for Ref<'a> {
+    /**
+    Create a buffer for a `()` value.
+    */
+    fn default() -> Self {
+        Ref::unit()
+    }
+}
+
 impl From<Ref<'static>> for Owned {
     fn from(value: Ref<'static>) -> Self {
-        Owned(value.0)
+        Owned(value.into_value())
     }
 }
 
@@ -198,20 +389,60 @@ impl Owned {
     pub fn buffer(v: impl Serialize) -> Result<Self, Error> {
         v.serialize(Serializer::new())
     }
+
+    /**
+    Buffer the value behind the reference `v` into an owned buffer.
+
+    This is like [`Owned::buffer`], but accepts unsized values like `str` and `[u8]`.
+    The resulting buffer is guaranteed to serialize to the same value as `v`.
+    */
+    pub fn buffer_ref<T: Serialize + ?Sized>(v: &T) -> Result<Self, Error> {
+        v.serialize(Serializer::new())
+    }
+
+    /**
+    Buffer the items in `iter` into an owned sequence buffer.
+
+    Each item is buffered as it's yielded, without collecting `iter` first.
+    If any item fails to buffer then buffering stops and the error is returned.
+    The resulting buffer is guaranteed to serialize to the same value as `iter` would when
+    serialized as a sequence.
+    */
+    pub fn buffer_iter<T: Serialize>(iter: impl IntoIterator<Item = T>) -> Result<Self, Error> {
+        Ok(Owned(Value::buffer_iter(iter)?))
+    }
+
+    /**
+    Get a borrowed buffer that views the contents of this one.
+
+    The resulting buffer doesn't allocate or copy any part of the original.
+    It's guaranteed to serialize to the same value as `self`.
+    */
+    pub fn as_ref(&self) -> Ref<'_> {
+        Ref(Value::Borrowed(&self.0))
+    }
 }
 
 /**
 A partly owned value.
 
 This buffer allows strings to be borrowed internally.
+It follows the same rules for equality, ordering, and hashing as [`Owned`].
 */
 #[derive(Clone, Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[repr(transparent)]
 pub struct Ref<'a>(Value<'a>);
 
 impl From<Owned> for Ref<'static> {
     fn from(value: Owned) -> Self {
-        Ref(value.0)
+        Ref(value.into_value())
+    }
+}
+
+impl Borrow<Ref<'static>> for Owned {
+    fn borrow(&self) -> &Ref<'static> {
+        // SAFETY: `Owned` and `Ref<'static>` are both `#[repr(transparent)]` wrappers around `Value<'static>`
+        unsafe { &*(&self.0 as *const Value<'static> as *const Ref<'static>) }
     }
 }
 
@@ -224,48 +455,81 @@ impl Ref<'static> {
     pub fn buffer(v: impl Serialize) -> Result<Self, Error> {
         Ok(v.serialize(Serializer::new())?.into())
     }
+
+    /**
+    Buffer the value behind the reference `v` into an owned buffer.
+
+    This is like [`Ref::buffer`], but accepts unsized values like `str` and `[u8]`.
+    The resulting buffer is guaranteed to serialize to the same value as `v`.
+    */
+    pub fn buffer_ref<T: Serialize + ?Sized>(v: &T) -> Result<Self, Error> {
+        Ok(v.serialize(Serializer::new())?.into())
+    }
+
+    /**
+    Buffer the items in `iter` into an owned sequence buffer.
+
+    Each item is buffered as it's yielded, without collecting `iter` first.
+    If any item fails to buffer then buffering stops and the error is returned.
+    The resulting buffer is guaranteed to serialize to the same value as `iter` would when
+    serialized as a sequence.
+    */
+    pub fn buffer_iter<T: Serialize>(iter: impl IntoIterator<Item = T>) -> Result<Self, Error> {
+        Ok(Ref(Value::buffer_iter(iter)?))
+    }
 }
 
 impl<'a> Ref<'a> {
+    /**
+    Convert this buffer into a fully owned one.
+
+    Any strings or byte-strings borrowed by the buffer will be copied.
+    Values that are already owned are moved into the new buffer as-is.
+    The resulting buffer is guaranteed to serialize to the same value as `self`.
+    */
+    pub fn into_owned(self) -> Owned {
+        Owned(self.into_value().into_static())
+    }
+
     /**
     Create a buffer for a `()` value.
     */
-    pub fn unit() -> Self {
+    pub const fn unit() -> Self {
         Ref(Value::Unit)
     }
 
     /**
     Create a buffer for a boolean value.
     */
-    pub fn bool(v: bool) -> Self {
+    pub const fn bool(v: bool) -> Self {
         Ref(Value::Bool(v))
     }
 
     /**
     Create a buffer for an unsigned integer value.
     */
-    pub fn u8(v: u8) -> Self {
+    pub const fn u8(v: u8) -> Self {
         Ref(Value::U8(v))
     }
 
     /**
     Create a buffer for an unsigned integer value.
     */
-    pub fn u16(v: u16) -> Self {
+    pub const fn u16(v: u16) -> Self {
         Ref(Value::U16(v))
     }
 
     /**
     Create a buffer for an unsigned integer value.
     */
-    pub fn u32(v: u32) -> Self {
+    pub const fn u32(v: u32) -> Self {
         Ref(Value::U32(v))
     }
 
     /**
     Create a buffer for an unsigned integer value.
     */
-    pub fn u64(v: u64) -> Self {
+    pub const fn u64(v: u64) -> Self {
         Ref(Value::U64(v))
     }
 
@@ -273,34 +537,34 @@ impl<'a> Ref<'a> {
     Create a buffer for an unsigned integer value.
     */
     pub fn u128(v: u128) -> Self {
-        Ref(Value::U128(v))
+        Ref(Value::U128(Box::new(v)))
     }
 
     /**
     Create a buffer for a signed integer value.
     */
-    pub fn i8(v: i8) -> Self {
+    pub const fn i8(v: i8) -> Self {
         Ref(Value::I8(v))
     }
 
     /**
     Create a buffer for a signed integer value.
     */
-    pub fn i16(v: i16) -> Self {
+    pub const fn i16(v: i16) -> Self {
         Ref(Value::I16(v))
     }
 
     /**
     Create a buffer for a signed integer value.
     */
-    pub fn i32(v: i32) -> Self {
+    pub const fn i32(v: i32) -> Self {
         Ref(Value::I32(v))
     }
 
     /**
     Create a buffer for a signed integer value.
     */
-    pub fn i64(v: i64) -> Self {
+    pub const fn i64(v: i64) -> Self {
         Ref(Value::I64(v))
     }
 
@@ -308,27 +572,27 @@ impl<'a> Ref<'a> {
     Create a buffer for a signed integer value.
     */
     pub fn i128(v: i128) -> Self {
-        Ref(Value::I128(v))
+        Ref(Value::I128(Box::new(v)))
     }
 
     /**
     Create a buffer for a binary floating point value.
     */
-    pub fn f32(v: f32) -> Self {
+    pub const fn f32(v: f32) -> Self {
         Ref(Value::F32(v))
     }
 
     /**
     Create a buffer for a binary floating point value.
     */
-    pub fn f64(v: f64) -> Self {
+    pub const fn f64(v: f64) -> Self {
         Ref(Value::F64(v))
     }
 
     /**
     Create a buffer for a single character value.
     */
-    pub fn char(v: char) -> Self {
+    pub const fn char(v: char) -> Self {
         Ref(Value::Char(v))
     }
 
@@ -336,7 +600,7 @@ impl<'a> Ref<'a> {
     Create a buffer for an owned string value.
     */
     pub fn owned_str(v: impl Into<String>) -> Self {
-        Ref(Value::Str(v.into().into_boxed_str()))
+        Ref(Value::Str(v.into().into()))
     }
 
     /**
@@ -363,7 +627,7 @@ impl<'a> Ref<'a> {
     /**
     Create a buffer for an `Option::None` value.
     */
-    pub fn none() -> Self {
+    pub const fn none() -> Self {
         Ref(Value::None)
     }
 
@@ -371,55 +635,62 @@ impl<'a> Ref<'a> {
     Create a buffer for an `Option::Some` value.
     */
     pub fn some(v: impl Into<Ref<'a>>) -> Self {
-        Ref(Value::Some(Box::new(v.into().0)))
+        Ref(Value::Some(Box::new(v.into().into_value())))
     }
 
     /**
     Create a buffer for a unit struct, like `struct A`.
     */
-    pub fn unit_struct(name: &'static str) -> Self {
-        Ref(Value::UnitStruct { name })
+    pub fn unit_struct(name: impl Into<Name<'a>>) -> Self {
+        Ref(Value::UnitStruct(Box::new(UnitStruct {
+            name: name.into(),
+        })))
     }
 
     /**
     Create a buffer for a newtype struct, like `struct A(T)`.
     */
-    pub fn newtype_struct(name: &'static str, value: impl Into<Ref<'a>>) -> Self {
-        Ref(Value::NewtypeStruct {
-            name,
-            value: Box::new(value.into().0),
-        })
+    pub fn newtype_struct(name: impl Into<Name<'a>>, value: impl Into<Ref<'a>>) -> Self {
+        Ref(Value::NewtypeStruct(Box::new(NewtypeStruct {
+            name: name.into(),
+            value: value.into().into_value(),
+        })))
     }
 
     /**
     Create a buffer for a struct with named fields, like `struct A { a: T, b: U }`.
+
+    If any field names aren't `'static` then the struct is serialized as a map. See [`Name`] for details.
     */
-    pub fn record_struct(
-        name: &'static str,
-        fields: impl IntoIterator<Item = (&'static str, Ref<'a>)>,
+    pub fn record_struct<K: Into<Name<'a>>>(
+        name: impl Into<Name<'a>>,
+        fields: impl IntoIterator<Item = (K, Ref<'a>)>,
     ) -> Self {
-        Ref(Value::Struct {
-            name,
+        Ref(Value::Struct(Box::new(Struct {
+            name: name.into(),
             fields: fields
                 .into_iter()
-                .map(|(k, v)| (k, v.0))
+                .map(|(k, v)| (k.into(), v.into_value()))
                 .collect::<Vec<_>>()
                 .into_boxed_slice(),
-        })
+        })))
     }
 
     /**
     Create a buffer for a struct with unnamed fields, like `struct A(T, U)`.
     */
-    pub fn tuple_struct(name: &'static str, fields: impl IntoIterator<Item = Ref<'a>>) -> Self {
-        Ref(Value::TupleStruct {
-            name,
+    pub fn tuple_struct(
+        name: impl Into<Name<'a>>,
+        fields: impl IntoIterator<Item = Ref<'a>>,
+    ) -> Self {
+        Ref(Value::TupleStruct(Box::new(TupleStruct {
+            name: name.into(),
             fields: fields
                 .into_iter()
-                .map(|v| v.0)
+                .map(Ref::into_value)
                 .collect::<Vec<_>>()
                 .into_boxed_slice(),
-        })
+        })))
     }
 
     /**
@@ -429,7 +700,7 @@ impl<'a> Ref<'a> {
         Ref(Value::Tuple(
             fields
                 .into_iter()
-                .map(|v| v.0)
+                .map(Ref::into_value)
                 .collect::<Vec<_>>()
                 .into_boxed_slice(),
         ))
@@ -438,71 +709,77 @@ impl<'a> Ref<'a> {
     /**
     Create a buffer for a unit enum variant, like `A::B`.
     */
-    pub fn unit_variant(name: &'static str, variant_index: u32, variant: &'static str) -> Self {
-        Ref(Value::UnitVariant {
-            name,
+    pub fn unit_variant(
+        name: impl Into<Name<'a>>,
+        variant_index: u32,
+        variant: impl Into<Name<'a>>,
+    ) -> Self {
+        Ref(Value::UnitVariant(Box::new(UnitVariant {
+            name: name.into(),
             variant_index,
-            variant,
-        })
+            variant: variant.into(),
+        })))
     }
 
     /**
     Create a buffer for a newtype enum variant, like `A::B(T)`.
     */
     pub fn newtype_variant(
-        name: &'static str,
+        name: impl Into<Name<'a>>,
         variant_index: u32,
-        variant: &'static str,
+        variant: impl Into<Name<'a>>,
         value: impl Into<Ref<'a>>,
     ) -> Self {
-        Ref(Value::NewtypeVariant {
-            name,
+        Ref(Value::NewtypeVariant(Box::new(NewtypeVariant {
+            name: name.into(),
             variant_index,
-            variant,
-            value: Box::new(value.into().0),
-        })
+            variant: variant.into(),
+            value: value.into().into_value(),
+        })))
     }
 
     /**
     Create a buffer for an enum variant with unnamed fields, like `A::B(T, U)`.
     */
     pub fn tuple_variant(
-        name: &'static str,
+        name: impl Into<Name<'a>>,
         variant_index: u32,
-        variant: &'static str,
+        variant: impl Into<Name<'a>>,
         fields: impl IntoIterator<Item = Ref<'a>>,
     ) -> Self {
-        Ref(Value::TupleVariant {
-            name,
+        Ref(Value::TupleVariant(Box::new(TupleVariant {
+            name: name.into(),
             variant_index,
-            variant,
+            variant: variant.into(),
             fields: fields
                 .into_iter()
-                .map(|v| v.0)
+                .map(Ref::into_value)
                 .collect::<Vec<_>>()
                 .into_boxed_slice(),
-        })
+        })))
     }
 
     /**
     Create a buffer for an enum variant with named fields, like `A::B { a: T, b: U }`.
+
+    If any field names aren't `'static` then the variant's fields are serialized as a map. See [`Name`] for details.
     */
-    pub fn record_struct_variant(
-        name: &'static str,
+    pub fn record_struct_variant<K: Into<Name<'a>>>(
+        name: impl Into<Name<'a>>,
         variant_index: u32,
-        variant: &'static str,
-        fields: impl IntoIterator<Item = (&'static str, Ref<'a>)>,
+        variant: impl Into<Name<'a>>,
+        fields: impl IntoIterator<Item = (K, Ref<'a>)>,
     ) -> Self {
-        Ref(Value::StructVariant {
-            name,
+        Ref(Value::StructVariant(Box::new(StructVariant {
+            name: name.into(),
             variant_index,
-            variant,
+            variant: variant.into(),
             fields: fields
                 .into_iter()
-                .map(|(k, v)| (k, v.0))
+                .map(|(k, v)| (k.into(), v.into_value()))
                 .collect::<Vec<_>>()
                 .into_boxed_slice(),
-        })
+        })))
     }
 
     /**
@@ -512,101 +789,566 @@ impl<'a> Ref<'a> {
         Ref(Value::Seq(
             fields
                 .into_iter()
-                .map(|v| v.0)
+                .map(Ref::into_value)
                 .collect::<Vec<_>>()
                 .into_boxed_slice(),
+            LenHint::Known,
         ))
     }
 
     /**
     Create a buffer for a map.
+
+    Keys can be any kind of value, not just strings. Maps with integer keys, like the ones
+    common in CBOR and MessagePack, or with composite keys like tuples and structs, are offered
+    to the value they're deserialized into with their keys intact. Entries are kept in the order
+    they're given, and their keys aren't checked for uniqueness. Use [`Ref::keyed_get`] to look
+    up an entry by a key that isn't a string.
     */
     pub fn map(fields: impl IntoIterator<Item = (Ref<'a>, Ref<'a>)>) -> Self {
         Ref(Value::Map(
             fields
                 .into_iter()
-                .map(|(k, v)| (k.0, v.0))
+                .map(|(k, v)| (k.into_value(), v.into_value()))
                 .collect::<Vec<_>>()
                 .into_boxed_slice(),
+            LenHint::Known,
         ))
     }
+
+    /**
+    Create a builder for a struct with named fields.
+
+    This is an alternative to [`Ref::record_struct`] for structs with many or optional fields.
+    */
+    pub fn struct_builder(name: impl Into<Name<'a>>) -> StructBuilder<'a> {
+        StructBuilder::new(name)
+    }
+
+    /**
+    Create a builder for a map.
+
+    This is an alternative to [`Ref::map`] for maps with many or optional entries.
+    */
+    pub fn map_builder() -> MapBuilder<'a> {
+        MapBuilder::new()
+    }
+}
+
+impl Owned {
+    /**
+    Create a buffer for a `()` value.
+    */
+    pub const fn unit() -> Self {
+        Owned(Value::Unit)
+    }
+
+    /**
+    Create a buffer for a boolean value.
+    */
+    pub const fn bool(v: bool) -> Self {
+        Owned(Value::Bool(v))
+    }
+
+    /**
+    Create a buffer for an unsigned integer value.
+    */
+    pub const fn u8(v: u8) -> Self {
+        Owned(Value::U8(v))
+    }
+
+    /**
+    Create a buffer for an unsigned integer value.
+    */
+    pub const fn u16(v: u16) -> Self {
+        Owned(Value::U16(v))
+    }
+
+    /**
+    Create a buffer for an unsigned integer value.
+    */
+    pub const fn u32(v: u32) -> Self {
+        Owned(Value::U32(v))
+    }
+
+    /**
+    Create a buffer for an unsigned integer value.
+    */
+    pub const fn u64(v: u64) -> Self {
+        Owned(Value::U64(v))
+    }
+
+    /**
+    Create a buffer for an unsigned integer value.
+    */
+    pub fn u128(v: u128) -> Self {
+        Ref::u128(v).into()
+    }
+
+    /**
+    Create a buffer for a signed integer value.
+    */
+    pub const fn i8(v: i8) -> Self {
+        Owned(Value::I8(v))
+    }
+
+    /**
+    Create a buffer for a signed integer value.
+    */
+    pub const fn i16(v: i16) -> Self {
+        Owned(Value::I16(v))
+    }
+
+    /**
+    Create a buffer for a signed integer value.
+    */
+    pub const fn i32(v: i32) -> Self {
+        Owned(Value::I32(v))
+    }
+
+    /**
+    Create a buffer for a signed integer value.
+    */
+    pub const fn i64(v: i64) -> Self {
+        Owned(Value::I64(v))
+    }
+
+    /**
+    Create a buffer for a signed integer value.
+    */
+    pub fn i128(v: i128) -> Self {
+        Ref::i128(v).into()
+    }
+
+    /**
+    Create a buffer for a binary floating point value.
+    */
+    pub const fn f32(v: f32) -> Self {
+        Owned(Value::F32(v))
+    }
+
+    /**
+    Create a buffer for a binary floating point value.
+    */
+    pub const fn f64(v: f64) -> Self {
+        Owned(Value::F64(v))
+    }
+
+    /**
+    Create a buffer for a single character value.
+    */
+    pub const fn char(v: char) -> Self {
+        Owned(Value::Char(v))
+    }
+
+    /**
+    Create a buffer for a string value.
+
+    Unlike [`Ref::str`], the string is always owned by the buffer.
+    */
+    pub fn str(v: impl Into<String>) -> Self {
+        Ref::owned_str(v).into()
+    }
+
+    /**
+    Create a buffer for a byte-string value.
+
+    Unlike [`Ref::bytes`], the byte-string is always owned by the buffer.
+    */
+    pub fn bytes(v: impl Into<Vec<u8>>) -> Self {
+        Ref::owned_bytes(v).into()
+    }
+
+    /**
+    Create a buffer for an `Option::None` value.
+    */
+    pub const fn none() -> Self {
+        Owned(Value::None)
+    }
+
+    /**
+    Create a buffer for an `Option::Some` value.
+    */
+    pub fn some(v: impl Into<Owned>) -> Self {
+        Ref::some(v.into()).into()
+    }
+
+    /**
+    Create a buffer for a unit struct, like `struct A`.
+    */
+    pub fn unit_struct(name: impl Into<Name<'static>>) -> Self {
+        Ref::unit_struct(name).into()
+    }
+
+    /**
+    Create a buffer for a newtype struct, like `struct A(T)`.
+    */
+    pub fn newtype_struct(name: impl Into<Name<'static>>, value: impl Into<Owned>) -> Self {
+        Ref::newtype_struct(name, value.into()).into()
+    }
+
+    /**
+    Create a buffer for a struct with named fields, like `struct A { a: T, b: U }`.
+    */
+    pub fn record_struct<K: Into<Name<'static>>>(
+        name: impl Into<Name<'static>>,
+        fields: impl IntoIterator<Item = (K, Owned)>,
+    ) -> Self {
+        Ref::record_struct(name, fields.into_iter().map(|(k, v)| (k, v.into()))).into()
+    }
+
+    /**
+    Create a buffer for a struct with unnamed fields, like `struct A(T, U)`.
+    */
+    pub fn tuple_struct(
+        name: impl Into<Name<'static>>,
+        fields: impl IntoIterator<Item = Owned>,
+    ) -> Self {
+        Ref::tuple_struct(name, fields.into_iter().map(Ref::from)).into()
+    }
+
+    /**
+    Create a buffer for a tuple, like `(T, U)`.
+    */
+    pub fn tuple(fields: impl IntoIterator<Item = Owned>) -> Self {
+        Ref::tuple(fields.into_iter().map(Ref::from)).into()
+    }
+
+    /**
+    Create a buffer for a unit enum variant, like `A::B`.
+    */
+    pub fn unit_variant(
+        name: impl Into<Name<'static>>,
+        variant_index: u32,
+        variant: impl Into<Name<'static>>,
+    ) -> Self {
+        Ref::unit_variant(name, variant_index, variant).into()
+    }
+
+    /**
+    Create a buffer for a newtype enum variant, like `A::B(T)`.
+    */
+    pub fn newtype_variant(
+        name: impl Into<Name<'static>>,
+        variant_index: u32,
+        variant: impl Into<Name<'static>>,
+        value: impl Into<Owned>,
+    ) -> Self {
+        Ref::newtype_variant(name, variant_index, variant, value.into()).into()
+    }
+
+    /**
+    Create a buffer for an enum variant with unnamed fields, like `A::B(T, U)`.
+    */
+    pub fn tuple_variant(
+        name: impl Into<Name<'static>>,
+        variant_index: u32,
+        variant: impl Into<Name<'static>>,
+        fields: impl IntoIterator<Item = Owned>,
+    ) -> Self {
+        Ref::tuple_variant(
+            name,
+            variant_index,
+            variant,
+            fields.into_iter().map(Ref::from),
+        )
+        .into()
+    }
+
+    /**
+    Create a buffer for an enum variant with named fields, like `A::B { a: T, b: U }`.
+    */
+    pub fn record_struct_variant<K: Into<Name<'static>>>(
+        name: impl Into<Name<'static>>,
+        variant_index: u32,
+        variant: impl Into<Name<'static>>,
+        fields: impl IntoIterator<Item = (K, Owned)>,
+    ) -> Self {
+        Ref::record_struct_variant(
+            name,
+            variant_index,
+            variant,
+            fields.into_iter().map(|(k, v)| (k, v.into())),
+        )
+        .into()
+    }
+
+    /**
+    Create a buffer for a sequence.
+    */
+    pub fn seq(fields: impl IntoIterator<Item = Owned>) -> Self {
+        Ref::seq(fields.into_iter().map(Ref::from)).into()
+    }
+
+    /**
+    Create a buffer for a map.
+
+    See [`Ref::map`] for details.
+    */
+    pub fn map(fields: impl IntoIterator<Item = (Owned, Owned)>) -> Self {
+        Ref::map(fields.into_iter().map(|(k, v)| (k.into(), v.into()))).into()
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($ty:ident,)*) => {
+        $(
+            impl<'a> From<$ty> for Ref<'a> {
+                fn from(v: $ty) -> Self {
+                    Ref::$ty(v)
+                }
+            }
+
+            impl From<$ty> for Owned {
+                fn from(v: $ty) -> Self {
+                    Ref::$ty(v).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive!(bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64, char,);
+
+impl<'a> From<&'a str> for Ref<'a> {
+    fn from(v: &'a str) -> Self {
+        Ref::str(v)
+    }
+}
+
+impl<'a> From<String> for Ref<'a> {
+    fn from(v: String) -> Self {
+        Ref::owned_str(v)
+    }
+}
+
+impl<'a> From<&'a str> for Owned {
+    fn from(v: &'a str) -> Self {
+        Ref::owned_str(v).into()
+    }
+}
+
+impl From<String> for Owned {
+    fn from(v: String) -> Self {
+        Ref::owned_str(v).into()
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/**
+A buffered value.
+
+The payloads of structs, enum variants, and 128-bit integers are boxed so they don't widen
+every other value in a buffer. Most buffers are dominated by primitives, which would otherwise
+all pay for the size of the largest variant.
+*/
+#[derive(Debug)]
 enum Value<'a> {
     Unit,
     U8(u8),
     U16(u16),
     U32(u32),
     U64(u64),
-    U128(u128),
+    U128(Box<u128>),
     I8(i8),
     I16(i16),
     I32(i32),
     I64(i64),
-    I128(i128),
+    I128(Box<i128>),
     F32(f32),
     F64(f64),
     Bool(bool),
     Char(char),
-    Str(Box<str>),
+    Str(OwnedStr),
+    // Only created by interning strings, which needs the `std` feature
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    SharedStr(Arc<str>),
     BorrowedStr(&'a str),
     Bytes(Box<[u8]>),
     BorrowedBytes(&'a [u8]),
+    #[cfg(feature = "bytes")]
+    SharedBytes(Box<::bytes::Bytes>),
+    #[cfg(feature = "json")]
+    RawJson(OwnedStr),
     None,
     Some(Box<Value<'a>>),
-    UnitStruct {
-        name: &'static str,
-    },
-    NewtypeStruct {
-        name: &'static str,
-        value: Box<Value<'a>>,
-    },
-    Struct {
-        name: &'static str,
-        fields: Box<[(&'static str, Value<'a>)]>,
-    },
+    UnitStruct(Box<UnitStruct<'a>>),
+    NewtypeStruct(Box<NewtypeStruct<'a>>),
+    Struct(Box<Struct<'a>>),
     Tuple(Box<[Value<'a>]>),
-    TupleStruct {
-        name: &'static str,
-        fields: Box<[Value<'a>]>,
-    },
-    UnitVariant {
-        name: &'static str,
-        variant_index: u32,
-        variant: &'static str,
-    },
-    NewtypeVariant {
-        name: &'static str,
-        variant_index: u32,
-        variant: &'static str,
-        value: Box<Value<'a>>,
-    },
-    TupleVariant {
-        name: &'static str,
-        variant_index: u32,
-        variant: &'static str,
-        fields: Box<[Value<'a>]>,
-    },
-    StructVariant {
-        name: &'static str,
-        variant_index: u32,
-        variant: &'static str,
-        fields: Box<[(&'static str, Value<'a>)]>,
-    },
-    Seq(Box<[Value<'a>]>),
-    Map(Box<[(Value<'a>, Value<'a>)]>),
+    TupleStruct(Box<TupleStruct<'a>>),
+    UnitVariant(Box<UnitVariant<'a>>),
+    NewtypeVariant(Box<NewtypeVariant<'a>>),
+    TupleVariant(Box<TupleVariant<'a>>),
+    StructVariant(Box<StructVariant<'a>>),
+    Seq(Box<[Value<'a>]>, LenHint),
+    Map(Box<[(Value<'a>, Value<'a>)]>, LenHint),
+    Borrowed(&'a Value<'a>),
+}
+
+#[derive(Clone, Debug)]
+struct UnitStruct<'a> {
+    name: Name<'a>,
+}
+
+#[derive(Clone, Debug)]
+struct NewtypeStruct<'a> {
+    name: Name<'a>,
+    value: Value<'a>,
+}
+
+#[derive(Clone, Debug)]
+struct Struct<'a> {
+    name: Name<'a>,
+    fields: Box<[(Name<'a>, Value<'a>)]>,
+}
+
+#[derive(Clone, Debug)]
+struct TupleStruct<'a> {
+    name: Name<'a>,
+    fields: Box<[Value<'a>]>,
+}
+
+#[derive(Clone, Debug)]
+struct UnitVariant<'a> {
+    name: Name<'a>,
+    variant_index: u32,
+    variant: Name<'a>,
+}
+
+#[derive(Clone, Debug)]
+struct NewtypeVariant<'a> {
+    name: Name<'a>,
+    variant_index: u32,
+    variant: Name<'a>,
+    value: Value<'a>,
+}
+
+#[derive(Clone, Debug)]
+struct TupleVariant<'a> {
+    name: Name<'a>,
+    variant_index: u32,
+    variant: Name<'a>,
+    fields: Box<[Value<'a>]>,
+}
+
+#[derive(Clone, Debug)]
+struct StructVariant<'a> {
+    name: Name<'a>,
+    variant_index: u32,
+    variant: Name<'a>,
+    fields: Box<[(Name<'a>, Value<'a>)]>,
+}
+
+/**
+Whether the length of a sequence or map was known before its elements were buffered.
+
+Some formats encode collections differently when their length isn't known upfront,
+so this is kept to serialize buffers the same way as their original values.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LenHint {
+    Known,
+    Unknown,
+}
+
+impl LenHint {
+    fn new(len: Option<usize>) -> Self {
+        match len {
+            Some(_) => LenHint::Known,
+            None => LenHint::Unknown,
+        }
+    }
+
+    fn len(self, len: usize) -> Option<usize> {
+        match self {
+            LenHint::Known => Some(len),
+            LenHint::Unknown => None,
+        }
+    }
+}
+
+impl Value<'static> {
+    fn buffer_iter<T: Serialize>(iter: impl IntoIterator<Item = T>) -> Result<Self, Error> {
+        let iter = iter.into_iter();
+
+        let mut fields = Vec::with_capacity(iter.size_hint().0);
+        for item in iter {
+            fields.push(item.serialize(Serializer::new())?.into_value());
+        }
+
+        Ok(Value::Seq(fields.into_boxed_slice(), LenHint::Known))
+    }
+}
+
+impl<'a> Value<'a> {
+    fn resolve(&self) -> &Value<'a> {
+        let mut value = self;
+
+        while let Value::Borrowed(borrowed) = value {
+            value = borrowed;
+        }
+
+        value
+    }
+
+    /**
+    Get the narrowest unsigned integer value that can hold `v`.
+    */
+    #[cfg(any(
+        feature = "json",
+        feature = "cbor",
+        feature = "rmpv",
+        feature = "toml",
+        feature = "yaml"
+    ))]
+    fn from_unsigned(v: u128) -> Value<'static> {
+        if let Ok(v) = u8::try_from(v) {
+            Value::U8(v)
+        } else if let Ok(v) = u16::try_from(v) {
+            Value::U16(v)
+        } else if let Ok(v) = u32::try_from(v) {
+            Value::U32(v)
+        } else if let Ok(v) = u64::try_from(v) {
+            Value::U64(v)
+        } else {
+            Value::U128(Box::new(v))
+        }
+    }
+
+    /**
+    Get the narrowest signed integer value that can hold `v`.
+    */
+    #[cfg(any(
+        feature = "json",
+        feature = "cbor",
+        feature = "rmpv",
+        feature = "toml",
+        feature = "yaml"
+    ))]
+    fn from_signed(v: i128) -> Value<'static> {
+        if let Ok(v) = i8::try_from(v) {
+            Value::I8(v)
+        } else if let Ok(v) = i16::try_from(v) {
+            Value::I16(v)
+        } else if let Ok(v) = i32::try_from(v) {
+            Value::I32(v)
+        } else if let Ok(v) = i64::try_from(v) {
+            Value::I64(v)
+        } else {
+            Value::I128(Box::new(v))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use core::marker::PhantomData;
+    use core::{fmt, marker::PhantomData};
 
     use alloc::borrow::{Cow, ToOwned};
     use serde::{
         de::{Deserializer, IntoDeserializer, Visitor},
         ser::SerializeMap,
-        Deserialize, Serialize,
+        Deserialize as _,
     };
     use serde_test::Token;
 
@@ -918,6 +1660,249 @@ mod tests {
         );
     }
 
+    #[test]
+    fn owned_as_ref() {
+        let owned = Owned::buffer(Struct { a: (), b: () }).unwrap();
+
+        serde_test::assert_ser_tokens(
+            &owned.as_ref(),
+            &[
+                Token::Struct {
+                    name: "Struct",
+                    len: 2,
+                },
+                Token::Str("a"),
+                Token::Unit,
+                Token::Str("b"),
+                Token::Unit,
+                Token::StructEnd,
+            ],
+        );
+
+        assert_eq!(
+            Struct { a: (), b: () },
+            Struct::deserialize(owned.as_ref().into_deserializer()).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_primitive() {
+        let short_lived = alloc::string::String::from("a string");
+
+        assert_eq!(Ref::some(Ref::u64(42)), Ref::some(42u64));
+        assert_eq!(Ref::i64(-1), Ref::from(-1i64));
+        assert_eq!(Ref::f64(1.5), Ref::from(1.5f64));
+        assert_eq!(Ref::bool(true), Ref::from(true));
+        assert_eq!(Ref::char('a'), Ref::from('a'));
+        assert_eq!(Owned::from(42u64), Owned::from(Ref::u64(42)));
+
+        assert!(matches!(
+            Ref::from(short_lived.as_str()).0,
+            Value::BorrowedStr("a string")
+        ));
+        assert!(matches!(Ref::from(short_lived.clone()).0, Value::Str(_)));
+        assert_eq!(Some("a string"), Owned::from(short_lived.as_str()).as_str());
+    }
+
+    #[test]
+    fn default_and_const_constructors() {
+        const UNIT: Ref<'static> = Ref::unit();
+        const NUMBER: Ref<'static> = Ref::i64(-1);
+        const OWNED: Owned = Owned::char('a');
+
+        #[derive(Default)]
+        struct Fields {
+            owned: Owned,
+            borrowed: Ref<'static>,
+        }
+
+        let fields = Fields::default();
+
+        assert_eq!(Owned::unit(), fields.owned);
+        assert_eq!(Ref::unit(), fields.borrowed);
+
+        assert_eq!(Ref::unit(), UNIT);
+        assert_eq!(Ref::i64(-1), NUMBER);
+        assert_eq!(Owned::from('a'), OWNED);
+    }
+
+    #[test]
+    fn borrow() {
+        use alloc::{borrow::Cow, collections::BTreeMap};
+        use std::collections::HashSet;
+
+        let owned = Owned::from(Ref::seq([Ref::str("a"), Ref::u8(1)]));
+        let borrowed: &Ref<'static> = owned.borrow();
+
+        assert_eq!(owned, *borrowed);
+
+        let map: BTreeMap<Owned, u8> = [(Owned::from("a"), 1), (owned.clone(), 2)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(Some(&1), map.get(&Ref::str("a")));
+        assert_eq!(Some(&2), map.get(&Ref::seq([Ref::str("a"), Ref::u8(1)])));
+        assert_eq!(None, map.get(&Ref::str("b")));
+
+        let set: HashSet<Owned> = [Owned::from(Ref::str("a")), Owned::from(1.0)]
+            .into_iter()
+            .collect();
+
+        assert!(set.contains(&Ref::str("a")));
+        assert!(set.contains(&Ref::f64(1.0)));
+        assert!(!set.contains(&Ref::f32(1.0)));
+
+        let cow: Cow<Ref<'static>> = Cow::Borrowed(owned.borrow());
+
+        assert_eq!(owned, cow.into_owned().into_owned());
+    }
+
+    #[test]
+    fn owned_constructors() {
+        let short_lived = alloc::string::String::from("a string");
+
+        let owned = Owned::record_struct(
+            "Struct",
+            [
+                ("a", Owned::str(short_lived.as_str())),
+                ("b", Owned::some(Owned::u64(42))),
+                ("c", Owned::seq([Owned::unit(), Owned::bytes(b"bytes")])),
+                (
+                    "d",
+                    Owned::map([(Owned::i8(-1), Owned::unit_variant("Enum", 0, "A"))]),
+                ),
+                (
+                    "e",
+                    Owned::newtype_variant("Enum", 1, "B", Owned::tuple([Owned::char('a')])),
+                ),
+            ],
+        );
+        drop(short_lived);
+
+        assert_eq!(
+            Ref::record_struct(
+                "Struct",
+                [
+                    ("a", Ref::str("a string")),
+                    ("b", Ref::some(Ref::u64(42))),
+                    ("c", Ref::seq([Ref::unit(), Ref::bytes(b"bytes")])),
+                    (
+                        "d",
+                        Ref::map([(Ref::i8(-1), Ref::unit_variant("Enum", 0, "A"))]),
+                    ),
+                    (
+                        "e",
+                        Ref::newtype_variant("Enum", 1, "B", Ref::tuple([Ref::char('a')])),
+                    ),
+                ],
+            ),
+            owned
+        );
+
+        assert_eq!(
+            Ref::tuple_struct("Struct", [Ref::none(), Ref::f64(1.5)]),
+            Owned::tuple_struct("Struct", [Owned::none(), Owned::f64(1.5)])
+        );
+        assert_eq!(
+            Ref::record_struct_variant("Enum", 2, "C", [("a", Ref::u128(1))]),
+            Owned::record_struct_variant("Enum", 2, "C", [("a", Owned::u128(1))])
+        );
+    }
+
+    #[test]
+    fn buffer_ref() {
+        let s = alloc::string::String::from("a string");
+        let owned = Owned::buffer_ref(s.as_str()).unwrap();
+
+        assert_eq!(Some("a string"), owned.as_str());
+        assert_eq!(Owned::buffer(&s).unwrap(), owned);
+
+        let bytes: &[u8] = &[1, 2, 3];
+
+        assert_eq!(
+            Ref::seq([Ref::u8(1), Ref::u8(2), Ref::u8(3)]),
+            Ref::buffer_ref(bytes).unwrap()
+        );
+        assert_eq!(
+            Ref::bytes(b"bytes"),
+            Ref::buffer_ref(serde_bytes::Bytes::new(b"bytes")).unwrap()
+        );
+    }
+
+    #[test]
+    fn buffer_iter() {
+        let owned = Owned::buffer_iter((0..3).map(|a| (a, "b"))).unwrap();
+
+        assert_eq!(
+            Owned::buffer(alloc::vec![(0, "b"), (1, "b"), (2, "b")]).unwrap(),
+            owned
+        );
+        assert_eq!(Kind::Seq, owned.kind());
+
+        struct Fails;
+
+        impl Serialize for Fails {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("failed"))
+            }
+        }
+
+        let mut yielded = 0;
+        assert!(Ref::buffer_iter([Fails, Fails].into_iter().inspect(|_| yielded += 1)).is_err());
+        assert_eq!(1, yielded);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn value_size() {
+        // Compact strings are stored inline, so they're wider than a boxed string
+        let expected = if cfg!(feature = "compact_str") {
+            32
+        } else {
+            24
+        };
+
+        assert_eq!(expected, core::mem::size_of::<Value>());
+    }
+
+    #[test]
+    fn ref_into_owned() {
+        let short_lived = alloc::string::String::from("a string");
+
+        let buf = Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::some(Ref::str(&short_lived))),
+                ("b", Ref::seq([Ref::bytes(short_lived.as_bytes())])),
+            ],
+        );
+
+        let owned = buf.clone().into_owned();
+        drop(buf);
+        drop(short_lived);
+
+        assert_eq!(
+            Value::Struct(Box::new(super::Struct {
+                name: "Struct".into(),
+                fields: alloc::vec![
+                    (
+                        "a".into(),
+                        Value::Some(Box::new(Value::Str("a string".into())))
+                    ),
+                    (
+                        "b".into(),
+                        Value::Seq(
+                            alloc::vec![Value::Bytes(b"a string".to_vec().into())].into(),
+                            LenHint::Known
+                        )
+                    ),
+                ]
+                .into()
+            })),
+            owned.0
+        );
+    }
+
     #[derive(Debug, Clone, Copy, PartialEq)]
     struct Input<S> {
         value: S,
@@ -931,7 +1916,7 @@ mod tests {
         }
     }
 
-    fn test_case<'de, S: Serialize + Deserialize<'de> + PartialEq + fmt::Debug + Clone>(
+    fn test_case<'de, S: Serialize + serde::Deserialize<'de> + PartialEq + fmt::Debug + Clone>(
         t: Input<S>,
         ref_buf: Input<Ref<'de>>,
         ref_tokens: Tokens<'de>,
@@ -961,7 +1946,7 @@ mod tests {
         assert_eq!(t, owned_to_t);
     }
 
-    fn i128_test_case<'de, T: Serialize + Deserialize<'de> + PartialEq + fmt::Debug>(
+    fn i128_test_case<'de, T: Serialize + serde::Deserialize<'de> + PartialEq + fmt::Debug>(
         v: T,
         ref_buf: Ref<'de>,
     ) {
@@ -986,7 +1971,7 @@ mod tests {
         }
     }
 
-    impl<'de> Deserialize<'de> for Str<'de> {
+    impl<'de> serde::Deserialize<'de> for Str<'de> {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
             D: serde::Deserializer<'de>,
@@ -1040,7 +2025,7 @@ mod tests {
         }
     }
 
-    impl<'de> Deserialize<'de> for Bytes<'de> {
+    impl<'de> serde::Deserialize<'de> for Bytes<'de> {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
             D: serde::Deserializer<'de>,
@@ -1097,6 +2082,7 @@ mod tests {
     struct NewtypeStruct(());
 
     #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+    #[allow(clippy::enum_variant_names)]
     enum Enum {
         UnitVariant,
         NewtypeVariant(()),
@@ -1123,7 +2109,7 @@ mod tests {
         }
     }
 
-    impl<'de> Deserialize<'de> for Map<'de> {
+    impl<'de> serde::Deserialize<'de> for Map<'de> {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
             D: Deserializer<'de>,
@@ -1148,6 +2134,7 @@ mod tests {
             let mut de = Vec::new();
 
             while let Some(k) = map.next_key()? {
+                #[allow(clippy::let_unit_value)]
                 let v = map.next_value()?;
 
                 de.push((k, v));
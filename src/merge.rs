@@ -0,0 +1,223 @@
+use core::mem;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use crate::{Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Merge another buffer into this one.
+
+    This is like a JSON Merge Patch over buffers, where `other` is the patch:
+
+    - If both buffers are maps, each entry in `other` is merged into the entry in `self` with an
+      equal key. Entries without an equal key in `self` are added after the existing ones.
+    - If both buffers are structs with named fields, each field in `other` is merged into the field
+      in `self` with the same name. Fields without a match in `self` are added after the existing ones.
+      The struct keeps the name it has in `self`.
+    - Otherwise, `self` is replaced by `other`.
+
+    Merging is applied recursively, so nested maps and structs are merged field by field rather than
+    replaced. If `self` has more than one entry with the same key then only the first is merged into.
+    If `other` has more than one entry with the same key then the last one wins.
+
+    Unlike JSON Merge Patch, a `None` or unit value in `other` doesn't remove an entry from `self`.
+    It replaces the entry's value like any other non-mergeable value.
+    */
+    pub fn merge(&mut self, other: Owned) {
+        self.0.merge(other.into_value())
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Merge another buffer into this one.
+
+    See [`Owned::merge`] for details. Values borrowed from other buffers are cloned
+    so their fields can be merged.
+    */
+    pub fn merge(&mut self, other: Ref<'a>) {
+        self.0.merge(other.into_value())
+    }
+}
+
+impl<'a> Value<'a> {
+    fn merge(&mut self, other: Value<'a>) {
+        // Nested values are merged using a stack instead of recursion
+        let mut stack = vec![(self, other)];
+
+        while let Some((target, mut patch)) = stack.pop() {
+            if let Value::Borrowed(v) = target {
+                *target = v.resolve().clone();
+            }
+
+            if !matches!(
+                (&*target, patch.resolve()),
+                (Value::Map(..), Value::Map(..)) | (Value::Struct(_), Value::Struct(_))
+            ) {
+                *target = patch;
+                continue;
+            }
+
+            if let Value::Borrowed(v) = patch {
+                patch = v.resolve().clone();
+            }
+
+            match (target, &mut patch) {
+                (Value::Map(fields, _), Value::Map(patch, _)) => {
+                    let patches = merge_entries(fields, mem::take(patch).into_vec());
+
+                    stack.extend(
+                        fields
+                            .iter_mut()
+                            .zip(patches)
+                            .filter_map(|((_, v), patch)| Some((v, patch?))),
+                    );
+                }
+                (Value::Struct(target), Value::Struct(patch)) => {
+                    let patches =
+                        merge_entries(&mut target.fields, mem::take(&mut patch.fields).into_vec());
+
+                    stack.extend(
+                        target
+                            .fields
+                            .iter_mut()
+                            .zip(patches)
+                            .filter_map(|((_, v), patch)| Some((v, patch?))),
+                    );
+                }
+                _ => unreachable!("only maps and structs are merged"),
+            }
+        }
+    }
+}
+
+/**
+Add the entries in `patch` that don't have a key in `fields`.
+
+The values of entries that do have a key in `fields` are returned at the index of the entry
+they should be merged into.
+*/
+fn merge_entries<'a, K: PartialEq>(
+    fields: &mut Box<[(K, Value<'a>)]>,
+    patch: Vec<(K, Value<'a>)>,
+) -> Vec<Option<Value<'a>>> {
+    let mut patches = Vec::new();
+    patches.resize_with(fields.len(), || None);
+
+    let mut added: Vec<(K, Value<'a>)> = Vec::new();
+
+    for (k, v) in patch {
+        if let Some(i) = fields.iter().position(|(existing, _)| *existing == k) {
+            patches[i] = Some(v);
+        } else if let Some(existing) = added.iter_mut().find(|(existing, _)| *existing == k) {
+            existing.1 = v;
+        } else {
+            added.push((k, v));
+        }
+    }
+
+    if !added.is_empty() {
+        let mut merged = mem::take(fields).into_vec();
+        merged.extend(added);
+
+        *fields = merged.into_boxed_slice();
+    }
+
+    patches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_maps() {
+        let mut base = Ref::map([
+            (Ref::str("a"), Ref::u8(1)),
+            (
+                Ref::str("b"),
+                Ref::map([(Ref::str("c"), Ref::u8(2)), (Ref::str("d"), Ref::u8(3))]),
+            ),
+        ]);
+
+        base.merge(Ref::map([
+            (Ref::str("b"), Ref::map([(Ref::str("d"), Ref::u8(4))])),
+            (Ref::str("e"), Ref::u8(5)),
+            (Ref::str("e"), Ref::u8(6)),
+        ]));
+
+        assert_eq!(
+            Ref::map([
+                (Ref::str("a"), Ref::u8(1)),
+                (
+                    Ref::str("b"),
+                    Ref::map([(Ref::str("c"), Ref::u8(2)), (Ref::str("d"), Ref::u8(4))]),
+                ),
+                (Ref::str("e"), Ref::u8(6)),
+            ]),
+            base
+        );
+    }
+
+    #[test]
+    fn merge_structs() {
+        let mut base = Owned::from(Ref::record_struct(
+            "Base",
+            [
+                ("a", Ref::u8(1)),
+                ("b", Ref::record_struct("Inner", [("c", Ref::u8(2))])),
+            ],
+        ));
+
+        base.merge(Owned::from(Ref::record_struct(
+            "Patch",
+            [
+                ("b", Ref::record_struct("Inner", [("d", Ref::u8(3))])),
+                ("a", Ref::none()),
+            ],
+        )));
+
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "Base",
+                [
+                    ("a", Ref::none()),
+                    (
+                        "b",
+                        Ref::record_struct("Inner", [("c", Ref::u8(2)), ("d", Ref::u8(3))]),
+                    ),
+                ],
+            )),
+            base
+        );
+    }
+
+    #[test]
+    fn merge_replace() {
+        let mut base = Ref::map([(Ref::str("a"), Ref::map([(Ref::str("b"), Ref::u8(1))]))]);
+        base.merge(Ref::map([(Ref::str("a"), Ref::seq([Ref::u8(1)]))]));
+
+        assert_eq!(Ref::map([(Ref::str("a"), Ref::seq([Ref::u8(1)]))]), base);
+
+        let mut base = Ref::record_struct("A", [("a", Ref::u8(1))]);
+        base.merge(Ref::map([(Ref::str("a"), Ref::u8(2))]));
+
+        assert_eq!(Ref::map([(Ref::str("a"), Ref::u8(2))]), base);
+    }
+
+    #[test]
+    fn merge_borrowed() {
+        let base = Owned::from(Ref::map([(Ref::str("a"), Ref::u8(1))]));
+        let patch = Owned::from(Ref::map([(Ref::str("b"), Ref::u8(2))]));
+
+        let mut merged = base.as_ref();
+        merged.merge(patch.as_ref());
+
+        assert_eq!(
+            Ref::map([(Ref::str("a"), Ref::u8(1)), (Ref::str("b"), Ref::u8(2))]),
+            merged
+        );
+        assert_eq!(Ref::map([(Ref::str("a"), Ref::u8(1))]), base.as_ref());
+    }
+}
@@ -0,0 +1,151 @@
+/*!
+Conversions between buffers and `rmpv`'s [`rmpv::Value`].
+*/
+
+use alloc::boxed::Box;
+use serde::ser::Error as _;
+
+use crate::{Error, LenHint, NewtypeStruct, Owned, Ref, Value};
+
+/**
+The name `rmpv` and `rmp-serde` use to serialize extension types.
+*/
+const EXT_NAME: &str = "_ExtStruct";
+
+impl From<rmpv::Value> for Owned {
+    /**
+    Convert a MessagePack value into a buffer.
+
+    Maps are converted into maps with keys of any type, arrays into sequences, binary into
+    byte-strings, and `nil` into `()`. Integers are converted into the narrowest integer type
+    that can hold them. Strings that aren't valid UTF-8 are converted into byte-strings.
+
+    Extension types are converted into the same newtype struct `rmpv` uses to serialize them:
+    `_ExtStruct((type, data))`, where `type` is an `i8` and `data` is a byte-string.
+    This means extension types are preserved when the buffer is serialized as MessagePack,
+    but will appear as a tuple in other formats.
+    */
+    fn from(value: rmpv::Value) -> Self {
+        Owned(from_msgpack(value))
+    }
+}
+
+impl TryFrom<Owned> for rmpv::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a MessagePack value.
+
+    Values are converted in the same way `rmpv` would serialize them.
+    */
+    fn try_from(value: Owned) -> Result<Self, Self::Error> {
+        rmpv::ext::to_value(&value).map_err(Error::custom)
+    }
+}
+
+impl<'a> TryFrom<Ref<'a>> for rmpv::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a MessagePack value.
+
+    Values are converted in the same way `rmpv` would serialize them.
+    */
+    fn try_from(value: Ref<'a>) -> Result<Self, Self::Error> {
+        rmpv::ext::to_value(&value).map_err(Error::custom)
+    }
+}
+
+fn from_msgpack(value: rmpv::Value) -> Value<'static> {
+    match value {
+        rmpv::Value::Nil => Value::Unit,
+        rmpv::Value::Boolean(v) => Value::Bool(v),
+        rmpv::Value::Integer(v) => match (v.as_u64(), v.as_i64()) {
+            (Some(v), _) => Value::from_unsigned(v.into()),
+            (None, Some(v)) => Value::from_signed(v.into()),
+            // `rmpv::Integer` is always either a `u64` or an `i64`
+            (None, None) => Value::Unit,
+        },
+        rmpv::Value::F32(v) => Value::F32(v),
+        rmpv::Value::F64(v) => Value::F64(v),
+        rmpv::Value::String(v) => {
+            if v.is_str() {
+                Value::Str(v.into_str().unwrap_or_default().into())
+            } else {
+                Value::Bytes(v.into_bytes().into_boxed_slice())
+            }
+        }
+        rmpv::Value::Binary(v) => Value::Bytes(v.into_boxed_slice()),
+        rmpv::Value::Array(v) => {
+            Value::Seq(v.into_iter().map(from_msgpack).collect(), LenHint::Known)
+        }
+        rmpv::Value::Map(v) => Value::Map(
+            v.into_iter()
+                .map(|(k, v)| (from_msgpack(k), from_msgpack(v)))
+                .collect(),
+            LenHint::Known,
+        ),
+        rmpv::Value::Ext(ty, data) => Value::NewtypeStruct(Box::new(NewtypeStruct {
+            name: EXT_NAME.into(),
+            value: Value::Tuple([Value::I8(ty), Value::Bytes(data.into_boxed_slice())].into()),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+    use rmpv::Value as MsgPack;
+
+    fn encode(v: &MsgPack) -> Vec<u8> {
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, v).unwrap();
+
+        buf
+    }
+
+    #[test]
+    fn msgpack_roundtrip() {
+        let value = MsgPack::Map(alloc::vec![
+            (MsgPack::from(1), MsgPack::Binary(b"bytes".to_vec())),
+            (
+                MsgPack::from("a"),
+                MsgPack::Array(alloc::vec![
+                    MsgPack::from(-1),
+                    MsgPack::from(u64::MAX),
+                    MsgPack::from(i64::MIN),
+                    MsgPack::F32(1.5),
+                    MsgPack::F64(2.5),
+                    MsgPack::Nil,
+                    MsgPack::Boolean(true),
+                ])
+            ),
+            (MsgPack::from("b"), MsgPack::Ext(7, alloc::vec![1, 2, 3])),
+        ]);
+
+        let owned = Owned::from(value.clone());
+
+        assert_eq!(
+            Some(b"bytes" as &[u8]),
+            owned.iter_map().unwrap().next().unwrap().1.as_bytes()
+        );
+        assert_eq!(
+            Some(1),
+            owned.iter_map().unwrap().next().unwrap().0.as_u128()
+        );
+        assert_eq!(encode(&value), rmp_serde::to_vec(&owned).unwrap());
+
+        assert_eq!(value, MsgPack::try_from(owned.as_ref()).unwrap());
+        assert_eq!(value, MsgPack::try_from(owned).unwrap());
+    }
+
+    #[test]
+    fn msgpack_invalid_utf8() {
+        // A `fixstr` of length 1 containing an invalid UTF-8 byte
+        let value = rmpv::decode::read_value(&mut &[0xa1, 0xff][..]).unwrap();
+
+        assert_eq!(Some(&[0xffu8] as &[u8]), Owned::from(value).as_bytes());
+    }
+}
@@ -0,0 +1,483 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    ops::Deref,
+};
+
+use alloc::{borrow::Cow, boxed::Box, string::String};
+use serde::de;
+
+use crate::{
+    owned_str::{self, OwnedStr},
+    Value,
+};
+
+/**
+The name of a struct, struct field, or enum variant.
+
+Serde requires these names to be `&'static str`s, but buffers can also be built with names that
+are only known at runtime, like the ones in a schema loaded from a file. A name can be created
+from a `&'static str`, a `String`, or a `Cow<'static, str>`. Names borrowed for a shorter lifetime
+can be created with [`Name::borrowed`].
+
+# Serializing names that aren't `'static`
+
+Buffers are serialized using the names they were created with, but only `'static` names can be
+passed to a [`serde::Serializer`]. When a buffer contains a struct or variant name that isn't
+`'static`, or a struct with any field names that aren't `'static`, it's serialized in the same way
+a self-describing format like JSON would represent it instead:
+
+- Unit structs are serialized as `()`.
+- Newtype structs are serialized as their inner value.
+- Tuple structs are serialized as tuples.
+- Structs with named fields are serialized as maps keyed by their field names.
+- Unit enum variants are serialized as strings with the variant name.
+- Other enum variants are serialized as maps with a single entry, keyed by the variant name.
+
+Buffers compare equal regardless of whether their names are `'static` or not.
+*/
+#[derive(Clone)]
+pub struct Name<'a>(Inner<'a>);
+
+#[derive(Clone)]
+enum Inner<'a> {
+    Static(&'static str),
+    Borrowed(&'a str),
+    Owned(OwnedStr),
+}
+
+impl<'a> Name<'a> {
+    /**
+    Create a name from a `'static` string.
+
+    This is the kind of name used by Rust types that implement [`serde::Serialize`].
+    */
+    pub const fn new(name: &'static str) -> Self {
+        Name(Inner::Static(name))
+    }
+
+    /**
+    Create a name from a borrowed string.
+
+    Buffers with borrowed names aren't serialized in the same way as Rust types with the same names would be.
+    See the type-level docs for details.
+    */
+    pub const fn borrowed(name: &'a str) -> Self {
+        Name(Inner::Borrowed(name))
+    }
+
+    /**
+    Get the name as a string.
+    */
+    pub fn as_str(&self) -> &str {
+        match self.0 {
+            Inner::Static(name) => name,
+            Inner::Borrowed(name) => name,
+            Inner::Owned(ref name) => name,
+        }
+    }
+
+    /**
+    Get the name as a `'static` string.
+
+    This method returns `None` if the name was created from an owned or borrowed string.
+    */
+    pub fn as_static(&self) -> Option<&'static str> {
+        match self.0 {
+            Inner::Static(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /**
+    Get a copy of this name that doesn't borrow any data.
+    */
+    pub(crate) fn to_static(&self) -> Name<'static> {
+        match self.0 {
+            Inner::Static(name) => Name(Inner::Static(name)),
+            Inner::Borrowed(name) => Name(Inner::Owned(name.into())),
+            Inner::Owned(ref name) => Name(Inner::Owned(name.clone())),
+        }
+    }
+
+    /**
+    Convert this name into one that doesn't borrow any data.
+    */
+    pub(crate) fn into_static(self) -> Name<'static> {
+        match self.0 {
+            Inner::Static(name) => Name(Inner::Static(name)),
+            Inner::Borrowed(name) => Name(Inner::Owned(name.into())),
+            Inner::Owned(name) => Name(Inner::Owned(name)),
+        }
+    }
+
+    /**
+    Get the number of bytes this name owns on the heap.
+    */
+    pub(crate) fn heap_size(&self) -> usize {
+        match self.0 {
+            Inner::Owned(ref name) => owned_str::heap_size(name),
+            _ => 0,
+        }
+    }
+
+    /**
+    Visit this name as a string.
+
+    `'static` and borrowed names are offered to the visitor as borrowed strings.
+    */
+    pub(crate) fn visit<'de, V, E>(self, visitor: V) -> Result<V::Value, E>
+    where
+        'a: 'de,
+        V: de::Visitor<'de>,
+        E: de::Error,
+    {
+        match self.0 {
+            Inner::Static(name) => visitor.visit_borrowed_str(name),
+            Inner::Borrowed(name) => visitor.visit_borrowed_str(name),
+            Inner::Owned(name) => visitor.visit_string(name.into()),
+        }
+    }
+
+    /**
+    Convert this name into a string value, like a map key.
+    */
+    pub(crate) fn into_value(self) -> Value<'a> {
+        match self.0 {
+            Inner::Static(name) => Value::BorrowedStr(name),
+            Inner::Borrowed(name) => Value::BorrowedStr(name),
+            Inner::Owned(name) => Value::Str(name),
+        }
+    }
+
+    /**
+    Convert a string value, like a map key, into a name.
+
+    If the value isn't a string then it's returned unchanged.
+    */
+    pub(crate) fn from_value(mut value: Value<'a>) -> Result<Self, Value<'a>> {
+        match value {
+            Value::Str(ref mut name) => Ok(Name(Inner::Owned(mem::take(name)))),
+            Value::SharedStr(ref name) => Ok(Name(Inner::Owned((**name).into()))),
+            Value::BorrowedStr(name) => Ok(Name(Inner::Borrowed(name))),
+            Value::Borrowed(v) => match *v.resolve() {
+                Value::Str(ref name) => Ok(Name(Inner::Borrowed(name))),
+                Value::SharedStr(ref name) => Ok(Name(Inner::Borrowed(name))),
+                Value::BorrowedStr(name) => Ok(Name(Inner::Borrowed(name))),
+                _ => Err(value),
+            },
+            value => Err(value),
+        }
+    }
+
+    /**
+    Whether a value, like a map key, can be converted into a name with [`Name::from_value`].
+    */
+    pub(crate) fn is_name(value: &Value) -> bool {
+        match value {
+            Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_) => true,
+            Value::Borrowed(v) => matches!(
+                v.resolve(),
+                Value::Str(_) | Value::SharedStr(_) | Value::BorrowedStr(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /**
+    Convert this name into a string, borrowing it if it isn't owned.
+    */
+    pub(crate) fn into_cow(self) -> Cow<'a, str> {
+        match self.0 {
+            Inner::Static(name) => Cow::Borrowed(name),
+            Inner::Borrowed(name) => Cow::Borrowed(name),
+            Inner::Owned(name) => Cow::Owned(name.into()),
+        }
+    }
+
+    /**
+    Take this name, leaving an empty one in its place.
+    */
+    pub(crate) fn take(&mut self) -> Self {
+        mem::replace(self, Name::new(""))
+    }
+
+    /**
+    Get a name that borrows from this one.
+    */
+    pub(crate) fn by_ref(&self) -> Name<'_> {
+        match self.0 {
+            Inner::Static(name) => Name(Inner::Static(name)),
+            Inner::Borrowed(name) => Name(Inner::Borrowed(name)),
+            Inner::Owned(ref name) => Name(Inner::Borrowed(name)),
+        }
+    }
+}
+
+impl<'a> From<&'static str> for Name<'a> {
+    fn from(name: &'static str) -> Self {
+        Name::new(name)
+    }
+}
+
+impl<'a> From<String> for Name<'a> {
+    fn from(name: String) -> Self {
+        Name(Inner::Owned(name.into()))
+    }
+}
+
+impl<'a> From<Box<str>> for Name<'a> {
+    fn from(name: Box<str>) -> Self {
+        Name(Inner::Owned(owned_str::from_boxed(name)))
+    }
+}
+
+impl<'a> From<Cow<'static, str>> for Name<'a> {
+    fn from(name: Cow<'static, str>) -> Self {
+        match name {
+            Cow::Borrowed(name) => Name::new(name),
+            Cow::Owned(name) => name.into(),
+        }
+    }
+}
+
+impl<'a> Deref for Name<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a> AsRef<str> for Name<'a> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<'a> fmt::Debug for Name<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a> fmt::Display for Name<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<'a, 'b> PartialEq<Name<'b>> for Name<'a> {
+    fn eq(&self, other: &Name<'b>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<'a> Eq for Name<'a> {}
+
+impl<'a> PartialEq<str> for Name<'a> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a, 'b> PartialEq<&'b str> for Name<'a> {
+    fn eq(&self, other: &&'b str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<'a, 'b> PartialOrd<Name<'b>> for Name<'a> {
+    fn partial_cmp(&self, other: &Name<'b>) -> Option<Ordering> {
+        Some(self.as_str().cmp(other.as_str()))
+    }
+}
+
+impl<'a> Ord for Name<'a> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<'a> Hash for Name<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{string::ToString, vec};
+    use serde::de::{Deserialize as _, IntoDeserializer as _};
+    use serde_test::{assert_ser_tokens, Token};
+
+    use crate::{Owned, Ref};
+
+    #[test]
+    fn static_names() {
+        assert_ser_tokens(
+            &Ref::unit_variant("Enum", 1, "A"),
+            &[Token::UnitVariant {
+                name: "Enum",
+                variant: "A",
+            }],
+        );
+
+        assert_ser_tokens(
+            &Ref::record_struct(Name::new("Struct"), [("a", Ref::i32(1))]),
+            &[
+                Token::Struct {
+                    name: "Struct",
+                    len: 1,
+                },
+                Token::Str("a"),
+                Token::I32(1),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn dynamic_names() {
+        let name = "Enum".to_string();
+        let variant = "A".to_string();
+
+        assert_ser_tokens(
+            &Ref::unit_variant(Name::borrowed(&name), 1, Name::borrowed(&variant)),
+            &[Token::Str("A")],
+        );
+
+        assert_ser_tokens(
+            &Ref::newtype_variant(name.clone(), 1, variant.clone(), Ref::i32(1)),
+            &[
+                Token::Map { len: Some(1) },
+                Token::Str("A"),
+                Token::I32(1),
+                Token::MapEnd,
+            ],
+        );
+
+        assert_ser_tokens(&Ref::unit_struct(name.clone()), &[Token::Unit]);
+
+        assert_ser_tokens(
+            &Ref::record_struct(name.clone(), [("a", Ref::i32(1))]),
+            &[
+                Token::Map { len: Some(1) },
+                Token::Str("a"),
+                Token::I32(1),
+                Token::MapEnd,
+            ],
+        );
+
+        assert_ser_tokens(
+            &Ref::tuple_variant(name.clone(), 1, variant.clone(), [Ref::i32(1)]),
+            &[
+                Token::Map { len: Some(1) },
+                Token::Str("A"),
+                Token::Tuple { len: 1 },
+                Token::I32(1),
+                Token::TupleEnd,
+                Token::MapEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn dynamic_field_names() {
+        let fields = ["a".to_string(), "b".to_string()];
+
+        assert_ser_tokens(
+            &Ref::record_struct(
+                "Struct",
+                fields.iter().map(|k| (Name::borrowed(k), Ref::i32(1))),
+            ),
+            &[
+                Token::Map { len: Some(2) },
+                Token::Str("a"),
+                Token::I32(1),
+                Token::Str("b"),
+                Token::I32(1),
+                Token::MapEnd,
+            ],
+        );
+
+        assert_ser_tokens(
+            &Ref::record_struct_variant(
+                "Enum",
+                0,
+                "A",
+                [
+                    (Name::new("a"), Ref::i32(1)),
+                    (fields[1].clone().into(), Ref::i32(2)),
+                ],
+            ),
+            &[
+                Token::Map { len: Some(1) },
+                Token::Str("A"),
+                Token::Map { len: Some(2) },
+                Token::Str("a"),
+                Token::I32(1),
+                Token::Str("b"),
+                Token::I32(2),
+                Token::MapEnd,
+                Token::MapEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn dynamic_field_names_deserialize() {
+        #[derive(serde_derive::Deserialize, Debug, PartialEq)]
+        struct Data {
+            a: i32,
+            b: bool,
+        }
+
+        let buf = Ref::record_struct(
+            "Data".to_string(),
+            [
+                ("a".to_string(), Ref::i32(1)),
+                ("b".to_string(), Ref::bool(true)),
+            ],
+        )
+        .into_owned();
+
+        assert_eq!(
+            Data { a: 1, b: true },
+            Data::deserialize((&buf).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            Data { a: 1, b: true },
+            Data::deserialize(buf.into_deserializer()).unwrap()
+        );
+    }
+
+    #[test]
+    fn dynamic_names_eq_static() {
+        let dynamic = Ref::record_struct(
+            "Struct".to_string(),
+            vec![("a", Ref::i32(1)), ("b", Ref::i32(2))],
+        );
+        let static_ = Ref::record_struct("Struct", vec![("a", Ref::i32(1)), ("b", Ref::i32(2))]);
+
+        assert_eq!(static_, dynamic);
+        assert_eq!(Owned::from(static_), Owned::from(dynamic));
+    }
+
+    #[test]
+    fn dynamic_names_into_static() {
+        let name = "Struct".to_string();
+
+        let owned = Ref::unit_variant(Name::borrowed(&name), 0, Name::borrowed(&name)).into_owned();
+        drop(name);
+
+        assert_eq!(
+            serde_json::json!("Struct"),
+            serde_json::to_value(&owned).unwrap()
+        );
+    }
+}
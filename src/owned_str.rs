@@ -0,0 +1,90 @@
+/*!
+The storage used for owned strings.
+
+By default, owned strings are boxed. When the `compact_str` feature is enabled they're stored as
+a `CompactString` instead, which keeps strings up to 24 bytes inline without allocating. That makes
+every value in a buffer 8 bytes larger, so it's worth it for buffers dominated by short strings,
+like field names, enum tags, and small values.
+*/
+
+#[cfg(not(feature = "compact_str"))]
+use alloc::boxed::Box;
+
+/**
+An owned string in a buffer.
+*/
+#[cfg(not(feature = "compact_str"))]
+pub(crate) type OwnedStr = Box<str>;
+
+/**
+An owned string in a buffer.
+*/
+#[cfg(feature = "compact_str")]
+pub(crate) type OwnedStr = compact_str::CompactString;
+
+/**
+Convert a boxed string into an owned string.
+*/
+#[cfg(not(feature = "compact_str"))]
+pub(crate) fn from_boxed(s: Box<str>) -> OwnedStr {
+    s
+}
+
+/**
+Convert a boxed string into an owned string.
+*/
+#[cfg(feature = "compact_str")]
+pub(crate) fn from_boxed(s: alloc::boxed::Box<str>) -> OwnedStr {
+    s.into()
+}
+
+/**
+Get the number of bytes an owned string holds on the heap.
+*/
+#[cfg(not(feature = "compact_str"))]
+pub(crate) fn heap_size(s: &OwnedStr) -> usize {
+    s.len()
+}
+
+/**
+Get the number of bytes an owned string holds on the heap.
+
+Strings that are stored inline don't hold anything on the heap.
+*/
+#[cfg(feature = "compact_str")]
+pub(crate) fn heap_size(s: &OwnedStr) -> usize {
+    if s.is_heap_allocated() {
+        s.capacity()
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+    use serde_test::{assert_ser_tokens, Token};
+
+    use crate::{Name, Owned};
+
+    #[test]
+    fn owned_str() {
+        let short = OwnedStr::from("short");
+        let long = OwnedStr::from("a string that's too long to store inline");
+
+        if cfg!(feature = "compact_str") {
+            assert_eq!(0, heap_size(&short));
+        } else {
+            assert_eq!(5, heap_size(&short));
+        }
+        assert!(heap_size(&long) >= long.len());
+
+        for s in ["short", "a string that's too long to store inline"] {
+            assert_ser_tokens(&Owned::from(s), &[Token::Str(s)]);
+            assert_ser_tokens(&Owned::from(String::from(s)), &[Token::Str(s)]);
+            assert_eq!(s, Name::from(String::from(s)).as_str());
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use alloc::{borrow::Cow, string::String};
+
+use crate::{Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Get a nested value by its path, using a JSON Pointer like `/server/ports/0`.
+
+    Pointers follow [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901). Each `/`-separated segment
+    is looked up in the value the previous segment pointed to:
+
+    - Structs, including enum variants, are searched by field name.
+    - Maps are searched for a key that's a string equal to the segment.
+    - Sequences, tuples, and structs with unnamed fields are indexed by a segment that's a decimal
+      number without leading zeros.
+
+    Within a segment, `~1` is unescaped into `/` and `~0` into `~`. The empty pointer `""` points at
+    the whole buffer.
+
+    This method returns `None` if the pointer is invalid, or if there's no value at its path.
+    */
+    pub fn get_path(&self, pointer: &str) -> Option<Ref<'_>> {
+        self.0.get_path(pointer).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get a mutable reference to a nested value by its path, using a JSON Pointer like `/server/ports/0`.
+
+    See [`Owned::get_path`] for details on how pointers are resolved.
+    */
+    pub fn get_path_mut(&mut self, pointer: &str) -> Option<&mut Owned> {
+        self.0.get_path_mut(pointer).map(Owned::from_value_mut)
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Get a nested value by its path, using a JSON Pointer like `/server/ports/0`.
+
+    See [`Owned::get_path`] for details on how pointers are resolved.
+    */
+    pub fn get_path(&self, pointer: &str) -> Option<Ref<'_>> {
+        self.0.get_path(pointer).map(|v| Ref(Value::Borrowed(v)))
+    }
+
+    /**
+    Get a mutable reference to a nested value by its path, using a JSON Pointer like `/server/ports/0`.
+
+    See [`Owned::get_path`] for details on how pointers are resolved. Values borrowed from other
+    buffers along the path are cloned so the value at the end of it can be changed.
+    */
+    pub fn get_path_mut(&mut self, pointer: &str) -> Option<&mut Ref<'a>> {
+        self.0.get_path_mut(pointer).map(Ref::from_value_mut)
+    }
+}
+
+impl<'a> Value<'a> {
+    fn get_path(&self, pointer: &str) -> Option<&Value<'a>> {
+        let mut value = self;
+
+        for segment in segments(pointer)? {
+            let segment = segment?;
+
+            value = if value.as_seq().is_some() {
+                value.get_index(index(&segment)?)?
+            } else {
+                value.get(&segment)?
+            };
+        }
+
+        Some(value)
+    }
+
+    fn get_path_mut(&mut self, pointer: &str) -> Option<&mut Value<'a>> {
+        let mut value = self;
+
+        for segment in segments(pointer)? {
+            let segment = segment?;
+
+            value = if value.as_seq().is_some() {
+                value.get_index_mut(index(&segment)?)?
+            } else {
+                value.get_mut(&segment)?
+            };
+        }
+
+        Some(value)
+    }
+}
+
+/**
+Split a pointer into its unescaped segments.
+
+Each segment is `None` if it contains an invalid escape sequence.
+*/
+pub(crate) fn segments(pointer: &str) -> Option<impl Iterator<Item = Option<Cow<'_, str>>>> {
+    let mut segments = pointer.split('/');
+
+    // A non-empty pointer must start with a `/`
+    if !segments.next()?.is_empty() {
+        return None;
+    }
+
+    Some(segments.map(unescape))
+}
+
+fn unescape(segment: &str) -> Option<Cow<'_, str>> {
+    if !segment.contains('~') {
+        return Some(Cow::Borrowed(segment));
+    }
+
+    let mut unescaped = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next()? {
+                '0' => unescaped.push('~'),
+                '1' => unescaped.push('/'),
+                _ => return None,
+            },
+            c => unescaped.push(c),
+        }
+    }
+
+    Some(Cow::Owned(unescaped))
+}
+
+pub(crate) fn index(segment: &str) -> Option<usize> {
+    // Indexes are decimal digits, without any sign or leading zeros
+    if segment.is_empty()
+        || !segment.bytes().all(|b| b.is_ascii_digit())
+        || (segment.len() > 1 && segment.starts_with('0'))
+    {
+        return None;
+    }
+
+    segment.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Owned {
+        Owned::from(Ref::record_struct(
+            "Config",
+            [
+                (
+                    "server",
+                    Ref::record_struct(
+                        "Server",
+                        [
+                            ("ports", Ref::seq([Ref::u16(80), Ref::u16(443)])),
+                            ("host", Ref::str("localhost")),
+                        ],
+                    ),
+                ),
+                (
+                    "env",
+                    Ref::map([
+                        (Ref::str("a/b"), Ref::u8(1)),
+                        (Ref::str("m~n"), Ref::u8(2)),
+                        (Ref::str(""), Ref::u8(3)),
+                    ]),
+                ),
+            ],
+        ))
+    }
+
+    #[test]
+    fn get_path() {
+        let config = config();
+
+        assert_eq!(Some(config.as_ref()), config.get_path(""));
+        assert_eq!(Some(Ref::u16(443)), config.get_path("/server/ports/1"));
+        assert_eq!(Some(Ref::str("localhost")), config.get_path("/server/host"));
+        assert_eq!(Some(Ref::u8(1)), config.get_path("/env/a~1b"));
+        assert_eq!(Some(Ref::u8(2)), config.get_path("/env/m~0n"));
+        assert_eq!(Some(Ref::u8(3)), config.get_path("/env/"));
+
+        assert_eq!(
+            Some(Ref::u16(80)),
+            config.as_ref().get_path("/server/ports/0")
+        );
+    }
+
+    #[test]
+    fn get_path_invalid() {
+        let config = config();
+
+        for pointer in [
+            "server",
+            "/missing",
+            "/server/ports/2",
+            "/server/ports/01",
+            "/server/ports/-",
+            "/server/ports/+1",
+            "/env/m~2n",
+            "/env/m~",
+            "/server/host/0",
+        ] {
+            assert!(config.get_path(pointer).is_none(), "{}", pointer);
+        }
+    }
+
+    #[test]
+    fn get_path_mut() {
+        let mut config = config();
+
+        *config.get_path_mut("/server/ports/0").unwrap() = Owned::from(8080u16);
+
+        assert_eq!(Some(Ref::u16(8080)), config.get_path("/server/ports/0"));
+        assert!(config.get_path_mut("/server/ports/2").is_none());
+    }
+}
@@ -0,0 +1,307 @@
+/*!
+Generating arbitrary buffers with `proptest`.
+*/
+
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::vec,
+    sample::select,
+    strategy::{BoxedStrategy, Just, Strategy, Union},
+};
+
+use crate::{
+    LenHint, Name, NewtypeStruct, NewtypeVariant, Owned, Struct, StructVariant, TupleStruct,
+    TupleVariant, UnitStruct, UnitVariant, Value,
+};
+
+/**
+The names given to generated structs and enums.
+*/
+const TYPE_NAMES: &[&str] = &["A", "B", "C", "D"];
+
+/**
+The names given to the fields of generated structs and enum variants.
+
+Fields are named in order, so a struct can't have more fields than there are names.
+*/
+const FIELD_NAMES: &[&str] = &[
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p",
+];
+
+/**
+The size of buffers to generate with [`proptest::arbitrary::any_with`].
+
+By default, buffers are generated at most 4 levels deep and 8 elements wide.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArbitraryOwned {
+    max_depth: u32,
+    max_width: usize,
+}
+
+impl Default for ArbitraryOwned {
+    fn default() -> Self {
+        ArbitraryOwned::new()
+    }
+}
+
+impl ArbitraryOwned {
+    /**
+    Generate buffers at most 4 levels deep and 8 elements wide.
+    */
+    pub fn new() -> Self {
+        ArbitraryOwned {
+            max_depth: 4,
+            max_width: 8,
+        }
+    }
+
+    /**
+    Set the maximum number of levels of nesting within generated buffers.
+
+    Values at the leaves of a buffer, like numbers and strings, aren't counted, so a depth of `0`
+    only generates buffers without any nesting.
+    */
+    pub fn with_max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /**
+    Set the maximum number of elements in each generated sequence, map, tuple, and struct.
+
+    Structs and enum variants have at most 16 fields, whatever the width.
+    */
+    pub fn with_max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+}
+
+impl Arbitrary for Owned {
+    type Parameters = ArbitraryOwned;
+    type Strategy = BoxedStrategy<Owned>;
+
+    /**
+    Generate arbitrary buffers.
+
+    Generated buffers cover every kind of value, including structs and enum variants nested
+    within each other, and maps with keys of any primitive type. Structs and enum variants have
+    `'static` names, so they serialize as structs. Map keys are unique within each map.
+    */
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy {
+        let depth = params.max_depth;
+        let width = params.max_width;
+
+        leaf()
+            .prop_recursive(
+                depth,
+                depth.saturating_mul(width as u32),
+                width.max(1) as u32,
+                move |inner| nested(inner, width),
+            )
+            .prop_map(Owned)
+            .boxed()
+    }
+}
+
+/**
+Generate a value that doesn't contain other values.
+*/
+fn leaf() -> BoxedStrategy<Value<'static>> {
+    Union::new([
+        Just(Value::Unit).boxed(),
+        any::<u8>().prop_map(Value::U8).boxed(),
+        any::<u16>().prop_map(Value::U16).boxed(),
+        any::<u32>().prop_map(Value::U32).boxed(),
+        any::<u64>().prop_map(Value::U64).boxed(),
+        any::<u128>().prop_map(|v| Value::U128(Box::new(v))).boxed(),
+        any::<i8>().prop_map(Value::I8).boxed(),
+        any::<i16>().prop_map(Value::I16).boxed(),
+        any::<i32>().prop_map(Value::I32).boxed(),
+        any::<i64>().prop_map(Value::I64).boxed(),
+        any::<i128>().prop_map(|v| Value::I128(Box::new(v))).boxed(),
+        any::<f32>().prop_map(Value::F32).boxed(),
+        any::<f64>().prop_map(Value::F64).boxed(),
+        any::<bool>().prop_map(Value::Bool).boxed(),
+        any::<char>().prop_map(Value::Char).boxed(),
+        any::<String>().prop_map(|v| Value::Str(v.into())).boxed(),
+        any::<String>()
+            .prop_map(|v| Value::SharedStr(Arc::from(v)))
+            .boxed(),
+        vec(any::<u8>(), 0..32)
+            .prop_map(|v| Value::Bytes(v.into_boxed_slice()))
+            .boxed(),
+        Just(Value::None).boxed(),
+        type_name()
+            .prop_map(|name| Value::UnitStruct(Box::new(UnitStruct { name })))
+            .boxed(),
+        variant()
+            .prop_map(|(name, variant_index, variant)| {
+                Value::UnitVariant(Box::new(UnitVariant {
+                    name,
+                    variant_index,
+                    variant,
+                }))
+            })
+            .boxed(),
+    ])
+    .boxed()
+}
+
+/**
+Generate a value that contains values generated by `inner`.
+*/
+fn nested(inner: BoxedStrategy<Value<'static>>, width: usize) -> BoxedStrategy<Value<'static>> {
+    let fields = {
+        let inner = inner.clone();
+        move || vec(inner.clone(), 0..=width)
+    };
+    let named_fields = {
+        let fields = fields.clone();
+        move || {
+            fields().prop_map(|fields| {
+                FIELD_NAMES
+                    .iter()
+                    .map(|name| Name::new(name))
+                    .zip(fields)
+                    .collect::<Vec<_>>()
+                    .into_boxed_slice()
+            })
+        }
+    };
+    let value = move || inner.clone();
+
+    Union::new([
+        value().prop_map(|v| Value::Some(Box::new(v))).boxed(),
+        (type_name(), value())
+            .prop_map(|(name, value)| Value::NewtypeStruct(Box::new(NewtypeStruct { name, value })))
+            .boxed(),
+        (type_name(), named_fields())
+            .prop_map(|(name, fields)| Value::Struct(Box::new(Struct { name, fields })))
+            .boxed(),
+        (type_name(), fields())
+            .prop_map(|(name, fields)| {
+                Value::TupleStruct(Box::new(TupleStruct {
+                    name,
+                    fields: fields.into_boxed_slice(),
+                }))
+            })
+            .boxed(),
+        fields()
+            .prop_map(|fields| Value::Tuple(fields.into_boxed_slice()))
+            .boxed(),
+        (fields(), any::<bool>())
+            .prop_map(|(fields, known)| {
+                Value::Seq(
+                    fields.into_boxed_slice(),
+                    if known {
+                        LenHint::Known
+                    } else {
+                        LenHint::Unknown
+                    },
+                )
+            })
+            .boxed(),
+        (vec((leaf(), value()), 0..=width), any::<bool>())
+            .prop_map(|(mut fields, known)| {
+                dedup_keys(&mut fields);
+
+                Value::Map(
+                    fields.into_boxed_slice(),
+                    if known {
+                        LenHint::Known
+                    } else {
+                        LenHint::Unknown
+                    },
+                )
+            })
+            .boxed(),
+        (variant(), value())
+            .prop_map(|((name, variant_index, variant), value)| {
+                Value::NewtypeVariant(Box::new(NewtypeVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    value,
+                }))
+            })
+            .boxed(),
+        (variant(), fields())
+            .prop_map(|((name, variant_index, variant), fields)| {
+                Value::TupleVariant(Box::new(TupleVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    fields: fields.into_boxed_slice(),
+                }))
+            })
+            .boxed(),
+        (variant(), named_fields())
+            .prop_map(|((name, variant_index, variant), fields)| {
+                Value::StructVariant(Box::new(StructVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    fields,
+                }))
+            })
+            .boxed(),
+    ])
+    .boxed()
+}
+
+/**
+Generate the name of a struct or enum.
+*/
+fn type_name() -> impl Strategy<Value = Name<'static>> + Clone {
+    select(TYPE_NAMES).prop_map(Name::new)
+}
+
+/**
+Generate the name of an enum, along with the index and name of one of its variants.
+
+Variants with the same index always have the same name.
+*/
+fn variant() -> impl Strategy<Value = (Name<'static>, u32, Name<'static>)> + Clone {
+    (type_name(), 0..TYPE_NAMES.len())
+        .prop_map(|(name, index)| (name, index as u32, Name::new(TYPE_NAMES[index])))
+}
+
+/**
+Remove entries whose keys are equal to the key of an earlier entry.
+*/
+fn dedup_keys(fields: &mut Vec<(Value<'static>, Value<'static>)>) {
+    let mut i = 0;
+
+    while i < fields.len() {
+        if fields[..i].iter().any(|(k, _)| *k == fields[i].0) {
+            fields.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{arbitrary::any_with, proptest};
+
+    proptest! {
+        #[test]
+        fn arbitrary_owned_round_trips(buf in any::<Owned>()) {
+            assert_eq!(buf, Owned::buffer(&buf).unwrap());
+        }
+
+        #[test]
+        fn arbitrary_owned_bounded(
+            buf in any_with::<Owned>(ArbitraryOwned::new().with_max_depth(2).with_max_width(3))
+        ) {
+            assert!(buf.max_depth() <= 3, "{:?}", buf);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use core::{iter, mem};
+
+use alloc::{boxed::Box, vec, vec::Vec};
+
+use crate::{Owned, Ref, Value};
+
+/**
+Which fields to remove with [`Owned::prune_nulls_with`].
+
+By default, fields with `None` or unit values are removed, but fields with empty collections are kept.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneNulls {
+    none: bool,
+    unit: bool,
+    empty: bool,
+}
+
+impl Default for PruneNulls {
+    fn default() -> Self {
+        PruneNulls::new()
+    }
+}
+
+impl PruneNulls {
+    /**
+    Remove fields with `None` or unit values.
+    */
+    pub fn new() -> Self {
+        PruneNulls {
+            none: true,
+            unit: true,
+            empty: false,
+        }
+    }
+
+    /**
+    Whether to remove fields with `None` values.
+    */
+    pub fn with_none(mut self, prune: bool) -> Self {
+        self.none = prune;
+        self
+    }
+
+    /**
+    Whether to remove fields with unit values, like `()`.
+    */
+    pub fn with_unit(mut self, prune: bool) -> Self {
+        self.unit = prune;
+        self
+    }
+
+    /**
+    Whether to remove fields with empty sequences or maps.
+
+    A map is considered empty if all of its fields would be removed.
+    */
+    pub fn with_empty(mut self, prune: bool) -> Self {
+        self.empty = prune;
+        self
+    }
+}
+
+impl Owned {
+    /**
+    Remove fields with `None` or unit values from every struct and map in this buffer.
+
+    This method returns the number of fields that were removed.
+    See [`Owned::prune_nulls_with`] for details.
+    */
+    pub fn prune_nulls(&mut self) -> usize {
+        self.0.prune_nulls(PruneNulls::new())
+    }
+
+    /**
+    Remove fields matching `prune` from every struct and map in this buffer.
+
+    Fields are removed from structs, including enum variants, and from maps. The elements of sequences
+    and tuples are never removed, since that would change the positions of other elements.
+    Nested values are pruned before the fields containing them, so a map that's left with no fields
+    can be removed as empty.
+
+    This method returns the number of fields that were removed. Fields nested within removed fields
+    aren't counted. The buffer is traversed using a stack instead of recursion, so deeply nested
+    buffers can't overflow the stack.
+    */
+    pub fn prune_nulls_with(&mut self, prune: PruneNulls) -> usize {
+        self.0.prune_nulls(prune)
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Remove fields with `None` or unit values from every struct and map in this buffer.
+
+    See [`Owned::prune_nulls_with`] for details.
+    */
+    pub fn prune_nulls(&mut self) -> usize {
+        self.0.prune_nulls(PruneNulls::new())
+    }
+
+    /**
+    Remove fields matching `prune` from every struct and map in this buffer.
+
+    See [`Owned::prune_nulls_with`] for details. Values borrowed from other buffers are cloned
+    if they contain fields to remove.
+    */
+    pub fn prune_nulls_with(&mut self, prune: PruneNulls) -> usize {
+        self.0.prune_nulls(prune)
+    }
+}
+
+/**
+What pruning will do to a value, worked out before the buffer is changed.
+*/
+#[derive(Clone, Copy, Default)]
+struct Plan {
+    /**
+    The number of values in this value's tree, including itself.
+    */
+    size: usize,
+    /**
+    Whether this value should be removed if it's the value of a field.
+    */
+    prune: bool,
+    /**
+    The number of fields that will be removed within this value.
+    */
+    removed: usize,
+}
+
+impl<'a> Value<'a> {
+    fn prune_nulls(&mut self, prune: PruneNulls) -> usize {
+        let plans = self.plan_prune(prune);
+        let removed = plans[0].removed;
+
+        // Each value is identified by its position in a pre-order traversal of the buffer,
+        // which is the same order the plan was made in
+        let mut stack = vec![(self, 0)];
+
+        while let Some((value, id)) = stack.pop() {
+            if plans[id].removed == 0 {
+                continue;
+            }
+
+            if let Value::Borrowed(v) = value {
+                *value = v.resolve().clone();
+            }
+
+            let children = child_ids(&plans, id);
+
+            match value {
+                Value::Map(fields, _) => prune_fields(fields, children, &plans, &mut stack),
+                Value::Struct(v) => prune_fields(&mut v.fields, children, &plans, &mut stack),
+                Value::StructVariant(v) => {
+                    prune_fields(&mut v.fields, children, &plans, &mut stack)
+                }
+                Value::Some(v) => stack.extend(Some(&mut **v).into_iter().zip(children)),
+                Value::NewtypeStruct(v) => {
+                    stack.extend(Some(&mut v.value).into_iter().zip(children))
+                }
+                Value::NewtypeVariant(v) => {
+                    stack.extend(Some(&mut v.value).into_iter().zip(children))
+                }
+                Value::Seq(fields, _) | Value::Tuple(fields) => {
+                    stack.extend(fields.iter_mut().zip(children))
+                }
+                Value::TupleStruct(v) => stack.extend(v.fields.iter_mut().zip(children)),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter_mut().zip(children)),
+                _ => (),
+            }
+        }
+
+        removed
+    }
+
+    /**
+    Work out what pruning will do to each value in the buffer, in pre-order.
+    */
+    fn plan_prune(&self, prune: PruneNulls) -> Vec<Plan> {
+        enum Step<'v, 'a> {
+            Enter(&'v Value<'a>),
+            Exit(&'v Value<'a>, usize),
+        }
+
+        let mut plans = Vec::new();
+        let mut stack = vec![Step::Enter(self)];
+
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Enter(value) => {
+                    let value = value.resolve();
+
+                    stack.push(Step::Exit(value, plans.len()));
+                    plans.push(Plan::default());
+
+                    // Children are pushed in reverse, so they're given ids in order
+                    let len = stack.len();
+                    value.for_each_child(|child| stack.push(Step::Enter(child)));
+                    stack[len..].reverse();
+                }
+                Step::Exit(value, id) => {
+                    plans[id].size = plans.len() - id;
+
+                    let mut fields = 0;
+                    let mut removed = 0;
+
+                    for child in child_ids(&plans, id) {
+                        let plan = plans[child];
+
+                        // Only the fields of structs and maps are removed
+                        if plan.prune && has_fields(value) {
+                            removed += 1;
+                        } else {
+                            fields += 1;
+                            removed += plan.removed;
+                        }
+                    }
+
+                    plans[id].removed = removed;
+                    plans[id].prune = match value {
+                        Value::None => prune.none,
+                        Value::Unit => prune.unit,
+                        Value::Seq(fields, _) => prune.empty && fields.is_empty(),
+                        Value::Map(..) => prune.empty && fields == 0,
+                        _ => false,
+                    };
+                }
+            }
+        }
+
+        plans
+    }
+
+    /**
+    Call `f` on each value nested directly within this one.
+
+    Map keys aren't considered nested values, since they're never pruned.
+    */
+    fn for_each_child<'v>(&'v self, mut f: impl FnMut(&'v Value<'a>)) {
+        match self {
+            Value::Some(v) => f(v),
+            Value::NewtypeStruct(v) => f(&v.value),
+            Value::NewtypeVariant(v) => f(&v.value),
+            Value::Seq(fields, _) | Value::Tuple(fields) => fields.iter().for_each(f),
+            Value::TupleStruct(v) => v.fields.iter().for_each(f),
+            Value::TupleVariant(v) => v.fields.iter().for_each(f),
+            Value::Struct(v) => v.fields.iter().for_each(|(_, v)| f(v)),
+            Value::StructVariant(v) => v.fields.iter().for_each(|(_, v)| f(v)),
+            Value::Map(fields, _) => fields.iter().for_each(|(_, v)| f(v)),
+            _ => (),
+        }
+    }
+}
+
+fn has_fields(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Map(..) | Value::Struct(_) | Value::StructVariant(_)
+    )
+}
+
+/**
+Iterate over the ids of the values nested directly within the value with `id`.
+*/
+fn child_ids(plans: &[Plan], id: usize) -> impl Iterator<Item = usize> + '_ {
+    let end = id + plans[id].size;
+    let mut next = id + 1;
+
+    iter::from_fn(move || {
+        if next >= end {
+            return None;
+        }
+
+        let child = next;
+        next += plans[child].size;
+
+        Some(child)
+    })
+}
+
+/**
+Remove the fields whose values are planned to be pruned, and queue the rest to be pruned themselves.
+*/
+fn prune_fields<'v, 'a, K>(
+    fields: &'v mut Box<[(K, Value<'a>)]>,
+    ids: impl Iterator<Item = usize>,
+    plans: &[Plan],
+    stack: &mut Vec<(&'v mut Value<'a>, usize)>,
+) {
+    let mut retained = Vec::with_capacity(fields.len());
+    let mut retained_ids = Vec::with_capacity(fields.len());
+
+    for (field, id) in Vec::from(mem::take(fields)).into_iter().zip(ids) {
+        if !plans[id].prune {
+            retained.push(field);
+            retained_ids.push(id);
+        }
+    }
+
+    *fields = retained.into_boxed_slice();
+
+    stack.extend(fields.iter_mut().map(|(_, v)| v).zip(retained_ids));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_nulls() {
+        let mut buf = Owned::from(Ref::record_struct(
+            "User",
+            [
+                ("name", Ref::str("Jane")),
+                ("email", Ref::none()),
+                ("extra", Ref::unit()),
+                (
+                    "tags",
+                    Ref::map([
+                        (Ref::str("a"), Ref::none()),
+                        (Ref::str("b"), Ref::seq([Ref::none()])),
+                    ]),
+                ),
+            ],
+        ));
+
+        assert_eq!(3, buf.prune_nulls());
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "User",
+                [
+                    ("name", Ref::str("Jane")),
+                    ("tags", Ref::map([(Ref::str("b"), Ref::seq([Ref::none()]))])),
+                ],
+            )),
+            buf
+        );
+
+        assert_eq!(0, buf.prune_nulls());
+    }
+
+    #[test]
+    fn prune_nulls_empty() {
+        let buf = Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::seq([])),
+                (
+                    "b",
+                    Ref::map([(Ref::str("c"), Ref::map([(Ref::str("d"), Ref::none())]))]),
+                ),
+                ("e", Ref::unit()),
+                ("f", Ref::some(Ref::u8(1))),
+            ],
+        );
+
+        let mut pruned = buf.clone();
+        assert_eq!(
+            2,
+            pruned.prune_nulls_with(PruneNulls::new().with_unit(false).with_empty(true))
+        );
+        assert_eq!(
+            Ref::record_struct("Struct", [("e", Ref::unit()), ("f", Ref::some(Ref::u8(1)))]),
+            pruned
+        );
+
+        let mut pruned = buf;
+        assert_eq!(
+            1,
+            pruned.prune_nulls_with(PruneNulls::new().with_none(false))
+        );
+        assert!(pruned
+            .get("b")
+            .unwrap()
+            .get("c")
+            .unwrap()
+            .get("d")
+            .is_some());
+    }
+
+    #[test]
+    fn prune_nulls_borrowed() {
+        let owned = Owned::from(Ref::map([
+            (Ref::str("a"), Ref::none()),
+            (Ref::str("b"), Ref::u8(1)),
+        ]));
+
+        let mut buf = Ref::seq([owned.as_ref()]);
+
+        assert_eq!(1, buf.prune_nulls());
+        assert_eq!(Ref::seq([Ref::map([(Ref::str("b"), Ref::u8(1))])]), buf);
+        assert!(owned.get("a").is_some());
+    }
+
+    #[test]
+    fn prune_nulls_deeply_nested() {
+        let mut buf = Ref::none();
+        for _ in 0..100_000 {
+            buf = Ref::record_struct("Struct", [("a", Ref::u8(1)), ("b", buf)]);
+        }
+
+        assert_eq!(1, buf.prune_nulls());
+    }
+}
@@ -0,0 +1,312 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::{Name, Owned, Ref, Value};
+
+/**
+A case convention to rename fields with, like `#[serde(rename_all)]`.
+
+Field names are split into words at underscores, hyphens, and changes from lowercase letters or digits
+to uppercase letters, so `snake_case`, `kebab-case`, `camelCase`, and `PascalCase` names can all be converted.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Case {
+    /**
+    `camelCase` names.
+    */
+    Camel,
+    /**
+    `PascalCase` names.
+    */
+    Pascal,
+    /**
+    `snake_case` names.
+    */
+    Snake,
+    /**
+    `kebab-case` names.
+    */
+    Kebab,
+}
+
+impl Case {
+    /**
+    Convert a field name to this case.
+    */
+    pub fn convert(self, field: &str) -> String {
+        let mut converted = String::with_capacity(field.len());
+
+        for (i, word) in words(field).into_iter().enumerate() {
+            match self {
+                Case::Camel | Case::Pascal => {
+                    let mut chars = word.chars();
+
+                    if let Some(first) = chars.next() {
+                        if i == 0 && self == Case::Camel {
+                            converted.extend(first.to_lowercase());
+                        } else {
+                            converted.extend(first.to_uppercase());
+                        }
+                    }
+
+                    converted.extend(chars.flat_map(char::to_lowercase));
+                }
+                Case::Snake | Case::Kebab => {
+                    if i > 0 {
+                        converted.push(if self == Case::Snake { '_' } else { '-' });
+                    }
+
+                    converted.extend(word.chars().flat_map(char::to_lowercase));
+                }
+            }
+        }
+
+        converted
+    }
+}
+
+/**
+Split a field name into its words.
+*/
+fn words(field: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+
+    for part in field.split(['_', '-']) {
+        let mut start = 0;
+        let mut prev: Option<char> = None;
+
+        for (i, c) in part.char_indices() {
+            if c.is_uppercase()
+                && prev.is_some_and(|prev| prev.is_lowercase() || prev.is_ascii_digit())
+            {
+                words.push(&part[start..i]);
+                start = i;
+            }
+
+            prev = Some(c);
+        }
+
+        words.push(&part[start..]);
+    }
+
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+impl Owned {
+    /**
+    Rename the fields of every struct and map in this buffer with `convert`.
+
+    Fields are renamed in structs, including enum variants, and in maps with string keys.
+    The values of fields, the names of structs and enum variants, and keys that aren't strings
+    are left intact. Use [`Case::convert`] to apply a case convention, like `#[serde(rename_all)]` does:
+
+    ```
+    # use serde_buf::{Case, Owned, Ref};
+    let mut buf = Owned::from(Ref::record_struct("User", [("user_name", Ref::str("Jane"))]));
+
+    buf.rename_fields(|field| Case::Camel.convert(field));
+
+    assert_eq!(Some(Ref::str("Jane")), buf.get("userName"));
+    ```
+
+    The buffer is traversed using a stack instead of recursion, so deeply nested buffers can't
+    overflow the stack.
+    */
+    pub fn rename_fields(&mut self, convert: impl Fn(&str) -> String) {
+        self.0.rename_fields(convert)
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Rename the fields of every struct and map in this buffer with `convert`.
+
+    See [`Owned::rename_fields`] for details. Collections borrowed from other buffers are cloned so
+    their fields can be renamed.
+    */
+    pub fn rename_fields(&mut self, convert: impl Fn(&str) -> String) {
+        self.0.rename_fields(convert)
+    }
+}
+
+impl<'a> Value<'a> {
+    fn rename_fields(&mut self, convert: impl Fn(&str) -> String) {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            if let Value::Borrowed(v) = value {
+                if !v.resolve().has_fields() {
+                    continue;
+                }
+
+                *value = v.resolve().clone();
+            }
+
+            match value {
+                Value::Some(v) => stack.push(v),
+                Value::NewtypeStruct(v) => stack.push(&mut v.value),
+                Value::NewtypeVariant(v) => stack.push(&mut v.value),
+                Value::Seq(fields, _) | Value::Tuple(fields) => stack.extend(fields.iter_mut()),
+                Value::TupleStruct(v) => stack.extend(v.fields.iter_mut()),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter_mut()),
+                Value::Struct(v) => {
+                    for (k, v) in v.fields.iter_mut() {
+                        *k = Name::from(convert(k));
+                        stack.push(v);
+                    }
+                }
+                Value::StructVariant(v) => {
+                    for (k, v) in v.fields.iter_mut() {
+                        *k = Name::from(convert(k));
+                        stack.push(v);
+                    }
+                }
+                Value::Map(fields, _) => {
+                    for (k, v) in fields.iter_mut() {
+                        if let Some(field) = k.as_str() {
+                            *k = Name::from(convert(field)).into_value();
+                        }
+
+                        stack.push(v);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /**
+    Whether this value is, or contains, a struct or map.
+    */
+    pub(crate) fn has_fields(&self) -> bool {
+        matches!(
+            self,
+            Value::Some(_)
+                | Value::NewtypeStruct(_)
+                | Value::NewtypeVariant(_)
+                | Value::Seq(..)
+                | Value::Tuple(_)
+                | Value::TupleStruct(_)
+                | Value::TupleVariant(_)
+                | Value::Struct(_)
+                | Value::StructVariant(_)
+                | Value::Map(..)
+                | Value::Borrowed(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_case() {
+        for (field, camel, pascal, snake, kebab) in [
+            (
+                "user_name",
+                "userName",
+                "UserName",
+                "user_name",
+                "user-name",
+            ),
+            ("userName", "userName", "UserName", "user_name", "user-name"),
+            ("UserName", "userName", "UserName", "user_name", "user-name"),
+            (
+                "user-name",
+                "userName",
+                "UserName",
+                "user_name",
+                "user-name",
+            ),
+            ("id", "id", "Id", "id", "id"),
+            (
+                "address_line2",
+                "addressLine2",
+                "AddressLine2",
+                "address_line2",
+                "address-line2",
+            ),
+            ("ip4Addr", "ip4Addr", "Ip4Addr", "ip4_addr", "ip4-addr"),
+            ("_private", "private", "Private", "private", "private"),
+            ("", "", "", "", ""),
+        ] {
+            assert_eq!(camel, Case::Camel.convert(field), "{}", field);
+            assert_eq!(pascal, Case::Pascal.convert(field), "{}", field);
+            assert_eq!(snake, Case::Snake.convert(field), "{}", field);
+            assert_eq!(kebab, Case::Kebab.convert(field), "{}", field);
+        }
+    }
+
+    #[test]
+    fn rename_fields() {
+        let mut buf = Owned::from(Ref::record_struct(
+            "User",
+            [
+                ("user_name", Ref::str("user_name")),
+                (
+                    "home_address",
+                    Ref::some(Ref::map([
+                        (Ref::str("street_name"), Ref::str("Main")),
+                        (Ref::u8(1), Ref::unit()),
+                    ])),
+                ),
+                (
+                    "login_events",
+                    Ref::seq([Ref::record_struct_variant(
+                        "Event",
+                        0,
+                        "Login_Event",
+                        [("occurred_at", Ref::u64(1))],
+                    )]),
+                ),
+            ],
+        ));
+
+        buf.rename_fields(|field| Case::Camel.convert(field));
+
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "User",
+                [
+                    ("userName", Ref::str("user_name")),
+                    (
+                        "homeAddress",
+                        Ref::some(Ref::map([
+                            (Ref::str("streetName"), Ref::str("Main")),
+                            (Ref::u8(1), Ref::unit()),
+                        ])),
+                    ),
+                    (
+                        "loginEvents",
+                        Ref::seq([Ref::record_struct_variant(
+                            "Event",
+                            0,
+                            "Login_Event",
+                            [("occurredAt", Ref::u64(1))],
+                        )]),
+                    ),
+                ],
+            )),
+            buf
+        );
+    }
+
+    #[test]
+    fn rename_fields_borrowed() {
+        let owned = Owned::from(Ref::map([(Ref::str("first_name"), Ref::str("Jane"))]));
+
+        let mut buf = Ref::seq([owned.as_ref(), Ref::str("last_name")]);
+        buf.rename_fields(|field| Case::Kebab.convert(field));
+
+        assert_eq!(
+            Ref::seq([
+                Ref::map([(Ref::str("first-name"), Ref::str("Jane"))]),
+                Ref::str("last_name")
+            ]),
+            buf
+        );
+        assert_eq!(Some(Ref::str("Jane")), owned.get("first_name"));
+    }
+}
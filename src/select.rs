@@ -0,0 +1,409 @@
+use core::mem;
+
+use alloc::{borrow::Cow, boxed::Box, vec, vec::Vec};
+
+use crate::{
+    pointer::{index, segments},
+    Name, Owned, Ref, Struct, StructVariant, TupleStruct, TupleVariant, Value,
+};
+
+impl Owned {
+    /**
+    Remove fields whose names don't satisfy `keep` from every struct and map in this buffer.
+
+    Fields are removed from structs, including enum variants, and from maps with string keys.
+    Entries of maps with keys that aren't strings are kept. The values of kept fields are searched
+    for more fields to remove, so `keep` applies to the names of fields at any depth.
+
+    This method returns the number of fields that were removed. Fields nested within removed fields
+    aren't counted. The buffer is traversed using a stack instead of recursion, so deeply nested
+    buffers can't overflow the stack.
+    */
+    pub fn retain_fields(&mut self, keep: impl Fn(&str) -> bool) -> usize {
+        self.0.retain_fields(keep)
+    }
+
+    /**
+    Select the values at `paths` in this buffer, using JSON Pointers like `/server/ports/0`.
+
+    The result is a new buffer with the same structure as this one, but containing only the values
+    at the given paths, and the structs, maps, and sequences that lead to them. Those collections keep
+    their names, and their remaining fields and elements stay in their original order. That means the
+    indexes of elements in sequences can change if earlier elements aren't selected. The whole value at
+    each path is kept.
+
+    Paths are resolved in the same way as [`Owned::get_path`]. Paths that are invalid, or that don't
+    point to a value in the buffer, are ignored. If no paths select any part of the buffer then the
+    result is a unit.
+    */
+    pub fn project(&self, paths: &[&str]) -> Owned {
+        Owned(self.0.project(paths))
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Remove fields whose names don't satisfy `keep` from every struct and map in this buffer.
+
+    See [`Owned::retain_fields`] for details. Values borrowed from other buffers are cloned
+    if they contain fields to remove.
+    */
+    pub fn retain_fields(&mut self, keep: impl Fn(&str) -> bool) -> usize {
+        self.0.retain_fields(keep)
+    }
+
+    /**
+    Select the values at `paths` in this buffer, using JSON Pointers like `/server/ports/0`.
+
+    See [`Owned::project`] for details.
+    */
+    pub fn project(&self, paths: &[&str]) -> Ref<'a> {
+        Ref(self.0.project(paths))
+    }
+}
+
+/**
+The parts of a value selected by a set of paths.
+*/
+#[derive(Default)]
+struct Selection<'p> {
+    /**
+    Whether the whole value is selected.
+    */
+    all: bool,
+    /**
+    The selections within fields and elements of the value, by the path segment that leads to them.
+    */
+    nested: Vec<(Cow<'p, str>, Selection<'p>)>,
+}
+
+impl<'p> Selection<'p> {
+    fn new(paths: &[&'p str]) -> Self {
+        let mut selection = Selection::default();
+
+        for path in paths {
+            selection.insert(path);
+        }
+
+        selection
+    }
+
+    fn insert(&mut self, path: &'p str) {
+        let Some(segments) =
+            segments(path).and_then(|segments| segments.collect::<Option<Vec<_>>>())
+        else {
+            return;
+        };
+
+        let mut selection = self;
+        for segment in segments {
+            let i = match selection.nested.iter().position(|(s, _)| *s == segment) {
+                Some(i) => i,
+                None => {
+                    selection.nested.push((segment, Selection::default()));
+                    selection.nested.len() - 1
+                }
+            };
+
+            selection = &mut selection.nested[i].1;
+        }
+
+        selection.all = true;
+    }
+
+    fn field(&self, field: &str) -> Option<&Selection<'p>> {
+        self.nested
+            .iter()
+            .find(|(segment, _)| segment == field)
+            .map(|(_, selection)| selection)
+    }
+
+    fn element(&self, element: usize) -> Option<&Selection<'p>> {
+        self.nested
+            .iter()
+            .find(|(segment, _)| index(segment) == Some(element))
+            .map(|(_, selection)| selection)
+    }
+}
+
+impl<'a> Value<'a> {
+    fn retain_fields(&mut self, keep: impl Fn(&str) -> bool) -> usize {
+        let mut removed = 0;
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            if let Value::Borrowed(v) = value {
+                if !v.resolve().has_fields() {
+                    continue;
+                }
+
+                *value = v.resolve().clone();
+            }
+
+            match value {
+                Value::Some(v) => stack.push(v),
+                Value::NewtypeStruct(v) => stack.push(&mut v.value),
+                Value::NewtypeVariant(v) => stack.push(&mut v.value),
+                Value::Seq(fields, _) | Value::Tuple(fields) => stack.extend(fields.iter_mut()),
+                Value::TupleStruct(v) => stack.extend(v.fields.iter_mut()),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter_mut()),
+                Value::Struct(v) => {
+                    removed += retain_boxed(&mut v.fields, |(k, _)| keep(k));
+                    stack.extend(v.fields.iter_mut().map(|(_, v)| v));
+                }
+                Value::StructVariant(v) => {
+                    removed += retain_boxed(&mut v.fields, |(k, _)| keep(k));
+                    stack.extend(v.fields.iter_mut().map(|(_, v)| v));
+                }
+                Value::Map(fields, _) => {
+                    removed += retain_boxed(fields, |(k, _)| match k.as_str() {
+                        Some(k) => keep(k),
+                        None => true,
+                    });
+                    stack.extend(fields.iter_mut().map(|(_, v)| v));
+                }
+                _ => (),
+            }
+        }
+
+        removed
+    }
+
+    fn project(&self, paths: &[&str]) -> Value<'a> {
+        self.project_selection(&Selection::new(paths))
+            .unwrap_or(Value::Unit)
+    }
+
+    /**
+    Select the parts of this value in `selection`.
+
+    This recurses through the value, but only as deep as the longest selected path.
+    */
+    fn project_selection(&self, selection: &Selection) -> Option<Value<'a>> {
+        if selection.all {
+            return Some(self.clone());
+        }
+
+        let fields = |fields: &[(Name<'a>, Value<'a>)]| -> Box<[(Name<'a>, Value<'a>)]> {
+            fields
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.project_selection(selection.field(k)?)?)))
+                .collect()
+        };
+
+        let elements = |fields: &[Value<'a>]| -> Box<[Value<'a>]> {
+            fields
+                .iter()
+                .enumerate()
+                .filter_map(|(i, v)| v.project_selection(selection.element(i)?))
+                .collect()
+        };
+
+        Some(match self.resolve() {
+            Value::Seq(v, len_hint) => Value::Seq(elements(v), *len_hint),
+            Value::Tuple(v) => Value::Tuple(elements(v)),
+            Value::TupleStruct(v) => Value::TupleStruct(Box::new(TupleStruct {
+                name: v.name.clone(),
+                fields: elements(&v.fields),
+            })),
+            Value::TupleVariant(v) => Value::TupleVariant(Box::new(TupleVariant {
+                name: v.name.clone(),
+                variant_index: v.variant_index,
+                variant: v.variant.clone(),
+                fields: elements(&v.fields),
+            })),
+            Value::Struct(v) => Value::Struct(Box::new(Struct {
+                name: v.name.clone(),
+                fields: fields(&v.fields),
+            })),
+            Value::StructVariant(v) => Value::StructVariant(Box::new(StructVariant {
+                name: v.name.clone(),
+                variant_index: v.variant_index,
+                variant: v.variant.clone(),
+                fields: fields(&v.fields),
+            })),
+            Value::Map(v, len_hint) => Value::Map(
+                v.iter()
+                    .filter_map(|(k, v)| {
+                        let selection = selection.field(k.as_str()?)?;
+
+                        Some((k.clone(), v.project_selection(selection)?))
+                    })
+                    .collect(),
+                *len_hint,
+            ),
+            _ => return None,
+        })
+    }
+}
+
+/**
+Remove the elements of a boxed slice that don't satisfy `keep`, returning how many were removed.
+
+The slice is only reallocated if any elements are removed.
+*/
+fn retain_boxed<T>(slice: &mut Box<[T]>, keep: impl Fn(&T) -> bool) -> usize {
+    if slice.iter().all(&keep) {
+        return 0;
+    }
+
+    let mut vec = Vec::from(mem::take(slice));
+    let len = vec.len();
+    vec.retain(keep);
+
+    *slice = vec.into_boxed_slice();
+
+    len - slice.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> Owned {
+        Owned::from(Ref::record_struct(
+            "Record",
+            [
+                ("id", Ref::u64(1)),
+                (
+                    "user",
+                    Ref::record_struct(
+                        "User",
+                        [
+                            ("id", Ref::u64(2)),
+                            ("name", Ref::str("Jane")),
+                            ("emails", Ref::seq([Ref::str("a@b"), Ref::str("c@d")])),
+                        ],
+                    ),
+                ),
+                (
+                    "tags",
+                    Ref::map([
+                        (Ref::str("id"), Ref::str("tag")),
+                        (Ref::str("env"), Ref::str("prod")),
+                        (Ref::u8(1), Ref::unit()),
+                    ]),
+                ),
+                (
+                    "events",
+                    Ref::seq([
+                        Ref::record_struct_variant("Event", 0, "Login", [("at", Ref::u64(3))]),
+                        Ref::record_struct_variant("Event", 1, "Logout", [("at", Ref::u64(4))]),
+                    ]),
+                ),
+            ],
+        ))
+    }
+
+    #[test]
+    fn retain_fields() {
+        let mut buf = record();
+
+        assert_eq!(
+            4,
+            buf.retain_fields(|field| field != "emails" && field != "env" && field != "at")
+        );
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "Record",
+                [
+                    ("id", Ref::u64(1)),
+                    (
+                        "user",
+                        Ref::record_struct(
+                            "User",
+                            [("id", Ref::u64(2)), ("name", Ref::str("Jane"))],
+                        ),
+                    ),
+                    (
+                        "tags",
+                        Ref::map([(Ref::str("id"), Ref::str("tag")), (Ref::u8(1), Ref::unit())]),
+                    ),
+                    (
+                        "events",
+                        Ref::seq([
+                            Ref::record_struct_variant::<&str>("Event", 0, "Login", []),
+                            Ref::record_struct_variant::<&str>("Event", 1, "Logout", []),
+                        ]),
+                    ),
+                ],
+            )),
+            buf
+        );
+
+        // Fields nested in removed fields aren't counted
+        assert_eq!(3, buf.retain_fields(|field| field == "id"));
+        assert_eq!(0, buf.retain_fields(|field| field == "id"));
+    }
+
+    #[test]
+    fn retain_fields_borrowed() {
+        let owned = record();
+
+        let mut buf = Ref::seq([owned.as_ref(), Ref::str("id")]);
+        buf.retain_fields(|field| field == "id");
+
+        assert_eq!(
+            Ref::seq([
+                Ref::record_struct("Record", [("id", Ref::u64(1))]),
+                Ref::str("id")
+            ]),
+            buf
+        );
+        assert_eq!(record(), owned);
+    }
+
+    #[test]
+    fn project() {
+        let buf = record();
+
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "Record",
+                [
+                    (
+                        "user",
+                        Ref::record_struct(
+                            "User",
+                            [
+                                ("name", Ref::str("Jane")),
+                                ("emails", Ref::seq([Ref::str("c@d")])),
+                            ],
+                        ),
+                    ),
+                    ("tags", Ref::map([(Ref::str("env"), Ref::str("prod"))])),
+                    (
+                        "events",
+                        Ref::seq([Ref::record_struct_variant(
+                            "Event",
+                            1,
+                            "Logout",
+                            [("at", Ref::u64(4))],
+                        )]),
+                    ),
+                ],
+            )),
+            buf.project(&[
+                "/events/1",
+                "/user/emails/1",
+                "/tags/env",
+                "/user/name",
+                "/user/missing",
+                "invalid",
+            ])
+        );
+
+        // Selecting a value selects everything within it
+        assert_eq!(
+            Ref::record_struct("Record", [("user", buf.get("user").unwrap())]),
+            buf.project(&["/user/name", "/user"])
+        );
+        assert_eq!(buf, buf.project(&[""]));
+        assert_eq!(
+            Ref::record_struct::<&str>("Record", []),
+            buf.as_ref().project(&["/missing"])
+        );
+        assert_eq!(Ref::unit(), Ref::u8(1).project(&["/a"]));
+        assert_eq!(Ref::u8(1), Ref::u8(1).project(&[""]));
+    }
+}
@@ -1,16 +1,22 @@
-use core::{cmp, fmt, marker::PhantomData};
+use core::{
+    cmp, fmt, mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use alloc::{boxed::Box, string::ToString, vec::Vec};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use serde::{
     ser::{
-        self, Error as _, SerializeMap as _, SerializeSeq as _, SerializeStruct as _,
+        self, SerializeMap as _, SerializeSeq as _, SerializeStruct as _,
         SerializeStructVariant as _, SerializeTuple as _, SerializeTupleStruct as _,
         SerializeTupleVariant as _,
     },
     Serialize,
 };
 
-use crate::{Error, Owned, Ref, Value};
+use crate::{
+    display, unique, Error, ErrorKind, LenHint, Name, NewtypeStruct, NewtypeVariant, Owned, Ref,
+    Struct, StructVariant, TupleStruct, TupleVariant, UnitStruct, UnitVariant, Value,
+};
 
 impl<'a> Serialize for Ref<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -41,104 +47,113 @@ impl<'a> Serialize for Value<'a> {
             Value::U16(v) => serializer.serialize_u16(v),
             Value::U32(v) => serializer.serialize_u32(v),
             Value::U64(v) => serializer.serialize_u64(v),
-            Value::U128(v) => serializer.serialize_u128(v),
+            Value::U128(ref v) => serializer.serialize_u128(**v),
             Value::I8(v) => serializer.serialize_i8(v),
             Value::I16(v) => serializer.serialize_i16(v),
             Value::I32(v) => serializer.serialize_i32(v),
             Value::I64(v) => serializer.serialize_i64(v),
-            Value::I128(v) => serializer.serialize_i128(v),
+            Value::I128(ref v) => serializer.serialize_i128(**v),
             Value::F32(v) => serializer.serialize_f32(v),
             Value::F64(v) => serializer.serialize_f64(v),
             Value::Bool(v) => serializer.serialize_bool(v),
             Value::Char(v) => serializer.serialize_char(v),
-            Value::Str(ref v) => serializer.serialize_str(&v),
+            Value::Str(ref v) => serializer.serialize_str(v),
+            Value::SharedStr(ref v) => serializer.serialize_str(v),
             Value::BorrowedStr(v) => serializer.serialize_str(v),
             Value::Bytes(ref v) => serializer.serialize_bytes(v),
             Value::BorrowedBytes(v) => serializer.serialize_bytes(v),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(ref v) => serializer.serialize_bytes(v),
+            #[cfg(feature = "json")]
+            Value::RawJson(ref v) => crate::json::serialize_raw(v, serializer),
             Value::None => serializer.serialize_none(),
             Value::Some(ref v) => serializer.serialize_some(v),
-            Value::UnitStruct { name } => serializer.serialize_unit_struct(name),
-            Value::NewtypeStruct { name, ref value } => {
-                serializer.serialize_newtype_struct(name, value)
-            }
-            Value::Struct { name, ref fields } => {
-                let mut serializer = serializer.serialize_struct(name, fields.len())?;
-
-                for (name, field) in &**fields {
-                    serializer.serialize_field(name, field)?;
+            Value::UnitStruct(ref v) => match v.name.as_static() {
+                Some(name) => serializer.serialize_unit_struct(name),
+                None => serializer.serialize_unit(),
+            },
+            Value::NewtypeStruct(ref v) => match v.name.as_static() {
+                Some(name) => serializer.serialize_newtype_struct(name, &v.value),
+                None => v.value.serialize(serializer),
+            },
+            Value::Struct(ref v) => match (v.name.as_static(), static_fields(&v.fields)) {
+                (Some(name), Some(fields)) => {
+                    let mut serializer = serializer.serialize_struct(name, v.fields.len())?;
+
+                    for (name, field) in fields {
+                        serializer.serialize_field(name, field)?;
+                    }
+
+                    serializer.end()
                 }
+                _ => MapFields(&v.fields).serialize(serializer),
+            },
+            Value::TupleStruct(ref v) => match v.name.as_static() {
+                Some(name) => {
+                    let mut serializer = serializer.serialize_tuple_struct(name, v.fields.len())?;
 
-                serializer.end()
-            }
-            Value::TupleStruct { name, ref fields } => {
-                let mut serializer = serializer.serialize_tuple_struct(name, fields.len())?;
+                    for field in &*v.fields {
+                        serializer.serialize_field(field)?;
+                    }
 
-                for field in &**fields {
-                    serializer.serialize_field(field)?;
+                    serializer.end()
                 }
-
-                serializer.end()
-            }
-            Value::Tuple(ref v) => {
-                let mut serializer = serializer.serialize_tuple(v.len())?;
-
-                for field in &**v {
-                    serializer.serialize_element(field)?;
+                None => TupleFields(&v.fields).serialize(serializer),
+            },
+            Value::Tuple(ref v) => TupleFields(v).serialize(serializer),
+            Value::UnitVariant(ref v) => match static_variant(&v.name, &v.variant) {
+                Some((name, variant)) => {
+                    serializer.serialize_unit_variant(name, v.variant_index, variant)
                 }
-
-                serializer.end()
-            }
-            Value::UnitVariant {
-                name,
-                variant_index,
-                variant,
-            } => serializer.serialize_unit_variant(name, variant_index, variant),
-            Value::NewtypeVariant {
-                name,
-                variant_index,
-                variant,
-                ref value,
-            } => serializer.serialize_newtype_variant(name, variant_index, variant, value),
-            Value::TupleVariant {
-                name,
-                variant_index,
-                variant,
-                ref fields,
-            } => {
-                let mut serializer = serializer.serialize_tuple_variant(
-                    name,
-                    variant_index,
-                    variant,
-                    fields.len(),
-                )?;
-
-                for field in &**fields {
-                    serializer.serialize_field(field)?;
+                None => serializer.serialize_str(&v.variant),
+            },
+            Value::NewtypeVariant(ref v) => match static_variant(&v.name, &v.variant) {
+                Some((name, variant)) => {
+                    serializer.serialize_newtype_variant(name, v.variant_index, variant, &v.value)
                 }
-
-                serializer.end()
-            }
-            Value::StructVariant {
-                name,
-                variant_index,
-                variant,
-                ref fields,
-            } => {
-                let mut serializer = serializer.serialize_struct_variant(
-                    name,
-                    variant_index,
-                    variant,
-                    fields.len(),
-                )?;
-
-                for (name, field) in &**fields {
-                    serializer.serialize_field(name, field)?;
+                None => serialize_tagged(serializer, &v.variant, &v.value),
+            },
+            Value::TupleVariant(ref v) => match static_variant(&v.name, &v.variant) {
+                Some((name, variant)) => {
+                    let mut serializer = serializer.serialize_tuple_variant(
+                        name,
+                        v.variant_index,
+                        variant,
+                        v.fields.len(),
+                    )?;
+
+                    for field in &*v.fields {
+                        serializer.serialize_field(field)?;
+                    }
+
+                    serializer.end()
+                }
+                None => serialize_tagged(serializer, &v.variant, &TupleFields(&v.fields)),
+            },
+            Value::StructVariant(ref v) => {
+                match (
+                    static_variant(&v.name, &v.variant),
+                    static_fields(&v.fields),
+                ) {
+                    (Some((name, variant)), Some(fields)) => {
+                        let mut serializer = serializer.serialize_struct_variant(
+                            name,
+                            v.variant_index,
+                            variant,
+                            v.fields.len(),
+                        )?;
+
+                        for (name, field) in fields {
+                            serializer.serialize_field(name, field)?;
+                        }
+
+                        serializer.end()
+                    }
+                    _ => serialize_tagged(serializer, &v.variant, &MapFields(&v.fields)),
                 }
-
-                serializer.end()
             }
-            Value::Seq(ref v) => {
-                let mut serializer = serializer.serialize_seq(Some(v.len()))?;
+            Value::Seq(ref v, len) => {
+                let mut serializer = serializer.serialize_seq(len.len(v.len()))?;
 
                 for field in &**v {
                     serializer.serialize_element(field)?;
@@ -146,16 +161,117 @@ impl<'a> Serialize for Value<'a> {
 
                 serializer.end()
             }
-            Value::Map(ref v) => {
-                let mut serializer = serializer.serialize_map(Some(v.len()))?;
+            Value::Map(ref v, len) => {
+                let mut serializer = serializer.serialize_map(len.len(v.len()))?;
 
                 for (key, value) in &**v {
-                    serializer.serialize_entry(key, value)?;
+                    serializer.serialize_entry(&MapKey(key), value)?;
                 }
 
                 serializer.end()
             }
+            Value::Borrowed(v) => v.serialize(serializer),
+        }
+    }
+}
+
+/**
+Get the names of an enum variant if they're both `'static`.
+*/
+fn static_variant(name: &Name, variant: &Name) -> Option<(&'static str, &'static str)> {
+    Some((name.as_static()?, variant.as_static()?))
+}
+
+/**
+Get the fields of a struct if all of their names are `'static`.
+*/
+fn static_fields<'v, 'a>(
+    fields: &'v [(Name<'a>, Value<'a>)],
+) -> Option<impl Iterator<Item = (&'static str, &'v Value<'a>)>> {
+    if fields.iter().all(|(name, _)| name.as_static().is_some()) {
+        Some(
+            fields
+                .iter()
+                .filter_map(|(name, field)| Some((name.as_static()?, field))),
+        )
+    } else {
+        None
+    }
+}
+
+/**
+Serialize an enum variant as a map with a single entry, like `{ variant: value }`.
+
+This is how self-describing formats represent externally tagged enums, and is used for variants
+with names that can't be passed to a serializer.
+*/
+fn serialize_tagged<S: serde::Serializer>(
+    serializer: S,
+    variant: &str,
+    value: &impl Serialize,
+) -> Result<S::Ok, S::Error> {
+    let mut serializer = serializer.serialize_map(Some(1))?;
+    serializer.serialize_entry(variant, value)?;
+    serializer.end()
+}
+
+/**
+The fields of a tuple, serialized as a tuple.
+*/
+struct TupleFields<'v, 'a>(&'v [Value<'a>]);
+
+impl<'v, 'a> Serialize for TupleFields<'v, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut serializer = serializer.serialize_tuple(self.0.len())?;
+
+        for field in self.0 {
+            serializer.serialize_element(field)?;
+        }
+
+        serializer.end()
+    }
+}
+
+/**
+The key of a map entry.
+
+Raw JSON keys are serialized as their parsed values, because formats can't write raw JSON keys.
+*/
+struct MapKey<'v, 'a>(&'v Value<'a>);
+
+impl<'v, 'a> Serialize for MapKey<'v, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match *self.0.resolve() {
+            #[cfg(feature = "json")]
+            Value::RawJson(ref v) => crate::json::serialize_raw_key(v, serializer),
+            ref key => key.serialize(serializer),
+        }
+    }
+}
+
+/**
+The fields of a struct, serialized as a map keyed by their names.
+*/
+struct MapFields<'v, 'a>(&'v [(Name<'a>, Value<'a>)]);
+
+impl<'v, 'a> Serialize for MapFields<'v, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut serializer = serializer.serialize_map(Some(self.0.len()))?;
+
+        for (name, field) in self.0 {
+            serializer.serialize_entry(name.as_str(), field)?;
         }
+
+        serializer.end()
     }
 }
 
@@ -164,74 +280,661 @@ impl ser::Error for Error {
     where
         T: fmt::Display,
     {
-        Error(msg.to_string())
+        Error::new(ErrorKind::Message, msg)
     }
 }
 
 /**
 A serializer that produces [`Owned`] buffers from an arbitrary [`serde::Serialize`].
+
+# Stack usage
+
+Buffering a value uses stack space for each level of nesting in it, like options, newtypes,
+sequences, maps, and structs. That recursion is driven by the value's [`Serialize`] implementation,
+which hands each nested value to the serializer by reference for the duration of a single call,
+so it can't be replaced with an explicit stack. Use [`Serializer::with_max_depth`] to bound the
+stack used when buffering untrusted values, like those deserialized from a payload. Once a value
+is buffered, dropping, cloning, and comparing it for equality doesn't recurse, however deeply it's nested.
+
+# Raw JSON
+
+With the `json` feature, a `serde_json::value::RawValue` is buffered as its raw JSON, without parsing it.
+When the buffer is serialized with `serde_json`, the raw JSON is written exactly as it was given.
+Other formats are given the raw JSON in the same way as they would be given a `RawValue`, as a struct
+with a single string field. Raw JSON in map keys is parsed and serialized as a regular value instead.
+Deserializing a `RawValue` from the buffer gives back the raw JSON, and deserializing any other type
+from it parses it first. Raw JSON is only equal to other raw JSON with exactly the same text.
 */
-pub struct Serializer(PhantomData<()>);
+pub struct Serializer {
+    config: Config,
+    depth: usize,
+    shared: Option<Arc<Shared>>,
+}
+
+/**
+State that's shared by all the serializers created while buffering a single value.
+
+This is kept behind a single pointer so serializers stay small, since one is created for each
+level of nesting in the value being buffered.
+*/
+#[derive(Debug, Default, Clone)]
+struct Shared {
+    #[cfg(feature = "std")]
+    interner: Option<crate::StringInterner>,
+    budget: Option<Budget>,
+}
+
+/**
+The number of bytes that can still be allocated while buffering a single value.
+*/
+#[derive(Debug)]
+struct Budget {
+    limit: usize,
+    remaining: AtomicUsize,
+}
+
+impl Budget {
+    fn new(limit: usize) -> Self {
+        Budget {
+            limit,
+            remaining: AtomicUsize::new(limit),
+        }
+    }
+
+    /**
+    Take `bytes` from the budget, or fail if there aren't enough left.
+    */
+    fn charge(&self, bytes: usize) -> Result<(), Error> {
+        self.remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(bytes)
+            })
+            .map(|_| ())
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::BudgetExceeded,
+                    format_args!("the byte budget of {} was exceeded", self.limit),
+                )
+            })
+    }
+}
+
+impl Clone for Budget {
+    fn clone(&self) -> Self {
+        Budget {
+            limit: self.limit,
+            remaining: AtomicUsize::new(self.remaining.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/**
+Options that change how values are buffered.
+
+These are shared by all the serializers created while buffering a single value.
+*/
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Config {
+    capacity_limit: usize,
+    max_depth: usize,
+    max_string_len: usize,
+    seq_as_bytes: bool,
+    unique_keys: bool,
+    sorted_map_entries: bool,
+    finite_floats: bool,
+    human_readable: bool,
+    error_paths: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            capacity_limit: 32,
+            max_depth: usize::MAX,
+            max_string_len: usize::MAX,
+            seq_as_bytes: false,
+            unique_keys: false,
+            sorted_map_entries: false,
+            finite_floats: false,
+            human_readable: true,
+            error_paths: false,
+        }
+    }
+}
+
+impl Config {
+    /**
+    Get the capacity to preallocate for a collection with the given length hint.
+    */
+    pub(crate) fn capacity(&self, len: usize) -> usize {
+        cmp::min(len, self.capacity_limit)
+    }
+
+    /**
+    Whether values should buffer themselves in a human-readable form.
+    */
+    pub(crate) fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    /**
+    Fail if a float can't be buffered.
+    */
+    pub(crate) fn check_float(&self, v: f64) -> Result<(), Error> {
+        if self.finite_floats && !v.is_finite() {
+            return Err(Error::new(
+                ErrorKind::NonFiniteFloat,
+                format_args!("the non-finite float {} can't be buffered", v),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /**
+    Fail if a string or byte-string with `len` bytes can't be buffered.
+    */
+    pub(crate) fn check_string_len(&self, len: usize) -> Result<(), Error> {
+        if len > self.max_string_len {
+            return Err(Error::new(
+                ErrorKind::LengthExceeded,
+                format_args!(
+                    "a string of {} bytes exceeds the maximum length of {}",
+                    len, self.max_string_len
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
 
 impl Serializer {
     /**
     Create a new serializer for an [`Owned`] buffer.
     */
     pub fn new() -> Self {
-        Serializer(PhantomData)
+        Serializer {
+            config: Config::default(),
+            depth: 0,
+            shared: None,
+        }
+    }
+
+    /**
+    Get a serializer for a value nested inside the one being buffered by this serializer.
+    */
+    pub(crate) fn child(&self) -> Result<Self, Error> {
+        let depth = self.depth + 1;
+
+        if depth > self.config.max_depth {
+            return Err(Error::new(
+                ErrorKind::DepthExceeded,
+                format_args!(
+                    "the maximum depth of {} was exceeded",
+                    self.config.max_depth
+                ),
+            ));
+        }
+
+        Ok(Serializer {
+            config: self.config,
+            depth,
+            shared: self.shared.clone(),
+        })
+    }
+
+    /**
+    Take `bytes` from the byte budget of the value being buffered, if it has one.
+    */
+    pub(crate) fn charge(&self, bytes: usize) -> Result<(), Error> {
+        match self
+            .shared
+            .as_ref()
+            .and_then(|shared| shared.budget.as_ref())
+        {
+            Some(budget) => budget.charge(bytes),
+            None => Ok(()),
+        }
+    }
+
+    /**
+    Get the state shared by the serializers for the value being buffered, so it can be configured.
+    */
+    fn shared_mut(&mut self) -> &mut Shared {
+        Arc::make_mut(self.shared.get_or_insert_with(Default::default))
+    }
+
+    /**
+    Set the maximum number of elements to preallocate space for in sequences, maps, and structs.
+
+    Collections report how many elements they have before they're buffered, but that hint isn't
+    guaranteed to be accurate. The default limit is 32, which is small enough that an untrusted
+    hint can't cause a large allocation. If you trust the hints of the values you're buffering
+    then raising this limit, or removing it with `usize::MAX`, can avoid reallocating while
+    buffering large collections.
+    */
+    pub fn with_capacity_limit(mut self, limit: usize) -> Self {
+        self.config.capacity_limit = limit;
+        self
+    }
+
+    /**
+    Set the maximum depth of values to buffer.
+
+    Buffering a value recurses into each value nested within it, like the contents of options,
+    newtypes, sequences, maps, and structs. A deeply nested value can overflow the stack.
+    When this limit is set, buffering a value nested more than `max_depth` levels deep will fail
+    with an error instead. By default, there's no limit.
+    */
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.config.max_depth = max_depth;
+        self
+    }
+
+    /**
+    Set the maximum length in bytes of strings and byte-strings to buffer.
+
+    When this limit is set, buffering a string or byte-string longer than `max_len` bytes,
+    including map keys, will fail with an error instead. Sequences buffered as byte-strings by
+    [`Serializer::with_seq_as_bytes`] are limited too. This can be used to enforce a policy on the
+    size of payloads when they're buffered rather than when they're eventually serialized. Buffers
+    built directly using constructors like [`crate::Ref::str`] aren't limited. By default, there's
+    no limit.
+    */
+    pub fn with_max_string_len(mut self, max_len: usize) -> Self {
+        self.config.max_string_len = max_len;
+        self
+    }
+
+    /**
+    Set the maximum number of bytes to allocate while buffering a value.
+
+    Limits on depth and length don't bound the total size of a buffer, which can still be large
+    if a value contains many medium-sized strings or collections. When this limit is set, the bytes
+    of each string and byte-string, and the space taken by each element of sequences, tuples, maps,
+    and structs, are taken from the budget as they're buffered. Buffering will fail with an error
+    as soon as the budget is exhausted instead. Strings are counted each time they're buffered, even
+    when their storage is shared by interning them. Smaller allocations, like the boxes
+    holding the names of structs, aren't counted. By default, there's no limit.
+    */
+    pub fn with_byte_budget(mut self, budget: usize) -> Self {
+        self.shared_mut().budget = Some(Budget::new(budget));
+        self
+    }
+
+    /**
+    Buffer non-empty sequences of `u8`s as byte-strings.
+
+    Serde treats collections like `Vec<u8>` as sequences, but formats like CBOR and MessagePack
+    have a more compact representation for byte-strings. When this option is enabled, a sequence
+    whose elements are all `u8`s is buffered as a byte-string instead. Fields that already serialize
+    as byte-strings, like those using `#[serde(with = "serde_bytes")]`, are always buffered as
+    byte-strings. Empty sequences are left as sequences because the type of their elements isn't known.
+    */
+    pub fn with_seq_as_bytes(mut self) -> Self {
+        self.config.seq_as_bytes = true;
+        self
+    }
+
+    /**
+    Fail when a map or struct contains the same key more than once.
+
+    Some formats allow maps to contain duplicate keys, which are kept in the buffer by default.
+    When this option is enabled, buffering a map or struct with a duplicate key will fail with an
+    error instead. Map keys are compared using the [`PartialEq`] implementation of buffers, so
+    keys of different types, like `1u8` and `1u16`, aren't considered duplicates.
+    */
+    pub fn reject_duplicate_keys(mut self) -> Self {
+        self.config.unique_keys = true;
+        self
+    }
+
+    /**
+    Sort the entries of maps by their keys as they're buffered.
+
+    Maps like `HashMap` produce their entries in an unpredictable order, so buffering the same map
+    twice can give different buffers. When this option is enabled, the entries of each map are sorted
+    using the [`Ord`] implementation of buffers once the map is buffered, so the result doesn't depend
+    on iteration order. Entries with equal keys keep their relative order. This gives the same result as
+    calling [`crate::Owned::canonicalize`] on the buffer afterwards. The fields of structs keep the order
+    they were buffered in.
+    */
+    pub fn sort_map_entries(mut self) -> Self {
+        self.config.sorted_map_entries = true;
+        self
+    }
+
+    /**
+    Fail when a float is `NaN` or infinite.
+
+    Some formats, like JSON, can't represent non-finite floats. When this option is enabled,
+    buffering a `NaN` or infinite float will fail with an error instead of when the buffer is
+    eventually serialized.
+    */
+    pub fn reject_non_finite_floats(mut self) -> Self {
+        self.config.finite_floats = true;
+        self
+    }
+
+    /**
+    Set whether values should buffer themselves in a human-readable form.
+
+    Some values, like timestamps and IP addresses, check [`serde::Serializer::is_human_readable`]
+    to decide between a readable form, like a string, and a compact one, like an integer or bytes.
+    By default, the serializer reports that it's human-readable, like `serde` itself does.
+    Buffers don't record which form their values were buffered in, so deserialize them with the same
+    setting using [`crate::Deserializer::human_readable`] so those values can read them back.
+    */
+    pub fn with_human_readable(mut self, human_readable: bool) -> Self {
+        self.config.human_readable = human_readable;
+        self
+    }
+
+    /**
+    Record the path to the value that failed to buffer in errors.
+
+    When this option is enabled, an error produced by a value nested in the one being buffered
+    includes the path to that value as a JSON Pointer, like `/users/3/email`. Fields of structs
+    and entries of maps are identified by their key, and elements of sequences and tuples by their
+    index. Map keys that aren't strings are written in the same notation as [`crate::Owned`]'s
+    [`Display`](fmt::Display) implementation. The path is available from [`Error::path`] and is
+    included when the error is displayed. The path is built as the error is returned, so this
+    option doesn't cost anything until buffering fails.
+    */
+    pub fn with_error_paths(mut self) -> Self {
+        self.config.error_paths = true;
+        self
+    }
+
+    /**
+    Share the storage of equal strings within a buffer.
+
+    Buffers of records often contain the same strings many times over, like the keys of maps or
+    the values of enum-like fields. When interning is enabled, each distinct string is only
+    allocated once, and every place it appears in the buffer shares that allocation. This
+    reduces the memory used by buffers with many repeated strings at the cost of a lookup for
+    each string buffered. The buffer serializes the same whether or not its strings are shared.
+    */
+    #[cfg(feature = "std")]
+    pub fn with_string_interning(mut self) -> Self {
+        self.shared_mut().interner = Some(crate::StringInterner::new());
+        self
+    }
+
+    /**
+    Share the storage of equal strings across every buffer produced using `interner`.
+
+    This is like [`Serializer::with_string_interning`], except strings are shared with any other
+    buffers produced by serializers using the same interner, not just within a single buffer.
+    That amortizes the cost of allocating strings that are repeated across many values, like the
+    keys and common values of records. See [`StringInterner`](crate::StringInterner) for details.
+    */
+    #[cfg(feature = "std")]
+    pub fn with_interner(mut self, interner: &crate::StringInterner) -> Self {
+        self.shared_mut().interner = Some(interner.clone());
+        self
+    }
+}
+
+impl Default for Serializer {
+    fn default() -> Self {
+        Serializer::new()
+    }
+}
+
+/**
+A serializer that produces buffers.
+
+The serializers for sequences, maps, and structs are shared by all buffering serializers,
+which use this trait to buffer the values nested within them.
+*/
+pub(crate) trait Buffer<'a>: serde::Serializer<Error = Error> {
+    /**
+    Get a serializer for a value nested inside the one being buffered by this serializer.
+    */
+    fn child(&self) -> Result<Self, Error>;
+
+    /**
+    Take `bytes` from the byte budget of the value being buffered, if it has one.
+    */
+    fn charge(&self, bytes: usize) -> Result<(), Error>;
+
+    /**
+    Get the options that change how values are buffered.
+    */
+    fn config(&self) -> &Config;
+
+    /**
+    Convert a buffer produced by this serializer into a value.
+    */
+    fn into_value(buf: Self::Ok) -> Value<'a>;
+
+    /**
+    Convert a value into a buffer produced by this serializer.
+    */
+    fn from_value(value: Value<'a>) -> Self::Ok;
+}
+
+impl Buffer<'static> for Serializer {
+    fn child(&self) -> Result<Self, Error> {
+        Serializer::child(self)
+    }
+
+    fn charge(&self, bytes: usize) -> Result<(), Error> {
+        Serializer::charge(self, bytes)
+    }
+
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn into_value(buf: Owned) -> Value<'static> {
+        buf.into_value()
+    }
+
+    fn from_value(value: Value<'static>) -> Owned {
+        Owned(value)
+    }
+}
+
+/**
+Buffer `value` nested within the value being buffered by `serializer`.
+
+If buffering fails and error paths are enabled, `segment` is added to the start of the error's path.
+*/
+fn buffer_nested<'a, S: Buffer<'a>, T: ?Sized + Serialize>(
+    serializer: &S,
+    value: &T,
+    segment: impl fmt::Display,
+) -> Result<Value<'a>, Error> {
+    match serializer.child().and_then(|child| value.serialize(child)) {
+        Ok(buf) => Ok(S::into_value(buf)),
+        Err(err) if serializer.config().error_paths => Err(err.at(segment)),
+        Err(err) => Err(err),
     }
 }
 
-pub struct SerializeSeq {
-    fields: Vec<Value<'static>>,
+/**
+The segment of an error path for a map entry, which is its key.
+*/
+struct KeySegment<'v, 'a>(&'v Value<'a>);
+
+impl<'v, 'a> fmt::Display for KeySegment<'v, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.as_str() {
+            Some(key) => f.write_str(key),
+            None => display::fmt_value(self.0, f, 0),
+        }
+    }
 }
 
-pub struct SerializeTuple {
-    fields: Vec<Value<'static>>,
+pub struct SerializeSeq<'a, S> {
+    serializer: S,
+    len: LenHint,
+    fields: Vec<Value<'a>>,
 }
 
-pub struct SerializeTupleStruct {
+pub struct SerializeTuple<'a, S> {
+    serializer: S,
+    fields: Vec<Value<'a>>,
+}
+
+pub struct SerializeTupleStruct<'a, S> {
+    serializer: S,
     name: &'static str,
-    fields: Vec<Value<'static>>,
+    fields: Vec<Value<'a>>,
 }
 
-pub struct SerializeTupleVariant {
+pub struct SerializeTupleVariant<'a, S> {
+    serializer: S,
     name: &'static str,
     variant_index: u32,
     variant: &'static str,
-    fields: Vec<Value<'static>>,
+    fields: Vec<Value<'a>>,
 }
 
-pub struct SerializeMap {
-    key: Option<Value<'static>>,
-    fields: Vec<(Value<'static>, Value<'static>)>,
+pub struct SerializeMap<'a, S> {
+    serializer: S,
+    len: LenHint,
+    key: Option<Value<'a>>,
+    fields: Vec<(Value<'a>, Value<'a>)>,
 }
 
-pub struct SerializeStruct {
+pub struct SerializeStruct<'a, S> {
+    serializer: S,
     name: &'static str,
-    fields: Vec<(&'static str, Value<'static>)>,
+    fields: Vec<(Name<'a>, Value<'a>)>,
 }
 
 /**
 A serializer that produces [`Owned`] buffers from struct variants.
 */
-pub struct SerializeStructVariant {
+pub struct SerializeStructVariant<'a, S> {
+    serializer: S,
     name: &'static str,
     variant_index: u32,
     variant: &'static str,
-    fields: Vec<(&'static str, Value<'static>)>,
+    fields: Vec<(Name<'a>, Value<'a>)>,
+}
+
+impl<'a, S> SerializeSeq<'a, S> {
+    pub(crate) fn new(serializer: S, len: Option<usize>) -> Self
+    where
+        S: Buffer<'a>,
+    {
+        SerializeSeq {
+            len: LenHint::new(len),
+            fields: Vec::with_capacity(serializer.config().capacity(len.unwrap_or(0))),
+            serializer,
+        }
+    }
+}
+
+impl<'a, S> SerializeTuple<'a, S> {
+    pub(crate) fn new(serializer: S, len: usize) -> Self
+    where
+        S: Buffer<'a>,
+    {
+        SerializeTuple {
+            fields: Vec::with_capacity(serializer.config().capacity(len)),
+            serializer,
+        }
+    }
+}
+
+impl<'a, S> SerializeTupleStruct<'a, S> {
+    pub(crate) fn new(serializer: S, name: &'static str, len: usize) -> Self
+    where
+        S: Buffer<'a>,
+    {
+        SerializeTupleStruct {
+            name,
+            fields: Vec::with_capacity(serializer.config().capacity(len)),
+            serializer,
+        }
+    }
+}
+
+impl<'a, S> SerializeTupleVariant<'a, S> {
+    pub(crate) fn new(
+        serializer: S,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Self
+    where
+        S: Buffer<'a>,
+    {
+        SerializeTupleVariant {
+            name,
+            variant_index,
+            variant,
+            fields: Vec::with_capacity(serializer.config().capacity(len)),
+            serializer,
+        }
+    }
+}
+
+impl<'a, S> SerializeMap<'a, S> {
+    pub(crate) fn new(serializer: S, len: Option<usize>) -> Self
+    where
+        S: Buffer<'a>,
+    {
+        SerializeMap {
+            len: LenHint::new(len),
+            key: None,
+            fields: Vec::with_capacity(serializer.config().capacity(len.unwrap_or(0))),
+            serializer,
+        }
+    }
+}
+
+impl<'a, S> SerializeStruct<'a, S> {
+    pub(crate) fn new(serializer: S, name: &'static str, len: usize) -> Self
+    where
+        S: Buffer<'a>,
+    {
+        SerializeStruct {
+            name,
+            fields: Vec::with_capacity(serializer.config().capacity(len)),
+            serializer,
+        }
+    }
+}
+
+impl<'a, S> SerializeStructVariant<'a, S> {
+    pub(crate) fn new(
+        serializer: S,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Self
+    where
+        S: Buffer<'a>,
+    {
+        SerializeStructVariant {
+            name,
+            variant_index,
+            variant,
+            fields: Vec::with_capacity(serializer.config().capacity(len)),
+            serializer,
+        }
+    }
 }
 
 impl serde::Serializer for Serializer {
     type Ok = Owned;
     type Error = Error;
-    type SerializeSeq = SerializeSeq;
-    type SerializeTuple = SerializeTuple;
-    type SerializeTupleStruct = SerializeTupleStruct;
-    type SerializeTupleVariant = SerializeTupleVariant;
-    type SerializeMap = SerializeMap;
-    type SerializeStruct = SerializeStruct;
-    type SerializeStructVariant = SerializeStructVariant;
+    type SerializeSeq = SerializeSeq<'static, Serializer>;
+    type SerializeTuple = SerializeTuple<'static, Serializer>;
+    type SerializeTupleStruct = SerializeTupleStruct<'static, Serializer>;
+    type SerializeTupleVariant = SerializeTupleVariant<'static, Serializer>;
+    type SerializeMap = SerializeMap<'static, Serializer>;
+    type SerializeStruct = SerializeStruct<'static, Serializer>;
+    type SerializeStructVariant = SerializeStructVariant<'static, Serializer>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         Ok(Owned(Value::Bool(v)))
@@ -254,7 +957,7 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::I128(v)))
+        Ok(Owned(Value::I128(Box::new(v))))
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
@@ -274,14 +977,18 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::U128(v)))
+        Ok(Owned(Value::U128(Box::new(v))))
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.config.check_float(v.into())?;
+
         Ok(Owned(Value::F32(v)))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        self.config.check_float(v)?;
+
         Ok(Owned(Value::F64(v)))
     }
 
@@ -290,10 +997,25 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.config.check_string_len(v.len())?;
+        self.charge(v.len())?;
+
+        #[cfg(feature = "std")]
+        if let Some(interner) = self
+            .shared
+            .as_ref()
+            .and_then(|shared| shared.interner.as_ref())
+        {
+            return Ok(Owned(Value::SharedStr(interner.intern(v))));
+        }
+
         Ok(Owned(Value::Str(v.into())))
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.config.check_string_len(v.len())?;
+        self.charge(v.len())?;
+
         Ok(Owned(Value::Bytes(v.into())))
     }
 
@@ -301,12 +1023,12 @@ impl serde::Serializer for Serializer {
         Ok(Owned(Value::None))
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         Ok(Owned(Value::Some(Box::new(
-            value.serialize(Serializer::new())?.0,
+            value.serialize(self.child()?)?.into_value(),
         ))))
     }
 
@@ -315,7 +1037,9 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::UnitStruct { name }))
+        Ok(Owned(Value::UnitStruct(Box::new(UnitStruct {
+            name: name.into(),
+        }))))
     }
 
     fn serialize_unit_variant(
@@ -324,28 +1048,28 @@ impl serde::Serializer for Serializer {
         variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::UnitVariant {
-            name,
+        Ok(Owned(Value::UnitVariant(Box::new(UnitVariant {
+            name: name.into(),
             variant_index,
-            variant,
-        }))
+            variant: variant.into(),
+        }))))
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        Ok(Owned(Value::NewtypeStruct {
-            name,
-            value: Box::new(value.serialize(Serializer::new())?.0),
-        }))
+        Ok(Owned(Value::NewtypeStruct(Box::new(NewtypeStruct {
+            name: name.into(),
+            value: value.serialize(self.child()?)?.into_value(),
+        }))))
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         name: &'static str,
         variant_index: u32,
@@ -353,26 +1077,22 @@ impl serde::Serializer for Serializer {
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        Ok(Owned(Value::NewtypeVariant {
-            name,
+        Ok(Owned(Value::NewtypeVariant(Box::new(NewtypeVariant {
+            name: name.into(),
             variant_index,
-            variant,
-            value: Box::new(value.serialize(Serializer::new())?.0),
-        }))
+            variant: variant.into(),
+            value: value.serialize(self.child()?)?.into_value(),
+        }))))
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        Ok(SerializeSeq {
-            fields: Vec::with_capacity(cmp::min(len.unwrap_or(0), 32)),
-        })
+        Ok(SerializeSeq::new(self, len))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        Ok(SerializeTuple {
-            fields: Vec::with_capacity(cmp::min(len, 32)),
-        })
+        Ok(SerializeTuple::new(self, len))
     }
 
     fn serialize_tuple_struct(
@@ -380,10 +1100,7 @@ impl serde::Serializer for Serializer {
         name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        Ok(SerializeTupleStruct {
-            name,
-            fields: Vec::with_capacity(cmp::min(len, 32)),
-        })
+        Ok(SerializeTupleStruct::new(self, name, len))
     }
 
     fn serialize_tuple_variant(
@@ -393,19 +1110,17 @@ impl serde::Serializer for Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Ok(SerializeTupleVariant {
+        Ok(SerializeTupleVariant::new(
+            self,
             name,
             variant_index,
             variant,
-            fields: Vec::with_capacity(cmp::min(len, 32)),
-        })
+            len,
+        ))
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        Ok(SerializeMap {
-            key: None,
-            fields: Vec::with_capacity(cmp::min(len.unwrap_or(0), 32)),
-        })
+        Ok(SerializeMap::new(self, len))
     }
 
     fn serialize_struct(
@@ -413,10 +1128,7 @@ impl serde::Serializer for Serializer {
         name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(SerializeStruct {
-            name,
-            fields: Vec::with_capacity(cmp::min(len, 32)),
-        })
+        Ok(SerializeStruct::new(self, name, len))
     }
 
     fn serialize_struct_variant(
@@ -426,80 +1138,98 @@ impl serde::Serializer for Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Ok(SerializeStructVariant {
+        Ok(SerializeStructVariant::new(
+            self,
             name,
             variant_index,
             variant,
-            fields: Vec::with_capacity(cmp::min(len, 32)),
-        })
+            len,
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.config.is_human_readable()
     }
 }
 
-impl ser::SerializeSeq for SerializeSeq {
-    type Ok = Owned;
+impl<'a, S: Buffer<'a>> ser::SerializeSeq for SerializeSeq<'a, S> {
+    type Ok = S::Ok;
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.fields.push(value.serialize(Serializer::new())?.0);
+        self.serializer.charge(mem::size_of::<Value>())?;
+        let value = buffer_nested(&self.serializer, value, self.fields.len())?;
+
+        self.fields.push(value);
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::Seq(self.fields.into_boxed_slice())))
+        if self.serializer.config().seq_as_bytes {
+            if let Some(bytes) = Value::u8_seq_as_bytes(&self.fields) {
+                self.serializer.config().check_string_len(bytes.len())?;
+
+                return Ok(S::from_value(Value::Bytes(bytes)));
+            }
+        }
+
+        Ok(S::from_value(Value::Seq(
+            self.fields.into_boxed_slice(),
+            self.len,
+        )))
     }
 }
 
-impl ser::SerializeMap for SerializeMap {
-    type Ok = Owned;
+impl<'a, S: Buffer<'a>> ser::SerializeMap for SerializeMap<'a, S> {
+    type Ok = S::Ok;
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         if self.key.is_some() {
-            return Err(Error::custom("missing map value"));
+            return Err(Error::new(ErrorKind::MissingMapValue, "missing map value"));
         }
 
-        self.key = Some(key.serialize(Serializer::new())?.0);
+        self.key = Some(S::into_value(key.serialize(self.serializer.child()?)?));
 
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let key = self
             .key
             .take()
-            .ok_or_else(|| Error::custom("missing map key"))?;
-        let value = value.serialize(Serializer::new())?.0;
+            .ok_or_else(|| Error::new(ErrorKind::MissingMapKey, "missing map key"))?;
+
+        self.serializer.charge(mem::size_of::<(Value, Value)>())?;
+        let value = buffer_nested(&self.serializer, value, KeySegment(&key))?;
 
         self.fields.push((key, value));
 
         Ok(())
     }
 
-    fn serialize_entry<K: ?Sized, V: ?Sized>(
-        &mut self,
-        key: &K,
-        value: &V,
-    ) -> Result<(), Self::Error>
+    fn serialize_entry<K, V>(&mut self, key: &K, value: &V) -> Result<(), Self::Error>
     where
-        K: Serialize,
-        V: Serialize,
+        K: ?Sized + Serialize,
+        V: ?Sized + Serialize,
     {
         if self.key.is_some() {
-            return Err(Error::custom("missing map value"));
+            return Err(Error::new(ErrorKind::MissingMapValue, "missing map value"));
         }
 
-        let key = key.serialize(Serializer::new())?.0;
-        let value = value.serialize(Serializer::new())?.0;
+        self.serializer.charge(mem::size_of::<(Value, Value)>())?;
+        let key = S::into_value(key.serialize(self.serializer.child()?)?);
+        let value = buffer_nested(&self.serializer, value, KeySegment(&key))?;
 
         self.fields.push((key, value));
 
@@ -508,125 +1238,618 @@ impl ser::SerializeMap for SerializeMap {
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         if self.key.is_some() {
-            return Err(Error::custom("missing map value"));
+            return Err(Error::new(ErrorKind::MissingMapValue, "missing map value"));
+        }
+
+        if self.serializer.config().unique_keys {
+            unique::check_map_keys(&self.fields)?;
+        }
+
+        let mut fields = self.fields;
+
+        // Keys are buffered before their map ends, so any maps within them are already sorted
+        if self.serializer.config().sorted_map_entries {
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
         }
 
-        Ok(Owned(Value::Map(self.fields.into_boxed_slice())))
+        Ok(S::from_value(Value::Map(
+            fields.into_boxed_slice(),
+            self.len,
+        )))
     }
 }
 
-impl ser::SerializeStruct for SerializeStruct {
-    type Ok = Owned;
+impl<'a, S: Buffer<'a>> ser::SerializeStruct for SerializeStruct<'a, S> {
+    type Ok = S::Ok;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.fields
-            .push((key, value.serialize(Serializer::new())?.0));
+        self.serializer.charge(mem::size_of::<(Name, Value)>())?;
+        let value = buffer_nested(&self.serializer, value, key)?;
+
+        self.fields.push((key.into(), value));
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::Struct {
-            name: self.name,
+        if self.serializer.config().unique_keys {
+            unique::check_fields(&self.fields)?;
+        }
+
+        #[cfg(feature = "json")]
+        if let Some(raw) = Value::from_raw_json_struct(self.name, &self.fields) {
+            return Ok(S::from_value(raw));
+        }
+
+        Ok(S::from_value(Value::Struct(Box::new(Struct {
+            name: self.name.into(),
             fields: self.fields.into_boxed_slice(),
-        }))
+        }))))
     }
 }
 
-impl ser::SerializeStructVariant for SerializeStructVariant {
-    type Ok = Owned;
+impl<'a, S: Buffer<'a>> ser::SerializeStructVariant for SerializeStructVariant<'a, S> {
+    type Ok = S::Ok;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.fields
-            .push((key, value.serialize(Serializer::new())?.0));
+        self.serializer.charge(mem::size_of::<(Name, Value)>())?;
+        let value = buffer_nested(&self.serializer, value, key)?;
+
+        self.fields.push((key.into(), value));
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::StructVariant {
-            name: self.name,
-            variant_index: self.variant_index,
-            variant: self.variant,
-            fields: self.fields.into_boxed_slice(),
-        }))
+        if self.serializer.config().unique_keys {
+            unique::check_fields(&self.fields)?;
+        }
+
+        Ok(S::from_value(Value::StructVariant(Box::new(
+            StructVariant {
+                name: self.name.into(),
+                variant_index: self.variant_index,
+                variant: self.variant.into(),
+                fields: self.fields.into_boxed_slice(),
+            },
+        ))))
     }
 }
 
-impl ser::SerializeTuple for SerializeTuple {
-    type Ok = Owned;
+impl<'a, S: Buffer<'a>> ser::SerializeTuple for SerializeTuple<'a, S> {
+    type Ok = S::Ok;
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.fields.push(value.serialize(Serializer::new())?.0);
+        self.serializer.charge(mem::size_of::<Value>())?;
+        let value = buffer_nested(&self.serializer, value, self.fields.len())?;
+
+        self.fields.push(value);
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::Tuple(self.fields.into_boxed_slice())))
+        Ok(S::from_value(Value::Tuple(self.fields.into_boxed_slice())))
     }
 }
 
-impl ser::SerializeTupleStruct for SerializeTupleStruct {
-    type Ok = Owned;
+impl<'a, S: Buffer<'a>> ser::SerializeTupleStruct for SerializeTupleStruct<'a, S> {
+    type Ok = S::Ok;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.fields.push(value.serialize(Serializer::new())?.0);
+        self.serializer.charge(mem::size_of::<Value>())?;
+        let value = buffer_nested(&self.serializer, value, self.fields.len())?;
+
+        self.fields.push(value);
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::TupleStruct {
-            name: self.name,
+        Ok(S::from_value(Value::TupleStruct(Box::new(TupleStruct {
+            name: self.name.into(),
             fields: self.fields.into_boxed_slice(),
-        }))
+        }))))
     }
 }
 
-impl ser::SerializeTupleVariant for SerializeTupleVariant {
-    type Ok = Owned;
+impl<'a, S: Buffer<'a>> ser::SerializeTupleVariant for SerializeTupleVariant<'a, S> {
+    type Ok = S::Ok;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.fields.push(value.serialize(Serializer::new())?.0);
+        self.serializer.charge(mem::size_of::<Value>())?;
+        let value = buffer_nested(&self.serializer, value, self.fields.len())?;
+
+        self.fields.push(value);
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Owned(Value::TupleVariant {
-            name: self.name,
+        Ok(S::from_value(Value::TupleVariant(Box::new(TupleVariant {
+            name: self.name.into(),
             variant_index: self.variant_index,
-            variant: self.variant,
+            variant: self.variant.into(),
             fields: self.fields.into_boxed_slice(),
-        }))
+        }))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializer_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Serializer>();
+
+        // Serializers with shared state can be moved to other threads too
+        let serializer = Serializer::new().with_byte_budget(16);
+
+        #[cfg(feature = "std")]
+        let serializer = serializer.with_interner(&crate::StringInterner::new());
+
+        let buf = std::thread::spawn(move || "a".serialize(serializer))
+            .join()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(Owned::from("a"), buf);
+    }
+
+    #[test]
+    fn max_string_len() {
+        use alloc::{collections::BTreeMap, string::String, vec};
+
+        fn buffer(v: impl Serialize) -> Result<Owned, Error> {
+            v.serialize(Serializer::new().with_max_string_len(5))
+        }
+
+        assert!(buffer("short").is_ok());
+        assert!(buffer(Ref::bytes(b"short")).is_ok());
+        assert!(buffer(vec![0u8; 6]).is_ok());
+
+        for err in [
+            buffer("too long").unwrap_err(),
+            buffer(Ref::bytes(b"too long")).unwrap_err(),
+            buffer(Some(vec![String::from("too long")])).unwrap_err(),
+            buffer(
+                [(String::from("too long"), 1)]
+                    .into_iter()
+                    .collect::<BTreeMap<_, _>>(),
+            )
+            .unwrap_err(),
+            vec![0u8; 6]
+                .serialize(Serializer::new().with_max_string_len(5).with_seq_as_bytes())
+                .unwrap_err(),
+        ] {
+            assert_eq!(ErrorKind::LengthExceeded, err.kind());
+        }
+
+        #[cfg(feature = "std")]
+        assert_eq!(
+            ErrorKind::LengthExceeded,
+            "too long"
+                .serialize(
+                    Serializer::new()
+                        .with_max_string_len(5)
+                        .with_string_interning(),
+                )
+                .unwrap_err()
+                .kind()
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        use alloc::{collections::BTreeMap, vec};
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        struct Newtype<T>(T);
+
+        #[derive(Serialize)]
+        struct Struct<T> {
+            a: T,
+        }
+
+        fn buffer(v: impl Serialize, max_depth: usize) -> Result<Owned, Error> {
+            v.serialize(Serializer::new().with_max_depth(max_depth))
+        }
+
+        assert!(buffer(1, 0).is_ok());
+        assert!(buffer(Some(1), 0).is_err());
+
+        assert!(buffer(Some(1), 1).is_ok());
+        assert!(buffer(Some(Some(1)), 1).is_err());
+        assert!(buffer(Newtype(Newtype(1)), 1).is_err());
+        assert!(buffer(vec![vec![1]], 1).is_err());
+        assert!(buffer((1, (1,)), 1).is_err());
+        assert!(buffer(BTreeMap::from([(1, vec![1])]), 1).is_err());
+        assert!(buffer(BTreeMap::from([(vec![1], 1)]), 1).is_err());
+        assert!(buffer(Struct { a: Struct { a: 1 } }, 1).is_err());
+
+        let mut deep = Ref::unit();
+        for _ in 0..200 {
+            deep = Ref::some(deep);
+        }
+
+        assert!(buffer(&deep, 128).is_err());
+        assert!(buffer(&deep, 200).is_ok());
+    }
+
+    #[test]
+    fn max_depth_deep_chain() {
+        let mut deep = Ref::unit();
+        for i in 0..100_000 {
+            deep = if i % 2 == 0 {
+                Ref::some(deep)
+            } else {
+                Ref::newtype_struct("Newtype", deep)
+            };
+        }
+
+        // Buffering stops at the limit instead of recursing through the whole chain
+        let err = deep
+            .serialize(Serializer::new().with_max_depth(128))
+            .unwrap_err();
+
+        assert_eq!(ErrorKind::DepthExceeded, err.kind());
+    }
+
+    #[test]
+    fn error_paths() {
+        use alloc::{collections::BTreeMap, string::ToString, vec};
+        use serde_derive::Serialize;
+
+        struct Fails;
+
+        impl Serialize for Fails {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(ser::Error::custom("failed"))
+            }
+        }
+
+        #[derive(Serialize)]
+        struct User {
+            name: &'static str,
+            email: Option<Fails>,
+        }
+
+        #[derive(Serialize)]
+        struct Users {
+            users: Vec<User>,
+        }
+
+        let users = Users {
+            users: vec![
+                User {
+                    name: "a",
+                    email: None,
+                },
+                User {
+                    name: "b",
+                    email: Some(Fails),
+                },
+            ],
+        };
+
+        let err = users
+            .serialize(Serializer::new().with_error_paths())
+            .unwrap_err();
+
+        assert_eq!(Some("/users/1/email"), err.path());
+        assert_eq!("failed at /users/1/email", err.to_string());
+
+        // Paths aren't recorded by default
+        assert!(users
+            .serialize(Serializer::new())
+            .unwrap_err()
+            .path()
+            .is_none());
+
+        let err = BTreeMap::from([(1, (0, Fails))])
+            .serialize(Serializer::new().with_error_paths())
+            .unwrap_err();
+        assert_eq!(Some("/1/1"), err.path());
+
+        let err = vec![vec![1]]
+            .serialize(Serializer::new().with_max_depth(1).with_error_paths())
+            .unwrap_err();
+        assert_eq!(ErrorKind::DepthExceeded, err.kind());
+        assert_eq!(Some("/0/0"), err.path());
+
+        // Errors produced by the value being buffered itself don't have a path
+        let err = Fails
+            .serialize(Serializer::new().with_error_paths())
+            .unwrap_err();
+        assert!(err.path().is_none());
+        assert_eq!("failed", err.to_string());
+    }
+
+    #[test]
+    fn byte_budget() {
+        use alloc::{string::String, vec};
+
+        fn buffer(v: impl Serialize, budget: usize) -> Result<Owned, Error> {
+            v.serialize(Serializer::new().with_byte_budget(budget))
+        }
+
+        assert!(buffer("short", 5).is_ok());
+        assert!(buffer(1u64, 0).is_ok());
+        assert_eq!(
+            ErrorKind::BudgetExceeded,
+            buffer("too long", 5).unwrap_err().kind()
+        );
+
+        // Each string fits in the budget, but all of them together don't
+        let strings = vec![String::from("medium"); 4];
+        let elements = 4 * mem::size_of::<Value>();
+
+        assert!(buffer(&strings, elements + 24).is_ok());
+        assert_eq!(
+            ErrorKind::BudgetExceeded,
+            buffer(&strings, elements + 23).unwrap_err().kind()
+        );
+
+        // The space for each element is counted, even when it doesn't allocate
+        assert!(buffer(vec![(); 4], elements).is_ok());
+        assert!(buffer(vec![(); 5], elements).is_err());
+        assert!(buffer(Ref::map([(Ref::u8(1), Ref::unit())]), 0).is_err());
+        assert!(buffer(Ref::record_struct("A", [("a", Ref::unit())]), 0).is_err());
+    }
+
+    #[test]
+    fn capacity_limit() {
+        assert_eq!(32, Serializer::new().config.capacity(100_000));
+        assert_eq!(
+            100_000,
+            Serializer::new()
+                .with_capacity_limit(usize::MAX)
+                .config
+                .capacity(100_000)
+        );
+
+        let v: Vec<u64> = (0..1000).collect();
+        assert_eq!(
+            Owned::buffer(&v).unwrap(),
+            v.serialize(Serializer::new().with_capacity_limit(usize::MAX))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn string_interning() {
+        use alloc::{collections::BTreeMap, vec};
+
+        let v = vec![
+            BTreeMap::from([("level", "info"), ("target", "app")]),
+            BTreeMap::from([("level", "info"), ("target", "app")]),
+        ];
+
+        let buf = v
+            .serialize(Serializer::new().with_string_interning())
+            .unwrap();
+
+        assert_eq!(Owned::buffer(&v).unwrap(), buf);
+
+        let shared = |buf: &Owned, i, key| match buf.get_index(i).unwrap().get(key).unwrap().0 {
+            Value::Borrowed(Value::SharedStr(v)) => v.clone(),
+            _ => panic!("expected a shared string"),
+        };
+
+        assert!(Arc::ptr_eq(
+            &shared(&buf, 0, "level"),
+            &shared(&buf, 1, "level")
+        ));
+        assert!(!Arc::ptr_eq(
+            &shared(&buf, 0, "level"),
+            &shared(&buf, 0, "target")
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn shared_interner() {
+        use alloc::collections::BTreeMap;
+
+        let interner = crate::StringInterner::new();
+
+        let records = [
+            BTreeMap::from([("level", "info"), ("msg", "started")]),
+            BTreeMap::from([("level", "info"), ("msg", "stopped")]),
+        ];
+
+        let bufs = records
+            .iter()
+            .map(|record| {
+                record
+                    .serialize(Serializer::new().with_interner(&interner))
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(Owned::buffer(&records[0]).unwrap(), bufs[0]);
+
+        let shared = |buf: &Owned, key| match buf.get(key).unwrap().0 {
+            Value::Borrowed(Value::SharedStr(v)) => v.clone(),
+            _ => panic!("expected a shared string"),
+        };
+
+        assert!(Arc::ptr_eq(
+            &shared(&bufs[0], "level"),
+            &shared(&bufs[1], "level")
+        ));
+
+        // "level", "msg", "info", "started", and "stopped" are each allocated once
+        assert_eq!(5, interner.len());
+        assert_eq!((3, 5), (interner.hits(), interner.misses()));
+    }
+
+    #[test]
+    fn sorted_map_entries() {
+        use std::collections::HashMap;
+
+        struct Entries<'a>(&'a [(&'a str, u8)]);
+
+        impl<'a> Serialize for Entries<'a> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_map(self.0.iter().map(|(k, v)| (k, v)))
+            }
+        }
+
+        let entries = Entries(&[("c", 1), ("a", 2), ("b", 3), ("a", 4)]);
+
+        let unsorted = entries.serialize(Serializer::new()).unwrap();
+        let sorted = entries
+            .serialize(Serializer::new().sort_map_entries())
+            .unwrap();
+
+        assert_eq!(
+            Owned::from(Ref::map([
+                (Ref::str("c"), Ref::u8(1)),
+                (Ref::str("a"), Ref::u8(2)),
+                (Ref::str("b"), Ref::u8(3)),
+                (Ref::str("a"), Ref::u8(4)),
+            ])),
+            unsorted
+        );
+        assert_eq!(
+            Owned::from(Ref::map([
+                (Ref::str("a"), Ref::u8(2)),
+                (Ref::str("a"), Ref::u8(4)),
+                (Ref::str("b"), Ref::u8(3)),
+                (Ref::str("c"), Ref::u8(1)),
+            ])),
+            sorted
+        );
+
+        // The same entries buffer the same way, whatever order they're iterated in
+        let buffer = |len: u32| {
+            let map: HashMap<u32, HashMap<u32, u32>> = (0..len)
+                .rev()
+                .map(|i| (i, (0..i).map(|j| (j, i)).collect()))
+                .collect();
+
+            map.serialize(Serializer::new().sort_map_entries()).unwrap()
+        };
+
+        let buf = buffer(32);
+        let mut canonical = buf.clone();
+        canonical.canonicalize();
+
+        assert!(buf == canonical);
+        assert!(buf == buffer(32));
+        assert!(buf
+            .iter_map()
+            .unwrap()
+            .map(|(k, _)| k.as_u128().unwrap())
+            .eq(0..32));
+    }
+
+    #[test]
+    fn unknown_len() {
+        use serde_test::Token;
+
+        struct Entries;
+
+        impl Serialize for Entries {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("a", &1)?;
+                map.end()
+            }
+        }
+
+        struct Stream;
+
+        impl Serialize for Stream {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut seq = serializer.serialize_seq(None)?;
+                seq.serialize_element(&Entries)?;
+                seq.serialize_element(&[1])?;
+                seq.end()
+            }
+        }
+
+        let tokens = [
+            Token::Seq { len: None },
+            Token::Map { len: None },
+            Token::Str("a"),
+            Token::I32(1),
+            Token::MapEnd,
+            Token::Tuple { len: 1 },
+            Token::I32(1),
+            Token::TupleEnd,
+            Token::SeqEnd,
+        ];
+
+        serde_test::assert_ser_tokens(&Stream, &tokens);
+
+        let buf = Owned::buffer(Stream).unwrap();
+        serde_test::assert_ser_tokens(&buf, &tokens);
+        serde_test::assert_ser_tokens(&buf.as_ref().into_owned(), &tokens);
+
+        serde_test::assert_ser_tokens(
+            &Owned::buffer(alloc::vec![1]).unwrap(),
+            &[Token::Seq { len: Some(1) }, Token::I32(1), Token::SeqEnd],
+        );
+    }
+
+    #[test]
+    fn seq_as_bytes() {
+        use alloc::{vec, vec::Vec};
+        use serde_derive::Serialize;
+
+        #[derive(Serialize)]
+        struct Data {
+            #[serde(with = "serde_bytes")]
+            a: Vec<u8>,
+            b: Vec<u8>,
+            c: Vec<u8>,
+            d: Vec<u16>,
+        }
+
+        let data = Data {
+            a: vec![1, 2],
+            b: vec![3, 4],
+            c: vec![],
+            d: vec![5, 6],
+        };
+
+        let buf = Owned::buffer(&data).unwrap();
+        assert_eq!(Some(&[1, 2][..]), buf.get("a").unwrap().as_bytes());
+        assert_eq!(None, buf.get("b").unwrap().as_bytes());
+
+        let buf = data
+            .serialize(Serializer::new().with_seq_as_bytes())
+            .unwrap();
+        assert_eq!(Some(&[1, 2][..]), buf.get("a").unwrap().as_bytes());
+        assert_eq!(Some(&[3, 4][..]), buf.get("b").unwrap().as_bytes());
+        assert_eq!(Some(0), buf.get("c").unwrap().len());
+        assert_eq!(None, buf.get("d").unwrap().as_bytes());
     }
 }
@@ -0,0 +1,198 @@
+/*!
+Conversions between buffers and `serde-value`'s [`serde_value::Value`].
+*/
+
+use alloc::boxed::Box;
+use serde::ser::Error as _;
+
+use crate::{Error, LenHint, NewtypeStruct, Owned, Ref, Value};
+
+impl From<serde_value::Value> for Owned {
+    /**
+    Convert a `serde-value` value into a buffer.
+
+    Every `serde-value` value has an equivalent in a buffer, so this conversion doesn't lose any
+    information. Newtypes are converted into newtype structs with an empty name.
+    */
+    fn from(value: serde_value::Value) -> Self {
+        Owned(from_serde_value(value))
+    }
+}
+
+impl TryFrom<Owned> for serde_value::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a `serde-value` value.
+
+    Values are converted in the same way `serde-value` would serialize them, which doesn't
+    preserve the shape of structs and enums:
+
+    - The names of structs and enums are dropped.
+    - Unit structs are converted into `()`.
+    - Tuples and tuple structs are converted into sequences.
+    - Structs are converted into maps keyed by their field names.
+    - Unit variants are converted into strings with the variant name.
+    - Newtype, tuple, and struct variants are converted into maps with a single entry keyed by
+      the variant name.
+    - Map entries with duplicate keys are collapsed, keeping the last value.
+
+    Converting a 128-bit integer will fail, because `serde-value` doesn't support them.
+    */
+    fn try_from(value: Owned) -> Result<Self, Self::Error> {
+        serde_value::to_value(&value).map_err(Error::custom)
+    }
+}
+
+impl<'a> TryFrom<Ref<'a>> for serde_value::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a `serde-value` value.
+
+    See the conversion from [`Owned`] for details on what information is dropped.
+    */
+    fn try_from(value: Ref<'a>) -> Result<Self, Self::Error> {
+        serde_value::to_value(&value).map_err(Error::custom)
+    }
+}
+
+fn from_serde_value(value: serde_value::Value) -> Value<'static> {
+    match value {
+        serde_value::Value::Bool(v) => Value::Bool(v),
+        serde_value::Value::U8(v) => Value::U8(v),
+        serde_value::Value::U16(v) => Value::U16(v),
+        serde_value::Value::U32(v) => Value::U32(v),
+        serde_value::Value::U64(v) => Value::U64(v),
+        serde_value::Value::I8(v) => Value::I8(v),
+        serde_value::Value::I16(v) => Value::I16(v),
+        serde_value::Value::I32(v) => Value::I32(v),
+        serde_value::Value::I64(v) => Value::I64(v),
+        serde_value::Value::F32(v) => Value::F32(v),
+        serde_value::Value::F64(v) => Value::F64(v),
+        serde_value::Value::Char(v) => Value::Char(v),
+        serde_value::Value::String(v) => Value::Str(v.into()),
+        serde_value::Value::Unit => Value::Unit,
+        serde_value::Value::Option(None) => Value::None,
+        serde_value::Value::Option(Some(v)) => Value::Some(Box::new(from_serde_value(*v))),
+        serde_value::Value::Newtype(v) => Value::NewtypeStruct(Box::new(NewtypeStruct {
+            name: "".into(),
+            value: from_serde_value(*v),
+        })),
+        serde_value::Value::Seq(v) => Value::Seq(
+            v.into_iter().map(from_serde_value).collect(),
+            LenHint::Known,
+        ),
+        serde_value::Value::Map(v) => Value::Map(
+            v.into_iter()
+                .map(|(k, v)| (from_serde_value(k), from_serde_value(v)))
+                .collect(),
+            LenHint::Known,
+        ),
+        serde_value::Value::Bytes(v) => Value::Bytes(v.into_boxed_slice()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{collections::BTreeMap, string::ToString, vec};
+    use serde_derive::Serialize;
+    use serde_value::Value as SerdeValue;
+
+    #[test]
+    fn serde_value_roundtrip() {
+        let value = SerdeValue::Map(BTreeMap::from([
+            (
+                SerdeValue::String("unsigned".into()),
+                SerdeValue::Seq(vec![
+                    SerdeValue::U8(1),
+                    SerdeValue::U16(2),
+                    SerdeValue::U32(3),
+                    SerdeValue::U64(4),
+                ]),
+            ),
+            (
+                SerdeValue::String("signed".into()),
+                SerdeValue::Seq(vec![
+                    SerdeValue::I8(-1),
+                    SerdeValue::I16(-2),
+                    SerdeValue::I32(-3),
+                    SerdeValue::I64(-4),
+                ]),
+            ),
+            (
+                SerdeValue::String("float".into()),
+                SerdeValue::Seq(vec![SerdeValue::F32(1.5), SerdeValue::F64(2.5)]),
+            ),
+            (
+                SerdeValue::U8(1),
+                SerdeValue::Seq(vec![
+                    SerdeValue::Unit,
+                    SerdeValue::Bool(true),
+                    SerdeValue::Char('a'),
+                    SerdeValue::Bytes(b"bytes".to_vec()),
+                    SerdeValue::Option(None),
+                    SerdeValue::Option(Some(Box::new(SerdeValue::Unit))),
+                    SerdeValue::Newtype(Box::new(SerdeValue::U8(1))),
+                ]),
+            ),
+        ]));
+
+        let owned = Owned::from(value.clone());
+
+        assert_eq!(
+            Some(3),
+            owned
+                .get("unsigned")
+                .unwrap()
+                .get_index(2)
+                .unwrap()
+                .as_u128()
+        );
+        assert_eq!(
+            Some(-3),
+            owned.get("signed").unwrap().get_index(2).unwrap().as_i128()
+        );
+
+        assert_eq!(value, SerdeValue::try_from(owned.as_ref()).unwrap());
+        assert_eq!(value, SerdeValue::try_from(owned).unwrap());
+    }
+
+    #[test]
+    fn serde_value_drops_shape() {
+        #[derive(Serialize)]
+        struct Struct {
+            a: i32,
+            b: Enum,
+        }
+
+        #[derive(Serialize)]
+        enum Enum {
+            Tuple(i32, i32),
+        }
+
+        let owned = Owned::buffer(Struct {
+            a: 1,
+            b: Enum::Tuple(2, 3),
+        })
+        .unwrap();
+
+        assert_eq!(
+            SerdeValue::Map(BTreeMap::from([
+                (SerdeValue::String("a".to_string()), SerdeValue::I32(1)),
+                (
+                    SerdeValue::String("b".to_string()),
+                    SerdeValue::Map(BTreeMap::from([(
+                        SerdeValue::String("Tuple".to_string()),
+                        SerdeValue::Seq(vec![SerdeValue::I32(2), SerdeValue::I32(3)]),
+                    )])),
+                ),
+            ])),
+            SerdeValue::try_from(owned).unwrap()
+        );
+
+        assert!(SerdeValue::try_from(Owned::buffer(1u128).unwrap()).is_err());
+    }
+}
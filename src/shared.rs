@@ -0,0 +1,154 @@
+/*!
+Sharing owned buffers between many consumers.
+*/
+
+use core::{fmt, ops::Deref};
+
+use alloc::sync::Arc;
+use serde::{de::IntoDeserializer, Serialize};
+
+use crate::{Deserializer, Error, Owned, Ref, RefDeserializer};
+
+/**
+A fully owned value that's cheap to clone.
+
+Cloning a shared buffer increments a reference count instead of copying its contents, so the same
+buffer can be handed to many consumers. Shared buffers can't be modified, but they dereference to
+[`Owned`], so any method that inspects a buffer can be called on them.
+Shared buffers follow the same rules for equality, ordering, and hashing as [`Owned`].
+*/
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedOwned(Arc<Owned>);
+
+impl Owned {
+    /**
+    Convert this buffer into one that's cheap to clone.
+
+    The buffer's contents are moved into the shared buffer as-is, without copying them.
+    */
+    pub fn into_shared(self) -> SharedOwned {
+        SharedOwned(Arc::new(self))
+    }
+}
+
+impl From<Owned> for SharedOwned {
+    fn from(value: Owned) -> Self {
+        value.into_shared()
+    }
+}
+
+impl SharedOwned {
+    /**
+    Get a fully owned buffer with the same contents as this one.
+
+    If this is the only reference to the buffer then its contents are moved into the new buffer.
+    Otherwise they're cloned.
+    */
+    pub fn into_owned(self) -> Owned {
+        Arc::try_unwrap(self.0).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    /**
+    Get a borrowed buffer that views the contents of this one.
+
+    See [`Owned::as_ref`] for details.
+    */
+    pub fn as_ref(&self) -> Ref<'_> {
+        Owned::as_ref(&self.0)
+    }
+}
+
+impl Deref for SharedOwned {
+    type Target = Owned;
+
+    fn deref(&self) -> &Owned {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SharedOwned {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for SharedOwned {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl Serialize for SharedOwned {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for SharedOwned {
+    type Deserializer = Deserializer<'de>;
+
+    /**
+    Get a deserializer for this buffer.
+
+    The buffer's contents are cloned if there are other references to it.
+    Use `&SharedOwned` to deserialize without cloning.
+    */
+    fn into_deserializer(self) -> Self::Deserializer {
+        self.into_owned().into_deserializer()
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de SharedOwned {
+    type Deserializer = RefDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        (&*self.0).into_deserializer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+    use serde::Deserialize;
+    use serde_test::{assert_ser_tokens, Token};
+
+    #[test]
+    fn shared_owned() {
+        let shared = Owned::from(Ref::seq([Ref::str("a"), Ref::u8(1)])).into_shared();
+        let cloned = shared.clone();
+
+        assert!(Arc::ptr_eq(&shared.0, &cloned.0));
+        assert_eq!(shared, cloned);
+        assert_eq!(Some("a"), cloned.get_index(0).unwrap().as_str());
+        assert_eq!(Ref::seq([Ref::str("a"), Ref::u8(1)]), cloned.as_ref());
+
+        assert_ser_tokens(
+            &cloned,
+            &[
+                Token::Seq { len: Some(2) },
+                Token::Str("a"),
+                Token::U8(1),
+                Token::SeqEnd,
+            ],
+        );
+
+        assert_eq!(
+            (String::from("a"), 1u8),
+            <(String, u8)>::deserialize((&cloned).into_deserializer()).unwrap()
+        );
+        assert_eq!(
+            (String::from("a"), 1u8),
+            <(String, u8)>::deserialize(cloned.into_deserializer()).unwrap()
+        );
+
+        assert_eq!(
+            Owned::from(Ref::seq([Ref::str("a"), Ref::u8(1)])),
+            shared.into_owned()
+        );
+    }
+}
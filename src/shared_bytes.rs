@@ -0,0 +1,80 @@
+/*!
+Buffering byte-strings held in a `bytes::Bytes` without copying them.
+*/
+
+use ::bytes::Bytes;
+
+use alloc::boxed::Box;
+
+use crate::{Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Create a buffer for a byte-string that shares the storage of `v`.
+
+    The byte-string isn't copied into the buffer, and cloning the buffer increments the reference
+    count of `v` instead of copying its contents. Like other large payloads, the `Bytes` handle
+    itself is boxed so it doesn't widen every other value in a buffer. The resulting buffer
+    serializes using `serialize_bytes`, the same as any other byte-string. Byte-strings buffered
+    by [`crate::Serializer`] are still copied, since serializers only see a borrowed `&[u8]`.
+    */
+    pub fn shared_bytes(v: Bytes) -> Self {
+        Owned(Value::SharedBytes(Box::new(v)))
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Create a buffer for a byte-string that shares the storage of `v`.
+
+    See [`Owned::shared_bytes`] for details.
+    */
+    pub fn shared_bytes(v: Bytes) -> Self {
+        Ref(Value::SharedBytes(Box::new(v)))
+    }
+}
+
+impl From<Bytes> for Owned {
+    fn from(v: Bytes) -> Self {
+        Owned::shared_bytes(v)
+    }
+}
+
+impl<'a> From<Bytes> for Ref<'a> {
+    fn from(v: Bytes) -> Self {
+        Ref::shared_bytes(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+    use serde_test::{assert_ser_tokens, Token};
+
+    #[test]
+    fn shared_bytes() {
+        let bytes = Bytes::from(vec![1, 2, 3]);
+
+        let buf = Owned::shared_bytes(bytes.clone());
+        let cloned = buf.clone();
+
+        assert_eq!(Some(bytes.as_ref()), cloned.as_bytes());
+        assert!(matches!(&cloned.0, Value::SharedBytes(v) if v.as_ptr() == bytes.as_ptr()));
+
+        assert_eq!(Owned::bytes([1, 2, 3]), buf);
+        assert_eq!(Ref::bytes(&[1, 2, 3]), buf);
+        assert!(Ref::bytes(&[1, 2]) < buf && buf < Ref::bytes(&[1, 2, 4]));
+
+        assert_ser_tokens(&buf, &[Token::Bytes(&[1, 2, 3])]);
+        assert_ser_tokens(
+            &Ref::seq([buf.as_ref()]),
+            &[
+                Token::Seq { len: Some(1) },
+                Token::Bytes(&[1, 2, 3]),
+                Token::SeqEnd,
+            ],
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use core::mem;
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    owned_str, Name, NewtypeStruct, NewtypeVariant, Owned, Ref, Struct, StructVariant, TupleStruct,
+    TupleVariant, UnitStruct, UnitVariant, Value,
+};
+
+impl Owned {
+    /**
+    Get the number of bytes of memory this buffer occupies.
+
+    This includes the size of every value in the buffer, along with the strings, bytes, and
+    collections it owns on the heap. Shared strings, like the ones produced by string interning,
+    are counted in full by every buffer that holds them.
+    */
+    pub fn heap_size(&self) -> usize {
+        self.0.heap_size()
+    }
+
+    /**
+    Get the number of values in this buffer.
+
+    Every value is counted, including the buffer itself, the keys and values of maps, and the
+    values wrapped by `Some`s, newtypes, and enum variants. The names of structs, fields, and
+    enum variants aren't values, so they aren't counted.
+    */
+    pub fn node_count(&self) -> usize {
+        self.0.metrics().0
+    }
+
+    /**
+    Get the number of values along the longest path from this buffer to a value nested within it.
+
+    A buffer containing a single scalar value has a depth of `1`. Each collection, `Some`, newtype,
+    and enum variant with fields adds `1` to the depth of the values within it.
+    */
+    pub fn max_depth(&self) -> usize {
+        self.0.metrics().1
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Get the number of bytes of memory this buffer occupies.
+
+    This includes the size of every value in the buffer, along with the strings, bytes, and
+    collections it owns on the heap. Borrowed strings, bytes, and values aren't owned by the
+    buffer, so they don't contribute to its size.
+    */
+    pub fn heap_size(&self) -> usize {
+        self.0.heap_size()
+    }
+
+    /**
+    Get the number of values in this buffer.
+
+    See [`Owned::node_count`] for details. Values borrowed from other buffers are counted
+    as if they were owned.
+    */
+    pub fn node_count(&self) -> usize {
+        self.0.metrics().0
+    }
+
+    /**
+    Get the number of values along the longest path from this buffer to a value nested within it.
+
+    See [`Owned::max_depth`] for details.
+    */
+    pub fn max_depth(&self) -> usize {
+        self.0.metrics().1
+    }
+}
+
+impl<'a> Value<'a> {
+    /**
+    Sum the size of this value and everything it owns.
+
+    Nested values are visited using a stack instead of recursion so deeply nested buffers can't
+    overflow the stack.
+    */
+    fn heap_size(&self) -> usize {
+        let mut size = mem::size_of::<Value>();
+        let mut stack = Vec::new();
+        let mut next = Some(self);
+
+        while let Some(value) = next.take().or_else(|| stack.pop()) {
+            size += match value {
+                Value::U128(_) => mem::size_of::<u128>(),
+                Value::I128(_) => mem::size_of::<i128>(),
+                Value::Str(v) => owned_str::heap_size(v),
+                Value::SharedStr(v) => v.len(),
+                Value::Bytes(v) => v.len(),
+                #[cfg(feature = "bytes")]
+                Value::SharedBytes(v) => v.len(),
+                #[cfg(feature = "json")]
+                Value::RawJson(v) => owned_str::heap_size(v),
+                Value::Some(v) => {
+                    next = Some(v);
+                    mem::size_of::<Value>()
+                }
+                Value::NewtypeStruct(v) => {
+                    next = Some(&v.value);
+                    mem::size_of::<NewtypeStruct>() + v.name.heap_size()
+                }
+                Value::Struct(v) => {
+                    stack.extend(v.fields.iter().map(|(_, v)| v));
+                    mem::size_of::<Struct>() + v.name.heap_size() + fields_size(&v.fields)
+                }
+                Value::Seq(fields, _) | Value::Tuple(fields) => {
+                    stack.extend(fields.iter());
+                    mem::size_of_val(&**fields)
+                }
+                Value::TupleStruct(v) => {
+                    stack.extend(v.fields.iter());
+                    mem::size_of::<TupleStruct>()
+                        + v.name.heap_size()
+                        + mem::size_of_val(&*v.fields)
+                }
+                Value::UnitStruct(v) => mem::size_of::<UnitStruct>() + v.name.heap_size(),
+                Value::UnitVariant(v) => {
+                    mem::size_of::<UnitVariant>() + v.name.heap_size() + v.variant.heap_size()
+                }
+                Value::NewtypeVariant(v) => {
+                    next = Some(&v.value);
+                    mem::size_of::<NewtypeVariant>() + v.name.heap_size() + v.variant.heap_size()
+                }
+                Value::TupleVariant(v) => {
+                    stack.extend(v.fields.iter());
+                    mem::size_of::<TupleVariant>()
+                        + v.name.heap_size()
+                        + v.variant.heap_size()
+                        + mem::size_of_val(&*v.fields)
+                }
+                Value::StructVariant(v) => {
+                    stack.extend(v.fields.iter().map(|(_, v)| v));
+                    mem::size_of::<StructVariant>()
+                        + v.name.heap_size()
+                        + v.variant.heap_size()
+                        + fields_size(&v.fields)
+                }
+                Value::Map(fields, _) => {
+                    stack.extend(fields.iter().flat_map(|(k, v)| [k, v]));
+                    mem::size_of_val(&**fields)
+                }
+                _ => 0,
+            };
+        }
+
+        size
+    }
+
+    /**
+    Count the values in this buffer, and find the depth of the most deeply nested one.
+
+    Nested values are visited using a stack instead of recursion, like [`Value::heap_size`].
+    */
+    fn metrics(&self) -> (usize, usize) {
+        let mut count = 0;
+        let mut max_depth = 0;
+        let mut stack = vec![(self, 1)];
+
+        while let Some((value, depth)) = stack.pop() {
+            count += 1;
+            max_depth = max_depth.max(depth);
+
+            let depth = depth + 1;
+
+            match value.resolve() {
+                Value::Some(v) => stack.push((v, depth)),
+                Value::NewtypeStruct(v) => stack.push((&v.value, depth)),
+                Value::NewtypeVariant(v) => stack.push((&v.value, depth)),
+                Value::Seq(fields, _) | Value::Tuple(fields) => {
+                    stack.extend(fields.iter().map(|v| (v, depth)))
+                }
+                Value::TupleStruct(v) => stack.extend(v.fields.iter().map(|v| (v, depth))),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter().map(|v| (v, depth))),
+                Value::Struct(v) => stack.extend(v.fields.iter().map(|(_, v)| (v, depth))),
+                Value::StructVariant(v) => stack.extend(v.fields.iter().map(|(_, v)| (v, depth))),
+                Value::Map(fields, _) => {
+                    stack.extend(fields.iter().flat_map(|(k, v)| [(k, depth), (v, depth)]))
+                }
+                _ => (),
+            }
+        }
+
+        (count, max_depth)
+    }
+}
+
+fn fields_size(fields: &[(Name, Value)]) -> usize {
+    mem::size_of_val(fields) + fields.iter().map(|(k, _)| k.heap_size()).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{string::String, vec};
+
+    const VALUE: usize = mem::size_of::<Value>();
+
+    #[test]
+    fn heap_size() {
+        // Short strings don't allocate when they're stored inline
+        let str_size = |len| {
+            if cfg!(feature = "compact_str") {
+                0
+            } else {
+                len
+            }
+        };
+
+        assert_eq!(VALUE, Ref::u8(1).heap_size());
+        assert_eq!(VALUE + 16, Ref::u128(1).heap_size());
+        assert_eq!(VALUE + str_size(5), Owned::from("a str").heap_size());
+        assert_eq!(VALUE, Ref::str("a str").heap_size());
+        assert_eq!(VALUE, Ref::bytes(b"bytes").heap_size());
+
+        assert_eq!(
+            VALUE + 3 * VALUE + str_size(1),
+            Ref::seq([Ref::u8(1), Ref::owned_str("a"), Ref::none()]).heap_size()
+        );
+        assert_eq!(2 * VALUE, Ref::some(Ref::unit()).heap_size());
+
+        let owned = Owned::buffer(vec![String::from("a"), String::from("bc")]).unwrap();
+        assert_eq!(
+            VALUE + 2 * VALUE + str_size(1) + str_size(2),
+            owned.heap_size()
+        );
+        assert_eq!(VALUE, owned.as_ref().heap_size());
+    }
+
+    #[test]
+    fn heap_size_deeply_nested() {
+        let mut deep = Ref::unit();
+        for _ in 0..200_000 {
+            deep = Ref::some(deep);
+        }
+
+        assert_eq!(200_001 * VALUE, deep.heap_size());
+    }
+
+    #[test]
+    fn node_count_max_depth() {
+        assert_eq!((1, 1), (Ref::u8(1).node_count(), Ref::u8(1).max_depth()));
+
+        let buf = Owned::from(Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::some(Ref::u8(1))),
+                (
+                    "b",
+                    Ref::map([(Ref::str("c"), Ref::seq([Ref::unit(), Ref::unit()]))]),
+                ),
+                ("d", Ref::unit_variant("Enum", 0, "A")),
+            ],
+        ));
+
+        assert_eq!(9, buf.node_count());
+        assert_eq!(4, buf.max_depth());
+
+        let borrowed = Ref::seq([buf.as_ref()]);
+
+        assert_eq!(10, borrowed.node_count());
+        assert_eq!(5, borrowed.max_depth());
+    }
+
+    #[test]
+    fn max_depth_deeply_nested() {
+        let mut deep = Ref::unit();
+        for _ in 0..200_000 {
+            deep = Ref::some(deep);
+        }
+
+        assert_eq!(200_001, deep.node_count());
+        assert_eq!(200_001, deep.max_depth());
+    }
+}
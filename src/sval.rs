@@ -0,0 +1,325 @@
+/*!
+Conversions between buffers and `sval`'s [`sval::Value`].
+*/
+
+use crate::{
+    Error, Name, NewtypeStruct, NewtypeVariant, Owned, Ref, Struct, StructVariant, TupleStruct,
+    TupleVariant, UnitVariant, Value,
+};
+
+impl Owned {
+    /**
+    Buffer the `sval` value `v` into an owned buffer.
+
+    Values are buffered in the same way `sval_serde` would serialize them. Records are
+    buffered as structs, tuples as tuples or tuple structs, enums as enum variants, and
+    `sval` text as strings.
+    */
+    pub fn buffer_sval(v: impl sval::Value) -> Result<Self, Error> {
+        Owned::buffer(sval_serde::ToSerialize::new(v))
+    }
+}
+
+impl sval::Value for Owned {
+    /**
+    Stream the buffer into an `sval::Stream`.
+
+    Items in sequences, maps, and structs are streamed one after the other, so only nested
+    values add to the stack. Strings and byte-strings are streamed as borrowed fragments.
+    */
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        self.0.stream(stream)
+    }
+}
+
+impl<'a> sval::Value for Ref<'a> {
+    /**
+    Stream the buffer into an `sval::Stream`.
+
+    Items in sequences, maps, and structs are streamed one after the other, so only nested
+    values add to the stack. Strings and byte-strings are streamed as borrowed fragments.
+    */
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        self.0.stream(stream)
+    }
+}
+
+impl<'a> sval::Value for Value<'a> {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        match self {
+            Value::Unit => stream.value(&()),
+            Value::U8(v) => stream.u8(*v),
+            Value::U16(v) => stream.u16(*v),
+            Value::U32(v) => stream.u32(*v),
+            Value::U64(v) => stream.u64(*v),
+            Value::U128(v) => stream.u128(**v),
+            Value::I8(v) => stream.i8(*v),
+            Value::I16(v) => stream.i16(*v),
+            Value::I32(v) => stream.i32(*v),
+            Value::I64(v) => stream.i64(*v),
+            Value::I128(v) => stream.i128(**v),
+            Value::F32(v) => stream.f32(*v),
+            Value::F64(v) => stream.f64(*v),
+            Value::Bool(v) => stream.bool(*v),
+            Value::Char(v) => stream.value_computed(v),
+            Value::Str(v) => stream.value(&**v),
+            Value::SharedStr(v) => stream.value(&**v),
+            Value::BorrowedStr(v) => stream.value(*v),
+            Value::Bytes(v) => stream.value(sval::BinarySlice::new(v)),
+            Value::BorrowedBytes(v) => stream.value(sval::BinarySlice::new(v)),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(v) => stream.value(sval::BinarySlice::new(v)),
+            #[cfg(feature = "json")]
+            Value::RawJson(v) => match Value::parse_raw_json(v) {
+                Ok(v) => stream.value_computed(&v),
+                Err(_) => sval::error(),
+            },
+            Value::None => stream.value(&None::<()>),
+            Value::Some(v) => {
+                stream.tagged_begin(
+                    Some(&sval::tags::RUST_OPTION_SOME),
+                    Some(&sval::Label::new("Some").with_tag(&sval::tags::VALUE_IDENT)),
+                    Some(&sval::Index::new(1).with_tag(&sval::tags::VALUE_OFFSET)),
+                )?;
+                stream.value(&**v)?;
+                stream.tagged_end(
+                    Some(&sval::tags::RUST_OPTION_SOME),
+                    Some(&sval::Label::new("Some").with_tag(&sval::tags::VALUE_IDENT)),
+                    Some(&sval::Index::new(1).with_tag(&sval::tags::VALUE_OFFSET)),
+                )
+            }
+            Value::UnitStruct(v) => stream.tag(None, Some(&name_label(&v.name)), None),
+            Value::UnitVariant(v) => {
+                let UnitVariant {
+                    name,
+                    variant_index,
+                    variant,
+                } = &**v;
+                let name = name_label(name);
+
+                stream.enum_begin(None, Some(&name), None)?;
+                stream.tag(
+                    None,
+                    Some(&name_label(variant)),
+                    Some(&sval::Index::new_u32(*variant_index)),
+                )?;
+                stream.enum_end(None, Some(&name), None)
+            }
+            Value::NewtypeStruct(v) => {
+                let NewtypeStruct { name, value } = &**v;
+                let name = name_label(name);
+
+                stream.tagged_begin(None, Some(&name), None)?;
+                stream.value(value)?;
+                stream.tagged_end(None, Some(&name), None)
+            }
+            Value::NewtypeVariant(v) => {
+                let NewtypeVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    value,
+                } = &**v;
+                let name = name_label(name);
+                let variant = name_label(variant);
+                let variant_index = sval::Index::new_u32(*variant_index);
+
+                stream.enum_begin(None, Some(&name), None)?;
+                stream.tagged_begin(None, Some(&variant), Some(&variant_index))?;
+                stream.value(value)?;
+                stream.tagged_end(None, Some(&variant), Some(&variant_index))?;
+                stream.enum_end(None, Some(&name), None)
+            }
+            Value::Seq(v, len) => {
+                stream.seq_begin(len.len(v.len()))?;
+
+                for v in v.iter() {
+                    stream.seq_value_begin()?;
+                    stream.value(v)?;
+                    stream.seq_value_end()?;
+                }
+
+                stream.seq_end()
+            }
+            Value::Tuple(v) => stream_tuple(stream, None, None, v),
+            Value::TupleStruct(v) => {
+                let TupleStruct { name, fields } = &**v;
+
+                stream_tuple(stream, Some(&name_label(name)), None, fields)
+            }
+            Value::TupleVariant(v) => {
+                let TupleVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    fields,
+                } = &**v;
+                let name = name_label(name);
+
+                stream.enum_begin(None, Some(&name), None)?;
+                stream_tuple(
+                    stream,
+                    Some(&name_label(variant)),
+                    Some(&sval::Index::new_u32(*variant_index)),
+                    fields,
+                )?;
+                stream.enum_end(None, Some(&name), None)
+            }
+            Value::Map(v, len) => {
+                stream.map_begin(len.len(v.len()))?;
+
+                for (k, v) in v.iter() {
+                    stream.map_key_begin()?;
+                    stream.value(k)?;
+                    stream.map_key_end()?;
+
+                    stream.map_value_begin()?;
+                    stream.value(v)?;
+                    stream.map_value_end()?;
+                }
+
+                stream.map_end()
+            }
+            Value::Struct(v) => {
+                let Struct { name, fields } = &**v;
+
+                stream_record(stream, Some(&name_label(name)), None, fields)
+            }
+            Value::StructVariant(v) => {
+                let StructVariant {
+                    name,
+                    variant_index,
+                    variant,
+                    fields,
+                } = &**v;
+                let name = name_label(name);
+
+                stream.enum_begin(None, Some(&name), None)?;
+                stream_record(
+                    stream,
+                    Some(&name_label(variant)),
+                    Some(&sval::Index::new_u32(*variant_index)),
+                    fields,
+                )?;
+                stream.enum_end(None, Some(&name), None)
+            }
+            Value::Borrowed(v) => v.stream(stream),
+        }
+    }
+}
+
+/**
+Get a label for a struct or variant name.
+
+Labels for `'static` names can be borrowed for any lifetime, so streams don't need to buffer them.
+*/
+fn name_label<'a>(name: &'a Name) -> sval::Label<'a> {
+    match name.as_static() {
+        Some(name) => sval::Label::new(name),
+        None => sval::Label::new_computed(name),
+    }
+}
+
+fn stream_tuple<'sval, S: sval::Stream<'sval> + ?Sized>(
+    stream: &mut S,
+    label: Option<&sval::Label>,
+    index: Option<&sval::Index>,
+    fields: &'sval [Value],
+) -> sval::Result {
+    stream.tuple_begin(None, label, index, Some(fields.len()))?;
+
+    for (i, v) in fields.iter().enumerate() {
+        let i = sval::Index::new(i);
+
+        stream.tuple_value_begin(None, &i)?;
+        stream.value(v)?;
+        stream.tuple_value_end(None, &i)?;
+    }
+
+    stream.tuple_end(None, label, index)
+}
+
+fn stream_record<'sval, S: sval::Stream<'sval> + ?Sized>(
+    stream: &mut S,
+    label: Option<&sval::Label>,
+    index: Option<&sval::Index>,
+    fields: &'sval [(Name, Value)],
+) -> sval::Result {
+    stream.record_begin(None, label, index, Some(fields.len()))?;
+
+    for (k, v) in fields.iter() {
+        let k = name_label(k);
+
+        stream.record_value_begin(None, &k)?;
+        stream.value(v)?;
+        stream.record_value_end(None, &k)?;
+    }
+
+    stream.record_end(None, label, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    #[derive(serde_derive::Serialize)]
+    struct Struct<'a> {
+        a: i32,
+        b: Option<&'a str>,
+        c: Vec<Enum>,
+        d: (u8, &'a [u8]),
+        e: Unit,
+        f: Newtype,
+    }
+
+    #[derive(serde_derive::Serialize)]
+    enum Enum {
+        Unit,
+        Newtype(i32),
+        Tuple(i32, i32),
+        Struct { a: i32 },
+    }
+
+    #[derive(serde_derive::Serialize)]
+    struct Unit;
+
+    #[derive(serde_derive::Serialize)]
+    struct Newtype(i64);
+
+    #[test]
+    fn sval_roundtrip() {
+        let owned = Owned::buffer(Struct {
+            a: 1,
+            b: Some("a"),
+            c: alloc::vec![
+                Enum::Unit,
+                Enum::Newtype(1),
+                Enum::Tuple(1, 2),
+                Enum::Struct { a: 1 },
+            ],
+            d: (1, b"bytes"),
+            e: Unit,
+            f: Newtype(-1),
+        })
+        .unwrap();
+
+        assert_eq!(owned, Owned::buffer_sval(&owned).unwrap());
+        assert_eq!(owned, Owned::buffer_sval(owned.as_ref()).unwrap());
+    }
+
+    #[test]
+    fn sval_buffer() {
+        let owned = Owned::buffer_sval(sval::MapSlice::new(&[("a", 1), ("b", 2)])).unwrap();
+
+        assert_eq!(Some(2), owned.get("b").unwrap().as_i128());
+    }
+
+    #[test]
+    fn sval_large_seq() {
+        let owned = Owned::buffer((0..100_000).collect::<Vec<i32>>()).unwrap();
+
+        assert_eq!(owned, Owned::buffer_sval(&owned).unwrap());
+    }
+}
@@ -0,0 +1,699 @@
+/*!
+Converting buffers to and from `serde_test` token streams.
+*/
+
+use core::slice;
+
+use alloc::{boxed::Box, vec::Vec};
+
+use serde::{ser, Serialize};
+use serde_test::Token;
+
+use crate::{
+    Error, ErrorKind, LenHint, Name, NewtypeStruct, NewtypeVariant, Owned, Ref, Struct,
+    StructVariant, TupleStruct, TupleVariant, UnitStruct, UnitVariant, Value,
+};
+
+impl Owned {
+    /**
+    Get the tokens that [`serde_test::assert_ser_tokens`] expects this buffer to serialize as.
+
+    Strings and byte-strings in the buffer are offered to `serde_test` as `Token::Str` and
+    `Token::Bytes`. Since tokens can only hold `'static` strings, the contents of any strings and
+    byte-strings in the buffer are leaked, so this method is only suitable for tests.
+
+    `serde_test` doesn't support 128-bit integers, so this method returns an error if the buffer
+    contains any.
+    */
+    pub fn to_tokens(&self) -> Result<Vec<Token>, Error> {
+        to_tokens(self)
+    }
+
+    /**
+    Build a buffer from the tokens that [`serde_test::assert_ser_tokens`] expects.
+
+    This is the inverse of [`Owned::to_tokens`]. Every `Token::Seq`, `Token::Map`, `Token::Struct`,
+    and other compound token must be closed by its matching `*End` token, and the fields of structs
+    and struct variants must be named by a `Token::Str` before each value.
+
+    Tokens don't carry the index of enum variants, so every variant in the buffer has an index of
+    `0`. `Token::Enum` describes how an enum is deserialized rather than serialized, so it isn't
+    supported; use `Token::UnitVariant`, `Token::NewtypeVariant`, `Token::TupleVariant`, or
+    `Token::StructVariant` instead.
+    */
+    pub fn from_tokens(tokens: &[Token]) -> Result<Owned, Error> {
+        let mut tokens = Tokens(tokens.iter());
+
+        let value = tokens.value()?;
+
+        if let Some(token) = tokens.0.next() {
+            return Err(unexpected(token, "the end of the tokens"));
+        }
+
+        Ok(Owned(value))
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Get the tokens that [`serde_test::assert_ser_tokens`] expects this buffer to serialize as.
+
+    See [`Owned::to_tokens`] for details.
+    */
+    pub fn to_tokens(&self) -> Result<Vec<Token>, Error> {
+        to_tokens(self)
+    }
+}
+
+fn to_tokens(v: &impl Serialize) -> Result<Vec<Token>, Error> {
+    let mut recorder = Recorder(Vec::new());
+    v.serialize(&mut recorder)?;
+
+    Ok(recorder.0)
+}
+
+/**
+A serializer that records the tokens it's given.
+*/
+struct Recorder(Vec<Token>);
+
+impl Recorder {
+    fn push(&mut self, token: Token) -> Result<&mut Self, Error> {
+        self.0.push(token);
+        Ok(self)
+    }
+}
+
+fn leak_str(v: &str) -> &'static str {
+    Box::leak(Box::from(v))
+}
+
+fn leak_bytes(v: &[u8]) -> &'static [u8] {
+    Box::leak(Box::from(v))
+}
+
+fn unsupported(ty: &str) -> Error {
+    Error::new(
+        ErrorKind::NumberOutOfRange,
+        format_args!("`serde_test` doesn't support {} values", ty),
+    )
+}
+
+impl ser::Serializer for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.push(Token::Bool(v)).map(drop)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.push(Token::I8(v)).map(drop)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.push(Token::I16(v)).map(drop)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.push(Token::I32(v)).map(drop)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.push(Token::I64(v)).map(drop)
+    }
+
+    fn serialize_i128(self, _: i128) -> Result<(), Error> {
+        Err(unsupported("i128"))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.push(Token::U8(v)).map(drop)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.push(Token::U16(v)).map(drop)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.push(Token::U32(v)).map(drop)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.push(Token::U64(v)).map(drop)
+    }
+
+    fn serialize_u128(self, _: u128) -> Result<(), Error> {
+        Err(unsupported("u128"))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.push(Token::F32(v)).map(drop)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.push(Token::F64(v)).map(drop)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.push(Token::Char(v)).map(drop)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.push(Token::Str(leak_str(v))).map(drop)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.push(Token::Bytes(leak_bytes(v))).map(drop)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.push(Token::None).map(drop)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, v: &T) -> Result<(), Error> {
+        v.serialize(self.push(Token::Some)?)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.push(Token::Unit).map(drop)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), Error> {
+        self.push(Token::UnitStruct { name }).map(drop)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.push(Token::UnitVariant { name, variant }).map(drop)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        v: &T,
+    ) -> Result<(), Error> {
+        v.serialize(self.push(Token::NewtypeStruct { name })?)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        v: &T,
+    ) -> Result<(), Error> {
+        v.serialize(self.push(Token::NewtypeVariant { name, variant })?)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        self.push(Token::Seq { len })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, Error> {
+        self.push(Token::Tuple { len })
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Self, Error> {
+        self.push(Token::TupleStruct { name, len })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, Error> {
+        self.push(Token::TupleVariant { name, variant, len })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        self.push(Token::Map { len })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self, Error> {
+        self.push(Token::Struct { name, len })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, Error> {
+        self.push(Token::StructVariant { name, variant, len })
+    }
+}
+
+impl ser::SerializeSeq for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.push(Token::SeqEnd).map(drop)
+    }
+}
+
+impl ser::SerializeTuple for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.push(Token::TupleEnd).map(drop)
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.push(Token::TupleStructEnd).map(drop)
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.push(Token::TupleVariantEnd).map(drop)
+    }
+}
+
+impl ser::SerializeMap for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, k: &T) -> Result<(), Error> {
+        k.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, v: &T) -> Result<(), Error> {
+        v.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.push(Token::MapEnd).map(drop)
+    }
+}
+
+impl ser::SerializeStruct for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        name: &'static str,
+        v: &T,
+    ) -> Result<(), Error> {
+        self.0.push(Token::Str(name));
+        v.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.push(Token::StructEnd).map(drop)
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Recorder {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        name: &'static str,
+        v: &T,
+    ) -> Result<(), Error> {
+        self.0.push(Token::Str(name));
+        v.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.push(Token::StructVariantEnd).map(drop)
+    }
+}
+
+/**
+A cursor over tokens being built into a buffer.
+*/
+struct Tokens<'t>(slice::Iter<'t, Token>);
+
+impl<'t> Tokens<'t> {
+    fn next(&mut self) -> Result<&'t Token, Error> {
+        self.0
+            .next()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidValue, "unexpected end of tokens"))
+    }
+
+    fn value(&mut self) -> Result<Value<'static>, Error> {
+        let token = self.next()?;
+
+        self.value_from(token)
+    }
+
+    /**
+    Build a value starting from a token that's already been taken from the cursor.
+    */
+    fn value_from(&mut self, token: &Token) -> Result<Value<'static>, Error> {
+        Ok(match *token {
+            Token::Bool(v) => Value::Bool(v),
+            Token::I8(v) => Value::I8(v),
+            Token::I16(v) => Value::I16(v),
+            Token::I32(v) => Value::I32(v),
+            Token::I64(v) => Value::I64(v),
+            Token::U8(v) => Value::U8(v),
+            Token::U16(v) => Value::U16(v),
+            Token::U32(v) => Value::U32(v),
+            Token::U64(v) => Value::U64(v),
+            Token::F32(v) => Value::F32(v),
+            Token::F64(v) => Value::F64(v),
+            Token::Char(v) => Value::Char(v),
+            Token::Str(v) | Token::BorrowedStr(v) | Token::String(v) => Value::Str(v.into()),
+            Token::Bytes(v) | Token::BorrowedBytes(v) | Token::ByteBuf(v) => Value::Bytes(v.into()),
+            Token::None => Value::None,
+            Token::Some => Value::Some(Box::new(self.value()?)),
+            Token::Unit => Value::Unit,
+            Token::UnitStruct { name } => Value::UnitStruct(Box::new(UnitStruct {
+                name: Name::new(name),
+            })),
+            Token::UnitVariant { name, variant } => Value::UnitVariant(Box::new(UnitVariant {
+                name: Name::new(name),
+                variant_index: 0,
+                variant: Name::new(variant),
+            })),
+            Token::NewtypeStruct { name } => Value::NewtypeStruct(Box::new(NewtypeStruct {
+                name: Name::new(name),
+                value: self.value()?,
+            })),
+            Token::NewtypeVariant { name, variant } => {
+                Value::NewtypeVariant(Box::new(NewtypeVariant {
+                    name: Name::new(name),
+                    variant_index: 0,
+                    variant: Name::new(variant),
+                    value: self.value()?,
+                }))
+            }
+            Token::Seq { len } => Value::Seq(self.fields(Token::SeqEnd)?, LenHint::new(len)),
+            Token::Tuple { .. } => Value::Tuple(self.fields(Token::TupleEnd)?),
+            Token::TupleStruct { name, .. } => Value::TupleStruct(Box::new(TupleStruct {
+                name: Name::new(name),
+                fields: self.fields(Token::TupleStructEnd)?,
+            })),
+            Token::TupleVariant { name, variant, .. } => {
+                Value::TupleVariant(Box::new(TupleVariant {
+                    name: Name::new(name),
+                    variant_index: 0,
+                    variant: Name::new(variant),
+                    fields: self.fields(Token::TupleVariantEnd)?,
+                }))
+            }
+            Token::Map { len } => Value::Map(self.entries()?, LenHint::new(len)),
+            Token::Struct { name, .. } => Value::Struct(Box::new(Struct {
+                name: Name::new(name),
+                fields: self.named_fields(Token::StructEnd)?,
+            })),
+            Token::StructVariant { name, variant, .. } => {
+                Value::StructVariant(Box::new(StructVariant {
+                    name: Name::new(name),
+                    variant_index: 0,
+                    variant: Name::new(variant),
+                    fields: self.named_fields(Token::StructVariantEnd)?,
+                }))
+            }
+            ref token => return Err(unexpected(token, "a value")),
+        })
+    }
+
+    /**
+    Build values until the `end` token is reached.
+    */
+    fn fields(&mut self, end: Token) -> Result<Box<[Value<'static>]>, Error> {
+        let mut fields = Vec::new();
+
+        loop {
+            let token = self.next()?;
+
+            if *token == end {
+                return Ok(fields.into_boxed_slice());
+            }
+
+            fields.push(self.value_from(token)?);
+        }
+    }
+
+    /**
+    Build map entries until a `Token::MapEnd` is reached.
+    */
+    fn entries(&mut self) -> Result<Box<[(Value<'static>, Value<'static>)]>, Error> {
+        let mut entries = Vec::new();
+
+        loop {
+            let token = self.next()?;
+
+            if *token == Token::MapEnd {
+                return Ok(entries.into_boxed_slice());
+            }
+
+            let key = self.value_from(token)?;
+
+            let token = self.next()?;
+            if *token == Token::MapEnd {
+                return Err(Error::new(
+                    ErrorKind::MissingMapValue,
+                    "a map key was followed by the end of the map",
+                ));
+            }
+
+            let value = self.value_from(token)?;
+
+            entries.push((key, value));
+        }
+    }
+
+    /**
+    Build named fields until the `end` token is reached.
+    */
+    fn named_fields(
+        &mut self,
+        end: Token,
+    ) -> Result<Box<[(Name<'static>, Value<'static>)]>, Error> {
+        let mut fields = Vec::new();
+
+        loop {
+            let token = self.next()?;
+
+            if *token == end {
+                return Ok(fields.into_boxed_slice());
+            }
+
+            match *token {
+                Token::Str(name) | Token::BorrowedStr(name) | Token::String(name) => {
+                    fields.push((Name::new(name), self.value()?))
+                }
+                ref token => return Err(unexpected(token, "a field name")),
+            }
+        }
+    }
+}
+
+fn unexpected(token: &Token, expected: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidType,
+        format_args!("expected {}, found `{:?}`", expected, token),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::String;
+    use serde_test::assert_ser_tokens;
+
+    #[test]
+    fn to_tokens() {
+        let short_lived = String::from("a string");
+
+        let buf = Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::str(&short_lived)),
+                ("b", Ref::seq([Ref::some(Ref::u8(1)), Ref::none()])),
+                ("c", Ref::map([(Ref::bytes(b"k"), Ref::unit())])),
+                (
+                    "d",
+                    Ref::tuple_variant("Enum", 1, "A", [Ref::f64(1.5), Ref::char('a')]),
+                ),
+            ],
+        );
+
+        let tokens = buf.to_tokens().unwrap();
+
+        assert_eq!(
+            &[
+                Token::Struct {
+                    name: "Struct",
+                    len: 4
+                },
+                Token::Str("a"),
+                Token::Str("a string"),
+                Token::Str("b"),
+                Token::Seq { len: Some(2) },
+                Token::Some,
+                Token::U8(1),
+                Token::None,
+                Token::SeqEnd,
+                Token::Str("c"),
+                Token::Map { len: Some(1) },
+                Token::Bytes(b"k"),
+                Token::Unit,
+                Token::MapEnd,
+                Token::Str("d"),
+                Token::TupleVariant {
+                    name: "Enum",
+                    variant: "A",
+                    len: 2
+                },
+                Token::F64(1.5),
+                Token::Char('a'),
+                Token::TupleVariantEnd,
+                Token::StructEnd,
+            ][..],
+            &tokens[..]
+        );
+
+        assert_ser_tokens(&buf, &tokens);
+        assert_ser_tokens(&buf.into_owned(), &tokens);
+    }
+
+    #[test]
+    fn to_tokens_unsupported() {
+        assert_eq!(
+            ErrorKind::NumberOutOfRange,
+            Ref::seq([Ref::u128(1)]).to_tokens().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn from_tokens() {
+        let buf = Owned::record_struct(
+            "Struct",
+            [
+                ("a", Owned::str("a string")),
+                ("b", Owned::seq([Owned::some(1u8), Owned::none()])),
+                ("c", Owned::map([(Owned::bytes(*b"k"), Owned::unit())])),
+                (
+                    "d",
+                    Owned::tuple_variant("Enum", 0, "A", [Owned::f64(1.5), Owned::char('a')]),
+                ),
+                (
+                    "e",
+                    Owned::newtype_struct("Newtype", Owned::unit_variant("Enum", 0, "B")),
+                ),
+            ],
+        );
+
+        let tokens = buf.to_tokens().unwrap();
+
+        assert_eq!(buf, Owned::from_tokens(&tokens).unwrap());
+
+        // Sequences and maps keep whether their length was known
+        let unknown_len = Owned::from_tokens(&[
+            Token::Seq { len: None },
+            Token::Map { len: None },
+            Token::BorrowedStr("a"),
+            Token::ByteBuf(b"b"),
+            Token::MapEnd,
+            Token::SeqEnd,
+        ])
+        .unwrap();
+
+        assert_ser_tokens(
+            &unknown_len,
+            &[
+                Token::Seq { len: None },
+                Token::Map { len: None },
+                Token::Str("a"),
+                Token::Bytes(b"b"),
+                Token::MapEnd,
+                Token::SeqEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn from_tokens_invalid() {
+        for tokens in [
+            &[][..],
+            &[Token::Seq { len: None }, Token::U8(1)][..],
+            &[Token::Some][..],
+        ] {
+            assert_eq!(
+                ErrorKind::InvalidValue,
+                Owned::from_tokens(tokens).unwrap_err().kind(),
+                "{:?}",
+                tokens
+            );
+        }
+
+        for tokens in [
+            &[Token::SeqEnd][..],
+            &[Token::Seq { len: None }, Token::MapEnd][..],
+            &[Token::U8(1), Token::U8(2)][..],
+            &[Token::Enum { name: "Enum" }, Token::Str("A"), Token::Unit][..],
+            &[
+                Token::Struct {
+                    name: "Struct",
+                    len: 1,
+                },
+                Token::U8(1),
+                Token::U8(1),
+                Token::StructEnd,
+            ][..],
+        ] {
+            assert_eq!(
+                ErrorKind::InvalidType,
+                Owned::from_tokens(tokens).unwrap_err().kind(),
+                "{:?}",
+                tokens
+            );
+        }
+
+        assert_eq!(
+            ErrorKind::MissingMapValue,
+            Owned::from_tokens(&[Token::Map { len: None }, Token::U8(1), Token::MapEnd])
+                .unwrap_err()
+                .kind()
+        );
+    }
+}
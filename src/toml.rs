@@ -0,0 +1,370 @@
+/*!
+Conversions between buffers and `toml`'s [`toml::Value`].
+*/
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{Error, ErrorKind, LenHint, Name, NewtypeStruct, Owned, Ref, Value};
+
+/**
+The name of the newtype struct TOML datetimes are converted into.
+*/
+const DATETIME_NAME: &str = "Datetime";
+
+/**
+The name `toml` uses to serialize datetimes.
+*/
+const DATETIME_PRIVATE_NAME: &str = "$__toml_private_Datetime";
+
+/**
+The field `toml` uses to serialize datetimes.
+*/
+const DATETIME_PRIVATE_FIELD: &str = "$__toml_private_datetime";
+
+impl From<toml::Value> for Owned {
+    /**
+    Convert a TOML value into a buffer.
+
+    Tables are converted into maps with string keys, and arrays into sequences. Integers are
+    converted into the narrowest integer type that can hold them, and floats into `f64`s, so
+    TOML values are buffered in the same way as the equivalent JSON or CBOR ones.
+
+    Buffers have no datetime type, so datetimes are converted into a newtype struct called
+    `Datetime` containing the datetime as an RFC 3339 string, like `Datetime("1979-05-27T07:32:00Z")`.
+    TOML's local dates and times, which aren't valid RFC 3339, are formatted in the same way as they
+    are in TOML documents, like `Datetime("07:32:00")`. Newtype structs serialize as the value they
+    contain in most formats, so datetimes will appear as strings when the buffer is serialized.
+    */
+    fn from(value: toml::Value) -> Self {
+        Owned(from_toml(value))
+    }
+}
+
+impl TryFrom<Owned> for toml::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a TOML value.
+
+    Values are converted in the same way `toml` would serialize them:
+
+    - Structs and maps are converted into tables. Fields that are `()`, unit structs, or
+      `Option::None` are skipped, because TOML has no null value.
+    - Sequences, tuples, and structs with unnamed fields are converted into arrays.
+    - Enum variants are externally tagged, so `A::B(T)` is converted into `{ B = T }`.
+    - Byte-strings are converted into arrays of integers, and characters into strings.
+
+    Newtype structs called `Datetime` that contain a valid TOML datetime string are converted back
+    into datetimes, as are datetimes that were buffered from `toml`'s own [`toml::value::Datetime`].
+
+    This method will fail if a map contains keys that aren't strings, if an integer doesn't fit
+    in an `i64`, or if the buffer contains `()`, a unit struct, or `Option::None` outside of a table.
+
+    `toml::Value` has its own `try_from` method that serializes any value, which is called instead of
+    this one by `toml::Value::try_from(buffer)`. Use `buffer.try_into()` to call this conversion.
+    */
+    fn try_from(value: Owned) -> Result<Self, Self::Error> {
+        into_toml(&value.0)
+    }
+}
+
+impl<'a> TryFrom<Ref<'a>> for toml::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a TOML value.
+
+    This conversion follows the same rules as the one from [`Owned`].
+    */
+    fn try_from(value: Ref<'a>) -> Result<Self, Self::Error> {
+        into_toml(&value.0)
+    }
+}
+
+fn from_toml(value: toml::Value) -> Value<'static> {
+    match value {
+        toml::Value::String(v) => Value::Str(v.into()),
+        // Non-negative integers are unsigned, like they are when buffering JSON
+        toml::Value::Integer(v) => match u64::try_from(v) {
+            Ok(v) => Value::from_unsigned(v.into()),
+            Err(_) => Value::from_signed(v.into()),
+        },
+        toml::Value::Float(v) => Value::F64(v),
+        toml::Value::Boolean(v) => Value::Bool(v),
+        toml::Value::Datetime(v) => Value::NewtypeStruct(Box::new(NewtypeStruct {
+            name: Name::new(DATETIME_NAME),
+            value: Value::Str(v.to_string().into()),
+        })),
+        toml::Value::Array(v) => Value::Seq(v.into_iter().map(from_toml).collect(), LenHint::Known),
+        toml::Value::Table(v) => Value::Map(
+            v.into_iter()
+                .map(|(k, v)| (Value::Str(k.into()), from_toml(v)))
+                .collect(),
+            LenHint::Known,
+        ),
+    }
+}
+
+fn into_toml(value: &Value) -> Result<toml::Value, Error> {
+    if let Some(v) = value.as_str() {
+        return Ok(toml::Value::String(v.into()));
+    }
+
+    if let Some(v) = value.as_bytes() {
+        return Ok(toml::Value::Array(
+            v.iter()
+                .map(|b| toml::Value::Integer((*b).into()))
+                .collect(),
+        ));
+    }
+
+    Ok(match value.resolve() {
+        Value::Unit | Value::None | Value::UnitStruct(_) => {
+            return Err(Error::new(
+                ErrorKind::InvalidType,
+                "TOML has no null value, so `()` and `None` can only be buffered as fields",
+            ))
+        }
+        Value::U8(v) => toml::Value::Integer((*v).into()),
+        Value::U16(v) => toml::Value::Integer((*v).into()),
+        Value::U32(v) => toml::Value::Integer((*v).into()),
+        Value::U64(v) => toml::Value::Integer(i64::try_from(*v).map_err(|_| out_of_range())?),
+        Value::U128(v) => toml::Value::Integer(i64::try_from(**v).map_err(|_| out_of_range())?),
+        Value::I8(v) => toml::Value::Integer((*v).into()),
+        Value::I16(v) => toml::Value::Integer((*v).into()),
+        Value::I32(v) => toml::Value::Integer((*v).into()),
+        Value::I64(v) => toml::Value::Integer(*v),
+        Value::I128(v) => toml::Value::Integer(i64::try_from(**v).map_err(|_| out_of_range())?),
+        Value::F32(v) => toml::Value::Float((*v).into()),
+        Value::F64(v) => toml::Value::Float(*v),
+        Value::Bool(v) => toml::Value::Boolean(*v),
+        Value::Char(v) => toml::Value::String(v.to_string()),
+        Value::Some(v) => into_toml(v)?,
+        Value::NewtypeStruct(v) => {
+            if v.name.as_str() == DATETIME_NAME {
+                if let Some(datetime) = v.value.as_str().and_then(|v| v.parse().ok()) {
+                    return Ok(toml::Value::Datetime(datetime));
+                }
+            }
+
+            into_toml(&v.value)?
+        }
+        Value::Struct(v) => {
+            if let (DATETIME_PRIVATE_NAME, [(field, datetime)]) = (v.name.as_str(), &*v.fields) {
+                if let (DATETIME_PRIVATE_FIELD, Some(Ok(datetime))) =
+                    (field.as_str(), datetime.as_str().map(str::parse))
+                {
+                    return Ok(toml::Value::Datetime(datetime));
+                }
+            }
+
+            into_toml_fields(v.fields.iter().map(|(k, v)| (k.as_str(), v)))?
+        }
+        Value::Tuple(fields) | Value::Seq(fields, _) => into_toml_seq(fields)?,
+        Value::TupleStruct(v) => into_toml_seq(&v.fields)?,
+        Value::UnitVariant(v) => toml::Value::String(v.variant.as_str().into()),
+        Value::NewtypeVariant(v) => into_toml_variant(&v.variant, into_toml(&v.value)?),
+        Value::TupleVariant(v) => into_toml_variant(&v.variant, into_toml_seq(&v.fields)?),
+        Value::StructVariant(v) => into_toml_variant(
+            &v.variant,
+            into_toml_fields(v.fields.iter().map(|(k, v)| (k.as_str(), v)))?,
+        ),
+        #[cfg(feature = "json")]
+        Value::RawJson(v) => into_toml(&Value::parse_raw_json(v)?)?,
+        Value::Map(fields, _) => into_toml_fields(
+            fields
+                .iter()
+                .map(|(k, v)| {
+                    let k = k.as_str().ok_or_else(|| {
+                        Error::new(ErrorKind::UnsupportedMapKey, "key must be a string")
+                    })?;
+
+                    Ok((k, v))
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+        )?,
+        // Strings, bytes, and borrowed values are handled above
+        Value::Str(_)
+        | Value::SharedStr(_)
+        | Value::BorrowedStr(_)
+        | Value::Bytes(_)
+        | Value::BorrowedBytes(_)
+        | Value::Borrowed(_) => unreachable!(),
+        #[cfg(feature = "bytes")]
+        Value::SharedBytes(_) => unreachable!(),
+    })
+}
+
+fn into_toml_seq(fields: &[Value]) -> Result<toml::Value, Error> {
+    Ok(toml::Value::Array(
+        fields.iter().map(into_toml).collect::<Result<_, _>>()?,
+    ))
+}
+
+fn into_toml_fields<'v, 'a: 'v>(
+    fields: impl IntoIterator<Item = (&'v str, &'v Value<'a>)>,
+) -> Result<toml::Value, Error> {
+    let mut table = toml::Table::new();
+
+    for (k, v) in fields {
+        // Fields without a value are skipped, like `toml` does when serializing them
+        if matches!(
+            v.resolve(),
+            Value::Unit | Value::None | Value::UnitStruct(_)
+        ) {
+            continue;
+        }
+
+        table.insert(String::from(k), into_toml(v)?);
+    }
+
+    Ok(toml::Value::Table(table))
+}
+
+fn into_toml_variant(variant: &str, value: toml::Value) -> toml::Value {
+    let mut table = toml::Table::new();
+    table.insert(variant.into(), value);
+
+    toml::Value::Table(table)
+}
+
+fn out_of_range() -> Error {
+    Error::new(
+        ErrorKind::NumberOutOfRange,
+        "TOML can only store integers that fit in an `i64`",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec;
+    use serde_derive::Serialize;
+
+    #[test]
+    fn from_toml_roundtrip() {
+        let toml = r#"
+title = "config"
+port = 8080
+ratio = 0.5
+enabled = true
+created = 1979-05-27T07:32:00Z
+local = 07:32:00
+
+[server]
+hosts = ["a", "b"]
+limits = [1, -1, 300, 4294967296]
+"#;
+
+        let value: toml::Value = toml::from_str(toml).unwrap();
+        let owned = Owned::from(value.clone());
+
+        assert_eq!(Some(Ref::str("config")), owned.get("title"));
+        assert_eq!(Some(Ref::u16(8080)), owned.get("port"));
+        assert_eq!(Some(Ref::f64(0.5)), owned.get("ratio"));
+        assert_eq!(
+            Some(Ref::newtype_struct(
+                "Datetime",
+                Ref::str("1979-05-27T07:32:00Z")
+            )),
+            owned.get("created")
+        );
+        assert_eq!(
+            Some(Ref::newtype_struct("Datetime", Ref::str("07:32:00"))),
+            owned.get("local")
+        );
+        assert_eq!(
+            Some(Ref::seq([
+                Ref::u8(1),
+                Ref::i8(-1),
+                Ref::u16(300),
+                Ref::u64(4294967296)
+            ])),
+            owned.get_path("/server/limits")
+        );
+
+        let from_ref: toml::Value = owned.as_ref().try_into().unwrap();
+        assert_eq!(value, from_ref);
+
+        let from_owned: toml::Value = owned.try_into().unwrap();
+        assert_eq!(value, from_owned);
+    }
+
+    #[test]
+    fn into_toml() {
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(i32),
+            Tuple(i32, i32),
+            Struct { a: i32 },
+        }
+
+        #[derive(Serialize)]
+        struct Config {
+            name: &'static str,
+            missing: Option<u8>,
+            unit: (),
+            created: toml::value::Datetime,
+            #[serde(with = "serde_bytes")]
+            bytes: &'static [u8],
+            variants: Vec<Enum>,
+        }
+
+        let owned = Owned::buffer(Config {
+            name: "a",
+            missing: None,
+            unit: (),
+            created: "1979-05-27".parse().unwrap(),
+            bytes: b"ab",
+            variants: vec![
+                Enum::Unit,
+                Enum::Newtype(1),
+                Enum::Tuple(1, 2),
+                Enum::Struct { a: 1 },
+            ],
+        })
+        .unwrap();
+
+        let expected: toml::Value = toml::from_str(
+            r#"
+name = "a"
+created = 1979-05-27
+bytes = [97, 98]
+variants = ["Unit", { Newtype = 1 }, { Tuple = [1, 2] }, { Struct = { a = 1 } }]
+"#,
+        )
+        .unwrap();
+
+        let value: toml::Value = owned.try_into().unwrap();
+        assert_eq!(expected, value);
+    }
+
+    #[test]
+    fn into_toml_invalid() {
+        let err =
+            TryInto::<toml::Value>::try_into(Ref::map([(Ref::u8(1), Ref::unit())])).unwrap_err();
+        assert_eq!(ErrorKind::UnsupportedMapKey, err.kind());
+
+        let err = TryInto::<toml::Value>::try_into(Ref::u64(u64::MAX)).unwrap_err();
+        assert_eq!(ErrorKind::NumberOutOfRange, err.kind());
+
+        let err = TryInto::<toml::Value>::try_into(Ref::seq([Ref::none()])).unwrap_err();
+        assert_eq!(ErrorKind::InvalidType, err.kind());
+
+        // A `Datetime` that isn't a valid datetime is converted as a string
+        assert_eq!(
+            toml::Value::String("not a datetime".into()),
+            TryInto::<toml::Value>::try_into(Ref::newtype_struct(
+                "Datetime",
+                Ref::str("not a datetime")
+            ))
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use core::mem;
+
+use alloc::vec;
+
+use crate::{Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Transform every scalar value in this buffer with `f`.
+
+    Scalars are the values that don't contain any other values, like numbers, strings, byte-strings,
+    units, and `None`s. The structure of the buffer around them is left intact, including the names
+    of structs, fields, and enum variants. Unit structs and unit enum variants are considered structure,
+    so they're not passed to `f`. The keys of maps aren't passed to `f` either.
+
+    Scalars are transformed depth-first, in the order they'd be serialized.
+    The buffer is traversed using a stack instead of recursion, so deeply nested buffers can't
+    overflow the stack.
+    */
+    pub fn map_leaves(mut self, mut f: impl FnMut(Owned) -> Owned) -> Owned {
+        self.0.map_leaves(|v| f(Owned(v)).into_value());
+        self
+    }
+
+    /**
+    Replace the values of fields named in `field_names` with `replacement`, anywhere in this buffer.
+
+    Fields are matched in structs, including enum variants, and in maps with string keys.
+    Field names must match exactly. Use [`Owned::redact_ignore_case`] to match them regardless of
+    ASCII case. The values produced by `replacement` aren't searched for more fields to redact.
+
+    This method returns the number of fields that were redacted. The buffer is traversed using a
+    stack instead of recursion, so deeply nested buffers can't overflow the stack.
+    */
+    pub fn redact(&mut self, field_names: &[&str], replacement: impl Fn() -> Owned) -> usize {
+        self.0.redact(
+            |field| field_names.contains(&field),
+            || replacement().into_value(),
+        )
+    }
+
+    /**
+    Replace the values of fields named in `field_names` with `replacement`, anywhere in this buffer,
+    ignoring the ASCII case of field names.
+
+    See [`Owned::redact`] for details.
+    */
+    pub fn redact_ignore_case(
+        &mut self,
+        field_names: &[&str],
+        replacement: impl Fn() -> Owned,
+    ) -> usize {
+        self.0.redact(
+            |field| {
+                field_names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(field))
+            },
+            || replacement().into_value(),
+        )
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Transform every scalar value in this buffer with `f`.
+
+    See [`Owned::map_leaves`] for details. Collections borrowed from other buffers are cloned so
+    their values can be transformed.
+    */
+    pub fn map_leaves(mut self, mut f: impl FnMut(Ref<'a>) -> Ref<'a>) -> Ref<'a> {
+        self.0.map_leaves(|v| f(Ref(v)).into_value());
+        self
+    }
+
+    /**
+    Replace the values of fields named in `field_names` with `replacement`, anywhere in this buffer.
+
+    See [`Owned::redact`] for details. Collections borrowed from other buffers are cloned so
+    their fields can be redacted.
+    */
+    pub fn redact(&mut self, field_names: &[&str], replacement: impl Fn() -> Ref<'a>) -> usize {
+        self.0.redact(
+            |field| field_names.contains(&field),
+            || replacement().into_value(),
+        )
+    }
+
+    /**
+    Replace the values of fields named in `field_names` with `replacement`, anywhere in this buffer,
+    ignoring the ASCII case of field names.
+
+    See [`Owned::redact`] for details.
+    */
+    pub fn redact_ignore_case(
+        &mut self,
+        field_names: &[&str],
+        replacement: impl Fn() -> Ref<'a>,
+    ) -> usize {
+        self.0.redact(
+            |field| {
+                field_names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(field))
+            },
+            || replacement().into_value(),
+        )
+    }
+}
+
+impl<'a> Value<'a> {
+    fn map_leaves(&mut self, mut f: impl FnMut(Value<'a>) -> Value<'a>) {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            if let Value::Borrowed(v) = value {
+                if v.resolve().is_leaf() {
+                    *value = f(mem::replace(value, Value::Unit));
+                    continue;
+                }
+
+                *value = v.resolve().clone();
+            }
+
+            // Children are pushed in reverse, so they're popped in the order they're serialized
+            match value {
+                Value::Some(v) => stack.push(v),
+                Value::NewtypeStruct(v) => stack.push(&mut v.value),
+                Value::NewtypeVariant(v) => stack.push(&mut v.value),
+                Value::Seq(fields, _) | Value::Tuple(fields) => {
+                    stack.extend(fields.iter_mut().rev())
+                }
+                Value::TupleStruct(v) => stack.extend(v.fields.iter_mut().rev()),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter_mut().rev()),
+                Value::Struct(v) => stack.extend(v.fields.iter_mut().rev().map(|(_, v)| v)),
+                Value::StructVariant(v) => stack.extend(v.fields.iter_mut().rev().map(|(_, v)| v)),
+                Value::Map(fields, _) => stack.extend(fields.iter_mut().rev().map(|(_, v)| v)),
+                Value::UnitStruct(_) | Value::UnitVariant(_) => (),
+                value => *value = f(mem::replace(value, Value::Unit)),
+            }
+        }
+    }
+
+    fn redact(
+        &mut self,
+        matches: impl Fn(&str) -> bool,
+        replacement: impl Fn() -> Value<'a>,
+    ) -> usize {
+        let mut redacted = 0;
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            if let Value::Borrowed(v) = value {
+                if v.resolve().is_leaf() {
+                    continue;
+                }
+
+                *value = v.resolve().clone();
+            }
+
+            let mut redact = |value: &mut Value<'a>| {
+                *value = replacement();
+                redacted += 1;
+            };
+
+            match value {
+                Value::Some(v) => stack.push(v),
+                Value::NewtypeStruct(v) => stack.push(&mut v.value),
+                Value::NewtypeVariant(v) => stack.push(&mut v.value),
+                Value::Seq(fields, _) | Value::Tuple(fields) => stack.extend(fields.iter_mut()),
+                Value::TupleStruct(v) => stack.extend(v.fields.iter_mut()),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter_mut()),
+                Value::Struct(v) => {
+                    for (k, v) in v.fields.iter_mut() {
+                        if matches(k) {
+                            redact(v);
+                        } else {
+                            stack.push(v);
+                        }
+                    }
+                }
+                Value::StructVariant(v) => {
+                    for (k, v) in v.fields.iter_mut() {
+                        if matches(k) {
+                            redact(v);
+                        } else {
+                            stack.push(v);
+                        }
+                    }
+                }
+                Value::Map(fields, _) => {
+                    for (k, v) in fields.iter_mut() {
+                        if k.as_str().map(&matches).unwrap_or(false) {
+                            redact(v);
+                        } else {
+                            stack.push(v);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        redacted
+    }
+
+    /**
+    Whether this value is a scalar that doesn't contain any other values or carry any names.
+    */
+    fn is_leaf(&self) -> bool {
+        !matches!(
+            self,
+            Value::Some(_)
+                | Value::NewtypeStruct(_)
+                | Value::NewtypeVariant(_)
+                | Value::Seq(..)
+                | Value::Tuple(_)
+                | Value::TupleStruct(_)
+                | Value::TupleVariant(_)
+                | Value::Struct(_)
+                | Value::StructVariant(_)
+                | Value::Map(..)
+                | Value::UnitStruct(_)
+                | Value::UnitVariant(_)
+                | Value::Borrowed(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+
+    #[test]
+    fn map_leaves() {
+        let buf = Owned::from(Ref::record_struct(
+            "User",
+            [
+                ("name", Ref::str("Jane")),
+                ("email", Ref::some(Ref::str("jane@example.com"))),
+                ("age", Ref::u8(42)),
+                (
+                    "tags",
+                    Ref::map([(Ref::str("role"), Ref::unit_variant("Role", 0, "Admin"))]),
+                ),
+            ],
+        ));
+
+        let mut visited = 0;
+        let scrubbed = buf.map_leaves(|v| {
+            visited += 1;
+
+            if v.as_str().is_some() {
+                Owned::from("***")
+            } else {
+                v
+            }
+        });
+
+        assert_eq!(3, visited);
+        assert_eq!(
+            Owned::from(Ref::record_struct(
+                "User",
+                [
+                    ("name", Ref::str("***")),
+                    ("email", Ref::some(Ref::str("***"))),
+                    ("age", Ref::u8(42)),
+                    (
+                        "tags",
+                        Ref::map([(Ref::str("role"), Ref::unit_variant("Role", 0, "Admin"))]),
+                    ),
+                ],
+            )),
+            scrubbed
+        );
+    }
+
+    #[test]
+    fn map_leaves_order() {
+        let mut order = alloc::vec::Vec::new();
+
+        let _ = Ref::seq([Ref::u8(1), Ref::tuple([Ref::u8(2), Ref::u8(3)]), Ref::u8(4)])
+            .map_leaves(|v| {
+                order.push(v.to_string());
+                v
+            });
+
+        assert_eq!(vec!["1", "2", "3", "4"], order);
+    }
+
+    #[test]
+    fn map_leaves_borrowed() {
+        let owned = Owned::from(Ref::seq([Ref::f64(1.4), Ref::f64(2.6)]));
+
+        let rounded = Ref::seq([owned.as_ref(), owned.get_index(0).unwrap()])
+            .map_leaves(|v| Ref::f64(v.as_f64().unwrap().round()));
+
+        assert_eq!(
+            Ref::seq([Ref::seq([Ref::f64(1.0), Ref::f64(3.0)]), Ref::f64(1.0)]),
+            rounded
+        );
+        assert_eq!(Some(1.4), owned.get_index(0).unwrap().as_f64());
+    }
+
+    #[test]
+    fn redact() {
+        let mut buf = Owned::from(Ref::record_struct(
+            "Request",
+            [
+                ("path", Ref::str("/login")),
+                (
+                    "headers",
+                    Ref::map([
+                        (Ref::str("Authorization"), Ref::str("Bearer abc")),
+                        (Ref::str("Accept"), Ref::str("*/*")),
+                    ]),
+                ),
+                (
+                    "body",
+                    Ref::seq([Ref::record_struct_variant(
+                        "Body",
+                        0,
+                        "Login",
+                        [("password", Ref::str("hunter2"))],
+                    )]),
+                ),
+            ],
+        ));
+
+        assert_eq!(
+            1,
+            buf.redact(&["password", "authorization"], || Owned::from("***"))
+        );
+        assert_eq!(
+            Some(Ref::str("Bearer abc")),
+            buf.get_path("/headers/Authorization")
+        );
+        assert_eq!(Some(Ref::str("***")), buf.get_path("/body/0/password"));
+
+        assert_eq!(
+            1,
+            buf.redact_ignore_case(&["authorization"], || Owned::from("***"))
+        );
+        assert_eq!(
+            Some(Ref::str("***")),
+            buf.get_path("/headers/Authorization")
+        );
+        assert_eq!(Some(Ref::str("*/*")), buf.get_path("/headers/Accept"));
+    }
+
+    #[test]
+    fn redact_borrowed() {
+        let owned = Owned::from(Ref::map([(Ref::str("secret"), Ref::u8(1))]));
+
+        let mut buf = Ref::seq([owned.as_ref()]);
+
+        assert_eq!(1, buf.redact(&["secret"], Ref::none));
+        assert_eq!(
+            Ref::seq([Ref::map([(Ref::str("secret"), Ref::none())])]),
+            buf
+        );
+        assert_eq!(Some(Ref::u8(1)), owned.get_path("/secret"));
+    }
+}
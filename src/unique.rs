@@ -0,0 +1,162 @@
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+use crate::{Error, ErrorKind, Name, Owned, Ref, Value};
+
+impl Owned {
+    /**
+    Find keys that appear more than once in the maps and structs of this buffer.
+
+    Every map and struct nested in the buffer is checked. Map keys are compared using the
+    [`PartialEq`] implementation of buffers, and struct fields by name. Each repeated occurrence
+    of a key is returned, so a key that appears three times in a map is returned twice.
+    */
+    pub fn find_duplicate_keys(&self) -> Vec<Ref<'_>> {
+        self.0.find_duplicate_keys()
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Find keys that appear more than once in the maps and structs of this buffer.
+
+    See [`Owned::find_duplicate_keys`] for details.
+    */
+    pub fn find_duplicate_keys(&self) -> Vec<Ref<'_>> {
+        self.0.find_duplicate_keys()
+    }
+}
+
+impl<'a> Value<'a> {
+    fn find_duplicate_keys(&self) -> Vec<Ref<'_>> {
+        let mut duplicates = Vec::new();
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value.resolve() {
+                Value::Some(v) => stack.push(v),
+                Value::NewtypeStruct(v) => stack.push(&v.value),
+                Value::NewtypeVariant(v) => stack.push(&v.value),
+                Value::Seq(fields, _) | Value::Tuple(fields) => stack.extend(fields.iter()),
+                Value::TupleStruct(v) => stack.extend(v.fields.iter()),
+                Value::TupleVariant(v) => stack.extend(v.fields.iter()),
+                Value::Struct(v) => {
+                    duplicates.extend(duplicate_fields(&v.fields));
+                    stack.extend(v.fields.iter().map(|(_, v)| v));
+                }
+                Value::StructVariant(v) => {
+                    duplicates.extend(duplicate_fields(&v.fields));
+                    stack.extend(v.fields.iter().map(|(_, v)| v));
+                }
+                Value::Map(fields, _) => {
+                    duplicates.extend(duplicate_map_keys(fields));
+                    stack.extend(fields.iter().flat_map(|(k, v)| [k, v]));
+                }
+                _ => (),
+            }
+        }
+
+        duplicates
+    }
+}
+
+/**
+Fail if a map contains the same key more than once.
+*/
+pub(crate) fn check_map_keys(fields: &[(Value, Value)]) -> Result<(), Error> {
+    match duplicate_map_keys(fields).next() {
+        Some(key) => Err(duplicate_key(key)),
+        None => Ok(()),
+    }
+}
+
+/**
+Fail if a struct contains the same field more than once.
+*/
+pub(crate) fn check_fields(fields: &[(Name, Value)]) -> Result<(), Error> {
+    match duplicate_fields(fields).next() {
+        Some(key) => Err(duplicate_key(key)),
+        None => Ok(()),
+    }
+}
+
+fn duplicate_key(key: Ref) -> Error {
+    Error::new(
+        ErrorKind::DuplicateKey,
+        format_args!("duplicate key {}", key),
+    )
+}
+
+fn duplicate_map_keys<'v, 'a>(
+    fields: &'v [(Value<'a>, Value<'a>)],
+) -> impl Iterator<Item = Ref<'v>> {
+    let mut seen = BTreeSet::new();
+
+    fields
+        .iter()
+        .filter(move |(k, _)| !seen.insert(k))
+        .map(|(k, _)| Ref(Value::Borrowed(k)))
+}
+
+fn duplicate_fields<'v>(fields: &'v [(Name, Value)]) -> impl Iterator<Item = Ref<'v>> {
+    let mut seen = BTreeSet::new();
+
+    fields
+        .iter()
+        .filter(move |(k, _)| !seen.insert(k.as_str()))
+        .map(|(k, _)| Ref(Value::BorrowedStr(k)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+    use serde::{ser::SerializeStruct as _, Serialize as _, Serializer as _};
+
+    use crate::Serializer;
+
+    #[test]
+    fn find_duplicate_keys() {
+        let buf = Ref::seq([
+            Ref::map([
+                (Ref::str("a"), Ref::unit()),
+                (Ref::str("b"), Ref::unit()),
+                (Ref::str("a"), Ref::unit()),
+                (Ref::u8(1), Ref::unit()),
+                (Ref::u16(1), Ref::unit()),
+            ]),
+            Ref::record_struct("Struct", [("c", Ref::unit()), ("c", Ref::unit())]),
+        ]);
+
+        let mut duplicates = buf.find_duplicate_keys();
+        duplicates.sort();
+
+        assert_eq!(vec![Ref::str("a"), Ref::str("c")], duplicates);
+
+        assert!(Owned::buffer(Ref::map([(Ref::str("a"), Ref::unit())]))
+            .unwrap()
+            .find_duplicate_keys()
+            .is_empty());
+    }
+
+    #[test]
+    fn unique_keys() {
+        let map = Ref::map([(Ref::str("a"), Ref::u8(1)), (Ref::str("a"), Ref::u8(2))]);
+
+        assert!(map.serialize(Serializer::new()).is_ok());
+
+        let err = map
+            .serialize(Serializer::new().reject_duplicate_keys())
+            .unwrap_err();
+        assert_eq!(ErrorKind::DuplicateKey, err.kind());
+        assert_eq!("duplicate key \"a\"", err.to_string());
+
+        let mut s = Serializer::new()
+            .reject_duplicate_keys()
+            .serialize_struct("Struct", 2)
+            .unwrap();
+        s.serialize_field("a", &1).unwrap();
+        s.serialize_field("a", &2).unwrap();
+        assert_eq!(ErrorKind::DuplicateKey, s.end().unwrap_err().kind());
+    }
+}
@@ -0,0 +1,376 @@
+/*!
+Inspecting buffers as `valuable`'s [`valuable::Valuable`].
+*/
+
+use alloc::vec::Vec;
+
+use valuable::{
+    EnumDef, Enumerable, Fields, Mappable, NamedField, NamedValues, StructDef, Structable,
+    Valuable, Variant, VariantDef, Visit,
+};
+
+use crate::{
+    Name, NewtypeStruct, NewtypeVariant, Owned, Ref, Struct, StructVariant, TupleStruct,
+    TupleVariant, UnitStruct, UnitVariant, Value,
+};
+
+impl Valuable for Owned {
+    /**
+    Get the buffer as a `valuable::Value`.
+
+    Structs are represented as `Structable`s, enum variants as `Enumerable`s, maps as `Mappable`s,
+    and sequences, tuples, and byte-strings as `Listable`s. Optional values are represented as
+    their inner value if they're present, or as `Unit` if they're not.
+    Struct and enum definitions are dynamic, since their fields are only known from the buffer.
+    */
+    fn as_value(&self) -> valuable::Value<'_> {
+        self.0.as_value()
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        self.0.visit(visit)
+    }
+}
+
+impl<'a> Valuable for Ref<'a> {
+    /**
+    Get the buffer as a `valuable::Value`.
+
+    See the implementation for [`Owned`] for details.
+    */
+    fn as_value(&self) -> valuable::Value<'_> {
+        self.0.as_value()
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        self.0.visit(visit)
+    }
+}
+
+impl<'a> Valuable for Value<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        match self {
+            Value::Unit => valuable::Value::Unit,
+            Value::U8(v) => valuable::Value::U8(*v),
+            Value::U16(v) => valuable::Value::U16(*v),
+            Value::U32(v) => valuable::Value::U32(*v),
+            Value::U64(v) => valuable::Value::U64(*v),
+            Value::U128(v) => valuable::Value::U128(**v),
+            Value::I8(v) => valuable::Value::I8(*v),
+            Value::I16(v) => valuable::Value::I16(*v),
+            Value::I32(v) => valuable::Value::I32(*v),
+            Value::I64(v) => valuable::Value::I64(*v),
+            Value::I128(v) => valuable::Value::I128(**v),
+            Value::F32(v) => valuable::Value::F32(*v),
+            Value::F64(v) => valuable::Value::F64(*v),
+            Value::Bool(v) => valuable::Value::Bool(*v),
+            Value::Char(v) => valuable::Value::Char(*v),
+            Value::Str(v) => valuable::Value::String(v),
+            Value::SharedStr(v) => valuable::Value::String(v),
+            Value::BorrowedStr(v) => valuable::Value::String(v),
+            Value::Bytes(v) => v.as_value(),
+            Value::BorrowedBytes(v) => v.as_value(),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(_) => valuable::Value::Listable(self),
+            // Raw JSON can't be parsed into a value that outlives this call
+            #[cfg(feature = "json")]
+            Value::RawJson(v) => valuable::Value::String(v),
+            Value::None => valuable::Value::Unit,
+            Value::Some(v) => v.as_value(),
+            Value::UnitStruct(v) => valuable::Value::Structable(&**v),
+            Value::NewtypeStruct(v) => valuable::Value::Structable(&**v),
+            Value::Struct(v) => valuable::Value::Structable(&**v),
+            Value::TupleStruct(v) => valuable::Value::Structable(&**v),
+            Value::Tuple(fields) | Value::Seq(fields, _) => fields.as_value(),
+            Value::UnitVariant(v) => valuable::Value::Enumerable(&**v),
+            Value::NewtypeVariant(v) => valuable::Value::Enumerable(&**v),
+            Value::TupleVariant(v) => valuable::Value::Enumerable(&**v),
+            Value::StructVariant(v) => valuable::Value::Enumerable(&**v),
+            Value::Map(..) => valuable::Value::Mappable(self),
+            Value::Borrowed(v) => v.as_value(),
+        }
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        match self {
+            Value::Bytes(v) => v.visit(visit),
+            Value::BorrowedBytes(v) => v.visit(visit),
+            #[cfg(feature = "bytes")]
+            Value::SharedBytes(v) => {
+                for b in v.iter() {
+                    visit.visit_value(valuable::Value::U8(*b));
+                }
+            }
+            Value::Some(v) => v.visit(visit),
+            Value::UnitStruct(v) => v.visit(visit),
+            Value::NewtypeStruct(v) => v.visit(visit),
+            Value::Struct(v) => v.visit(visit),
+            Value::TupleStruct(v) => v.visit(visit),
+            Value::Tuple(fields) | Value::Seq(fields, _) => fields.visit(visit),
+            Value::UnitVariant(v) => v.visit(visit),
+            Value::NewtypeVariant(v) => v.visit(visit),
+            Value::TupleVariant(v) => v.visit(visit),
+            Value::StructVariant(v) => v.visit(visit),
+            Value::Map(fields, _) => {
+                for (k, v) in fields.iter() {
+                    visit.visit_entry(k.as_value(), v.as_value());
+                }
+            }
+            Value::Borrowed(v) => v.visit(visit),
+            value => visit.visit_value(value.as_value()),
+        }
+    }
+}
+
+/**
+Shared byte-strings are the only values represented as a `Listable` directly.
+
+Other byte-strings, sequences, and tuples use the `Listable` implementations of their fields.
+*/
+#[cfg(feature = "bytes")]
+impl<'a> valuable::Listable for Value<'a> {
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match self {
+            Value::SharedBytes(v) => v.len(),
+            _ => 0,
+        };
+
+        (len, Some(len))
+    }
+}
+
+/**
+Maps are the only values represented as a `Mappable`.
+*/
+impl<'a> Mappable for Value<'a> {
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match self {
+            Value::Map(fields, _) => fields.len(),
+            _ => 0,
+        };
+
+        (len, Some(len))
+    }
+}
+
+impl<'a> Valuable for UnitStruct<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Structable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit.visit_unnamed_fields(&[]);
+    }
+}
+
+impl<'a> Structable for UnitStruct<'a> {
+    fn definition(&self) -> StructDef<'_> {
+        StructDef::new_dynamic(&self.name, Fields::Unnamed(0))
+    }
+}
+
+impl<'a> Valuable for NewtypeStruct<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Structable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit.visit_unnamed_fields(&[self.value.as_value()]);
+    }
+}
+
+impl<'a> Structable for NewtypeStruct<'a> {
+    fn definition(&self) -> StructDef<'_> {
+        StructDef::new_dynamic(&self.name, Fields::Unnamed(1))
+    }
+}
+
+impl<'a> Valuable for TupleStruct<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Structable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit_unnamed_fields(&self.fields, visit);
+    }
+}
+
+impl<'a> Structable for TupleStruct<'a> {
+    fn definition(&self) -> StructDef<'_> {
+        StructDef::new_dynamic(&self.name, Fields::Unnamed(self.fields.len()))
+    }
+}
+
+impl<'a> Valuable for Struct<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Structable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit_named_fields(&self.fields, visit);
+    }
+}
+
+impl<'a> Structable for Struct<'a> {
+    fn definition(&self) -> StructDef<'_> {
+        // Field names are only known from the buffer, so they're visited rather than defined
+        StructDef::new_dynamic(&self.name, Fields::Named(&[]))
+    }
+}
+
+impl<'a> Valuable for UnitVariant<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Enumerable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit.visit_unnamed_fields(&[]);
+    }
+}
+
+impl<'a> Enumerable for UnitVariant<'a> {
+    fn definition(&self) -> EnumDef<'_> {
+        EnumDef::new_dynamic(&self.name, &[])
+    }
+
+    fn variant(&self) -> Variant<'_> {
+        Variant::Dynamic(VariantDef::new(&self.variant, Fields::Unnamed(0)))
+    }
+}
+
+impl<'a> Valuable for NewtypeVariant<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Enumerable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit.visit_unnamed_fields(&[self.value.as_value()]);
+    }
+}
+
+impl<'a> Enumerable for NewtypeVariant<'a> {
+    fn definition(&self) -> EnumDef<'_> {
+        EnumDef::new_dynamic(&self.name, &[])
+    }
+
+    fn variant(&self) -> Variant<'_> {
+        Variant::Dynamic(VariantDef::new(&self.variant, Fields::Unnamed(1)))
+    }
+}
+
+impl<'a> Valuable for TupleVariant<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Enumerable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit_unnamed_fields(&self.fields, visit);
+    }
+}
+
+impl<'a> Enumerable for TupleVariant<'a> {
+    fn definition(&self) -> EnumDef<'_> {
+        EnumDef::new_dynamic(&self.name, &[])
+    }
+
+    fn variant(&self) -> Variant<'_> {
+        Variant::Dynamic(VariantDef::new(
+            &self.variant,
+            Fields::Unnamed(self.fields.len()),
+        ))
+    }
+}
+
+impl<'a> Valuable for StructVariant<'a> {
+    fn as_value(&self) -> valuable::Value<'_> {
+        valuable::Value::Enumerable(self)
+    }
+
+    fn visit(&self, visit: &mut dyn Visit) {
+        visit_named_fields(&self.fields, visit);
+    }
+}
+
+impl<'a> Enumerable for StructVariant<'a> {
+    fn definition(&self) -> EnumDef<'_> {
+        EnumDef::new_dynamic(&self.name, &[])
+    }
+
+    fn variant(&self) -> Variant<'_> {
+        Variant::Dynamic(VariantDef::new(&self.variant, Fields::Named(&[])))
+    }
+}
+
+fn visit_unnamed_fields(fields: &[Value], visit: &mut dyn Visit) {
+    let values = fields.iter().map(|v| v.as_value()).collect::<Vec<_>>();
+
+    visit.visit_unnamed_fields(&values);
+}
+
+fn visit_named_fields(fields: &[(Name, Value)], visit: &mut dyn Visit) {
+    let names = fields
+        .iter()
+        .map(|(k, _)| NamedField::new(k))
+        .collect::<Vec<_>>();
+    let values = fields.iter().map(|(_, v)| v.as_value()).collect::<Vec<_>>();
+
+    visit.visit_named_fields(&NamedValues::new(&names, &values));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::format;
+
+    #[test]
+    fn as_value() {
+        let buf = Owned::from(Ref::record_struct(
+            "User",
+            [
+                ("name", Ref::str("Jane")),
+                ("email", Ref::none()),
+                ("roles", Ref::seq([Ref::unit_variant("Role", 0, "Admin")])),
+                ("tags", Ref::map([(Ref::str("a"), Ref::some(Ref::u8(1)))])),
+                ("id", Ref::newtype_struct("Id", Ref::u128(42))),
+            ],
+        ));
+
+        assert_eq!(
+            r#"User { name: "Jane", email: (), roles: [Role::Admin], tags: {"a": 1}, id: Id(42) }"#,
+            format!("{:?}", buf.as_value())
+        );
+        assert_eq!(
+            format!("{:?}", buf.as_value()),
+            format!(
+                "{:?}",
+                Ref::seq([buf.as_ref()]).get_index(0).unwrap().as_value()
+            )
+        );
+    }
+
+    #[test]
+    fn as_value_variants() {
+        for (expected, buf) in [
+            (
+                "Enum::A(1, 2)",
+                Ref::tuple_variant("Enum", 0, "A", [Ref::u8(1), Ref::u8(2)]),
+            ),
+            (
+                "Enum::B { c: true }",
+                Ref::record_struct_variant("Enum", 1, "B", [("c", Ref::bool(true))]),
+            ),
+            (
+                "Enum::D([1, 2])",
+                Ref::newtype_variant("Enum", 2, "D", Ref::bytes(&[1, 2])),
+            ),
+            #[cfg(feature = "bytes")]
+            (
+                "Enum::D([1, 2])",
+                Ref::newtype_variant("Enum", 2, "D", Ref::shared_bytes(alloc::vec![1, 2].into())),
+            ),
+        ] {
+            assert_eq!(expected, format!("{:?}", buf.as_value()));
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use core::fmt;
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use serde::{
+    de::{self, Error as _},
+    ser, Serialize as _,
+};
+
+use crate::{Error, LenHint, Owned, Value};
+
+impl Owned {
+    /**
+    Buffer whatever value `d` contains into an owned buffer.
+
+    This uses [`serde::Deserializer::deserialize_any`], so it's only suitable for self-describing
+    formats like JSON or CBOR. The buffer captures the value as the format describes it, rather
+    than the Rust type it may have been serialized from. That means struct and enum names,
+    along with the widths of integers, may be different. Enums in particular aren't recoverable
+    and will be buffered as maps with a single entry, like `{ variant: value }`.
+    */
+    pub fn buffer_from_deserializer<'de, D: de::Deserializer<'de>>(d: D) -> Result<Self, Error> {
+        Ok(Owned(
+            d.deserialize_any(ValueVisitor).map_err(Error::custom)?,
+        ))
+    }
+}
+
+/**
+Transcode the value in the deserializer `d` into the serializer `s`.
+
+The value is buffered using [`Owned::buffer_from_deserializer`], so the same caveats about
+self-describing formats and enums apply. The buffered value is then serialized by reference into
+`s`, so only a single copy of it is held at a time.
+*/
+pub fn transcode<'de, D, S>(d: D, s: S) -> Result<S::Ok, S::Error>
+where
+    D: de::Deserializer<'de>,
+    S: ser::Serializer,
+{
+    let buffered = Owned::buffer_from_deserializer(d).map_err(ser::Error::custom)?;
+
+    buffered.serialize(s)
+}
+
+struct ValueVisitor;
+
+impl<'de> de::DeserializeSeed<'de> for ValueVisitor {
+    type Value = Value<'static>;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        d.deserialize_any(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for ValueVisitor {
+    type Value = Value<'static>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i8<E: de::Error>(self, v: i8) -> Result<Self::Value, E> {
+        Ok(Value::I8(v))
+    }
+
+    fn visit_i16<E: de::Error>(self, v: i16) -> Result<Self::Value, E> {
+        Ok(Value::I16(v))
+    }
+
+    fn visit_i32<E: de::Error>(self, v: i32) -> Result<Self::Value, E> {
+        Ok(Value::I32(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Value::I64(v))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Self::Value, E> {
+        Ok(Value::I128(Box::new(v)))
+    }
+
+    fn visit_u8<E: de::Error>(self, v: u8) -> Result<Self::Value, E> {
+        Ok(Value::U8(v))
+    }
+
+    fn visit_u16<E: de::Error>(self, v: u16) -> Result<Self::Value, E> {
+        Ok(Value::U16(v))
+    }
+
+    fn visit_u32<E: de::Error>(self, v: u32) -> Result<Self::Value, E> {
+        Ok(Value::U32(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Value::U64(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Self::Value, E> {
+        Ok(Value::U128(Box::new(v)))
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<Self::Value, E> {
+        Ok(Value::F32(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Value::F64(v))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Self::Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Value::Str(v.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Value::Str(v.into()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Value::Bytes(v.into()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Value::Bytes(v.into_boxed_slice()))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::None)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        Ok(Value::Some(Box::new(d.deserialize_any(self)?)))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        d.deserialize_any(self)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Vec::new();
+
+        while let Some(field) = seq.next_element_seed(ValueVisitor)? {
+            fields.push(field);
+        }
+
+        Ok(Value::Seq(fields.into_boxed_slice(), LenHint::Known))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = Vec::new();
+
+        while let Some(entry) = map.next_entry_seed(ValueVisitor, ValueVisitor)? {
+            fields.push(entry);
+        }
+
+        Ok(Value::Map(fields.into_boxed_slice(), LenHint::Known))
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        use serde::de::VariantAccess as _;
+
+        let (variant, value) = data.variant_seed(ValueVisitor)?;
+        let value = value.newtype_variant_seed(ValueVisitor)?;
+
+        Ok(Value::Map([(variant, value)].into(), LenHint::Known))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::de::IntoDeserializer;
+
+    use crate::Ref;
+
+    #[test]
+    fn buffer_from_json() {
+        let mut de = serde_json::Deserializer::from_str(
+            r#"{"a":1,"b":[-1,1.5,"s",null,true],"c":{"Variant":{"d":{}}}}"#,
+        );
+
+        let owned = Owned::buffer_from_deserializer(&mut de).unwrap();
+
+        assert_eq!(
+            Ref::map([
+                (Ref::str("a"), Ref::u64(1)),
+                (
+                    Ref::str("b"),
+                    Ref::seq([
+                        Ref::i64(-1),
+                        Ref::f64(1.5),
+                        Ref::str("s"),
+                        Ref::unit(),
+                        Ref::bool(true),
+                    ])
+                ),
+                (
+                    Ref::str("c"),
+                    Ref::map([(
+                        Ref::str("Variant"),
+                        Ref::map([(Ref::str("d"), Ref::map([]))])
+                    )])
+                ),
+            ]),
+            owned.as_ref()
+        );
+    }
+
+    #[test]
+    fn buffer_from_buffer() {
+        let buffered = Ref::seq([
+            Ref::some(Ref::u8(1)),
+            Ref::none(),
+            Ref::owned_bytes(*b"bytes"),
+        ]);
+
+        let owned = Owned::buffer_from_deserializer(buffered.clone().into_deserializer()).unwrap();
+
+        assert_eq!(buffered, owned.as_ref());
+    }
+
+    #[test]
+    fn transcode_json() {
+        let mut de = serde_json::Deserializer::from_str(r#"{"a":[1,2],"b":null}"#);
+
+        let mut buf = alloc::vec::Vec::new();
+        transcode(&mut de, &mut serde_json::Serializer::pretty(&mut buf)).unwrap();
+
+        assert_eq!(
+            "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": null\n}",
+            core::str::from_utf8(&buf).unwrap()
+        );
+
+        let mut de = serde_json::Deserializer::from_str(r#"{"a":"#);
+
+        assert!(transcode(
+            &mut de,
+            &mut serde_json::Serializer::new(alloc::vec::Vec::new())
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn buffer_from_deserializer_error() {
+        let mut de = serde_json::Deserializer::from_str(r#"{"a":"#);
+
+        assert!(Owned::buffer_from_deserializer(&mut de).is_err());
+    }
+}
@@ -0,0 +1,433 @@
+use alloc::vec;
+
+use crate::{Owned, Ref, Value};
+
+/**
+A callback-based visitor for the values in a buffer.
+
+Buffers are walked depth-first by [`Owned::walk`] or [`Ref::walk`], without going through serde.
+Every method has a default implementation that does nothing, so visitors only need to implement
+the ones they're interested in.
+
+Collections are visited as a matching pair of `enter_*` and `leave_*` calls, with their contents
+visited in between:
+
+- Sequences, tuples, and structs with unnamed fields call [`BufVisitor::enter_seq`], then each element.
+- Maps call [`BufVisitor::enter_map`], then [`BufVisitor::visit_key`] followed by the value for each entry.
+- Structs with named fields call [`BufVisitor::enter_struct`], then [`BufVisitor::visit_field`] followed
+  by the value for each field.
+- Enum variants call [`BufVisitor::enter_variant`], then their fields, if they have any, as a
+  newtype, sequence, or struct.
+
+Everything else, including unit structs, is passed to [`BufVisitor::visit_scalar`].
+*/
+#[allow(unused_variables)]
+pub trait BufVisitor<'v> {
+    /**
+    Visit a value that doesn't contain any other values, like a number or string.
+    */
+    fn visit_scalar(&mut self, value: Ref<'v>) {}
+
+    /**
+    Begin visiting an optional value that's present.
+    */
+    fn enter_some(&mut self) {}
+
+    /**
+    Finish visiting an optional value that's present.
+    */
+    fn leave_some(&mut self) {}
+
+    /**
+    Begin visiting a newtype struct, or the value of a newtype enum variant.
+    */
+    fn enter_newtype(&mut self, name: &'v str) {}
+
+    /**
+    Finish visiting a newtype struct, or the value of a newtype enum variant.
+    */
+    fn leave_newtype(&mut self) {}
+
+    /**
+    Begin visiting a sequence, tuple, or the fields of a struct or enum variant with unnamed fields.
+
+    The name is the name of the tuple struct or enum variant, if there is one.
+    */
+    fn enter_seq(&mut self, name: Option<&'v str>, len: usize) {}
+
+    /**
+    Finish visiting a sequence.
+    */
+    fn leave_seq(&mut self) {}
+
+    /**
+    Begin visiting a map.
+    */
+    fn enter_map(&mut self, len: usize) {}
+
+    /**
+    Visit the key of a map entry, before its value is visited.
+
+    The key isn't walked. It can be passed to [`Ref::walk`] to visit its contents.
+    */
+    fn visit_key(&mut self, key: Ref<'v>) {}
+
+    /**
+    Finish visiting a map.
+    */
+    fn leave_map(&mut self) {}
+
+    /**
+    Begin visiting the fields of a struct or enum variant with named fields.
+
+    The name is the name of the struct or enum variant.
+    */
+    fn enter_struct(&mut self, name: &'v str, len: usize) {}
+
+    /**
+    Visit the name of a struct field, before its value is visited.
+    */
+    fn visit_field(&mut self, name: &'v str) {}
+
+    /**
+    Finish visiting the fields of a struct.
+    */
+    fn leave_struct(&mut self) {}
+
+    /**
+    Begin visiting an enum variant.
+    */
+    fn enter_variant(&mut self, name: &'v str, variant_index: u32, variant: &'v str) {}
+
+    /**
+    Finish visiting an enum variant.
+    */
+    fn leave_variant(&mut self) {}
+}
+
+impl Owned {
+    /**
+    Walk the values in this buffer depth-first, passing them to `visitor`.
+
+    The buffer is walked using a stack instead of recursion, so deeply nested buffers can't
+    overflow the stack.
+    */
+    pub fn walk<'v>(&'v self, visitor: &mut impl BufVisitor<'v>) {
+        self.0.walk(visitor)
+    }
+}
+
+impl<'a> Ref<'a> {
+    /**
+    Walk the values in this buffer depth-first, passing them to `visitor`.
+
+    See [`Owned::walk`] for details.
+    */
+    pub fn walk<'v>(&'v self, visitor: &mut impl BufVisitor<'v>) {
+        self.0.walk(visitor)
+    }
+}
+
+enum Step<'v, 'a> {
+    Value(&'v Value<'a>),
+    Key(&'v Value<'a>),
+    Field(&'v str),
+    LeaveSome,
+    LeaveNewtype,
+    LeaveSeq,
+    LeaveMap,
+    LeaveStruct,
+    LeaveVariant,
+}
+
+impl<'a> Value<'a> {
+    fn walk<'v>(&'v self, visitor: &mut impl BufVisitor<'v>) {
+        // Steps are pushed in reverse, so they're popped in the order they're visited
+        let mut stack = vec![Step::Value(self)];
+
+        while let Some(step) = stack.pop() {
+            let value = match step {
+                Step::Value(value) => value.resolve(),
+                Step::Key(key) => {
+                    visitor.visit_key(Ref(Value::Borrowed(key)));
+                    continue;
+                }
+                Step::Field(name) => {
+                    visitor.visit_field(name);
+                    continue;
+                }
+                Step::LeaveSome => {
+                    visitor.leave_some();
+                    continue;
+                }
+                Step::LeaveNewtype => {
+                    visitor.leave_newtype();
+                    continue;
+                }
+                Step::LeaveSeq => {
+                    visitor.leave_seq();
+                    continue;
+                }
+                Step::LeaveMap => {
+                    visitor.leave_map();
+                    continue;
+                }
+                Step::LeaveStruct => {
+                    visitor.leave_struct();
+                    continue;
+                }
+                Step::LeaveVariant => {
+                    visitor.leave_variant();
+                    continue;
+                }
+            };
+
+            match value {
+                Value::Some(v) => {
+                    visitor.enter_some();
+                    stack.push(Step::LeaveSome);
+                    stack.push(Step::Value(v));
+                }
+                Value::NewtypeStruct(v) => {
+                    visitor.enter_newtype(&v.name);
+                    stack.push(Step::LeaveNewtype);
+                    stack.push(Step::Value(&v.value));
+                }
+                Value::Seq(fields, _) | Value::Tuple(fields) => {
+                    visitor.enter_seq(None, fields.len());
+                    stack.push(Step::LeaveSeq);
+                    stack.extend(fields.iter().rev().map(Step::Value));
+                }
+                Value::TupleStruct(v) => {
+                    visitor.enter_seq(Some(&v.name), v.fields.len());
+                    stack.push(Step::LeaveSeq);
+                    stack.extend(v.fields.iter().rev().map(Step::Value));
+                }
+                Value::Map(fields, _) => {
+                    visitor.enter_map(fields.len());
+                    stack.push(Step::LeaveMap);
+                    for (k, v) in fields.iter().rev() {
+                        stack.push(Step::Value(v));
+                        stack.push(Step::Key(k));
+                    }
+                }
+                Value::Struct(v) => {
+                    visitor.enter_struct(&v.name, v.fields.len());
+                    stack.push(Step::LeaveStruct);
+                    for (k, v) in v.fields.iter().rev() {
+                        stack.push(Step::Value(v));
+                        stack.push(Step::Field(k));
+                    }
+                }
+                Value::UnitVariant(v) => {
+                    visitor.enter_variant(&v.name, v.variant_index, &v.variant);
+                    visitor.leave_variant();
+                }
+                Value::NewtypeVariant(v) => {
+                    visitor.enter_variant(&v.name, v.variant_index, &v.variant);
+                    visitor.enter_newtype(&v.variant);
+                    stack.push(Step::LeaveVariant);
+                    stack.push(Step::LeaveNewtype);
+                    stack.push(Step::Value(&v.value));
+                }
+                Value::TupleVariant(v) => {
+                    visitor.enter_variant(&v.name, v.variant_index, &v.variant);
+                    visitor.enter_seq(Some(&v.variant), v.fields.len());
+                    stack.push(Step::LeaveVariant);
+                    stack.push(Step::LeaveSeq);
+                    stack.extend(v.fields.iter().rev().map(Step::Value));
+                }
+                Value::StructVariant(v) => {
+                    visitor.enter_variant(&v.name, v.variant_index, &v.variant);
+                    visitor.enter_struct(&v.variant, v.fields.len());
+                    stack.push(Step::LeaveVariant);
+                    stack.push(Step::LeaveStruct);
+                    for (k, v) in v.fields.iter().rev() {
+                        stack.push(Step::Value(v));
+                        stack.push(Step::Field(k));
+                    }
+                }
+                value => visitor.visit_scalar(Ref(Value::Borrowed(value))),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl<'v> BufVisitor<'v> for Events {
+        fn visit_scalar(&mut self, value: Ref<'v>) {
+            self.0.push(value.to_string());
+        }
+
+        fn enter_some(&mut self) {
+            self.0.push("enter_some".into());
+        }
+
+        fn leave_some(&mut self) {
+            self.0.push("leave_some".into());
+        }
+
+        fn enter_newtype(&mut self, name: &'v str) {
+            self.0.push(format!("enter_newtype {}", name));
+        }
+
+        fn leave_newtype(&mut self) {
+            self.0.push("leave_newtype".into());
+        }
+
+        fn enter_seq(&mut self, name: Option<&'v str>, len: usize) {
+            self.0.push(format!("enter_seq {:?} {}", name, len));
+        }
+
+        fn leave_seq(&mut self) {
+            self.0.push("leave_seq".into());
+        }
+
+        fn enter_map(&mut self, len: usize) {
+            self.0.push(format!("enter_map {}", len));
+        }
+
+        fn visit_key(&mut self, key: Ref<'v>) {
+            self.0.push(format!("key {}", key));
+        }
+
+        fn leave_map(&mut self) {
+            self.0.push("leave_map".into());
+        }
+
+        fn enter_struct(&mut self, name: &'v str, len: usize) {
+            self.0.push(format!("enter_struct {} {}", name, len));
+        }
+
+        fn visit_field(&mut self, name: &'v str) {
+            self.0.push(format!("field {}", name));
+        }
+
+        fn leave_struct(&mut self) {
+            self.0.push("leave_struct".into());
+        }
+
+        fn enter_variant(&mut self, name: &'v str, variant_index: u32, variant: &'v str) {
+            self.0.push(format!(
+                "enter_variant {}::{} {}",
+                name, variant, variant_index
+            ));
+        }
+
+        fn leave_variant(&mut self) {
+            self.0.push("leave_variant".into());
+        }
+    }
+
+    #[test]
+    fn walk() {
+        let buf = Ref::record_struct(
+            "Struct",
+            [
+                ("a", Ref::some(Ref::u8(1))),
+                (
+                    "b",
+                    Ref::seq([
+                        Ref::map([(
+                            Ref::str("c"),
+                            Ref::newtype_struct("Newtype", Ref::bool(true)),
+                        )]),
+                        Ref::tuple_variant("Enum", 1, "D", [Ref::unit()]),
+                    ]),
+                ),
+            ],
+        );
+
+        let mut events = Events::default();
+        buf.walk(&mut events);
+
+        assert_eq!(
+            vec![
+                "enter_struct Struct 2",
+                "field a",
+                "enter_some",
+                "1",
+                "leave_some",
+                "field b",
+                "enter_seq None 2",
+                "enter_map 1",
+                "key \"c\"",
+                "enter_newtype Newtype",
+                "true",
+                "leave_newtype",
+                "leave_map",
+                "enter_variant Enum::D 1",
+                "enter_seq Some(\"D\") 1",
+                "()",
+                "leave_seq",
+                "leave_variant",
+                "leave_seq",
+                "leave_struct",
+            ],
+            events.0
+        );
+    }
+
+    #[test]
+    fn walk_collect() {
+        struct Strings<'v>(Vec<Ref<'v>>);
+
+        impl<'v> BufVisitor<'v> for Strings<'v> {
+            fn visit_scalar(&mut self, value: Ref<'v>) {
+                if value.as_str().is_some() {
+                    self.0.push(value);
+                }
+            }
+        }
+
+        let buf = Owned::from(Ref::seq([
+            Ref::str("a"),
+            Ref::u8(1),
+            Ref::seq([Ref::str("b")]),
+        ]));
+
+        let mut strings = Strings(Vec::new());
+        buf.walk(&mut strings);
+
+        assert_eq!(vec![Ref::str("a"), Ref::str("b")], strings.0);
+    }
+
+    #[test]
+    fn walk_deeply_nested() {
+        let mut buf = Ref::unit();
+        for _ in 0..100_000 {
+            buf = Ref::some(buf);
+        }
+
+        struct Depth(usize, usize);
+
+        impl<'v> BufVisitor<'v> for Depth {
+            fn enter_some(&mut self) {
+                self.0 += 1;
+                self.1 = self.1.max(self.0);
+            }
+
+            fn leave_some(&mut self) {
+                self.0 -= 1;
+            }
+        }
+
+        let mut depth = Depth(0, 0);
+        buf.walk(&mut depth);
+
+        assert_eq!(100_000, depth.1);
+    }
+}
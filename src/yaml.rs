@@ -0,0 +1,246 @@
+/*!
+Conversions between buffers and `serde_yaml`'s [`serde_yaml::Value`].
+*/
+
+use core::mem;
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use serde_yaml::value::{Tag, TaggedValue};
+
+use crate::{Error, LenHint, Owned, Ref, Value};
+
+impl From<serde_yaml::Value> for Owned {
+    /**
+    Convert a YAML value into a buffer.
+
+    Mappings are converted into maps with keys of any type, sequences into sequences, and `null`
+    into `()`. Integers are converted into the narrowest integer type that can hold them, and all
+    other numbers into `f64`s, so YAML values are buffered in the same way as the equivalent JSON ones.
+
+    A mapping entry with a `null` value, like `key: ~`, is converted into an entry with a `()` value.
+    It's kept distinct from a key that's missing from the mapping, which has no entry in the buffer.
+
+    Tagged values, like `!Point [1, 2]`, are converted into the same map with a single entry
+    `serde_yaml` uses to serialize them: `{"!Point": [1, 2]}`. This means tags are preserved when
+    the buffer is converted back into YAML, but will appear as maps in other formats.
+
+    Aliases are resolved by `serde_yaml` when a document is parsed, so values referenced through
+    anchors are copied wherever they're used. Merge keys like `<<: *base` aren't resolved unless
+    [`serde_yaml::Value::apply_merge`] is called before converting.
+    */
+    fn from(value: serde_yaml::Value) -> Self {
+        Owned(from_yaml(value))
+    }
+}
+
+impl TryFrom<Owned> for serde_yaml::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a YAML value.
+
+    Values are converted in the same way `serde_yaml` would serialize them. `()` and `Option::None`
+    are converted into `null`, so fields with no value are kept in mappings. Enum variants with
+    fields are converted into tagged values, like `!Variant value`.
+
+    Maps with a single string key that starts with `!`, like `{"!Point": [1, 2]}`, are converted
+    into tagged values, like `!Point [1, 2]`, so tagged values converted into a buffer are restored.
+    */
+    fn try_from(value: Owned) -> Result<Self, Self::Error> {
+        into_yaml(&value)
+    }
+}
+
+impl<'a> TryFrom<Ref<'a>> for serde_yaml::Value {
+    type Error = Error;
+
+    /**
+    Convert a buffer into a YAML value.
+
+    This conversion follows the same rules as the one from [`Owned`].
+    */
+    fn try_from(value: Ref<'a>) -> Result<Self, Self::Error> {
+        into_yaml(&value)
+    }
+}
+
+fn from_yaml(value: serde_yaml::Value) -> Value<'static> {
+    match value {
+        serde_yaml::Value::Null => Value::Unit,
+        serde_yaml::Value::Bool(v) => Value::Bool(v),
+        serde_yaml::Value::Number(v) => {
+            if let Some(v) = v.as_u64() {
+                Value::from_unsigned(v.into())
+            } else if let Some(v) = v.as_i64() {
+                Value::from_signed(v.into())
+            } else {
+                // All YAML numbers can be represented as `f64`s, including `.nan` and `.inf`
+                Value::F64(v.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        serde_yaml::Value::String(v) => Value::Str(v.into()),
+        serde_yaml::Value::Sequence(v) => {
+            Value::Seq(v.into_iter().map(from_yaml).collect(), LenHint::Known)
+        }
+        serde_yaml::Value::Mapping(v) => Value::Map(
+            v.into_iter()
+                .map(|(k, v)| (from_yaml(k), from_yaml(v)))
+                .collect(),
+            LenHint::Known,
+        ),
+        serde_yaml::Value::Tagged(v) => Value::Map(
+            [(Value::Str(v.tag.to_string().into()), from_yaml(v.value))].into(),
+            LenHint::Known,
+        ),
+    }
+}
+
+fn into_yaml(value: &impl serde::Serialize) -> Result<serde_yaml::Value, Error> {
+    serde_yaml::to_value(value)
+        .map(retag)
+        .map_err(Error::from_source)
+}
+
+/**
+Convert maps with a single entry like `{"!Tag": value}` back into tagged values.
+
+`serde_yaml` only recognizes tags while serializing its own tagged values, so tags from a buffer
+are serialized as regular maps.
+*/
+fn retag(value: serde_yaml::Value) -> serde_yaml::Value {
+    match value {
+        serde_yaml::Value::Sequence(v) => {
+            serde_yaml::Value::Sequence(v.into_iter().map(retag).collect())
+        }
+        serde_yaml::Value::Mapping(v) => {
+            let mut entries: Vec<_> = v.into_iter().collect();
+
+            match &mut *entries {
+                [(serde_yaml::Value::String(k), v)] if k.len() > 1 && k.starts_with('!') => {
+                    serde_yaml::Value::Tagged(Box::new(TaggedValue {
+                        tag: Tag::new(mem::take(k)),
+                        value: retag(mem::take(v)),
+                    }))
+                }
+                _ => serde_yaml::Value::Mapping(
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| (retag(k), retag(v)))
+                        .collect(),
+                ),
+            }
+        }
+        serde_yaml::Value::Tagged(mut v) => {
+            v.value = retag(v.value);
+            serde_yaml::Value::Tagged(v)
+        }
+        v => v,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_derive::Serialize;
+
+    #[test]
+    fn yaml_roundtrip() {
+        let yaml = r#"
+base: &base
+  host: localhost
+  ports: [80, -1, 70000, 1.5]
+copy: *base
+empty: ~
+point: !Point [1, 2]
+? [1, 2]
+: complex key
+1: integer key
+"#;
+
+        let value: serde_yaml::Value = serde_yaml::from_str(yaml).unwrap();
+        let owned = Owned::from(value.clone());
+
+        // Aliases are resolved into copies of the anchored value
+        assert_eq!(owned.get("base"), owned.get("copy"));
+        assert_eq!(
+            Some(Ref::seq([
+                Ref::u8(80),
+                Ref::i8(-1),
+                Ref::u32(70000),
+                Ref::f64(1.5)
+            ])),
+            owned.get_path("/copy/ports")
+        );
+
+        // `null` values are kept, and missing keys aren't
+        assert_eq!(Some(Ref::unit()), owned.get("empty"));
+        assert_eq!(None, owned.get("missing"));
+
+        assert_eq!(
+            Some(Ref::map([(
+                Ref::str("!Point"),
+                Ref::seq([Ref::u8(1), Ref::u8(2)])
+            )])),
+            owned.get("point")
+        );
+        assert_eq!(
+            Some(Ref::str("complex key")),
+            owned.keyed_get(&Ref::seq([Ref::u8(1), Ref::u8(2)]))
+        );
+
+        let from_ref: serde_yaml::Value = owned.as_ref().try_into().unwrap();
+        assert_eq!(value, from_ref);
+
+        let from_owned: serde_yaml::Value = owned.try_into().unwrap();
+        assert_eq!(value, from_owned);
+    }
+
+    #[test]
+    fn yaml_to_json() {
+        let value: serde_yaml::Value = serde_yaml::from_str("a: &a [1, ~]\nb: *a\n1: c\n").unwrap();
+
+        let owned = Owned::from(value);
+
+        assert_eq!(
+            r#"{"a":[1,null],"b":[1,null],"1":"c"}"#,
+            serde_json::to_string(&owned).unwrap()
+        );
+    }
+
+    #[test]
+    fn into_yaml() {
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(i32),
+            Struct { a: i32 },
+        }
+
+        #[derive(Serialize)]
+        struct Config {
+            name: &'static str,
+            missing: Option<u8>,
+            variants: [Enum; 3],
+        }
+
+        let owned = Owned::buffer(Config {
+            name: "a",
+            missing: None,
+            variants: [Enum::Unit, Enum::Newtype(1), Enum::Struct { a: 1 }],
+        })
+        .unwrap();
+
+        let expected: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+name: a
+missing: null
+variants: [Unit, !Newtype 1, !Struct { a: 1 }]
+"#,
+        )
+        .unwrap();
+
+        let value: serde_yaml::Value = owned.try_into().unwrap();
+        assert_eq!(expected, value);
+    }
+}